@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, Method},
+    middleware::Next,
+    response::Response,
+};
+
+use super::handlers::ApiError;
+use super::ApiState;
+
+/// Rejects a request unless it carries `Authorization: Bearer <API_TOKEN>`. Without
+/// an `API_TOKEN` the API is read-only: anything that trades or changes settings is
+/// refused. Routes added after this layer (the health check) are left open.
+pub async fn require_token(
+    State(state): State<ApiState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    match state.api_token.as_deref() {
+        Some(expected) => check_token(request.headers(), expected)?,
+        None => check_read_only(request.method())?,
+    }
+    Ok(next.run(request).await)
+}
+
+fn check_read_only(method: &Method) -> Result<(), ApiError> {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(());
+    }
+    Err(ApiError::Forbidden(
+        "Set API_TOKEN to enable write endpoints".to_string(),
+    ))
+}
+
+fn check_token(headers: &HeaderMap, expected: &str) -> Result<(), ApiError> {
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| ApiError::Unauthorized("Missing bearer token".to_string()))?;
+
+    if !constant_time_eq(provided.trim().as_bytes(), expected.as_bytes()) {
+        return Err(ApiError::Unauthorized("Invalid bearer token".to_string()));
+    }
+    Ok(())
+}
+
+/// Compares without bailing at the first differing byte, so timing doesn't leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(authorization: Option<&'static str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = authorization {
+            headers.insert(header::AUTHORIZATION, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_only_the_configured_bearer_token_is_accepted() {
+        assert!(check_token(&headers(Some("Bearer s3cret")), "s3cret").is_ok());
+
+        for authorization in [
+            None,
+            Some("Bearer wrong"),
+            Some("s3cret"),
+            Some("Bearer s3cre"),
+        ] {
+            assert!(matches!(
+                check_token(&headers(authorization), "s3cret"),
+                Err(ApiError::Unauthorized(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_without_a_token_only_reads_are_allowed() {
+        assert!(check_read_only(&Method::GET).is_ok());
+        for method in [Method::POST, Method::PUT, Method::DELETE, Method::PATCH] {
+            assert!(matches!(
+                check_read_only(&method),
+                Err(ApiError::Forbidden(_))
+            ));
+        }
+    }
+}
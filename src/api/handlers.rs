@@ -1,19 +1,41 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
 use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use redis::aio::MultiplexedConnection;
 use redis::AsyncCommands;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
 
 use super::ApiState;
-use crate::bot::{ClosedPosition, OpenPosition};
+use crate::bot::command::{self, BotCommand, CommandError, ManualEntry};
+use crate::bot::control::BotStatus;
+use crate::bot::missed::MissedEntry;
+use crate::bot::{ClosedPosition, OpenPosition, Position, ZoneId, ZoneOverride, Zones};
+use crate::cache::codec;
+use crate::calendar::{MacroGuard, NoTradeWindow};
+use crate::config::runtime::{RuntimeOverride, Tunables, MAX_LEVERAGE};
+use crate::exchange::bitget::PlaceOrderData;
+use crate::exchange::PRICE_UNAVAILABLE;
+use crate::graph::rollup::Rollups;
+use crate::graph::{Graph, PerformanceSummary, ReturnBasis, ZonePerformance};
 use crate::helper::{
-    PartialProfitTarget, TRADING_BOT_ACTIVE, TRADING_BOT_CLOSE_POSITIONS, TRADING_CAPITAL,
-    TRADING_PARTIAL_PROFIT_TARGET,
+    Helper, PartialProfitTarget, TRADING_BOT_ACTIVE, TRADING_BOT_CLOSE_POSITIONS,
+    TRADING_BOT_MISSED_ENTRIES, TRADING_BOT_MOMENTUM_INDICATORS, TRADING_BOT_ZONES,
+    TRADING_CAPITAL, TRADING_PARTIAL_PROFIT_TARGET,
 };
+use crate::trackers::health::{TrackerStatus, TRACKERS};
+use crate::trackers::momentum::MomentumIndicators;
 
 /// Pagination query parameters
 #[derive(Debug, Deserialize)]
@@ -56,6 +78,9 @@ pub enum ApiError {
     RedisError(String),
     NotFound(String),
     InvalidInput(String),
+    ExchangeError(String),
+    Unauthorized(String),
+    Forbidden(String),
 }
 
 impl IntoResponse for ApiError {
@@ -64,6 +89,9 @@ impl IntoResponse for ApiError {
             ApiError::RedisError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             ApiError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::ExchangeError(msg) => (StatusCode::BAD_GATEWAY, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
         };
 
         let body = Json(ErrorResponse { error: message });
@@ -119,7 +147,7 @@ pub async fn get_closed_positions(
     let mut conn = state.redis_conn.lock().await;
 
     // When filtering by date, fetch all positions and filter in-app
-    let raw_positions: Vec<String> = if from_date.is_some() || to_date.is_some() {
+    let raw_positions: Vec<Vec<u8>> = if from_date.is_some() || to_date.is_some() {
         conn.lrange(TRADING_BOT_CLOSE_POSITIONS, 0, -1)
             .await
             .map_err(|e| ApiError::RedisError(format!("Failed to fetch positions: {e}")))?
@@ -134,7 +162,7 @@ pub async fn get_closed_positions(
     // Deserialize and filter positions
     let mut positions: Vec<ClosedPosition> = raw_positions
         .iter()
-        .filter_map(|p| serde_json::from_str(p).ok())
+        .filter_map(|p| codec::decode(p).ok())
         .filter(|pos: &ClosedPosition| {
             if let Some(from) = from_date {
                 if pos.exit_time < from {
@@ -194,6 +222,96 @@ pub async fn get_active_position(
     }
 }
 
+/// POST /api/positions/close
+/// Has the bot close the active position at market and returns the recorded close
+pub async fn close_position(
+    State(state): State<ApiState>,
+) -> Result<Json<ClosedPosition>, ApiError> {
+    let closed = command::ask(&state.commands, |reply| BotCommand::Close { reply })
+        .await
+        .map_err(|e| command_error("Failed to close position", e))?;
+
+    closed
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("No open position to close".to_string()))
+}
+
+/// Body of `POST /api/positions/open`
+#[derive(Debug, Deserialize)]
+pub struct OpenPositionRequest {
+    pub side: Position,
+    pub entry_price: f64,
+    pub leverage: f64,
+    /// Fraction of the margin risked, as in `RANGER_RISK_PERCENTAGE`
+    pub risk_pct: f64,
+}
+
+impl OpenPositionRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.side == Position::Flat {
+            return Err(ApiError::InvalidInput(
+                "Side must be Long or Short".to_string(),
+            ));
+        }
+        if !self.entry_price.is_finite() || self.entry_price <= 0.0 {
+            return Err(ApiError::InvalidInput(
+                "Entry price must be greater than 0".to_string(),
+            ));
+        }
+        if !(1.0..=MAX_LEVERAGE).contains(&self.leverage) {
+            return Err(ApiError::InvalidInput(format!(
+                "Leverage must be between 1 and {MAX_LEVERAGE}"
+            )));
+        }
+        if !(self.risk_pct > 0.0 && self.risk_pct <= 1.0) {
+            return Err(ApiError::InvalidInput(
+                "Risk must be a fraction between 0 and 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Response for a manual entry
+#[derive(Debug, Serialize)]
+pub struct OpenPositionResponse {
+    pub order: PlaceOrderData,
+    pub position: OpenPosition,
+}
+
+/// POST /api/positions/open
+/// Has the bot place a discretionary market entry, which it then manages like its own
+pub async fn open_position(
+    State(state): State<ApiState>,
+    Json(request): Json<OpenPositionRequest>,
+) -> Result<Json<OpenPositionResponse>, ApiError> {
+    request.validate()?;
+
+    let entry = ManualEntry {
+        side: request.side,
+        entry_price: Helper::f64_to_decimal(request.entry_price),
+        leverage: Helper::f64_to_decimal(request.leverage),
+        risk_pct: Helper::f64_to_decimal(request.risk_pct),
+    };
+    let (position, order) = command::ask(&state.commands, |reply| BotCommand::Open {
+        entry,
+        reply,
+    })
+    .await
+    .map_err(|e| command_error("Failed to open position", e))?;
+
+    Ok(Json(OpenPositionResponse { order, position }))
+}
+
+/// A command the bot turned down is the caller's to fix; anything else went wrong
+/// on the way to the exchange
+fn command_error(context: &str, e: CommandError) -> ApiError {
+    match e {
+        CommandError::Rejected(reason) => ApiError::InvalidInput(reason),
+        e => ApiError::ExchangeError(format!("{context}: {e}")),
+    }
+}
+
 /// GET /api/positions/profit-targets
 /// Returns the current partial profit targets
 pub async fn get_profit_targets(
@@ -217,6 +335,47 @@ pub async fn get_profit_targets(
     }
 }
 
+/// GET /api/zones
+/// Returns the zones the bot is trading, as last written by the SMC tracker or `PUT`
+pub async fn get_zones(State(state): State<ApiState>) -> Result<Json<Zones>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let raw_zones: Option<String> = conn
+        .get(TRADING_BOT_ZONES)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch zones: {e}")))?;
+
+    match raw_zones {
+        Some(raw) => {
+            let zones: Zones = serde_json::from_str(&raw)
+                .map_err(|e| ApiError::RedisError(format!("Failed to deserialize zones: {e}")))?;
+            Ok(Json(zones))
+        }
+        None => Err(ApiError::NotFound("No zones stored yet".to_string())),
+    }
+}
+
+/// PUT /api/zones
+/// Overwrites the zones by hand; the next SMC run replaces them again
+pub async fn set_zones(
+    State(state): State<ApiState>,
+    Json(zones): Json<Zones>,
+) -> Result<Json<Zones>, ApiError> {
+    zones
+        .validate()
+        .map_err(|e| ApiError::InvalidInput(e.to_string()))?;
+
+    let raw = serde_json::to_string(&zones)
+        .map_err(|e| ApiError::InvalidInput(format!("Failed to serialize zones: {e}")))?;
+    let mut conn = state.redis_conn.lock().await;
+    let _: () = conn
+        .set(TRADING_BOT_ZONES, raw)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to store zones: {e}")))?;
+
+    Ok(Json(zones))
+}
+
 /// Response for trading capital
 #[derive(Debug, Serialize)]
 pub struct TradingCapitalResponse {
@@ -248,6 +407,8 @@ pub struct WeeklyRoiEntry {
     pub year: i32,
     pub week: u32,
     pub roi_percent: f64,
+    /// ROI after entry/exit fees and funding
+    pub net_roi_percent: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -261,6 +422,8 @@ pub struct MonthlyRoiEntry {
     pub year: i32,
     pub month: u32,
     pub roi_percent: f64,
+    /// ROI after entry/exit fees and funding
+    pub net_roi_percent: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -268,36 +431,41 @@ pub struct MonthlyRoiResponse {
     pub data: Vec<MonthlyRoiEntry>,
 }
 
+/// Stored rollups; rebuilt from the full list when missing or predating net ROI.
+async fn load_rollups(state: &ApiState) -> Result<Rollups, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let rollups = Rollups::load(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to load rollups: {e}")))?;
+    if !rollups.needs_rebuild() {
+        return Ok(rollups);
+    }
+
+    Rollups::rebuild(&mut conn, state.max_closed_history)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to rebuild rollups: {e}")))
+}
+
 /// GET /api/analytics/weekly
 /// Returns weekly ROI breakdown
 pub async fn get_weekly_roi(
     State(state): State<ApiState>,
 ) -> Result<Json<WeeklyRoiResponse>, ApiError> {
-    use crate::graph::Graph;
-
-    let mut conn = state.redis_conn.lock().await;
-
-    // Load all closed positions
-    let positions = Graph::load_all_closed_positions(&mut conn)
-        .await
-        .map_err(|e| ApiError::RedisError(format!("Failed to load positions: {e}")))?;
-
-    // Calculate weekly ROI
-    let mut graph = Graph::new();
-    let weekly_roi = graph.cumulative_roi_weekly(&positions);
+    let rollups = load_rollups(&state).await?;
 
-    // Convert to response format and sort by year/week
-    let mut data: Vec<WeeklyRoiEntry> = weekly_roi
+    // BTreeMap iteration is already sorted by year/week
+    let data: Vec<WeeklyRoiEntry> = rollups
+        .weekly
         .into_iter()
-        .map(|((year, week), roi)| WeeklyRoiEntry {
+        .map(|((year, week), bucket)| WeeklyRoiEntry {
             year,
             week,
-            roi_percent: roi,
+            roi_percent: bucket.roi_percent,
+            net_roi_percent: bucket.net_roi_percent.unwrap_or(bucket.roi_percent),
         })
         .collect();
 
-    data.sort_by_key(|entry| (entry.year, entry.week));
-
     Ok(Json(WeeklyRoiResponse { data }))
 }
 
@@ -306,30 +474,668 @@ pub async fn get_weekly_roi(
 pub async fn get_monthly_roi(
     State(state): State<ApiState>,
 ) -> Result<Json<MonthlyRoiResponse>, ApiError> {
-    use crate::graph::Graph;
+    let rollups = load_rollups(&state).await?;
+
+    let data: Vec<MonthlyRoiEntry> = rollups
+        .monthly
+        .into_iter()
+        .map(|((year, month), bucket)| MonthlyRoiEntry {
+            year,
+            month,
+            roi_percent: bucket.roi_percent,
+            net_roi_percent: bucket.net_roi_percent.unwrap_or(bucket.roi_percent),
+        })
+        .collect();
+
+    Ok(Json(MonthlyRoiResponse { data }))
+}
+
+/// Response for the drawdown report
+#[derive(Debug, Serialize)]
+pub struct DrawdownResponse {
+    pub max_drawdown_percent: f64,
+}
+
+/// GET /api/analytics/drawdown
+/// Returns the largest peak-to-trough decline of realised equity, in percent, with
+/// the curve starting from the same capital as the equity report
+pub async fn get_drawdown(
+    State(state): State<ApiState>,
+) -> Result<Json<DrawdownResponse>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+    let starting_capital = starting_capital(&mut conn, state.margin).await?;
+
+    let positions = Graph::load_all_closed_positions(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch positions: {e}")))?;
+
+    Ok(Json(DrawdownResponse {
+        max_drawdown_percent: Graph::max_drawdown(&positions, starting_capital),
+    }))
+}
+
+/// GET /api/analytics/zones
+/// Returns trade count, win rate and net PnL per entry zone, worst zone first
+pub async fn get_zone_performance(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<ZonePerformance>>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let positions = Graph::load_all_closed_positions(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch positions: {e}")))?;
+
+    Ok(Json(Graph::performance_by_zone(&positions)))
+}
+
+/// One zone's sizing override, as listed and set through `/api/zones/overrides`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneOverrideEntry {
+    pub zone_id: ZoneId,
+    #[serde(flatten)]
+    pub zone_override: ZoneOverride,
+}
+
+impl ZoneOverrideEntry {
+    /// Same bounds as a manual entry and the `/api/config` overrides
+    fn validate(&self) -> Result<(), ApiError> {
+        if self
+            .zone_override
+            .leverage
+            .is_some_and(|l| !(1.0..=MAX_LEVERAGE).contains(&l))
+        {
+            return Err(ApiError::InvalidInput(format!(
+                "Leverage must be between 1 and {MAX_LEVERAGE}"
+            )));
+        }
+        if self
+            .zone_override
+            .risk_pct
+            .is_some_and(|r| !(r > 0.0 && r <= 1.0))
+        {
+            return Err(ApiError::InvalidInput(
+                "Risk must be a fraction between 0 and 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// GET /api/zones/overrides
+/// Returns every per-zone leverage / risk override
+pub async fn get_zone_overrides(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<ZoneOverrideEntry>>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let overrides = ZoneOverride::load_all(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch zone overrides: {e}")))?;
+
+    Ok(Json(
+        overrides
+            .into_iter()
+            .map(|(zone_id, zone_override)| ZoneOverrideEntry {
+                zone_id,
+                zone_override,
+            })
+            .collect(),
+    ))
+}
+
+/// POST /api/zones/overrides
+/// Sets a zone's leverage / risk override; leaving both unset clears it
+pub async fn set_zone_override(
+    State(state): State<ApiState>,
+    Json(entry): Json<ZoneOverrideEntry>,
+) -> Result<Json<ZoneOverrideEntry>, ApiError> {
+    entry.validate()?;
+
+    let mut conn = state.redis_conn.lock().await;
+    entry
+        .zone_override
+        .store(&mut conn, entry.zone_id)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to store zone override: {e}")))?;
+
+    Ok(Json(entry))
+}
+
+/// POST /api/bot/pause
+/// Stops new entries; an open position is still managed to its SL/TP
+pub async fn pause_bot(State(state): State<ApiState>) -> Result<Json<BotStatus>, ApiError> {
+    set_paused(state, true).await
+}
+
+/// POST /api/bot/resume
+/// Lets the bot take new entries again
+pub async fn resume_bot(State(state): State<ApiState>) -> Result<Json<BotStatus>, ApiError> {
+    set_paused(state, false).await
+}
+
+async fn set_paused(state: ApiState, paused: bool) -> Result<Json<BotStatus>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    BotStatus::set_paused(&mut conn, paused)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to store paused state: {e}")))?;
+
+    let status = BotStatus::load(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch bot status: {e}")))?;
+    Ok(Json(status))
+}
+
+/// GET /api/bot/status
+/// Returns whether entries are paused, the current position and the last cycle time
+pub async fn get_bot_status(State(state): State<ApiState>) -> Result<Json<BotStatus>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let status = BotStatus::load(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch bot status: {e}")))?;
+    Ok(Json(status))
+}
+
+/// How long each health probe may take before it counts as failed
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Response for `/api/health`
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    pub healthy: bool,
+    pub redis: bool,
+    pub exchange: bool,
+    pub last_price: Option<f64>,
+    pub last_price_at: Option<DateTime<Utc>>,
+    pub errors: Vec<String>,
+}
+
+impl HealthResponse {
+    fn new(
+        redis: Result<(), String>,
+        exchange: Result<(), String>,
+        last_price: Option<(f64, DateTime<Utc>)>,
+    ) -> Self {
+        let errors: Vec<String> = [&redis, &exchange]
+            .into_iter()
+            .filter_map(|r| r.clone().err())
+            .collect();
+        Self {
+            healthy: errors.is_empty(),
+            redis: redis.is_ok(),
+            exchange: exchange.is_ok(),
+            last_price: last_price.map(|(price, _)| price),
+            last_price_at: last_price.map(|(_, at)| at),
+            errors,
+        }
+    }
+}
+
+/// GET /api/health
+/// 200 when redis answers PING and the exchange returns a price, 503 otherwise
+pub async fn health(State(state): State<ApiState>) -> Response {
+    let redis = {
+        let mut conn = state.redis_conn.lock().await;
+        let cmd = redis::cmd("PING");
+        let ping = cmd.query_async::<_, String>(&mut *conn);
+        match tokio::time::timeout(HEALTH_TIMEOUT, ping).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(format!("Redis PING failed: {e}")),
+            Err(_) => Err("Redis PING timed out".to_string()),
+        }
+    };
+
+    let price = tokio::time::timeout(HEALTH_TIMEOUT, state.exchange.get_current_price()).await;
+    let exchange = match price {
+        Ok(Ok(price)) if price == PRICE_UNAVAILABLE => {
+            Err("Exchange price could not be parsed".to_string())
+        }
+        Ok(Ok(price)) => {
+            *state.last_price.lock().unwrap() = Some((price, Utc::now()));
+            Ok(())
+        }
+        Ok(Err(e)) => Err(format!("Exchange price fetch failed: {e}")),
+        Err(_) => Err("Exchange price fetch timed out".to_string()),
+    };
+
+    let last_price = *state.last_price.lock().unwrap();
+    let report = HealthResponse::new(redis, exchange, last_price);
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
+}
+
+/// GET /api/stream
+/// Server-sent events with the price, position, open position and targets after every cycle
+pub async fn stream(
+    State(state): State<ApiState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let updates = state.updates.subscribe();
+
+    let events = futures_util::stream::unfold(updates, |mut updates| async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => {
+                    let event = Event::default().event("update").json_data(&update);
+                    return Some((event, updates));
+                }
+                // A slow client just misses the stale updates
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Stream subscriber lagged, skipped {skipped} updates");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Response for `/api/config`
+#[derive(Debug, Serialize)]
+pub struct ConfigResponse {
+    /// What the bot sizes with: the env config with the override applied
+    pub effective: Tunables,
+    pub overrides: RuntimeOverride,
+}
+
+/// GET /api/config
+/// Returns the runtime tunables override and the values the bot ends up using
+pub async fn get_config(State(state): State<ApiState>) -> Json<ConfigResponse> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let overrides = RuntimeOverride::load(&mut conn).await;
+    Json(ConfigResponse {
+        effective: overrides.apply(state.tunables),
+        overrides,
+    })
+}
+
+/// PUT /api/config
+/// Replaces the runtime override; the bot picks it up on its next cycle
+pub async fn set_config(
+    State(state): State<ApiState>,
+    Json(overrides): Json<RuntimeOverride>,
+) -> Result<Json<ConfigResponse>, ApiError> {
+    overrides
+        .validate()
+        .map_err(|e| ApiError::InvalidInput(e.to_string()))?;
+
+    let mut conn = state.redis_conn.lock().await;
+    overrides
+        .store(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to store config override: {e}")))?;
+
+    Ok(Json(ConfigResponse {
+        effective: overrides.apply(state.tunables),
+        overrides,
+    }))
+}
+
+/// GET /api/trackers/status
+/// Returns each tracker's last successful run and last error, to alert on a stalled tracker
+pub async fn get_tracker_status(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<TrackerStatus>>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let mut statuses = Vec::with_capacity(TRACKERS.len());
+    for name in TRACKERS {
+        statuses.push(TrackerStatus::load(&mut conn, name).await);
+    }
+    Ok(Json(statuses))
+}
 
+/// GET /api/positions/closed.csv
+/// Returns every closed position as a CSV download, for importing into spreadsheets
+pub async fn get_closed_positions_csv(State(state): State<ApiState>) -> Result<Response, ApiError> {
     let mut conn = state.redis_conn.lock().await;
 
-    // Load all closed positions
     let positions = Graph::load_all_closed_positions(&mut conn)
         .await
-        .map_err(|e| ApiError::RedisError(format!("Failed to load positions: {e}")))?;
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch positions: {e}")))?;
+
+    let mut body = Vec::new();
+    Graph::write_csv(&positions, &mut body)
+        .map_err(|e| ApiError::RedisError(format!("Failed to write CSV: {e}")))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"closed_positions.csv\"",
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Query parameters for the performance summary
+#[derive(Debug, Deserialize)]
+pub struct SummaryParams {
+    /// Sharpe observations: `trade` (default) or `daily`
+    #[serde(default)]
+    pub basis: ReturnBasis,
+    /// Risk-free return in percent per observation
+    #[serde(default)]
+    pub risk_free_rate: f64,
+    /// Only trades on this symbol; unset summarises every symbol together
+    #[serde(default)]
+    pub symbol: Option<String>,
+}
+
+/// GET /api/analytics/summary
+/// Returns win rate, profit factor, Sharpe ratio and win/loss sizes over all closed trades,
+/// or one symbol's with `?symbol=`
+pub async fn get_analytics_summary(
+    Query(params): Query<SummaryParams>,
+    State(state): State<ApiState>,
+) -> Result<Json<PerformanceSummary>, ApiError> {
+    if !params.risk_free_rate.is_finite() {
+        return Err(ApiError::InvalidInput(
+            "risk_free_rate must be a number".to_string(),
+        ));
+    }
+
+    let mut conn = state.redis_conn.lock().await;
+
+    let mut positions = Graph::load_all_closed_positions(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch positions: {e}")))?;
+    if let Some(symbol) = &params.symbol {
+        positions.retain(|p| p.symbol.as_deref() == Some(symbol.as_str()));
+    }
+
+    Ok(Json(Graph::summary(
+        &positions,
+        params.risk_free_rate,
+        params.basis,
+    )))
+}
+
+/// One point on the equity curve
+#[derive(Debug, Serialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EquityCurveResponse {
+    pub starting_capital: f64,
+    pub points: Vec<EquityPoint>,
+}
+
+/// The stored trading capital, or `margin` when none is stored
+async fn starting_capital(
+    conn: &mut MultiplexedConnection,
+    margin: f64,
+) -> Result<f64, ApiError> {
+    let raw_capital: Option<String> = conn
+        .get(TRADING_CAPITAL)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch trading capital: {e}")))?;
+    Ok(raw_capital
+        .and_then(|raw| serde_json::from_str::<Option<Decimal>>(&raw).ok().flatten())
+        .and_then(|capital| capital.to_f64())
+        .unwrap_or(margin))
+}
+
+/// GET /api/analytics/equity
+/// Returns account equity after each closed trade, starting from the stored trading
+/// capital (or the configured margin when none is stored)
+pub async fn get_equity_curve(
+    State(state): State<ApiState>,
+) -> Result<Json<EquityCurveResponse>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
 
-    // Calculate monthly ROI
-    let mut graph = Graph::new();
-    let monthly_roi = graph.cumulative_roi_monthly(&positions);
+    let starting_capital = starting_capital(&mut conn, state.margin).await?;
 
-    // Convert to response format and sort by year/month
-    let mut data: Vec<MonthlyRoiEntry> = monthly_roi
+    let positions = Graph::load_all_closed_positions(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch positions: {e}")))?;
+
+    let points = Graph::equity_curve(&positions, starting_capital)
         .into_iter()
-        .map(|((year, month), roi)| MonthlyRoiEntry {
-            year,
-            month,
-            roi_percent: roi,
-        })
+        .map(|(timestamp, equity)| EquityPoint { timestamp, equity })
         .collect();
 
-    data.sort_by_key(|entry| (entry.year, entry.month));
+    Ok(Json(EquityCurveResponse {
+        starting_capital,
+        points,
+    }))
+}
 
-    Ok(Json(MonthlyRoiResponse { data }))
+/// Response for a rollup recompute
+#[derive(Debug, Serialize)]
+pub struct RecomputeRollupsResponse {
+    pub weeks: usize,
+    pub months: usize,
+}
+
+/// POST /api/admin/rollups/recompute
+/// Rebuilds the weekly/monthly rollups from the full closed-positions list
+pub async fn recompute_rollups(
+    State(state): State<ApiState>,
+) -> Result<Json<RecomputeRollupsResponse>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let rollups = Rollups::rebuild(&mut conn, state.max_closed_history)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to rebuild rollups: {e}")))?;
+
+    Ok(Json(RecomputeRollupsResponse {
+        weeks: rollups.weekly.len(),
+        months: rollups.monthly.len(),
+    }))
+}
+
+/// GET /api/analytics/momentum
+/// Returns the latest 5m momentum indicators, or null if the tracker hasn't written any
+pub async fn get_momentum(
+    State(state): State<ApiState>,
+) -> Result<Json<Option<MomentumIndicators>>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let raw: Option<String> = conn
+        .get(TRADING_BOT_MOMENTUM_INDICATORS)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch momentum: {e}")))?;
+
+    match raw {
+        Some(raw) => {
+            let indicators: MomentumIndicators = serde_json::from_str(&raw).map_err(|e| {
+                ApiError::RedisError(format!("Failed to deserialize momentum: {e}"))
+            })?;
+            Ok(Json(Some(indicators)))
+        }
+        None => Ok(Json(None)),
+    }
+}
+
+/// Response for the macro guard's no-trade windows
+#[derive(Debug, Serialize)]
+pub struct MacroWindowsResponse {
+    pub trading_allowed: bool,
+    /// Windows containing `now`
+    pub active: Vec<NoTradeWindow>,
+    pub windows: Vec<NoTradeWindow>,
+}
+
+impl MacroWindowsResponse {
+    pub fn at(now: DateTime<Utc>, windows: Vec<NoTradeWindow>) -> Self {
+        let active = windows
+            .iter()
+            .filter(|w| now >= w.start && now <= w.end)
+            .cloned()
+            .collect();
+        Self {
+            trading_allowed: MacroGuard::trading_allowed(now, &windows),
+            active,
+            windows,
+        }
+    }
+}
+
+/// GET /api/macro/windows
+/// Returns the macro no-trade windows the bot last built and whether entries are allowed now
+pub async fn get_macro_windows(
+    State(state): State<ApiState>,
+) -> Result<Json<MacroWindowsResponse>, ApiError> {
+    let mut conn = state.redis_conn.lock().await;
+
+    let windows = MacroGuard::load_published(&mut conn)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch macro windows: {e}")))?;
+
+    Ok(Json(MacroWindowsResponse::at(Utc::now(), windows)))
+}
+
+/// Paginated response for missed entries
+#[derive(Debug, Serialize)]
+pub struct MissedEntriesResponse {
+    pub entries: Vec<MissedEntry>,
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
+}
+
+/// GET /api/missed-entries
+/// Returns zone entries that a guard or the exchange stopped, newest first
+pub async fn get_missed_entries(
+    Query(params): Query<PaginationParams>,
+    State(state): State<ApiState>,
+) -> Result<Json<MissedEntriesResponse>, ApiError> {
+    if params.page == 0 {
+        return Err(ApiError::InvalidInput(
+            "Page must be greater than 0".to_string(),
+        ));
+    }
+    if params.limit == 0 || params.limit > 20 {
+        return Err(ApiError::InvalidInput(
+            "Limit must be between 1 and 20".to_string(),
+        ));
+    }
+
+    let mut conn = state.redis_conn.lock().await;
+
+    let total: usize = conn
+        .llen(TRADING_BOT_MISSED_ENTRIES)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to count missed entries: {e}")))?;
+
+    let start = (params.page - 1) * params.limit;
+    let end = start + params.limit - 1;
+    let entries = MissedEntry::load(&mut conn, start as isize, end as isize)
+        .await
+        .map_err(|e| ApiError::RedisError(format!("Failed to fetch missed entries: {e}")))?;
+
+    Ok(Json(MissedEntriesResponse {
+        entries,
+        total,
+        page: params.page,
+        limit: params.limit,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::{Side, Zone};
+    use chrono::Duration;
+
+    #[test]
+    fn test_open_position_request_is_validated() {
+        let request = |side, leverage, risk_pct| OpenPositionRequest {
+            side,
+            entry_price: 65000.0,
+            leverage,
+            risk_pct,
+        };
+
+        assert!(request(Position::Long, 10.0, 0.02).validate().is_ok());
+        assert!(request(Position::Flat, 10.0, 0.02).validate().is_err());
+        assert!(request(Position::Short, 0.5, 0.02).validate().is_err());
+        assert!(request(Position::Short, 200.0, 0.02).validate().is_err());
+        assert!(request(Position::Long, 10.0, 0.0).validate().is_err());
+        assert!(request(Position::Long, 10.0, 1.5).validate().is_err());
+    }
+
+    #[test]
+    fn test_zone_override_is_validated() {
+        let entry = |leverage, risk_pct| ZoneOverrideEntry {
+            zone_id: ZoneId::from_zone(&Zone {
+                low: 59_000.0,
+                high: 60_000.0,
+                side: Side::Long,
+            }),
+            zone_override: ZoneOverride { leverage, risk_pct },
+        };
+
+        assert!(entry(Some(10.0), Some(0.02)).validate().is_ok());
+        assert!(entry(None, None).validate().is_ok());
+        assert!(entry(Some(MAX_LEVERAGE), None).validate().is_ok());
+        assert!(entry(Some(200.0), None).validate().is_err());
+        assert!(entry(Some(0.5), None).validate().is_err());
+        assert!(entry(Some(f64::NAN), None).validate().is_err());
+        assert!(entry(None, Some(0.0)).validate().is_err());
+        assert!(entry(None, Some(1.5)).validate().is_err());
+    }
+
+    #[test]
+    fn test_health_reports_each_failure() {
+        let at = Utc::now();
+
+        let report = HealthResponse::new(Ok(()), Ok(()), Some((65000.0, at)));
+        assert!(report.healthy);
+        assert_eq!(report.last_price, Some(65000.0));
+        assert_eq!(report.last_price_at, Some(at));
+
+        let report = HealthResponse::new(
+            Ok(()),
+            Err("Exchange price fetch timed out".to_string()),
+            Some((65000.0, at)),
+        );
+        assert!(!report.healthy);
+        assert!(report.redis);
+        assert!(!report.exchange);
+        assert_eq!(report.errors, vec!["Exchange price fetch timed out"]);
+        // the last good price is still reported
+        assert_eq!(report.last_price, Some(65000.0));
+    }
+
+    #[test]
+    fn test_macro_windows_report_active_window() {
+        let now = Utc::now();
+        let window = |start: Duration, end: Duration, reason: &str| NoTradeWindow {
+            start: now + start,
+            end: now + end,
+            reason: reason.to_string(),
+        };
+        let windows = vec![
+            window(
+                Duration::hours(-1),
+                Duration::hours(1),
+                "Fed Interest Rate Decision",
+            ),
+            window(Duration::hours(20), Duration::hours(44), "Core CPI (MoM)"),
+        ];
+
+        let report = MacroWindowsResponse::at(now, windows);
+        assert!(!report.trading_allowed);
+        assert_eq!(report.active.len(), 1);
+        assert_eq!(report.active[0].reason, "Fed Interest Rate Decision");
+        assert_eq!(report.windows.len(), 2);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["trading_allowed"], false);
+        assert_eq!(json["active"][0]["reason"], "Fed Interest Rate Decision");
+    }
 }
@@ -1,22 +1,73 @@
+pub mod auth;
 pub mod handlers;
 
-use axum::{routing::get, Router};
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
 use redis::aio::MultiplexedConnection;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tower_http::cors::{Any, CorsLayer};
 
+use crate::bot::command::BotCommand;
+use crate::bot::feed::BotUpdate;
+use crate::config::runtime::Tunables;
+use crate::exchange::Exchange;
+
+/// A fetched price and when it was fetched
+pub type PriceAt = (f64, DateTime<Utc>);
+
 /// Shared state for API handlers
 #[derive(Clone)]
 pub struct ApiState {
     pub redis_conn: Arc<Mutex<MultiplexedConnection>>,
+    /// Configured `MARGIN`, used when no trading capital has been stored yet
+    pub margin: f64,
+    /// Env sizing tunables that `/api/config` overrides are applied to
+    pub tunables: Tunables,
+    /// Same exchange the bot trades through, for the health check's price
+    pub exchange: Arc<dyn Exchange>,
+    /// Configured `MAX_CLOSED_HISTORY`, so a rollup rebuild knows when the list is capped
+    pub max_closed_history: usize,
+    /// Operator actions on the position, which the bot loop carries out
+    pub commands: mpsc::Sender<BotCommand>,
+    /// The bot loop's per-cycle state, fanned out to `/api/stream` subscribers
+    pub updates: broadcast::Sender<BotUpdate>,
+    /// Configured `API_TOKEN`; `None` leaves the API unauthenticated and read-only
+    pub api_token: Option<String>,
+    /// Last price `/api/health` fetched successfully, and when
+    pub last_price: Arc<std::sync::Mutex<Option<PriceAt>>>,
 }
 
 /// Create and configure the API router
-pub fn create_router(redis_conn: MultiplexedConnection) -> Router {
+#[allow(clippy::too_many_arguments)]
+pub fn create_router(
+    redis_conn: MultiplexedConnection,
+    exchange: Arc<dyn Exchange>,
+    margin: f64,
+    tunables: Tunables,
+    max_closed_history: usize,
+    commands: mpsc::Sender<BotCommand>,
+    updates: broadcast::Sender<BotUpdate>,
+    api_token: Option<String>,
+) -> Router {
     let state = ApiState {
         redis_conn: Arc::new(Mutex::new(redis_conn)),
+        margin,
+        tunables,
+        exchange,
+        max_closed_history,
+        commands,
+        updates,
+        api_token,
+        last_price: Arc::new(std::sync::Mutex::new(None)),
     };
+    if state.api_token.is_none() {
+        log::warn!("API_TOKEN is not set, the API is read-only");
+    }
 
     // Configure CORS to allow all origins (adjust for production)
     let cors = CorsLayer::new()
@@ -26,7 +77,13 @@ pub fn create_router(redis_conn: MultiplexedConnection) -> Router {
 
     Router::new()
         .route("/api/positions/closed", get(handlers::get_closed_positions))
+        .route(
+            "/api/positions/closed.csv",
+            get(handlers::get_closed_positions_csv),
+        )
         .route("/api/positions/active", get(handlers::get_active_position))
+        .route("/api/positions/open", post(handlers::open_position))
+        .route("/api/positions/close", post(handlers::close_position))
         .route(
             "/api/positions/profit-targets",
             get(handlers::get_profit_targets),
@@ -34,6 +91,40 @@ pub fn create_router(redis_conn: MultiplexedConnection) -> Router {
         .route("/api/capital", get(handlers::get_trading_capital))
         .route("/api/analytics/weekly", get(handlers::get_weekly_roi))
         .route("/api/analytics/monthly", get(handlers::get_monthly_roi))
+        .route("/api/analytics/momentum", get(handlers::get_momentum))
+        .route("/api/analytics/drawdown", get(handlers::get_drawdown))
+        .route("/api/analytics/summary", get(handlers::get_analytics_summary))
+        .route("/api/analytics/equity", get(handlers::get_equity_curve))
+        .route("/api/analytics/zones", get(handlers::get_zone_performance))
+        .route("/api/macro/windows", get(handlers::get_macro_windows))
+        .route("/api/missed-entries", get(handlers::get_missed_entries))
+        .route("/api/trackers/status", get(handlers::get_tracker_status))
+        .route("/api/bot/pause", post(handlers::pause_bot))
+        .route("/api/bot/resume", post(handlers::resume_bot))
+        .route("/api/bot/status", get(handlers::get_bot_status))
+        .route("/api/stream", get(handlers::stream))
+        .route(
+            "/api/config",
+            get(handlers::get_config).put(handlers::set_config),
+        )
+        .route(
+            "/api/zones",
+            get(handlers::get_zones).put(handlers::set_zones),
+        )
+        .route(
+            "/api/zones/overrides",
+            get(handlers::get_zone_overrides).post(handlers::set_zone_override),
+        )
+        .route(
+            "/api/admin/rollups/recompute",
+            post(handlers::recompute_rollups),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_token,
+        ))
+        // Readiness probes can't carry the token
+        .route("/api/health", get(handlers::health))
         .layer(cors)
         .with_state(state)
 }
@@ -0,0 +1,217 @@
+use log::info;
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::helper::{Helper, TRADING_CAPITAL};
+
+/// Strategies that draw from the shared trading capital.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Strategy {
+    Ranger,
+    Scalper,
+    Capitulation,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [Strategy::Ranger, Strategy::Scalper, Strategy::Capitulation];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Strategy::Ranger => "ranger",
+            Strategy::Scalper => "scalper",
+            Strategy::Capitulation => "capitulation",
+        }
+    }
+
+    /// Redis key holding this strategy's sub-allocation
+    pub fn capital_key(&self) -> String {
+        format!("{TRADING_CAPITAL}:{}", self.as_str())
+    }
+}
+
+/// Per-strategy slices of the trading capital. Each strategy sizes off its own slice
+/// and only its own PnL moves it; `TRADING_CAPITAL` is kept as the sum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Allocations {
+    pub by_strategy: BTreeMap<Strategy, Decimal>,
+}
+
+impl Allocations {
+    pub fn get(&self, strategy: Strategy) -> Decimal {
+        self.by_strategy.get(&strategy).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CapitalAllocator {
+    weights: BTreeMap<Strategy, Decimal>,
+}
+
+impl CapitalAllocator {
+    /// Weights are normalised, so `1, 1, 2` and `0.25, 0.25, 0.5` mean the same split.
+    /// All-zero weights fall back to giving everything to the ranger.
+    pub fn new(ranger: f64, scalper: f64, capitulation: f64) -> Self {
+        let raw = [
+            (Strategy::Ranger, Helper::f64_to_decimal(ranger.max(0.0))),
+            (Strategy::Scalper, Helper::f64_to_decimal(scalper.max(0.0))),
+            (Strategy::Capitulation, Helper::f64_to_decimal(capitulation.max(0.0))),
+        ];
+        let sum: Decimal = raw.iter().map(|(_, w)| *w).sum();
+
+        let weights = if sum.is_zero() {
+            raw.iter()
+                .map(|(s, _)| (*s, if *s == Strategy::Ranger { dec!(1) } else { dec!(0) }))
+                .collect()
+        } else {
+            raw.iter().map(|(s, w)| (*s, *w / sum)).collect()
+        };
+
+        Self { weights }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.ranger_capital_weight,
+            config.scalper_capital_weight,
+            config.capitulation_capital_weight,
+        )
+    }
+
+    pub fn weight(&self, strategy: Strategy) -> Decimal {
+        self.weights.get(&strategy).copied().unwrap_or_default()
+    }
+
+    /// Splits `total` across strategies by weight
+    pub fn split(&self, total: Decimal) -> Allocations {
+        Allocations {
+            by_strategy: Strategy::ALL
+                .iter()
+                .map(|s| (*s, (total * self.weight(*s)).round_dp(2)))
+                .collect(),
+        }
+    }
+
+    /// Reads a strategy's slice. When it has never been stored, it is carved out of the
+    /// existing total (`TRADING_CAPITAL`) or, failing that, out of `fallback_total`.
+    pub async fn load(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        strategy: Strategy,
+        fallback_total: Decimal,
+    ) -> Decimal {
+        let raw: Option<String> = conn.get(strategy.capital_key()).await.unwrap_or(None);
+        if let Some(v) = raw.and_then(|r| serde_json::from_str::<Decimal>(&r).ok()) {
+            return v;
+        }
+
+        let total_raw: Option<String> = conn.get(TRADING_CAPITAL).await.unwrap_or(None);
+        let total = total_raw
+            .and_then(|r| serde_json::from_str::<Decimal>(&r).ok())
+            .unwrap_or(fallback_total);
+
+        let seeded = self.split(total).get(strategy);
+        info!("CapitalAllocator: seeding {strategy:?} with {seeded} of {total}");
+        seeded
+    }
+
+    /// The slices to store when `strategy`'s becomes `amount`. Slices never stored yet
+    /// are seeded from `total`, the capital before any slice existed, so a strategy
+    /// that hasn't traded keeps its share instead of counting as zero.
+    pub fn settle(
+        &self,
+        stored: &BTreeMap<Strategy, Decimal>,
+        total: Decimal,
+        strategy: Strategy,
+        amount: Decimal,
+    ) -> Allocations {
+        let seeds = self.split(total);
+        let mut by_strategy: BTreeMap<Strategy, Decimal> = Strategy::ALL
+            .iter()
+            .map(|s| (*s, stored.get(s).copied().unwrap_or(seeds.get(*s))))
+            .collect();
+        by_strategy.insert(strategy, amount);
+        Allocations { by_strategy }
+    }
+
+    /// Stores a strategy's slice, seeding any slice not stored yet (see `settle`), and
+    /// refreshes `TRADING_CAPITAL` as the sum of all slices.
+    pub async fn store(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        strategy: Strategy,
+        amount: Decimal,
+        fallback_total: Decimal,
+    ) -> anyhow::Result<()> {
+        let mut stored = BTreeMap::new();
+        for s in Strategy::ALL {
+            let raw: Option<String> = conn.get(s.capital_key()).await?;
+            if let Some(v) = raw.and_then(|r| serde_json::from_str::<Decimal>(&r).ok()) {
+                stored.insert(s, v);
+            }
+        }
+        let total_raw: Option<String> = conn.get(TRADING_CAPITAL).await?;
+        let total = total_raw
+            .and_then(|r| serde_json::from_str::<Decimal>(&r).ok())
+            .unwrap_or(fallback_total);
+
+        let slices = self.settle(&stored, total, strategy, amount);
+        let sum: Decimal = slices.by_strategy.values().copied().sum();
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (s, v) in &slices.by_strategy {
+            pipe.set(s.capital_key(), serde_json::to_string(v)?).ignore();
+        }
+        pipe.set(TRADING_CAPITAL, serde_json::to_string(&sum)?).ignore();
+        let _: () = pipe.query_async(conn).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_follows_normalised_weights() {
+        let allocator = CapitalAllocator::new(2.0, 1.0, 1.0);
+        let alloc = allocator.split(dec!(1000));
+
+        assert_eq!(alloc.get(Strategy::Ranger), dec!(500));
+        assert_eq!(alloc.get(Strategy::Scalper), dec!(250));
+        assert_eq!(alloc.get(Strategy::Capitulation), dec!(250));
+        assert_eq!(alloc.by_strategy.values().copied().sum::<Decimal>(), dec!(1000));
+    }
+
+    #[test]
+    fn test_first_store_seeds_the_other_slices_from_the_original_total() {
+        let allocator = CapitalAllocator::new(0.5, 0.3, 0.2);
+
+        // nothing stored yet: the ranger banks 10 on its 500 of 1000
+        let slices = allocator.settle(&BTreeMap::new(), dec!(1000), Strategy::Ranger, dec!(510));
+        assert_eq!(slices.get(Strategy::Ranger), dec!(510));
+        assert_eq!(slices.get(Strategy::Scalper), dec!(300));
+        assert_eq!(slices.get(Strategy::Capitulation), dec!(200));
+
+        // the new total is 1010, yet the scalper's later win only moves its own slice
+        let stored = slices.by_strategy;
+        let total: Decimal = stored.values().copied().sum();
+        assert_eq!(total, dec!(1010));
+        let slices = allocator.settle(&stored, total, Strategy::Scalper, dec!(342.5));
+        assert_eq!(slices.get(Strategy::Scalper), dec!(342.5));
+        assert_eq!(slices.get(Strategy::Ranger), dec!(510));
+        assert_eq!(slices.get(Strategy::Capitulation), dec!(200));
+    }
+
+    #[test]
+    fn test_zero_weights_fall_back_to_ranger() {
+        let allocator = CapitalAllocator::new(0.0, 0.0, 0.0);
+        assert_eq!(allocator.weight(Strategy::Ranger), dec!(1));
+        assert_eq!(allocator.split(dec!(300)).get(Strategy::Ranger), dec!(300));
+    }
+}
@@ -0,0 +1,121 @@
+use rust_decimal::Decimal;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::bot::{ClosedPosition, OpenPosition, Position};
+use crate::exchange::bitget::PlaceOrderData;
+
+/// Operator commands that can wait for the loop; more and the API waits to send
+const COMMAND_CAPACITY: usize = 8;
+
+/// Where the loop sends a command's outcome
+pub type Reply<T> = oneshot::Sender<Result<T, CommandError>>;
+
+/// Operator actions the API hands to the trading loop. The loop owns the position,
+/// so it carries them out between ticks instead of the API writing its state.
+#[derive(Debug)]
+pub enum BotCommand {
+    /// Enter at market on the primary symbol; the bot manages it from then on
+    Open {
+        entry: ManualEntry,
+        reply: Reply<(OpenPosition, PlaceOrderData)>,
+    },
+    /// Close the primary symbol's position at market; `None` when already flat
+    Close { reply: Reply<Option<ClosedPosition>> },
+}
+
+/// A discretionary entry, sized like the ranger's own off its current margin
+#[derive(Debug, Clone, Copy)]
+pub struct ManualEntry {
+    pub side: Position,
+    pub entry_price: Decimal,
+    pub leverage: Decimal,
+    pub risk_pct: Decimal,
+}
+
+/// Why a command wasn't carried out
+#[derive(Debug)]
+pub enum CommandError {
+    /// Turned down before anything reached the exchange
+    Rejected(String),
+    /// The exchange or the store failed on the way
+    Failed(anyhow::Error),
+    /// No trading loop is running to take it
+    Unavailable,
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Failed(e)
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rejected(reason) => write!(f, "{reason}"),
+            Self::Failed(e) => write!(f, "{e}"),
+            Self::Unavailable => write!(f, "The trading loop is not running"),
+        }
+    }
+}
+
+pub fn channel() -> (mpsc::Sender<BotCommand>, mpsc::Receiver<BotCommand>) {
+    mpsc::channel(COMMAND_CAPACITY)
+}
+
+/// Hands a command to the trading loop and waits for it to be carried out
+pub async fn ask<T>(
+    commands: &mpsc::Sender<BotCommand>,
+    command: impl FnOnce(Reply<T>) -> BotCommand,
+) -> Result<T, CommandError> {
+    let (reply, answer) = oneshot::channel();
+    commands
+        .send(command(reply))
+        .await
+        .map_err(|_| CommandError::Unavailable)?;
+    answer.await.map_err(|_| CommandError::Unavailable)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_commands_are_answered_by_the_loop() {
+        let (commands, mut inbox) = channel();
+        // stands in for the trading loop
+        tokio::spawn(async move {
+            while let Some(command) = inbox.recv().await {
+                match command {
+                    BotCommand::Close { reply } => {
+                        let _ = reply.send(Ok(None));
+                    }
+                    BotCommand::Open { reply, .. } => {
+                        let _ = reply.send(Err(CommandError::Rejected("already open".into())));
+                    }
+                }
+            }
+        });
+
+        let closed = ask(&commands, |reply| BotCommand::Close { reply }).await;
+        assert!(matches!(closed, Ok(None)));
+
+        let entry = ManualEntry {
+            side: Position::Long,
+            entry_price: Decimal::from(65_000),
+            leverage: Decimal::from(10),
+            risk_pct: Decimal::new(5, 2),
+        };
+        let opened = ask(&commands, |reply| BotCommand::Open { entry, reply }).await;
+        assert!(matches!(opened, Err(CommandError::Rejected(_))));
+    }
+
+    #[tokio::test]
+    async fn test_commands_without_a_loop_are_unavailable() {
+        let (commands, inbox) = channel();
+        drop(inbox);
+
+        let closed = ask(&commands, |reply| BotCommand::Close { reply }).await;
+        assert!(matches!(closed, Err(CommandError::Unavailable)));
+    }
+}
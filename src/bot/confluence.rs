@@ -2,16 +2,25 @@ use log::warn;
 use redis::AsyncCommands;
 use serde::Deserialize;
 
+use crate::bot::missed::MissReason;
+use crate::bot::Position;
+use crate::config::Config;
 use crate::helper::{
-    TRADING_BOT_GAUSSIAN_3D, TRADING_BOT_ICHIMOKU_CROSS,
+    TRADING_BOT_GAUSSIAN_3D, TRADING_BOT_ICHIMOKU_CROSS, TRADING_BOT_ICHIMOKU_KUMO,
+    TRADING_BOT_ICHIMOKU_SIGNALS, TRADING_BOT_MOMENTUM,
     TRADING_BOT_RSI_DIV_1D, TRADING_BOT_RSI_DIV_4H,
-    TRADING_BOT_RSI_REGIME, TRADING_BOT_TREND_STATE,
+    TRADING_BOT_RSI_REGIME, TRADING_BOT_RSI_SNAPSHOT_15M, TRADING_BOT_SMC_SIGNAL,
+    TRADING_BOT_TREND_STATE,
 };
 use crate::regime::{GaussianRegime3D, GaussianRegime3DSnapshot};
-use crate::trackers::ichimoku::{IchimokuCrossSnapshot, IchimokuCrossState};
+use crate::trackers::ichimoku::{
+    ichimoku_bias, Bias, IchimokuCrossSnapshot, IchimokuCrossState, IchimokuSignals,
+    KumoSnapshot, TenkanKijunCross,
+};
+use crate::trackers::momentum::{MomentumSignal, MomentumSnapshot};
 use crate::trackers::rsi_divergence_indicator::{RsiDivEvent, RsiDivSnapshot};
-use crate::trackers::rsi_regime_tracker::{RegimeState, RsiRegimeSnapshot};
-use crate::trackers::smart_money_concepts::{TrendDirection, TrendState};
+use crate::trackers::rsi_regime_tracker::{RegimeState, RsiRegimeSnapshot, RsiSnapshot};
+use crate::trackers::smart_money_concepts::{SmcSignal, StrongKind, TrendDirection, TrendState};
 
 pub struct ConfluenceGate {
     pub trend_direction: Option<TrendDirection>,
@@ -20,6 +29,15 @@ pub struct ConfluenceGate {
     pub gaussian_3d:     Option<GaussianRegime3D>,
     pub rsi_div_4h:      Option<Vec<RsiDivEvent>>,
     pub rsi_div_1d:      Option<Vec<RsiDivEvent>>,
+    /// Cached short-term RSI used by the optional entry filter.
+    pub entry_rsi:       Option<f64>,
+    /// Latest Strong Low / Strong High, for `REQUIRE_CONFLUENCE`.
+    pub smc_strong:      Option<StrongKind>,
+    pub momentum:        Option<MomentumSnapshot>,
+    /// Latest weekly cloud, for the optional Kumo direction filter.
+    pub kumo:            Option<KumoSnapshot>,
+    /// Weekly Tenkan/Kijun bias, for the optional weekly bias filter.
+    pub weekly_bias:     Option<TenkanKijunCross>,
 }
 
 impl ConfluenceGate {
@@ -43,7 +61,226 @@ impl ConfluenceGate {
             rsi_div_1d: read_json::<RsiDivSnapshot>(conn, TRADING_BOT_RSI_DIV_1D)
                 .await
                 .map(|s| s.events),
+            entry_rsi: read_json::<RsiSnapshot>(conn, TRADING_BOT_RSI_SNAPSHOT_15M)
+                .await
+                .map(|s| s.rsi),
+            smc_strong: read_json::<SmcSignal>(conn, TRADING_BOT_SMC_SIGNAL)
+                .await
+                .and_then(|s| s.kind),
+            momentum: read_json::<MomentumSnapshot>(conn, TRADING_BOT_MOMENTUM).await,
+            kumo: read_json::<KumoSnapshot>(conn, TRADING_BOT_ICHIMOKU_KUMO).await,
+            weekly_bias: read_json::<IchimokuSignals>(conn, TRADING_BOT_ICHIMOKU_SIGNALS)
+                .await
+                .and_then(|s| s.bias()),
+        }
+    }
+
+    /// RSI entry filter: refuses longs into an overbought RSI.
+    /// A missing snapshot never blocks — the filter only vetoes on data it has.
+    pub fn rsi_permits_long(&self, max_entry_rsi: f64) -> bool {
+        match self.entry_rsi {
+            Some(rsi) if rsi > max_entry_rsi => {
+                warn!("ConfluenceGate: long vetoed — RSI {rsi:.2} > {max_entry_rsi:.2}");
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// RSI entry filter: refuses shorts into an oversold RSI.
+    pub fn rsi_permits_short(&self, min_entry_rsi: f64) -> bool {
+        match self.entry_rsi {
+            Some(rsi) if rsi < min_entry_rsi => {
+                warn!("ConfluenceGate: short vetoed — RSI {rsi:.2} < {min_entry_rsi:.2}");
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Momentum entry filter: refuses longs while 5m momentum is strongly bearish.
+    /// No snapshot (tracker off or key expired) never blocks.
+    pub fn momentum_permits_long(&self) -> bool {
+        if self.momentum.as_ref().is_some_and(|m| m.strong_bearish) {
+            warn!("ConfluenceGate: long vetoed — momentum strongly bearish");
+            return false;
+        }
+        true
+    }
+
+    /// Momentum entry filter: refuses shorts while 5m momentum is strongly bullish.
+    pub fn momentum_permits_short(&self) -> bool {
+        if self.momentum.as_ref().is_some_and(|m| m.strong_bullish) {
+            warn!("ConfluenceGate: short vetoed — momentum strongly bullish");
+            return false;
+        }
+        true
+    }
+
+    /// Kumo filter: longs only above the weekly cloud. No stored cloud never blocks.
+    pub fn kumo_permits_long(&self, price: f64) -> bool {
+        match self.kumo.as_ref().map(|k| ichimoku_bias(price, k)) {
+            Some(Bias::Above) | None => true,
+            Some(bias) => {
+                warn!("ConfluenceGate: long vetoed — price {price:.2} {bias:?} the cloud");
+                false
+            }
+        }
+    }
+
+    /// Kumo filter: shorts only below the weekly cloud.
+    pub fn kumo_permits_short(&self, price: f64) -> bool {
+        match self.kumo.as_ref().map(|k| ichimoku_bias(price, k)) {
+            Some(Bias::Below) | None => true,
+            Some(bias) => {
+                warn!("ConfluenceGate: short vetoed — price {price:.2} {bias:?} the cloud");
+                false
+            }
+        }
+    }
+
+    /// Weekly bias filter: no longs against a bearish weekly Tenkan/Kijun bias.
+    /// No stored signals never blocks.
+    pub fn weekly_bias_permits_long(&self) -> bool {
+        if self.weekly_bias == Some(TenkanKijunCross::Bearish) {
+            warn!("ConfluenceGate: long vetoed — weekly bias bearish");
+            return false;
+        }
+        true
+    }
+
+    /// Weekly bias filter: no shorts against a bullish weekly bias.
+    pub fn weekly_bias_permits_short(&self) -> bool {
+        if self.weekly_bias == Some(TenkanKijunCross::Bullish) {
+            warn!("ConfluenceGate: short vetoed — weekly bias bullish");
+            return false;
+        }
+        true
+    }
+
+    /// High-conviction long: the latest SMC event must be a Strong Low and momentum
+    /// must not be strongly bearish. Unlike the vetoes, a missing SMC signal blocks.
+    pub fn confirms_long(&self) -> bool {
+        if self.smc_strong != Some(StrongKind::StrongLow) {
+            warn!("ConfluenceGate: long skipped — no supporting Strong Low ({:?})", self.smc_strong);
+            return false;
+        }
+        if self.momentum.as_ref().is_some_and(|m| m.strong_bearish) {
+            warn!("ConfluenceGate: long skipped — momentum strongly bearish");
+            return false;
+        }
+        true
+    }
+
+    /// High-conviction short: latest SMC event is a Strong High and momentum isn't strongly bullish.
+    pub fn confirms_short(&self) -> bool {
+        if self.smc_strong != Some(StrongKind::StrongHigh) {
+            warn!("ConfluenceGate: short skipped — no supporting Strong High ({:?})", self.smc_strong);
+            return false;
+        }
+        if self.momentum.as_ref().is_some_and(|m| m.strong_bullish) {
+            warn!("ConfluenceGate: short skipped — momentum strongly bullish");
+            return false;
         }
+        true
+    }
+
+    /// Signals currently against an open `pos`: a momentum reversal, an opposing
+    /// SMC Strong event, and the Kijun/SpanB cross flipped the other way.
+    pub fn opposing_signals(&self, pos: Position) -> u8 {
+        let (momentum, strong, cross) = match pos {
+            Position::Long => (
+                self.momentum
+                    .as_ref()
+                    .is_some_and(|m| m.overall_signal == MomentumSignal::Bearish),
+                self.smc_strong == Some(StrongKind::StrongHigh),
+                self.ichimoku_cross == Some(IchimokuCrossState::KijunBelowSpanB),
+            ),
+            Position::Short => (
+                self.momentum
+                    .as_ref()
+                    .is_some_and(|m| m.overall_signal == MomentumSignal::Bullish),
+                self.smc_strong == Some(StrongKind::StrongLow),
+                self.ichimoku_cross == Some(IchimokuCrossState::KijunAboveSpanB),
+            ),
+            Position::Flat => return 0,
+        };
+        [momentum, strong, cross].into_iter().filter(|&s| s).count() as u8
+    }
+
+    /// Ensemble exit: close a winning position once `threshold` signals agree
+    /// against it, before a TP is reached. Losers are left to the stop; a
+    /// threshold of 0 disables it.
+    pub fn ensemble_exit(
+        &self,
+        pos: Position,
+        entry_price: f64,
+        price: f64,
+        threshold: u8,
+    ) -> bool {
+        let in_profit = match pos {
+            Position::Long => price > entry_price,
+            Position::Short => price < entry_price,
+            Position::Flat => false,
+        };
+        if threshold == 0 || !in_profit {
+            return false;
+        }
+        let against = self.opposing_signals(pos);
+        if against >= threshold {
+            warn!("ConfluenceGate: ensemble exit — {against} signals against the {pos:?}");
+            return true;
+        }
+        false
+    }
+
+    /// First filter standing in the way of an entry on `side`, checked in the
+    /// order the entry flow has always applied them; `None` lets the entry through.
+    pub fn blocks_entry(&self, side: Position, price: f64, config: &Config) -> Option<MissReason> {
+        match side {
+            Position::Long => {
+                if !self.permits_long() {
+                    return Some(MissReason::ConfluenceVeto);
+                }
+                if config.use_rsi_filter && !self.rsi_permits_long(config.max_entry_rsi) {
+                    return Some(MissReason::RsiFilter);
+                }
+                if config.use_momentum_filter && !self.momentum_permits_long() {
+                    return Some(MissReason::MomentumFilter);
+                }
+                if config.use_kumo_filter && !self.kumo_permits_long(price) {
+                    return Some(MissReason::KumoFilter);
+                }
+                if config.use_weekly_bias_filter && !self.weekly_bias_permits_long() {
+                    return Some(MissReason::WeeklyBiasFilter);
+                }
+                if config.require_confluence && !self.confirms_long() {
+                    return Some(MissReason::NoConfluence);
+                }
+            }
+            Position::Short => {
+                if !self.permits_short() {
+                    return Some(MissReason::ConfluenceVeto);
+                }
+                if config.use_rsi_filter && !self.rsi_permits_short(config.min_entry_rsi) {
+                    return Some(MissReason::RsiFilter);
+                }
+                if config.use_momentum_filter && !self.momentum_permits_short() {
+                    return Some(MissReason::MomentumFilter);
+                }
+                if config.use_kumo_filter && !self.kumo_permits_short(price) {
+                    return Some(MissReason::KumoFilter);
+                }
+                if config.use_weekly_bias_filter && !self.weekly_bias_permits_short() {
+                    return Some(MissReason::WeeklyBiasFilter);
+                }
+                if config.require_confluence && !self.confirms_short() {
+                    return Some(MissReason::NoConfluence);
+                }
+            }
+            Position::Flat => {}
+        }
+        None
     }
 
     pub fn permits_long(&self) -> bool {
@@ -185,3 +422,169 @@ async fn read_json<T: for<'de> Deserialize<'de>>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate_with_rsi(entry_rsi: Option<f64>) -> ConfluenceGate {
+        ConfluenceGate {
+            trend_direction: None,
+            rsi_regime: None,
+            ichimoku_cross: None,
+            gaussian_3d: None,
+            rsi_div_4h: None,
+            rsi_div_1d: None,
+            entry_rsi,
+            smc_strong: None,
+            momentum: None,
+            kumo: None,
+            weekly_bias: None,
+        }
+    }
+
+    fn momentum(signal: MomentumSignal, strong_bullish: bool, strong_bearish: bool) -> MomentumSnapshot {
+        MomentumSnapshot {
+            overall_signal: signal,
+            strong_bullish,
+            strong_bearish,
+            atr: None,
+            last_close: None,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn overbought_rsi_blocks_long_entry() {
+        let gate = gate_with_rsi(Some(78.0));
+        assert!(!gate.rsi_permits_long(70.0));
+    }
+
+    #[test]
+    fn neutral_rsi_permits_long_entry() {
+        let gate = gate_with_rsi(Some(52.0));
+        assert!(gate.rsi_permits_long(70.0));
+        assert!(gate.rsi_permits_short(30.0));
+    }
+
+    #[test]
+    fn oversold_rsi_blocks_short_entry() {
+        let gate = gate_with_rsi(Some(21.0));
+        assert!(!gate.rsi_permits_short(30.0));
+        assert!(gate.rsi_permits_long(70.0));
+    }
+
+    #[test]
+    fn missing_rsi_never_blocks() {
+        let gate = gate_with_rsi(None);
+        assert!(gate.rsi_permits_long(70.0));
+        assert!(gate.rsi_permits_short(30.0));
+    }
+
+    #[test]
+    fn zone_touch_without_smc_support_is_skipped() {
+        let mut gate = gate_with_rsi(None);
+        gate.momentum = Some(momentum(MomentumSignal::Neutral, false, false));
+        assert!(!gate.confirms_long());
+
+        // A Strong High supports shorts, not longs
+        gate.smc_strong = Some(StrongKind::StrongHigh);
+        assert!(!gate.confirms_long());
+    }
+
+    #[test]
+    fn strong_low_with_neutral_momentum_is_taken() {
+        let mut gate = gate_with_rsi(None);
+        gate.smc_strong = Some(StrongKind::StrongLow);
+        gate.momentum = Some(momentum(MomentumSignal::Neutral, false, false));
+        assert!(gate.confirms_long());
+        assert!(!gate.confirms_short());
+    }
+
+    #[test]
+    fn strongly_opposed_momentum_blocks_confirmation() {
+        let mut gate = gate_with_rsi(None);
+        gate.smc_strong = Some(StrongKind::StrongLow);
+        gate.momentum = Some(momentum(MomentumSignal::Bearish, false, true));
+        assert!(!gate.confirms_long());
+    }
+
+    #[test]
+    fn momentum_filter_vetoes_only_strong_opposition() {
+        let mut gate = gate_with_rsi(None);
+        assert!(gate.momentum_permits_long());
+        assert!(gate.momentum_permits_short());
+
+        gate.momentum = Some(momentum(MomentumSignal::Bearish, false, true));
+        assert!(!gate.momentum_permits_long());
+        assert!(gate.momentum_permits_short());
+
+        gate.momentum = Some(momentum(MomentumSignal::Bearish, false, false));
+        assert!(gate.momentum_permits_long());
+    }
+
+    #[test]
+    fn kumo_filter_takes_trades_only_on_the_cloud_side() {
+        let mut gate = gate_with_rsi(None);
+        assert!(gate.kumo_permits_long(50_000.0));
+
+        gate.kumo = Some(KumoSnapshot {
+            leading_span_a: 60_000.0,
+            leading_span_b: 55_000.0,
+            updated_at: chrono::Utc::now(),
+        });
+        assert!(gate.kumo_permits_long(62_000.0));
+        assert!(!gate.kumo_permits_short(62_000.0));
+        assert!(!gate.kumo_permits_long(58_000.0));
+        assert!(!gate.kumo_permits_short(58_000.0));
+        assert!(gate.kumo_permits_short(50_000.0));
+    }
+
+    #[test]
+    fn weekly_bias_filter_blocks_entries_against_the_bias() {
+        let mut config = Config::for_tests();
+        config.use_rsi_filter = false;
+        config.use_momentum_filter = false;
+        config.use_kumo_filter = false;
+        config.require_confluence = false;
+        config.use_weekly_bias_filter = true;
+
+        let mut gate = gate_with_rsi(None);
+        assert_eq!(gate.blocks_entry(Position::Long, 60_000.0, &config), None);
+        assert_eq!(gate.blocks_entry(Position::Short, 60_000.0, &config), None);
+
+        gate.weekly_bias = Some(TenkanKijunCross::Bearish);
+        assert_eq!(
+            gate.blocks_entry(Position::Long, 60_000.0, &config),
+            Some(MissReason::WeeklyBiasFilter)
+        );
+        assert_eq!(gate.blocks_entry(Position::Short, 60_000.0, &config), None);
+
+        gate.weekly_bias = Some(TenkanKijunCross::Bullish);
+        assert_eq!(gate.blocks_entry(Position::Long, 60_000.0, &config), None);
+        assert_eq!(
+            gate.blocks_entry(Position::Short, 60_000.0, &config),
+            Some(MissReason::WeeklyBiasFilter)
+        );
+
+        config.use_weekly_bias_filter = false;
+        assert_eq!(gate.blocks_entry(Position::Short, 60_000.0, &config), None);
+    }
+
+    #[test]
+    fn two_opposing_signals_exit_a_winning_long() {
+        let mut gate = gate_with_rsi(None);
+        gate.momentum = Some(momentum(MomentumSignal::Bearish, false, false));
+        assert_eq!(gate.opposing_signals(Position::Long), 1);
+        assert!(!gate.ensemble_exit(Position::Long, 60_000.0, 61_000.0, 2));
+
+        gate.smc_strong = Some(StrongKind::StrongHigh);
+        assert_eq!(gate.opposing_signals(Position::Long), 2);
+        assert!(gate.ensemble_exit(Position::Long, 60_000.0, 61_000.0, 2));
+
+        // same signals support a short, a losing long is left to its stop, 0 is off
+        assert_eq!(gate.opposing_signals(Position::Short), 0);
+        assert!(!gate.ensemble_exit(Position::Long, 60_000.0, 59_000.0, 2));
+        assert!(!gate.ensemble_exit(Position::Long, 60_000.0, 61_000.0, 0));
+    }
+}
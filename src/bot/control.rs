@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::Serialize;
+
+use crate::bot::{OpenPosition, Position};
+use crate::helper::{
+    TRADING_BOT_ACTIVE, TRADING_BOT_LAST_CYCLE, TRADING_BOT_PAUSED, TRADING_BOT_POSITION,
+};
+
+/// What `/api/bot/status` reports: whether entries are paused, the stored position and
+/// when the bot last ran a cycle
+#[derive(Debug, Clone, Serialize)]
+pub struct BotStatus {
+    pub paused: bool,
+    pub position: Position,
+    pub open_position: Option<OpenPosition>,
+    pub last_cycle: Option<DateTime<Utc>>,
+}
+
+impl BotStatus {
+    /// Builds the status from the raw stored values; anything unreadable is left out
+    pub fn from_stored(
+        paused: Option<String>,
+        position: Option<String>,
+        open_position: Option<String>,
+        last_cycle: Option<String>,
+    ) -> Self {
+        let position = position
+            .as_deref()
+            .map_or(Position::Flat, Position::from_stored);
+        let open_position = open_position
+            .filter(|_| position != Position::Flat)
+            .and_then(|r| serde_json::from_str(&r).ok());
+
+        Self {
+            paused: paused.is_some_and(|p| p == "true"),
+            position,
+            open_position,
+            last_cycle: last_cycle
+                .and_then(|r| DateTime::parse_from_rfc3339(&r).ok())
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
+
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection) -> anyhow::Result<Self> {
+        let paused: Option<String> = conn.get(TRADING_BOT_PAUSED).await?;
+        let position: Option<String> = conn.get(TRADING_BOT_POSITION).await?;
+        let open_position: Option<String> = conn.get(TRADING_BOT_ACTIVE).await?;
+        let last_cycle: Option<String> = conn.get(TRADING_BOT_LAST_CYCLE).await?;
+
+        Ok(Self::from_stored(
+            paused,
+            position,
+            open_position,
+            last_cycle,
+        ))
+    }
+
+    /// Paused bots keep managing an open position but take no new entries
+    pub async fn is_paused(conn: &mut redis::aio::MultiplexedConnection) -> bool {
+        let raw: Option<String> = conn.get(TRADING_BOT_PAUSED).await.unwrap_or(None);
+        raw.is_some_and(|p| p == "true")
+    }
+
+    pub async fn set_paused(
+        conn: &mut redis::aio::MultiplexedConnection,
+        paused: bool,
+    ) -> anyhow::Result<()> {
+        let _: () = conn.set(TRADING_BOT_PAUSED, paused.to_string()).await?;
+        Ok(())
+    }
+
+    pub async fn record_cycle(
+        conn: &mut redis::aio::MultiplexedConnection,
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let _: () = conn.set(TRADING_BOT_LAST_CYCLE, at.to_rfc3339()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_reads_stored_values() {
+        let open = OpenPosition {
+            pos: Position::Long,
+            ..OpenPosition::default_open_position()
+        };
+        let at = Utc::now();
+
+        let status = BotStatus::from_stored(
+            Some("true".to_string()),
+            Some(Position::Long.as_str()),
+            Some(open.as_str()),
+            Some(at.to_rfc3339()),
+        );
+        assert!(status.paused);
+        assert_eq!(status.position, Position::Long);
+        assert_eq!(status.open_position.map(|o| o.id), Some(open.id));
+        assert_eq!(status.last_cycle, Some(at));
+
+        // a flat bot leaves its last open position behind; don't report it
+        let status = BotStatus::from_stored(
+            Some("false".to_string()),
+            Some(Position::Flat.as_str()),
+            Some(open.as_str()),
+            None,
+        );
+        assert!(!status.paused);
+        assert!(status.open_position.is_none());
+        assert!(status.last_cycle.is_none());
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+
+/// How long a day's hash outlives its day, so old days clean themselves up
+const DAY_TTL_SECS: usize = 2 * 24 * 60 * 60;
+
+const REALIZED_PNL: &str = "realized_pnl";
+const ANNOUNCED: &str = "announced";
+
+/// The hash holding `day`'s total under `key`; each UTC day starts a fresh one
+fn day_key(key: &str, day: NaiveDate) -> String {
+    format!("{key}:{day}")
+}
+
+/// Realized PnL per UTC day, the dollar circuit breaker behind `max_daily_loss`.
+/// Once the day's losses pass the limit no new entries are taken until UTC
+/// midnight; open positions keep being managed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyLoss {
+    pub day: NaiveDate,
+    pub realized_pnl: Decimal,
+    /// Set once the trip has been logged, so it's reported once per day
+    pub announced: bool,
+}
+
+impl DailyLoss {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            day: now.date_naive(),
+            realized_pnl: Decimal::ZERO,
+            announced: false,
+        }
+    }
+
+    /// Realized PnL so far today; a stale day counts as zero (UTC midnight reset)
+    pub fn pnl_for(&self, now: DateTime<Utc>) -> Decimal {
+        if self.day == now.date_naive() {
+            self.realized_pnl
+        } else {
+            Decimal::ZERO
+        }
+    }
+
+    /// Whether today's losses exceed `max_daily_loss`; 0 disables the breaker
+    pub fn is_tripped(&self, now: DateTime<Utc>, max_daily_loss: Decimal) -> bool {
+        max_daily_loss > Decimal::ZERO && -self.pnl_for(now) > max_daily_loss
+    }
+
+    /// `now`'s day as read back from its hash
+    fn from_fields(now: DateTime<Utc>, fields: &HashMap<String, String>) -> Self {
+        Self {
+            realized_pnl: fields
+                .get(REALIZED_PNL)
+                .and_then(|pnl| Decimal::from_str(pnl).ok())
+                .unwrap_or(Decimal::ZERO),
+            announced: fields.contains_key(ANNOUNCED),
+            ..Self::new(now)
+        }
+    }
+
+    /// Today's total under `key`, one for the whole account (`TRADING_BOT_DAILY_PNL`)
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection, key: &str) -> Self {
+        let now = Utc::now();
+        let fields: HashMap<String, String> = conn
+            .hgetall(day_key(key, now.date_naive()))
+            .await
+            .unwrap_or_default();
+        Self::from_fields(now, &fields)
+    }
+
+    /// Marks today's trip as reported
+    pub async fn announce(
+        &mut self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> anyhow::Result<()> {
+        self.announced = true;
+        let day = day_key(key, self.day);
+        let _: () = redis::pipe()
+            .atomic()
+            .hset(&day, ANNOUNCED, 1)
+            .ignore()
+            .expire(&day, DAY_TTL_SECS)
+            .ignore()
+            .query_async(conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Adds a realized close to today's total. The add happens in Redis, so closes
+    /// landing at once (ranger and scalper share the primary key) all count.
+    pub async fn record_close(
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+        pnl: Decimal,
+    ) -> anyhow::Result<()> {
+        let day = day_key(key, Utc::now().date_naive());
+        let _: () = redis::pipe()
+            .atomic()
+            .cmd("HINCRBYFLOAT")
+            .arg(&day)
+            .arg(REALIZED_PNL)
+            .arg(pnl.to_string())
+            .ignore()
+            .expire(&day, DAY_TTL_SECS)
+            .ignore()
+            .query_async(conn)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_breaker_trips_past_the_daily_loss() {
+        let morning = Utc.with_ymd_and_hms(2025, 3, 10, 8, 0, 0).unwrap();
+
+        let daily = DailyLoss::from_fields(morning, &fields(&[(REALIZED_PNL, "-35")]));
+        assert!(!daily.is_tripped(morning, dec!(100)));
+
+        // HINCRBYFLOAT's float arithmetic can leave a long tail of decimals
+        let daily =
+            DailyLoss::from_fields(morning, &fields(&[(REALIZED_PNL, "-115.00000000000000001")]));
+        assert!(daily.pnl_for(morning) < dec!(-114.99));
+        assert!(daily.is_tripped(morning, dec!(100)));
+        assert!(!daily.announced);
+
+        // zero disables it
+        assert!(!daily.is_tripped(morning, Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_breaker_resets_at_utc_midnight() {
+        let late = Utc.with_ymd_and_hms(2025, 3, 10, 23, 59, 0).unwrap();
+        let next_day = Utc.with_ymd_and_hms(2025, 3, 11, 0, 1, 0).unwrap();
+        let daily = DailyLoss::from_fields(
+            late,
+            &fields(&[(REALIZED_PNL, "-500"), (ANNOUNCED, "1")]),
+        );
+        assert!(daily.announced);
+
+        assert!(daily.is_tripped(late, dec!(100)));
+        assert!(!daily.is_tripped(next_day, dec!(100)));
+
+        // the next day reads a hash of its own, with nothing announced yet
+        assert_ne!(
+            day_key("trading_bot:daily_pnl", late.date_naive()),
+            day_key("trading_bot:daily_pnl", next_day.date_naive())
+        );
+        assert_eq!(
+            day_key("trading_bot:daily_pnl", next_day.date_naive()),
+            "trading_bot:daily_pnl:2025-03-11"
+        );
+        assert_eq!(DailyLoss::from_fields(next_day, &HashMap::new()), DailyLoss::new(next_day));
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{bail, Result};
+
+use crate::bot::symbols::SymbolKeys;
+use crate::bot::{OpenPosition, Position};
+use crate::helper::PartialProfitTarget;
+
+/// Everything a filled entry leaves in redis, assembled before anything is written so
+/// the position, its open position and its targets land in one MULTI/EXEC. A crash can
+/// then only leave the previous state or the new one, never a mix of both.
+#[derive(Debug, Clone)]
+pub struct EntryRecord {
+    pub pos: Position,
+    pub open_position: OpenPosition,
+    pub targets: Vec<PartialProfitTarget>,
+}
+
+impl EntryRecord {
+    pub fn new(
+        pos: Position,
+        open_position: &OpenPosition,
+        targets: &[PartialProfitTarget],
+    ) -> Result<Self> {
+        if pos == Position::Flat || open_position.pos != pos {
+            bail!(
+                "Entry {} is {:?} but was recorded as {pos:?}",
+                open_position.id,
+                open_position.pos
+            );
+        }
+        if let Some(last) = targets.last() {
+            if open_position.tp != Some(last.target_price) {
+                bail!(
+                    "Entry {} takes profit at {:?} but its last target is {}",
+                    open_position.id,
+                    open_position.tp,
+                    last.target_price
+                );
+            }
+        }
+        Ok(Self {
+            pos,
+            open_position: open_position.clone(),
+            targets: targets.to_vec(),
+        })
+    }
+
+    /// The key/value pairs the transaction sets
+    pub fn writes<'k>(&self, keys: &'k SymbolKeys) -> Result<Vec<(&'k str, String)>> {
+        Ok(vec![
+            (&keys.position, self.pos.as_str()),
+            (&keys.active, self.open_position.as_str()),
+            (&keys.partial_targets, serde_json::to_string(&self.targets)?),
+        ])
+    }
+
+    /// Writes the entry and drops its intent in a single transaction
+    pub async fn store(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        keys: &SymbolKeys,
+    ) -> Result<()> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (key, value) in self.writes(keys)? {
+            pipe.set(key, value).ignore();
+        }
+        pipe.del(&keys.entry_intent).ignore();
+
+        let _: () = pipe.query_async(conn).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::{TRADING_BOT_ACTIVE, TRADING_BOT_POSITION, TRADING_PARTIAL_PROFIT_TARGET};
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+
+    fn targets() -> Vec<PartialProfitTarget> {
+        [dec!(65500), dec!(66000)]
+            .into_iter()
+            .map(|target_price| PartialProfitTarget {
+                target_price,
+                fraction: dec!(0.5),
+                sl: Some(dec!(64500)),
+                size_btc: dec!(0.005),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_open_position_persists_a_consistent_entry() {
+        let open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(65000),
+            position_size: dec!(0.01),
+            tp: Some(dec!(66000)),
+            ..OpenPosition::default_open_position()
+        };
+        let record = EntryRecord::new(Position::Long, &open, &targets()).unwrap();
+
+        let keys = SymbolKeys::new("BTCUSDT", true);
+        let stored: HashMap<&str, String> = record.writes(&keys).unwrap().into_iter().collect();
+
+        let pos = Position::from_stored(&stored[TRADING_BOT_POSITION]);
+        let active: OpenPosition = serde_json::from_str(&stored[TRADING_BOT_ACTIVE]).unwrap();
+        let stored_targets: Vec<PartialProfitTarget> =
+            serde_json::from_str(&stored[TRADING_PARTIAL_PROFIT_TARGET]).unwrap();
+
+        assert_eq!(pos, Position::Long);
+        assert_eq!(active.pos, pos);
+        assert_eq!(active.id, open.id);
+        assert_eq!(stored_targets, targets());
+        assert_eq!(active.tp, stored_targets.last().map(|t| t.target_price));
+    }
+
+    #[test]
+    fn test_open_position_refuses_a_mismatched_entry() {
+        let open = OpenPosition {
+            pos: Position::Short,
+            tp: Some(dec!(66000)),
+            ..OpenPosition::default_open_position()
+        };
+        assert!(EntryRecord::new(Position::Long, &open, &targets()).is_err());
+
+        let open = OpenPosition {
+            pos: Position::Long,
+            tp: Some(dec!(70000)),
+            ..OpenPosition::default_open_position()
+        };
+        assert!(EntryRecord::new(Position::Long, &open, &targets()).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::bot::{OpenPosition, Position};
+use crate::helper::PartialProfitTarget;
+
+/// Updates kept per subscriber; one that falls further behind skips to the newest
+const FEED_CAPACITY: usize = 64;
+
+/// Live bot state pushed to `/api/stream` after every cycle
+#[derive(Debug, Clone, Serialize)]
+pub struct BotUpdate {
+    pub at: DateTime<Utc>,
+    pub symbol: String,
+    pub price: f64,
+    pub position: Position,
+    pub open_position: Option<OpenPosition>,
+    pub targets: Vec<PartialProfitTarget>,
+}
+
+impl BotUpdate {
+    pub fn new(
+        symbol: &str,
+        price: f64,
+        position: Position,
+        open_position: &OpenPosition,
+        targets: &[PartialProfitTarget],
+    ) -> Self {
+        Self {
+            at: Utc::now(),
+            symbol: symbol.to_string(),
+            price,
+            position,
+            open_position: (position != Position::Flat).then(|| open_position.clone()),
+            targets: targets.to_vec(),
+        }
+    }
+}
+
+pub fn channel() -> broadcast::Sender<BotUpdate> {
+    broadcast::channel(FEED_CAPACITY).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_cycle_updates() {
+        let updates = channel();
+        let mut rx = updates.subscribe();
+        let open = OpenPosition {
+            pos: Position::Short,
+            ..OpenPosition::default_open_position()
+        };
+
+        updates
+            .send(BotUpdate::new(
+                "BTCUSDT",
+                65_000.0,
+                Position::Short,
+                &open,
+                &[],
+            ))
+            .unwrap();
+        updates
+            .send(BotUpdate::new(
+                "BTCUSDT",
+                64_900.0,
+                Position::Flat,
+                &open,
+                &[],
+            ))
+            .unwrap();
+
+        let update = rx.recv().await.unwrap();
+        assert_eq!(update.price, 65_000.0);
+        assert_eq!(update.open_position.map(|o| o.id), Some(open.id));
+
+        // a flat bot's leftover open position isn't pushed
+        let update = rx.recv().await.unwrap();
+        assert_eq!(update.position, Position::Flat);
+        assert!(update.open_position.is_none());
+    }
+}
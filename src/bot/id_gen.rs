@@ -0,0 +1,131 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use log::warn;
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+/// Source of position ids. The position id doubles as the exchange client order id,
+/// so a deterministic generator makes entries (and their closes) easy to assert on.
+pub trait IdGen: Send + Sync + std::fmt::Debug {
+    fn next_id(&self) -> Uuid;
+
+    /// The counter behind the last id, for generators that must resume it after a
+    /// restart. Default: `None` (nothing to persist).
+    fn counter(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Production generator: random v4 UUIDs.
+#[derive(Debug, Default)]
+pub struct UuidGen;
+
+impl IdGen for UuidGen {
+    fn next_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// Deterministic generator: the seed fills the high 64 bits and a counter the low 64,
+/// so `seed = 7` yields `...0007-0000-0000-0000-0001`, `...0002`, and so on.
+#[derive(Debug)]
+pub struct SequentialIdGen {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl SequentialIdGen {
+    #[cfg(test)]
+    pub fn new(seed: u64) -> Self {
+        Self::resume(seed, 0)
+    }
+
+    /// Continues after `counter`, the last one handed out before a restart
+    pub fn resume(seed: u64, counter: u64) -> Self {
+        Self {
+            seed,
+            counter: AtomicU64::new(counter),
+        }
+    }
+}
+
+impl IdGen for SequentialIdGen {
+    fn next_id(&self) -> Uuid {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        Uuid::from_u128(((self.seed as u128) << 64) | n as u128)
+    }
+
+    fn counter(&self) -> Option<u64> {
+        Some(self.counter.load(Ordering::Relaxed))
+    }
+}
+
+/// `ID_SEED` set → deterministic ids picking up after `counter`, otherwise random ones
+pub fn from_seed(seed: Option<u64>, counter: u64) -> Box<dyn IdGen> {
+    match seed {
+        Some(seed) => Box::new(SequentialIdGen::resume(seed, counter)),
+        None => Box::new(UuidGen),
+    }
+}
+
+/// Next id from `ids`, storing its counter under `counter_key` so a restart doesn't
+/// hand out the same ids again
+pub async fn next_stored(
+    ids: &dyn IdGen,
+    conn: &mut redis::aio::MultiplexedConnection,
+    counter_key: &str,
+) -> Uuid {
+    let id = ids.next_id();
+    if let Some(counter) = ids.counter() {
+        let stored: redis::RedisResult<()> = conn.set(counter_key, counter).await;
+        if let Err(e) = stored {
+            warn!("Failed to store the id counter: {e}");
+        }
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_ids_are_predictable() {
+        let ids = SequentialIdGen::new(7);
+
+        let first = ids.next_id();
+        let second = ids.next_id();
+
+        assert_eq!(first.to_string(), "00000000-0000-0007-0000-000000000001");
+        assert_eq!(second.to_string(), "00000000-0000-0007-0000-000000000002");
+    }
+
+    #[test]
+    fn test_same_seed_replays_same_ids() {
+        let a = from_seed(Some(42), 0);
+        let b = from_seed(Some(42), 0);
+        assert_eq!(a.next_id(), b.next_id());
+        assert_eq!(a.next_id(), b.next_id());
+    }
+
+    #[test]
+    fn test_uuid_gen_is_random() {
+        let ids = from_seed(None, 0);
+        assert_ne!(ids.next_id(), ids.next_id());
+        assert_eq!(ids.counter(), None);
+    }
+
+    #[test]
+    fn test_restart_resumes_after_the_stored_counter() {
+        let before = from_seed(Some(7), 0);
+        let first = before.next_id();
+        let second = before.next_id();
+
+        // the bot persists `counter()` after every id and resumes from it on start
+        let after = from_seed(Some(7), before.counter().unwrap());
+        let third = after.next_id();
+        assert_ne!(third, first);
+        assert_ne!(third, second);
+        assert_eq!(third.to_string(), "00000000-0000-0007-0000-000000000003");
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::{OpenPosition, Position};
+
+/// An entry journalled just before its order goes out and cleared once the filled
+/// position is stored. One left behind means the bot died in between, and the
+/// exchange may hold a position the persisted state knows nothing about.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryIntent {
+    pub pos: Position,
+    pub open_position: OpenPosition,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Recovery {
+    /// The exchange holds the position: take it over as if the entry completed
+    Adopt(Position, Box<OpenPosition>),
+    /// The order never filled: drop the intent and stay flat
+    Discard,
+    /// The exchange can't say; keep the intent for the operator and stay flat
+    Unresolved,
+}
+
+impl EntryIntent {
+    pub fn new(pos: Position, open_position: &OpenPosition) -> Self {
+        Self {
+            pos,
+            open_position: open_position.clone(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// `exchange_has_position` is `None` when the exchange can't report it
+    pub fn reconcile(self, exchange_has_position: Option<bool>) -> Recovery {
+        match exchange_has_position {
+            Some(true) => Recovery::Adopt(self.pos, Box::new(self.open_position)),
+            Some(false) => Recovery::Discard,
+            None => Recovery::Unresolved,
+        }
+    }
+
+    /// `key` is the symbol's own, see `SymbolKeys`
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection, key: &str) -> Option<Self> {
+        let raw: Option<String> = conn.get(key).await.unwrap_or(None);
+        raw.and_then(|r| serde_json::from_str(&r).ok())
+    }
+
+    pub async fn store(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> anyhow::Result<()> {
+        let _: () = conn.set(key, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    pub async fn clear(
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> anyhow::Result<()> {
+        let _: () = conn.del(key).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{Exchange, MockExchange};
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_crash_between_fill_and_confirm_adopts_the_position() {
+        let exchange = MockExchange::new(65_000.0);
+        let open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(65000),
+            position_size: dec!(0.01),
+            ..OpenPosition::default_open_position()
+        };
+
+        // intent journalled, order fills, then the process dies before confirming
+        let intent = EntryIntent::new(Position::Long, &open);
+        exchange.place_market_order(&open).await.unwrap();
+        let restored: EntryIntent =
+            serde_json::from_str(&serde_json::to_string(&intent).unwrap()).unwrap();
+
+        match restored.reconcile(exchange.has_open_position().await.unwrap()) {
+            Recovery::Adopt(pos, adopted) => {
+                assert_eq!(pos, Position::Long);
+                assert_eq!(adopted.id, open.id);
+                assert_eq!(adopted.entry_price, dec!(65000));
+            }
+            other => panic!("expected the filled position to be adopted, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unfilled_intent_is_discarded() {
+        let exchange = MockExchange::new(65_000.0);
+        let intent = EntryIntent::new(Position::Short, &OpenPosition::default_open_position());
+
+        let recovery = intent.reconcile(exchange.has_open_position().await.unwrap());
+        assert!(matches!(recovery, Recovery::Discard));
+    }
+}
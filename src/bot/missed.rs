@@ -0,0 +1,200 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::zones::ZoneId;
+use crate::bot::{Position, Zone};
+use crate::helper::TRADING_BOT_MISSED_ENTRIES;
+
+/// What stopped a would-be entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissReason {
+    MacroGuard,
+    Maintenance,
+    DailyCap,
+    DailyLoss,
+    ZoneDisabled,
+    ConfluenceVeto,
+    RsiFilter,
+    MomentumFilter,
+    KumoFilter,
+    WeeklyBiasFilter,
+    NoConfluence,
+    OrderFailed,
+}
+
+/// A zone touch that would have opened a position but didn't. Kept so the cost
+/// (or saving) of each filter can be measured against what price did next.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissedEntry {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+    pub intended_side: Position,
+    pub zone: Option<Zone>,
+    pub reason: MissReason,
+}
+
+impl MissedEntry {
+    pub fn new(
+        timestamp: DateTime<Utc>,
+        price: f64,
+        intended_side: Position,
+        zone: Option<Zone>,
+        reason: MissReason,
+    ) -> Self {
+        Self {
+            timestamp,
+            price,
+            intended_side,
+            zone,
+            reason,
+        }
+    }
+
+    /// Pushes onto the newest end of the list, keeping at most `max` records (0 = unbounded)
+    pub async fn record(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        max: usize,
+    ) -> Result<()> {
+        let _: () = conn
+            .lpush(TRADING_BOT_MISSED_ENTRIES, serde_json::to_string(self)?)
+            .await?;
+        if max > 0 {
+            let _: () = conn
+                .ltrim(TRADING_BOT_MISSED_ENTRIES, 0, max as isize - 1)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Newest first; unreadable records are skipped
+    pub async fn load(
+        conn: &mut redis::aio::MultiplexedConnection,
+        start: isize,
+        end: isize,
+    ) -> Result<Vec<Self>> {
+        let raw: Vec<String> = conn.lrange(TRADING_BOT_MISSED_ENTRIES, start, end).await?;
+        Ok(raw
+            .iter()
+            .filter_map(|r| serde_json::from_str(r).ok())
+            .collect())
+    }
+}
+
+/// Keeps a guard that stays shut from logging a miss on every tick: one record per
+/// zone-and-reason episode. The episode ends when price leaves the zone or the entry
+/// gets through, and a different reason starts a new one.
+#[derive(Debug, Clone, Default)]
+pub struct MissEpisode {
+    current: Option<(ZoneId, MissReason)>,
+}
+
+impl MissEpisode {
+    /// Whether a miss in `zone` for `reason` starts a new episode, and so gets recorded
+    pub fn starts(&mut self, zone: &Zone, reason: MissReason) -> bool {
+        let episode = Some((ZoneId::from_zone(zone), reason));
+        if self.current == episode {
+            return false;
+        }
+        self.current = episode;
+        true
+    }
+
+    pub fn end(&mut self) {
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::confluence::ConfluenceGate;
+    use crate::bot::Side;
+    use crate::config::Config;
+    use crate::trackers::momentum::{MomentumSignal, MomentumSnapshot};
+
+    #[test]
+    fn test_guard_blocked_entry_records_its_reason() {
+        let mut config = Config::for_tests();
+        config.use_rsi_filter = false;
+        config.use_kumo_filter = false;
+        config.use_weekly_bias_filter = false;
+        config.require_confluence = false;
+        config.use_momentum_filter = true;
+
+        let gate = ConfluenceGate {
+            trend_direction: None,
+            rsi_regime: None,
+            ichimoku_cross: None,
+            gaussian_3d: None,
+            rsi_div_4h: None,
+            rsi_div_1d: None,
+            entry_rsi: None,
+            smc_strong: None,
+            momentum: Some(MomentumSnapshot {
+                overall_signal: MomentumSignal::Bearish,
+                strong_bullish: false,
+                strong_bearish: true,
+                atr: None,
+                last_close: None,
+                updated_at: Utc::now(),
+            }),
+            kumo: None,
+            weekly_bias: None,
+        };
+        let zone = Zone {
+            low: 59_000.0,
+            high: 60_000.0,
+            side: Side::Long,
+        };
+
+        let reason = gate.blocks_entry(Position::Long, 59_500.0, &config);
+        assert_eq!(reason, Some(MissReason::MomentumFilter));
+
+        let missed = MissedEntry::new(
+            Utc::now(),
+            59_500.0,
+            Position::Long,
+            Some(zone),
+            reason.unwrap(),
+        );
+        let json = serde_json::to_value(&missed).unwrap();
+        assert_eq!(json["reason"], "momentum_filter");
+        assert_eq!(json["intended_side"], "Long");
+        assert_eq!(json["zone"]["low"], 59_000.0);
+
+        let back: MissedEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.reason, MissReason::MomentumFilter);
+    }
+
+    #[test]
+    fn test_blocked_ticks_in_one_zone_record_a_single_miss() {
+        let zone = Zone {
+            low: 59_000.0,
+            high: 60_000.0,
+            side: Side::Long,
+        };
+        let other = Zone {
+            low: 61_000.0,
+            high: 62_000.0,
+            side: Side::Long,
+        };
+        let mut episode = MissEpisode::default();
+
+        let recorded = (0..5)
+            .filter(|_| episode.starts(&zone, MissReason::MacroGuard))
+            .count();
+        assert_eq!(recorded, 1);
+
+        // another reason or another zone is a new episode
+        assert!(episode.starts(&zone, MissReason::DailyCap));
+        assert!(episode.starts(&other, MissReason::DailyCap));
+
+        // price left the zone and came back: recorded again
+        episode.end();
+        assert!(episode.starts(&other, MissReason::DailyCap));
+    }
+}
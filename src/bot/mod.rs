@@ -2,40 +2,77 @@ use anyhow::anyhow;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use log::{info, warn};
-use redis::{AsyncCommands, RedisError};
+use redis::AsyncCommands;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::ops::Div;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{mpsc, watch};
 use uuid::Uuid;
 
-use crate::bot::zones::ZoneGuard;
-use crate::bot::zones::ZoneId;
-use crate::bot::zones::{Zone, Zones};
-use crate::calendar::MacroGuard;
-use crate::config::Config;
+use crate::cache::codec::{self, Serialization};
+use crate::cache::health::{self, RedisHealth};
+use crate::cache::RedisClient;
+use crate::calendar::{FlattenPolicy, MacroGuard};
+use crate::config::runtime::{RuntimeOverride, Tunables};
+use crate::config::{Config, ZoneEntryRef};
+use crate::exchange::bitget::accounts::AccountRouter;
 use crate::exchange::bitget::fees::BitgetFuturesFees;
+use crate::exchange::bitget::maintenance::MaintenanceGuard;
 use crate::exchange::bitget::BitgetWsClient;
 use crate::exchange::bitget::PlaceOrderData;
 use crate::exchange::bitunix::ws::BitunixWsClient;
 use crate::exchange::Exchange;
+use crate::graph::rollup::Rollups;
 use crate::graph::Graph;
-use crate::helper::TRADING_BOT_LOSS_COUNT;
-use crate::helper::TRADING_PARTIAL_PROFIT_TARGET;
+use crate::trackers::momentum::MomentumSnapshot;
+use crate::helper::TRADING_BOT_ID_COUNTER;
+use crate::helper::TRADING_BOT_MOMENTUM;
 use crate::helper::{
-    Helper, PartialProfitTarget, TRADING_BOT_ACTIVE, TRADING_BOT_CLOSE_POSITIONS,
-    TRADING_BOT_POSITION, TRADING_BOT_ZONES, TRADING_CAPITAL,
+    Helper, PartialProfitTarget, TRADING_BOT_CLOSE_POSITIONS, TRADING_BOT_DAILY_PNL,
 };
 use futures_util::StreamExt;
 
-//pub mod scalper;
-
+pub mod allocator;
+pub mod command;
 pub mod confluence;
+pub mod control;
+pub mod daily_loss;
+pub mod entry;
+pub mod feed;
+pub mod id_gen;
+pub mod intent;
+pub mod missed;
+pub mod resting;
+pub mod scalper;
+pub mod staleness;
+pub mod symbols;
+pub mod throttle;
 pub mod zones;
 
+use allocator::{CapitalAllocator, Strategy};
+use command::{BotCommand, CommandError, ManualEntry};
 use confluence::ConfluenceGate;
+use control::BotStatus;
+use daily_loss::DailyLoss;
+use entry::EntryRecord;
+use feed::BotUpdate;
+use id_gen::IdGen;
+use intent::{EntryIntent, Recovery};
+use missed::{MissEpisode, MissReason, MissedEntry};
+use resting::RestingEntry;
+use staleness::StalenessGuard;
+use symbols::{SymbolKeys, SymbolState};
+use throttle::DailyEntryThrottle;
+use zones::ZoneGuard;
+
+/// Zone types live in `bot::zones`, next to the guard that keys on them; re-exported
+/// so the rest of the crate trades and guards the same `Zone`.
+pub use zones::{Side, Zone, ZoneId, ZoneOverride, Zones};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Position {
@@ -45,14 +82,27 @@ pub enum Position {
 }
 
 impl Position {
-    fn as_str(&self) -> &'static str {
-        match self {
-            Position::Flat => "Flat",
-            Position::Long => "Long",
-            Position::Short => "Short",
-        }
+    /// Canonical stored form – the same serde JSON (`"Long"`, quotes included) that
+    /// `OpenPosition` / `ClosedPosition` embed, so every key parses the same way.
+    pub fn as_str(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Parses the canonical form; bare `Long` written by older builds is still
+    /// accepted, anything else is Flat.
+    pub fn from_stored(raw: &str) -> Position {
+        serde_json::from_str(raw)
+            .or_else(|_| serde_json::from_value(serde_json::Value::String(raw.to_string())))
+            .unwrap_or(Position::Flat)
     }
 }
+/// One price the bot acted on, recorded per symbol when `RECORD_PRICES` is on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecordedPrice {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClosedPosition {
     pub id: uuid::Uuid,
@@ -71,9 +121,28 @@ pub struct ClosedPosition {
     pub roi: Option<Decimal>,
     pub leverage: Option<Decimal>,
     pub margin: Option<Decimal>,
+    /// Exchange order to reconcile against: the closing order where there is one,
+    /// otherwise the position's latest order
     pub order_id: Option<String>,
+    /// Net of entry + exit taker fees and funding
     pub pnl_after_fees: Option<Decimal>,
     pub exit_fee: Option<Decimal>,
+    #[serde(default)]
+    pub entry_fee: Option<Decimal>,
+    /// Funding paid (positive) or received (negative) while open
+    #[serde(default)]
+    pub funding_fee: Option<Decimal>,
+    /// Worst / best unrealized PnL seen while the position was open
+    #[serde(default)]
+    pub max_adverse_excursion: Option<Decimal>,
+    #[serde(default)]
+    pub max_favorable_excursion: Option<Decimal>,
+    /// Zone the entry was taken in; `None` for trades recorded before zones were tagged
+    #[serde(default)]
+    pub zone_id: Option<ZoneId>,
+    /// Symbol traded; `None` for trades recorded before symbols were tagged
+    #[serde(default)]
+    pub symbol: Option<String>,
 }
 
 impl ClosedPosition {
@@ -96,9 +165,34 @@ pub struct OpenPosition {
     pub quantity: Option<Decimal>,
     pub leverage: Option<Decimal>,
     pub risk_pct: Option<Decimal>,
+    /// Latest exchange order on the position: the entry fill, then each partial close
     pub order_id: Option<String>,
     #[serde(default)]
     pub position_id: Option<String>,
+    /// Worst unrealized PnL since entry (MAE), ≤ 0
+    #[serde(default)]
+    pub max_adverse_excursion: Option<Decimal>,
+    /// Best unrealized PnL since entry (MFE), ≥ 0
+    #[serde(default)]
+    pub max_favorable_excursion: Option<Decimal>,
+    /// Bitget account the position was opened on; exits are routed back to it
+    #[serde(default)]
+    pub account: Option<String>,
+    /// Already cut down ahead of a macro event, so the reduction isn't repeated every cycle
+    #[serde(default)]
+    pub macro_reduced: bool,
+    /// Funding rate when the position was opened, used to charge funding at exit
+    #[serde(default)]
+    pub entry_funding_rate: Option<Decimal>,
+    /// Left open after the final target and managed only by the trailing stop
+    #[serde(default)]
+    pub runner: bool,
+    /// Zone the entry was taken in, carried onto every `ClosedPosition` it produces
+    #[serde(default)]
+    pub zone_id: Option<ZoneId>,
+    /// Symbol the position is on; `None` for positions opened before symbols were tagged
+    #[serde(default)]
+    pub symbol: Option<String>,
 }
 
 impl OpenPosition {
@@ -106,7 +200,214 @@ impl OpenPosition {
         serde_json::to_string(self).unwrap()
     }
 
-    fn default_open_position() -> OpenPosition {
+    /// Folds `price` into the MAE / MFE. Returns true if either moved, so callers
+    /// only persist when something changed.
+    pub fn track_excursion(&mut self, price: Decimal) -> bool {
+        let unrealized = Helper::compute_pnl(self.pos, self.entry_price, self.position_size, price);
+
+        let mae = self.max_adverse_excursion.unwrap_or_default().min(unrealized);
+        let mfe = self.max_favorable_excursion.unwrap_or_default().max(unrealized);
+        let changed = Some(mae) != self.max_adverse_excursion
+            || Some(mfe) != self.max_favorable_excursion;
+
+        self.max_adverse_excursion = Some(mae);
+        self.max_favorable_excursion = Some(mfe);
+        changed
+    }
+
+    /// Quantity to close so that `target_exposure` of the current quantity stays
+    /// open, rounded down to the symbol's `size_precision` decimals.
+    pub fn reduction_for(&self, target_exposure: f64, size_precision: u32) -> Decimal {
+        let quantity = self.quantity.unwrap_or(self.position_size);
+        let keep = Decimal::from_f64(target_exposure.clamp(0.0, 1.0)).unwrap_or(Decimal::ONE);
+        (quantity * (Decimal::ONE - keep))
+            .round_dp_with_strategy(size_precision, rust_decimal::RoundingStrategy::ToZero)
+            .max(Decimal::ZERO)
+    }
+
+    /// Ratchets the runner's stop to `trail_pct` percent behind `price`; it only ever
+    /// tightens. Returns true if the stop moved.
+    pub fn trail_runner(&mut self, price: Decimal, trail_pct: Decimal) -> bool {
+        let offset = price * trail_pct / dec!(100.0);
+        let candidate = match self.pos {
+            Position::Long => price - offset,
+            Position::Short => price + offset,
+            Position::Flat => return false,
+        }
+        .round_dp(2);
+
+        self.tighten_stop(candidate)
+    }
+
+    /// `trail_runner`, moving the exchange-side stop along with it so the runner is
+    /// still covered if the bot goes away. Returns true if the stop moved.
+    pub async fn ratchet_runner(
+        &mut self,
+        price: Decimal,
+        trail_pct: Decimal,
+        exchange: &dyn Exchange,
+    ) -> Result<bool> {
+        if !self.trail_runner(price, trail_pct) {
+            return Ok(false);
+        }
+        self.update_exchange_stop(exchange).await?;
+        Ok(true)
+    }
+
+    /// Closes the runner at market once its stop is hit. `None` when the exchange
+    /// stop got there first and there's nothing left to close.
+    pub async fn close_runner(&self, exchange: &dyn Exchange) -> Result<Option<PlaceOrderData>> {
+        match Bot::place_close_order(exchange, self).await {
+            Ok(exec_price) => Ok(Some(exec_price)),
+            Err(e) => match exchange.has_open_position().await {
+                Ok(Some(false)) => {
+                    info!("Exchange stop already closed the {:?} runner", self.pos);
+                    Ok(None)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Closes `slice` of the position with a reduce-only order, which becomes the
+    /// position's latest order. Returns that order: the slice's closing order.
+    pub async fn close_partial(
+        &mut self,
+        slice: &OpenPosition,
+        exchange: &dyn Exchange,
+    ) -> Result<PlaceOrderData> {
+        let exec_price = Bot::place_close_order(exchange, slice).await?;
+        self.order_id = Some(exec_price.order_id.clone());
+        Ok(exec_price)
+    }
+
+    /// Re-arms the exchange-side exits of a position taken over after a crash, which
+    /// only the entry path would otherwise have placed: the position TP/SL where the
+    /// exchange keys it by position id, else the stop on its own.
+    pub async fn arm_exchange_exits(
+        &mut self,
+        tp: Option<f64>,
+        exchange: &dyn Exchange,
+    ) -> Result<()> {
+        let sl = self.sl.map(Helper::decimal_to_f64);
+        if let Some(pos_id) = exchange.get_position_id().await? {
+            self.position_id = Some(pos_id.clone());
+            return exchange.place_initial_tpsl(&pos_id, tp, sl).await;
+        }
+        if let Some(sl) = sl {
+            exchange.update_stop_loss(self, sl).await?;
+        }
+        Ok(())
+    }
+
+    /// Once price is `activate_pct` percent past entry in our favour, ratchets the stop
+    /// to `distance` behind it; it only ever tightens. Returns true if the stop moved.
+    pub fn trail_stop(&mut self, price: Decimal, activate_pct: Decimal, distance: Decimal) -> bool {
+        if activate_pct <= Decimal::ZERO || distance <= Decimal::ZERO {
+            return false;
+        }
+        let activation = self.entry_price * activate_pct / dec!(100.0);
+        let (active, candidate) = match self.pos {
+            Position::Long => (price >= self.entry_price + activation, price - distance),
+            Position::Short => (price <= self.entry_price - activation, price + distance),
+            Position::Flat => return false,
+        };
+
+        active && self.tighten_stop(candidate.round_dp(2))
+    }
+
+    /// Stop for a position stored without one, from the margin, leverage and risk it was
+    /// opened with; `margin` and `defaults` stand in for whatever an older record lacks
+    pub fn fallback_stop_loss(&self, margin: Decimal, defaults: &Tunables) -> Decimal {
+        Helper::stop_loss_price(
+            self.entry_price,
+            self.margin.unwrap_or(margin),
+            self.leverage.unwrap_or(Helper::f64_to_decimal(defaults.leverage)),
+            self.risk_pct.unwrap_or(Helper::f64_to_decimal(defaults.risk_pct)),
+            self.pos,
+        )
+    }
+
+    /// A partial target's new stop, pulled back `buffer_pct` behind `price` if the market
+    /// has already run through it. A stop the trail has already tightened further is kept.
+    pub fn partial_stop(
+        &self,
+        sl: Option<Decimal>,
+        price: Decimal,
+        buffer_pct: Decimal,
+    ) -> Option<Decimal> {
+        let sl = sl.map(|sl| {
+            if buffer_pct <= Decimal::ZERO {
+                return sl;
+            }
+            let safe = Helper::clamp_stop_loss(sl, price, self.pos, buffer_pct);
+            if safe != sl {
+                warn!("Target stop {sl} would trigger at {price}, moving it to {safe}");
+            }
+            safe
+        });
+
+        match sl {
+            Some(sl) if !self.is_tighter_stop(sl) => self.sl,
+            other => other,
+        }
+    }
+
+    /// Closes `slice` of this position on the exchange and carries the rest forward,
+    /// `remaining` in size and aimed at `tp`. When `sl` changes the stop (the first
+    /// target's breakeven) it is moved on the exchange too.
+    pub async fn take_partial(
+        &mut self,
+        slice: &OpenPosition,
+        remaining: Decimal,
+        tp: Decimal,
+        sl: Option<Decimal>,
+        exchange: &dyn Exchange,
+    ) -> Result<PlaceOrderData> {
+        let exec_price = self.close_partial(slice, exchange).await?;
+        self.position_size = remaining;
+        self.quantity = Some(remaining);
+        self.tp = Some(tp);
+
+        let previous_sl = std::mem::replace(&mut self.sl, sl);
+        if self.sl != previous_sl {
+            // don't leave the entry stop live on the exchange
+            if let Err(e) = self.update_exchange_stop(exchange).await {
+                warn!("Failed to move the exchange stop to {:?}: {e}", self.sl);
+            }
+        }
+        Ok(exec_price)
+    }
+
+    /// Whether `candidate` sits closer to price than the current stop
+    pub fn is_tighter_stop(&self, candidate: Decimal) -> bool {
+        match self.sl {
+            None => true,
+            Some(sl) if self.pos == Position::Long => candidate > sl,
+            Some(sl) => candidate < sl,
+        }
+    }
+
+    /// Moves the exchange-side stop to `sl`. Returns false if there's no stop to send.
+    pub async fn update_exchange_stop(&self, exchange: &dyn Exchange) -> Result<bool> {
+        let Some(sl) = self.sl else {
+            return Ok(false);
+        };
+        exchange
+            .update_stop_loss(self, Helper::decimal_to_f64(sl))
+            .await?;
+        Ok(true)
+    }
+
+    fn tighten_stop(&mut self, candidate: Decimal) -> bool {
+        let tighter = self.is_tighter_stop(candidate);
+        if tighter {
+            self.sl = Some(candidate);
+        }
+        tighter
+    }
+
+    pub(crate) fn default_open_position() -> OpenPosition {
         OpenPosition {
             id: Uuid::nil(),
             pos: Position::Flat,
@@ -121,15 +422,21 @@ impl OpenPosition {
             leverage: Some(dec!(35.00)),
             order_id: Some("".to_string()),
             position_id: None,
+            max_adverse_excursion: None,
+            max_favorable_excursion: None,
+            account: None,
+            macro_reduced: false,
+            entry_funding_rate: None,
+            runner: false,
+            zone_id: None,
+            symbol: None,
         }
     }
 
     async fn load_open_position(
         conn: &mut redis::aio::MultiplexedConnection,
-        //id: Uuid,
+        key: &str,
     ) -> Result<OpenPosition> {
-        let key = "trading::active".to_string();
-
         let open_pos: String = conn.get(key).await?;
 
         Ok(serde_json::from_str(&open_pos)?)
@@ -137,10 +444,9 @@ impl OpenPosition {
 
     async fn store_open_position(
         mut conn: redis::aio::MultiplexedConnection,
+        key: &str,
         open_pos: &OpenPosition,
     ) -> Result<()> {
-        let key = TRADING_BOT_ACTIVE;
-
         let _: () = conn.set(key, open_pos.as_str()).await?;
 
         Ok(())
@@ -175,40 +481,88 @@ pub struct Bot<'a> {
     zone_guard: ZoneGuard,
 
     macro_guard: MacroGuard,
+    maintenance: MaintenanceGuard,
+
+    id_gen: Box<dyn IdGen>,
+
+    /// Stop-loss basis for the current tick (mark / last / index per config);
+    /// `None` judges stops on the cycle price.
+    stop_price: Option<Decimal>,
+    /// When the price being acted on was published; `None` (replay) skips the check
+    price_at: Option<DateTime<Utc>>,
+    staleness: StalenessGuard,
+    /// The blocked entry last recorded, so a shut guard isn't recorded every tick
+    missed: MissEpisode,
+    accounts: AccountRouter,
+    /// Each cycle's state, for `/api/stream`
+    updates: tokio::sync::broadcast::Sender<BotUpdate>,
+    /// Operator commands from the API, carried out between ticks
+    commands: mpsc::Receiver<BotCommand>,
+    command_tx: mpsc::Sender<BotCommand>,
+    /// Sizing tunables: the env config with any `/api/config` override applied
+    tunables: Tunables,
+    /// Keys of the symbol currently being traded
+    keys: SymbolKeys,
+    /// The other configured symbols' state, parked until their next tick
+    symbols: BTreeMap<String, SymbolState>,
+    /// Whether `redis_conn` is usable; cycles are skipped while it isn't
+    redis_health: RedisHealth,
 }
 
 impl<'a> Bot<'a> {
     pub async fn new(
         mut conn: redis::aio::MultiplexedConnection,
         config: &'a Config,
+        http: reqwest::Client,
     ) -> Result<Self> {
-        let pos: Position = Self::load_position(&mut conn)
-            .await
-            .unwrap_or(Position::Flat);
-
-        let zones: Zones = Self::load_zones(&mut conn)
-            .await
-            .unwrap_or_else(|_| Zones::default());
-
-        let open_pos = OpenPosition::load_open_position(&mut conn)
-            .await
-            .unwrap_or_else(|_| OpenPosition::default_open_position());
-
-        let current_margin = Self::load_current_margin(&mut conn, config).await;
-
-        let partial_profit_target = Self::load_partial_profit_target(&mut conn)
-            .await
-            .unwrap_or_else(|_| [].to_vec());
+        let mut symbols = BTreeMap::new();
+        let mut primary = None;
+        for keys in SymbolKeys::for_config(config) {
+            let state = SymbolState::load(&mut conn, keys, config).await;
+            if state.keys.primary {
+                primary = Some(state);
+            } else {
+                symbols.insert(state.keys.symbol.clone(), state);
+            }
+        }
+        let SymbolState {
+            keys,
+            pos,
+            open_pos,
+            zones,
+            partial_profit_target,
+            current_margin,
+            loss_count,
+            price_at,
+            staleness,
+            maintenance,
+            missed,
+        } = primary
+            .ok_or_else(|| anyhow!("{} is not among the configured symbols", config.symbol))?;
 
-        let loss_count = Self::load_loss_count(&mut conn).await.unwrap_or(0);
+        let id_counter: Option<u64> = conn.get(TRADING_BOT_ID_COUNTER).await?;
 
         //let smc = SmcEngine::new(3, 3);
 
-        let fees = BitgetFuturesFees::new(conn.clone());
+        let fees = BitgetFuturesFees::new(conn.clone(), http);
+
+        let mut zone_guard = ZoneGuard::new(1, conn.clone(), 60 * 60);
+        match zone_guard.hydrate().await {
+            Ok(loaded) => info!("Loaded guard stats for {loaded} zones"),
+            Err(e) => warn!("Failed to load zone guard stats: {e}"),
+        }
 
-        let zone_guard = ZoneGuard::new(1, conn.clone(), 60 * 60);
+        let macro_guard = MacroGuard::new(
+            &mut conn.clone(),
+            config.calendar_tz,
+            config.macro_countries.clone(),
+        )
+        .await?;
 
-        let macro_guard = MacroGuard::new(&mut conn.clone()).await?;
+        let tunables = RuntimeOverride::load(&mut conn)
+            .await
+            .apply(Tunables::from_config(config));
+        let (command_tx, commands) = command::channel();
 
         Ok(Self {
             open_pos,
@@ -222,11 +576,72 @@ impl<'a> Bot<'a> {
             fees,
             zone_guard,
             macro_guard,
+            maintenance,
+            id_gen: id_gen::from_seed(config.id_seed, id_counter.unwrap_or(0)),
+            stop_price: None,
+            price_at,
+            staleness,
+            missed,
+            accounts: AccountRouter::new(config.accounts.clone(), config.account_routing),
+            updates: feed::channel(),
+            commands,
+            command_tx,
+            tunables,
+            keys,
+            symbols,
+            redis_health: RedisHealth::default(),
         })
     }
 
-    async fn load_loss_count(conn: &mut redis::aio::MultiplexedConnection) -> Result<usize> {
-        let opt: Option<String> = conn.get(TRADING_BOT_LOSS_COUNT).await?;
+    /// Makes `symbol` the one being traded, parking the current symbol's state until
+    /// its next tick. Returns false for a symbol that isn't configured.
+    fn switch_symbol(&mut self, symbol: &str) -> bool {
+        if self.keys.symbol == symbol {
+            return true;
+        }
+        let Some(next) = self.symbols.remove(symbol) else {
+            return false;
+        };
+        let parked = SymbolState {
+            keys: std::mem::replace(&mut self.keys, next.keys),
+            pos: std::mem::replace(&mut self.pos, next.pos),
+            open_pos: std::mem::replace(&mut self.open_pos, next.open_pos),
+            zones: std::mem::replace(&mut self.zones, next.zones),
+            partial_profit_target: std::mem::replace(
+                &mut self.partial_profit_target,
+                next.partial_profit_target,
+            ),
+            current_margin: std::mem::replace(&mut self.current_margin, next.current_margin),
+            loss_count: std::mem::replace(&mut self.loss_count, next.loss_count),
+            price_at: std::mem::replace(&mut self.price_at, next.price_at),
+            staleness: std::mem::replace(&mut self.staleness, next.staleness),
+            maintenance: std::mem::replace(&mut self.maintenance, next.maintenance),
+            missed: std::mem::replace(&mut self.missed, next.missed),
+        };
+        self.symbols.insert(parked.keys.symbol.clone(), parked);
+        true
+    }
+
+    /// Decimals the active symbol's order sizes are rounded to
+    fn size_precision(&self) -> u32 {
+        self.config.size_precision(&self.keys.symbol)
+    }
+
+    /// Sender the API subscribes to for live updates
+    pub fn updates(&self) -> tokio::sync::broadcast::Sender<BotUpdate> {
+        self.updates.clone()
+    }
+
+    /// Sender the API hands operator commands to
+    pub fn commands(&self) -> mpsc::Sender<BotCommand> {
+        self.command_tx.clone()
+    }
+
+    async fn load_loss_count(
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> Result<usize> {
+        let opt: Option<String> = conn.get(key).await?;
 
         let u = serde_json::from_str::<usize>(&opt.unwrap_or("0".to_string()));
         Ok(u.unwrap_or(0))
@@ -234,8 +649,9 @@ impl<'a> Bot<'a> {
 
     async fn load_partial_profit_target(
         conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
     ) -> Result<Vec<PartialProfitTarget>> {
-        let raw_jsons: String = conn.get(TRADING_PARTIAL_PROFIT_TARGET).await?;
+        let raw_jsons: String = conn.get(key).await?;
 
         let vecs = serde_json::from_str::<Vec<PartialProfitTarget>>(&raw_jsons)
             .map_err(|e| anyhow!("Failed to parse: {}", e))?;
@@ -243,50 +659,281 @@ impl<'a> Bot<'a> {
         Ok(vecs)
     }
 
-    async fn load_zones(conn: &mut redis::aio::MultiplexedConnection) -> Result<Zones> {
-        let json: String = conn.get(TRADING_BOT_ZONES).await?;
+    async fn load_zones(conn: &mut redis::aio::MultiplexedConnection, key: &str) -> Result<Zones> {
+        let json: String = conn.get(key).await?;
         Ok(serde_json::from_str(&json)?)
     }
 
-    pub async fn load_position(conn: &mut redis::aio::MultiplexedConnection) -> Result<Position> {
-        let opt: Option<String> = conn.get(TRADING_BOT_POSITION).await?;
+    /// The stored zones, or the symbol's fallback until the first ones are stored. A
+    /// redis failure is an error, not a fallback.
+    async fn load_zones_or_default(
+        conn: &mut redis::aio::MultiplexedConnection,
+        keys: &SymbolKeys,
+    ) -> Result<Zones> {
+        let json: Option<String> = conn.get(&keys.zones).await?;
+        match json {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(keys.fallback_zones()),
+        }
+    }
+
+    pub async fn load_position(
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> Result<Position> {
+        let opt: Option<String> = conn.get(key).await?;
 
-        Ok(match opt.as_deref() {
-            Some("Flat") => Position::Flat,
-            Some("Long") => Position::Long,
-            Some("Short") => Position::Short,
-            _ => Position::Flat,
-        })
+        Ok(opt.as_deref().map_or(Position::Flat, Position::from_stored))
     }
 
-    async fn store_position(&mut self, pos: Position, open_pos: &OpenPosition) -> Result<()> {
-        let _: () = self
-            .redis_conn
-            .set(TRADING_BOT_POSITION, pos.as_str())
-            .await?;
+    /// What `store_position` writes: the position, the open position and the
+    /// profit targets, the last deleted (`None`) once there are none left
+    fn position_writes<'k>(
+        keys: &'k SymbolKeys,
+        pos: Position,
+        open_pos: &OpenPosition,
+        targets: &[PartialProfitTarget],
+    ) -> Result<Vec<(&'k str, Option<String>)>> {
+        let targets = if targets.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(targets)?)
+        };
+        Ok(vec![
+            (&keys.position, Some(pos.as_str())),
+            (&keys.active, Some(open_pos.as_str())),
+            (&keys.partial_targets, targets),
+        ])
+    }
 
-        OpenPosition::store_open_position(self.redis_conn.clone(), open_pos).await?;
+    /// Persists the position, open position and targets in a single transaction, so a
+    /// crash can't leave `Bot::new` reading one without the others
+    async fn store_position(&mut self, pos: Position, open_pos: &OpenPosition) -> Result<()> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (key, value) in
+            Self::position_writes(&self.keys, pos, open_pos, &self.partial_profit_target)?
+        {
+            match value {
+                Some(value) => pipe.set(key, value).ignore(),
+                None => pipe.del(key).ignore(),
+            };
+        }
 
+        let _: () = pipe.query_async(&mut self.redis_conn).await?;
         Ok(())
     }
 
-    /// Store *one* closed position in the list named `TRADING_BOT_CLOSE_POSITIONS`.
+    /// Store *one* closed position in the list named `TRADING_BOT_CLOSE_POSITIONS`,
+    /// keeping at most `max_history` of the newest (0 = unbounded). Its realized PnL
+    /// counts towards the account-wide daily loss, whatever symbol it traded.
     pub async fn store_closed_position(
         conn: &mut redis::aio::MultiplexedConnection,
         pos: &ClosedPosition,
+        format: Serialization,
+        max_history: usize,
     ) -> Result<()> {
         let key = TRADING_BOT_CLOSE_POSITIONS;
-        let json = serde_json::to_string(pos)?;
+        let raw = codec::encode(pos, format)?;
 
         // LPUSH pushes to the **left** of the list – newest element first
-        let _: () = conn.lpush(key, json).await?;
+        let _: () = conn.lpush(key, raw).await?;
+        if max_history > 0 {
+            let _: () = conn.ltrim(key, 0, max_history as isize - 1).await?;
+        }
+
+        // Keep the weekly/monthly analytics rollups in step with the list
+        if let Err(e) = Rollups::record(conn, pos).await {
+            warn!("Failed to update ROI rollups: {e}");
+        }
 
-        // OPTIONAL: keep only the last N trades (e.g. 10 000)
-        // conn.ltrim(key, 0, 9999).await?;
+        let realized = pos.pnl_after_fees.unwrap_or(pos.pnl);
+        if let Err(e) = DailyLoss::record_close(conn, TRADING_BOT_DAILY_PNL, realized).await {
+            warn!("Failed to update the daily realized PnL: {e}");
+        }
 
         Ok(())
     }
 
+    /// Carries out an operator command on the active (primary) symbol and answers it
+    async fn handle_command(&mut self, command: BotCommand, exchange: &dyn Exchange) {
+        match command {
+            BotCommand::Open { entry, reply } => {
+                let opened = self.open_manually(entry, exchange).await;
+                let _ = reply.send(opened);
+            }
+            BotCommand::Close { reply } => {
+                let closed = self.close_manually(exchange).await;
+                let _ = reply.send(closed.map_err(CommandError::from));
+            }
+        }
+    }
+
+    /// Enters at market for the API and manages the position like the ranger's own:
+    /// sized off the current margin, with its targets drawn from the entry
+    async fn open_manually(
+        &mut self,
+        entry: ManualEntry,
+        exchange: &dyn Exchange,
+    ) -> Result<(OpenPosition, PlaceOrderData), CommandError> {
+        if self.pos != Position::Flat {
+            return Err(CommandError::Rejected(format!(
+                "A {:?} position is already open",
+                self.pos
+            )));
+        }
+
+        self.delete_partial_profit_target().await?;
+        self.pos = entry.side;
+        let entry_price = Helper::decimal_to_f64(entry.entry_price);
+        let leverage = Helper::decimal_to_f64(entry.leverage);
+        let _: Result<()> =
+            Self::build_partial_profit_targets(self, entry_price, self.pos, leverage).await;
+        self.open_pos = self
+            .prepare_open_position(
+                entry.side,
+                entry.entry_price,
+                entry.leverage,
+                entry.risk_pct,
+                Decimal::ONE,
+            )
+            .await;
+        if self.open_pos.position_size <= Decimal::ZERO {
+            self.abandon_entry().await;
+            return Err(CommandError::Rejected(
+                "Position size must be greater than 0".to_string(),
+            ));
+        }
+
+        let placed = match self.journal_entry().await {
+            Ok(()) => Self::place_entry_order(exchange, &self.open_pos).await,
+            Err(e) => Err(e),
+        };
+        let exec_price = match placed {
+            Ok(exec_price) => exec_price,
+            Err(e) => {
+                self.abandon_entry().await;
+                return Err(e.into());
+            }
+        };
+        warn!(
+            "Manually opened {:?} {} at {exec_price:?}",
+            self.pos, self.open_pos.id
+        );
+
+        if let Ok(Some(pos_id)) = exchange.get_position_id().await {
+            self.open_pos.position_id = Some(pos_id.clone());
+            let tp = self.exchange_take_profit();
+            let sl = self.open_pos.sl.map(Helper::decimal_to_f64);
+            if let Err(e) = exchange.place_initial_tpsl(&pos_id, tp, sl).await {
+                warn!("Failed to place initial TPSL on manual entry: {e}");
+            }
+        }
+
+        self.open_pos.order_id = Some(exec_price.order_id.clone());
+        self.open_position().await?;
+        Ok((self.open_pos.clone(), exec_price))
+    }
+
+    /// Closes the active position at market for the API, booked like any other full
+    /// close. `None` when already flat.
+    async fn close_manually(&mut self, exchange: &dyn Exchange) -> Result<Option<ClosedPosition>> {
+        if self.pos == Position::Flat {
+            return Ok(None);
+        }
+
+        let price = Helper::f64_to_decimal(exchange.get_current_price().await?);
+        self.open_pos.tp = Some(price);
+        let exec_price: PlaceOrderData = Self::place_close_order(exchange, &self.open_pos).await?;
+        warn!(
+            "Manually closed {:?} {} at {exec_price:?}",
+            self.pos, self.open_pos.id
+        );
+        self.open_pos.order_id = Some(exec_price.order_id);
+
+        let closed = match self.pos {
+            Position::Long => self.close_long_position(price).await?,
+            _ => self.close_short_position(price).await?,
+        };
+        self.pos = Position::Flat;
+        self.partial_profit_target = [].to_vec();
+        let pos_snapshot = self.open_pos.clone();
+        self.store_position(self.pos, &pos_snapshot).await?;
+        Ok(Some(closed))
+    }
+
+    /// ATR from the momentum tracker's latest snapshot, if it's running
+    async fn load_atr(&mut self) -> Option<Decimal> {
+        let raw: Option<String> = self.redis_conn.get(TRADING_BOT_MOMENTUM).await.ok()?;
+        let atr = raw
+            .and_then(|r| serde_json::from_str::<MomentumSnapshot>(&r).ok())
+            .and_then(|s| s.atr)
+            .and_then(Decimal::from_f64);
+        if atr.is_none() {
+            warn!("No ATR snapshot is available, using the risk_pct stop and margin sizing");
+        }
+        atr
+    }
+
+    /// Latest 5m candle close from the momentum snapshot, when entries need one
+    async fn load_entry_close(&mut self) -> Option<f64> {
+        if self.config.zone_entry_ref == ZoneEntryRef::Touch {
+            return None;
+        }
+        let raw: Option<String> = self.redis_conn.get(TRADING_BOT_MOMENTUM).await.ok()?;
+        let close = raw
+            .and_then(|r| serde_json::from_str::<MomentumSnapshot>(&r).ok())
+            .and_then(|s| s.last_close);
+        if close.is_none() {
+            warn!("ZONE_ENTRY_REF=candle_close but no candle close is available, skipping entries");
+        }
+        close
+    }
+
+    /// Side and zone the ranger would enter at `price`; long zones take precedence.
+    fn entry_candidate(
+        &self,
+        price: f64,
+        entry_close: Option<f64>,
+        entry_ref: ZoneEntryRef,
+    ) -> Option<(Position, Zone)> {
+        if price == 1.11 {
+            return None;
+        }
+        let admits = |z: &&Zone| z.admits_entry(price, entry_close, entry_ref);
+        if let Some(zone) = self.zones.long_zones.iter().find(admits) {
+            return Some((Position::Long, *zone));
+        }
+        self.zones
+            .short_zones
+            .iter()
+            .find(admits)
+            .map(|zone| (Position::Short, *zone))
+    }
+
+    /// Records a zone entry that didn't happen, once per zone-and-reason episode;
+    /// failures only warn so the cycle carries on.
+    async fn record_missed_entry(
+        &mut self,
+        price: f64,
+        candidate: Option<(Position, Zone)>,
+        reason: MissReason,
+    ) {
+        let Some((side, zone)) = candidate else {
+            return;
+        };
+        if !self.config.record_missed_entries || !self.missed.starts(&zone, reason) {
+            return;
+        }
+        let missed = MissedEntry::new(Utc::now(), price, side, Some(zone), reason);
+        if let Err(e) = missed
+            .record(&mut self.redis_conn, self.config.missed_entries_max)
+            .await
+        {
+            warn!("Failed to record missed entry: {e}");
+        }
+    }
+
     async fn prepare_open_position(
         &mut self,
         pos: Position,
@@ -297,8 +944,30 @@ impl<'a> Bot<'a> {
     ) -> OpenPosition {
         let current_margin = self.current_margin * funding_multiplier;
 
-        let sl = Helper::stop_loss_price(entry_price, current_margin, leverage, risk_pct, pos);
-        let qty = Helper::contract_amount(entry_price, current_margin, leverage);
+        let atr = if self.config.use_atr_stop || self.config.use_vol_sizing {
+            self.load_atr().await
+        } else {
+            None
+        };
+        let sl = match atr.filter(|_| self.config.use_atr_stop) {
+            Some(atr) => Helper::atr_stop_loss_price(
+                entry_price,
+                atr,
+                Helper::f64_to_decimal(self.config.atr_stop_multiple),
+                pos,
+            ),
+            None => Helper::stop_loss_price(entry_price, current_margin, leverage, risk_pct, pos),
+        };
+        let size_precision = self.size_precision();
+        let qty = Self::size_entry(
+            self.config,
+            &mut self.partial_profit_target,
+            entry_price,
+            current_margin,
+            leverage,
+            atr,
+            size_precision,
+        );
         let tp = self
             .partial_profit_target
             .last()
@@ -315,7 +984,7 @@ impl<'a> Bot<'a> {
             .calc_margin_for_entry(entry_price, qty, current_margin)
             .await;
         OpenPosition {
-            id: Uuid::new_v4(),
+            id: self.next_position_id().await,
             pos,
             entry_price,
             position_size: qty, //does the same thing as quantity :(
@@ -328,18 +997,62 @@ impl<'a> Bot<'a> {
             risk_pct: Some(risk_pct),
             order_id: Some("".to_string()),
             position_id: None,
+            max_adverse_excursion: None,
+            max_favorable_excursion: None,
+            account: Some(self.accounts.select(Strategy::Ranger).name.clone()),
+            macro_reduced: false,
+            entry_funding_rate: None,
+            runner: false,
+            zone_id: None,
+            symbol: Some(self.keys.symbol.clone()),
         }
     }
 
+    /// Entry quantity for `margin` at `leverage`, shrunk by `atr` when vol sizing is on.
+    /// `targets` were built for the full size, so they are rescaled to the quantity.
+    fn size_entry(
+        config: &Config,
+        targets: &mut [PartialProfitTarget],
+        entry_price: Decimal,
+        margin: Decimal,
+        leverage: Decimal,
+        atr: Option<Decimal>,
+        size_precision: u32,
+    ) -> Decimal {
+        let qty = match atr.filter(|_| config.use_vol_sizing) {
+            Some(atr) => Helper::volatility_adjusted_size(
+                entry_price,
+                margin,
+                leverage,
+                atr,
+                Helper::f64_to_decimal(config.vol_target_risk),
+            ),
+            None => Helper::contract_amount(entry_price, margin, leverage),
+        };
+        Helper::rescale_target_sizes(targets, qty, size_precision);
+        qty
+    }
+
+    /// Next position id from the id generator, persisting its counter so a restart
+    /// doesn't hand out the same ids again
+    async fn next_position_id(&mut self) -> Uuid {
+        id_gen::next_stored(
+            self.id_gen.as_ref(),
+            &mut self.redis_conn,
+            TRADING_BOT_ID_COUNTER,
+        )
+        .await
+    }
+
     async fn delete_partial_profit_target(&mut self) -> Result<()> {
-        let _: () = self.redis_conn.del(TRADING_PARTIAL_PROFIT_TARGET).await?;
+        let _: () = self.redis_conn.del(&self.keys.partial_targets).await?;
 
         self.partial_profit_target = [].to_vec();
 
         Ok(())
     }
 
-    pub async fn close_long_position(&mut self, price: Decimal) -> Result<()> {
+    pub async fn close_long_position(&mut self, price: Decimal) -> Result<ClosedPosition> {
         let dec_config_margin = Helper::f64_to_decimal(self.config.margin);
         let roi = Helper::calc_roi(
             self.open_pos.margin.unwrap_or(dec_config_margin),
@@ -355,7 +1068,7 @@ impl<'a> Bot<'a> {
             price,
         );
 
-        let (pnl_after_fees, exit_fee) = self.fees.calc_pnl_for_exit(&self.open_pos, price).await;
+        let (pnl_after_fees, costs) = self.fees.calc_pnl_for_exit(&self.open_pos, price).await;
         let closed_pos = ClosedPosition {
             id: self.open_pos.id,
             entry_price: self.open_pos.entry_price,
@@ -372,40 +1085,69 @@ impl<'a> Bot<'a> {
             margin: self.open_pos.margin,
             order_id: self.open_pos.order_id.clone(),
             pnl_after_fees: Some(pnl_after_fees),
-            exit_fee: Some(exit_fee),
+            exit_fee: Some(costs.exit_fee),
+            entry_fee: Some(costs.entry_fee),
+            funding_fee: Some(costs.funding_fee),
+            max_adverse_excursion: self.open_pos.max_adverse_excursion,
+            max_favorable_excursion: self.open_pos.max_favorable_excursion,
+            zone_id: self.open_pos.zone_id,
+            symbol: Some(self.keys.symbol.clone()),
         };
-        let _ = Self::store_closed_position(&mut self.redis_conn, &closed_pos).await;
+        let _ = Self::store_closed_position(
+            &mut self.redis_conn,
+            &closed_pos,
+            self.config.serialization,
+            self.config.max_closed_history,
+        )
+        .await;
 
         //update the margin based on the pnl
         let _ = Self::prepare_current_margin(self, pnl_after_fees).await;
 
+        self.record_zone_result(Position::Long, pnl_after_fees)
+            .await;
+
         //Track loss count
-        let total_profit_count = 4;
+        let total_profit_count = self.config.profit_target_count();
         //This means that we did not hit any of the targets
         if self.partial_profit_target.len() == total_profit_count {
-            //Track also the zone where we got a loss
-            let zone = self
-                .zones
-                .long_zones
-                .iter()
-                .find(|z| z.contains(Helper::decimal_to_f64(self.open_pos.entry_price)));
-
-            if let Some(zone) = zone {
-                warn!(
-                    "Losing zone found for price: {}; zone: {:?}",
-                    self.open_pos.entry_price, zone
-                );
-                let zone_id = ZoneId::from_zone(zone);
-                self.zone_guard
-                    .record_trade_result(zone_id, Helper::decimal_to_f64(pnl_after_fees))
-                    .await;
-            }
-
             info!("Loss count: {}", self.loss_count);
             let _ = self.store_loss_count(pnl_after_fees).await;
         }
-        self.loss_count = Self::load_loss_count(&mut self.redis_conn).await?;
-        Ok(())
+        self.loss_count = Self::load_loss_count(&mut self.redis_conn, &self.keys.loss_count).await?;
+        Ok(closed_pos)
+    }
+
+    /// Feeds a closed entry to the zone guard, against the zone it was entered from
+    /// (looked up by entry price for positions that predate `zone_id`). An entry that
+    /// banked any target counts as a win for its zone.
+    async fn record_zone_result(&mut self, side: Position, pnl_after_fees: Decimal) {
+        let zones = match side {
+            Position::Short => &self.zones.short_zones,
+            _ => &self.zones.long_zones,
+        };
+        let entry_price = Helper::decimal_to_f64(self.open_pos.entry_price);
+        let Some(zone_id) = self.open_pos.zone_id.or_else(|| {
+            zones
+                .iter()
+                .find(|z| z.contains(entry_price))
+                .map(ZoneId::from_zone)
+        }) else {
+            return;
+        };
+
+        let targets_hit = self.partial_profit_target.len() < self.config.profit_target_count();
+        let pnl = if targets_hit {
+            pnl_after_fees.max(Decimal::ZERO)
+        } else {
+            pnl_after_fees
+        };
+        if pnl < Decimal::ZERO {
+            warn!("Losing zone {zone_id:?} for entry at {entry_price}");
+        }
+        self.zone_guard
+            .record_trade_result(zone_id, Helper::decimal_to_f64(pnl))
+            .await;
     }
 
     async fn store_loss_count(&mut self, pnl: Decimal) -> Result<()> {
@@ -415,7 +1157,7 @@ impl<'a> Bot<'a> {
             //Store the loss count in redis for 12hours
             if let Err(e) = self
                 .redis_conn
-                .set_ex::<_, _, ()>(TRADING_BOT_LOSS_COUNT, self.loss_count, 43200) //12hours reset
+                .set_ex::<_, _, ()>(&self.keys.loss_count, self.loss_count, 43200) //12hours reset
                 .await
             {
                 warn!("Failed to store loss count: {e}");
@@ -427,16 +1169,23 @@ impl<'a> Bot<'a> {
     pub async fn load_current_margin(
         redis_conn: &mut redis::aio::MultiplexedConnection,
         config: &'a Config,
+        keys: &SymbolKeys,
     ) -> Decimal {
-        let key = TRADING_CAPITAL;
-
-        let raw_margin: Result<Option<String>, RedisError> = redis_conn.get(key).await;
-
-        let mut margin = match raw_margin {
-            Ok(Some(raw_margin)) => serde_json::from_str::<Decimal>(&raw_margin)
-                .unwrap_or_else(|_| Helper::f64_to_decimal(config.margin)),
-            Ok(None) => Helper::f64_to_decimal(config.margin),
-            Err(_) => Helper::f64_to_decimal(config.margin),
+        let mut margin = if keys.primary {
+            // The ranger sizes off its own slice of the trading capital
+            let allocator = CapitalAllocator::from_config(config);
+            allocator
+                .load(
+                    redis_conn,
+                    Strategy::Ranger,
+                    Helper::f64_to_decimal(config.margin),
+                )
+                .await
+        } else {
+            // Other symbols keep a margin of their own, outside the allocator
+            let raw: Option<String> = redis_conn.get(&keys.capital).await.unwrap_or(None);
+            raw.and_then(|r| serde_json::from_str::<Decimal>(&r).ok())
+                .unwrap_or(Helper::f64_to_decimal(config.margin))
         };
 
         if margin <= dec!(5.00) {
@@ -449,7 +1198,8 @@ impl<'a> Bot<'a> {
     }
 
     pub async fn prepare_current_margin(&mut self, pnl: Decimal) -> Decimal {
-        let mut current_margin = Self::load_current_margin(&mut self.redis_conn, self.config).await;
+        let mut current_margin =
+            Self::load_current_margin(&mut self.redis_conn, self.config, &self.keys).await;
         info!("redis_current_margin: {current_margin:?}");
         info!("prepare_current_margin pnl: {pnl:?}");
 
@@ -464,8 +1214,19 @@ impl<'a> Bot<'a> {
 
         self.current_margin = current_margin;
 
-        let _ = Self::store_current_margin(current_margin, &mut self.redis_conn).await;
-        let _ = OpenPosition::store_open_position(self.redis_conn.clone(), &self.open_pos).await;
+        let _ = Self::store_current_margin(
+            current_margin,
+            &mut self.redis_conn,
+            self.config,
+            &self.keys,
+        )
+        .await;
+        let _ = OpenPosition::store_open_position(
+            self.redis_conn.clone(),
+            &self.keys.active,
+            &self.open_pos,
+        )
+        .await;
 
         current_margin
     }
@@ -473,24 +1234,35 @@ impl<'a> Bot<'a> {
     async fn store_current_margin(
         current_margin: Decimal,
         conn: &mut redis::aio::MultiplexedConnection,
+        config: &Config,
+        keys: &SymbolKeys,
     ) -> Result<()> {
-        let json = serde_json::to_string(&current_margin).expect("Failed to serialize margin");
-
-        let _: () = conn.set(TRADING_CAPITAL, json).await?;
-
-        Ok(())
+        if !keys.primary {
+            let _: () = conn
+                .set(&keys.capital, serde_json::to_string(&current_margin)?)
+                .await?;
+            return Ok(());
+        }
+        CapitalAllocator::from_config(config)
+            .store(
+                conn,
+                Strategy::Ranger,
+                current_margin,
+                Helper::f64_to_decimal(config.margin),
+            )
+            .await
     }
 
-    pub async fn close_short_position(&mut self, price: Decimal) -> Result<()> {
+    pub async fn close_short_position(&mut self, price: Decimal) -> Result<ClosedPosition> {
         let pnl = Helper::compute_pnl(
             self.open_pos.pos,
             self.open_pos.entry_price,
             self.open_pos.position_size,
             price,
         );
-        let (pnl_after_fees, exit_fee) = self.fees.calc_pnl_for_exit(&self.open_pos, price).await;
+        let (pnl_after_fees, costs) = self.fees.calc_pnl_for_exit(&self.open_pos, price).await;
         info!(
-            "close_short_position: pnl, pnl_after_fees, exit_fees -> {pnl:?}, {pnl_after_fees:?}, {exit_fee:?}"
+            "close_short_position: pnl, pnl_after_fees, exit_fees -> {pnl:?}, {pnl_after_fees:?}, {costs:?}"
         );
         let roi = Helper::calc_roi(
             self.open_pos
@@ -517,41 +1289,38 @@ impl<'a> Bot<'a> {
             margin: self.open_pos.margin,
             order_id: self.open_pos.order_id.clone(),
             pnl_after_fees: Some(pnl_after_fees),
-            exit_fee: Some(exit_fee),
+            exit_fee: Some(costs.exit_fee),
+            entry_fee: Some(costs.entry_fee),
+            funding_fee: Some(costs.funding_fee),
+            max_adverse_excursion: self.open_pos.max_adverse_excursion,
+            max_favorable_excursion: self.open_pos.max_favorable_excursion,
+            zone_id: self.open_pos.zone_id,
+            symbol: Some(self.keys.symbol.clone()),
         };
-        let _ = Self::store_closed_position(&mut self.redis_conn, &closed_pos).await;
+        let _ = Self::store_closed_position(
+            &mut self.redis_conn,
+            &closed_pos,
+            self.config.serialization,
+            self.config.max_closed_history,
+        )
+        .await;
 
         //update the margin based on the pnl
         let _ = Self::prepare_current_margin(self, pnl_after_fees).await;
 
+        self.record_zone_result(Position::Short, pnl_after_fees)
+            .await;
+
         //Track loss count
-        let total_profit_count = 4;
+        let total_profit_count = self.config.profit_target_count();
         //This means that we did not hit any of the targets
         if self.partial_profit_target.len() == total_profit_count {
-            //Track also the zone where we got a loss
-            let zone = self
-                .zones
-                .short_zones
-                .iter()
-                .find(|z| z.contains(Helper::decimal_to_f64(self.open_pos.entry_price)));
-
-            if let Some(zone) = zone {
-                warn!(
-                    "Losing zone found for price: {}; zone: {:?}",
-                    self.open_pos.entry_price, zone
-                );
-                let zone_id = ZoneId::from_zone(zone);
-                self.zone_guard
-                    .record_trade_result(zone_id, Helper::decimal_to_f64(pnl_after_fees))
-                    .await;
-            }
-
             let _ = self.store_loss_count(pnl_after_fees).await;
         }
 
-        self.loss_count = Self::load_loss_count(&mut self.redis_conn).await?;
+        self.loss_count = Self::load_loss_count(&mut self.redis_conn, &self.keys.loss_count).await?;
 
-        Ok(())
+        Ok(closed_pos)
     }
 
     pub async fn take_profit_on_long(
@@ -563,19 +1332,93 @@ impl<'a> Bot<'a> {
 
         self.open_pos.tp = Some(price);
 
-        let exec_price: PlaceOrderData = exchange.modify_market_order(&self.open_pos).await?;
+        let exec_price: PlaceOrderData = Self::place_close_order(exchange, &self.open_pos).await?;
 
         info!("Ranger Closed LONG at {exec_price:?}");
 
-        let _: () = Self::close_long_position(self, price).await?;
+        Self::close_long_position(self, price).await?;
 
         self.pos = Position::Flat;
 
         Ok(())
     }
 
-    async fn take_partial_profit_on_long(
-        &mut self,
+    /// Moves the exchange-side stop to the current one; failures only warn since the
+    /// bot watches the stop itself
+    async fn push_stop_loss(&self, exchange: &dyn Exchange) {
+        if let Err(e) = self.open_pos.update_exchange_stop(exchange).await {
+            warn!(
+                "Failed to move the exchange stop to {:?}: {e}",
+                self.open_pos.sl
+            );
+        }
+    }
+
+    /// Trails the runner's stop, on the exchange as well, and stores it when it moves
+    async fn trail_runner(&mut self, price: Decimal, exchange: &dyn Exchange) -> Result<()> {
+        if !self.open_pos.runner {
+            return Ok(());
+        }
+        let trail_pct = Helper::f64_to_decimal(self.config.runner_trail_pct);
+        let moved = match self.open_pos.ratchet_runner(price, trail_pct, exchange).await {
+            Ok(moved) => moved,
+            Err(e) => {
+                warn!(
+                    "Failed to move the runner's exchange stop to {:?}: {e}",
+                    self.open_pos.sl
+                );
+                true
+            }
+        };
+        if moved {
+            info!(
+                "{:?} runner trailing stop moved to {:?}",
+                self.pos, self.open_pos.sl
+            );
+            OpenPosition::store_open_position(
+                self.redis_conn.clone(),
+                &self.keys.active,
+                &self.open_pos,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// The take profit to register on the exchange at entry. None while runners are
+    /// kept, or the exchange would close the runner at the final target.
+    fn exchange_take_profit(&self) -> Option<f64> {
+        if self.config.runner_fraction > 0.0 {
+            return None;
+        }
+        self.open_pos.tp.map(Helper::decimal_to_f64)
+    }
+
+    /// Trails the stop behind a position that has run far enough in our favour
+    async fn trail_stop(&mut self, price: Decimal, exchange: &dyn Exchange) -> Result<()> {
+        let moved = self.open_pos.trail_stop(
+            price,
+            Helper::f64_to_decimal(self.config.trail_activate_pct),
+            Helper::f64_to_decimal(self.config.trail_distance),
+        );
+        if moved {
+            info!(
+                "{:?} trailing stop moved to {:?}",
+                self.pos, self.open_pos.sl
+            );
+            self.push_stop_loss(exchange).await;
+            OpenPosition::store_open_position(
+                self.redis_conn.clone(),
+                &self.keys.active,
+                &self.open_pos,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn take_partial_profit_on_long(
+        &mut self,
         price: f64,
         target: PartialProfitTarget,
         exchange: &dyn Exchange,
@@ -587,7 +1430,7 @@ impl<'a> Bot<'a> {
         let dec_price = Helper::f64_to_decimal(price);
 
         if qty_to_close <= dec!(0.0000) {
-            let _: () = Self::close_long_position(self, dec_price).await?;
+            Self::close_long_position(self, dec_price).await?;
         }
 
         if self.partial_profit_target.is_empty() {
@@ -604,7 +1447,7 @@ impl<'a> Bot<'a> {
         if remaining_size <= dec!(0.0000) {
             self.open_pos.quantity = Some(remaining_size);
             self.open_pos.position_size = remaining_size;
-            let _: () = Self::close_long_position(self, dec_price).await?;
+            Self::close_long_position(self, dec_price).await?;
         }
 
         let roi = Helper::calc_roi(
@@ -633,16 +1476,36 @@ impl<'a> Bot<'a> {
             risk_pct: self.open_pos.risk_pct,
             order_id: self.open_pos.order_id.clone(),
             position_id: self.open_pos.position_id.clone(),
+            max_adverse_excursion: self.open_pos.max_adverse_excursion,
+            max_favorable_excursion: self.open_pos.max_favorable_excursion,
+            account: self.open_pos.account.clone(),
+            macro_reduced: self.open_pos.macro_reduced,
+            entry_funding_rate: self.open_pos.entry_funding_rate,
+            runner: self.open_pos.runner,
+            zone_id: self.open_pos.zone_id,
+            symbol: Some(self.keys.symbol.clone()),
         };
 
-        let (pnl_after_fees, exit_fee) = self
+        let (pnl_after_fees, costs) = self
             .fees
             .calc_pnl_for_exit(&modified_open_pos, dec_price)
             .await;
 
-        //Exchange call to take profit
-        //self.open_pos.tp = Some(dec_price);
-        let exec_price: PlaceOrderData = exchange.modify_market_order(&modified_open_pos).await?;
+        //Exchange call to take profit, moving the stop up behind the target
+        let closed_sl = self.open_pos.sl;
+        let buffer_pct = Helper::f64_to_decimal(self.config.partial_sl_buffer_pct);
+        let sl = self.open_pos.partial_stop(target.sl, dec_price, buffer_pct);
+        let exec_price: PlaceOrderData = self
+            .open_pos
+            .take_partial(
+                &modified_open_pos,
+                remaining_size,
+                target.target_price,
+                sl,
+                exchange,
+            )
+            .await?;
+        self.open_pos.symbol = Some(self.keys.symbol.clone());
         info!("exec_price: {exec_price:?}");
 
         let closed_pos = ClosedPosition {
@@ -655,36 +1518,32 @@ impl<'a> Bot<'a> {
             entry_time: self.open_pos.entry_time,
             pnl,
             quantity: Some(qty_to_close),
-            sl: self.open_pos.sl,
+            sl: closed_sl,
             roi: Some(roi),
             leverage: self.open_pos.leverage,
             margin: self.open_pos.margin,
-            order_id: self.open_pos.order_id.clone(),
+            order_id: Some(exec_price.order_id),
             pnl_after_fees: Some(pnl_after_fees),
-            exit_fee: Some(exit_fee),
+            exit_fee: Some(costs.exit_fee),
+            entry_fee: Some(costs.entry_fee),
+            funding_fee: Some(costs.funding_fee),
+            max_adverse_excursion: self.open_pos.max_adverse_excursion,
+            max_favorable_excursion: self.open_pos.max_favorable_excursion,
+            zone_id: self.open_pos.zone_id,
+            symbol: Some(self.keys.symbol.clone()),
         };
-        let _ = Self::store_closed_position(&mut self.redis_conn, &closed_pos).await;
+        let _ = Self::store_closed_position(
+            &mut self.redis_conn,
+            &closed_pos,
+            self.config.serialization,
+            self.config.max_closed_history,
+        )
+        .await;
 
         //update the margin based on the pnl
         let _ = Self::prepare_current_margin(self, pnl_after_fees).await;
 
-        self.open_pos = OpenPosition {
-            id: self.open_pos.id,
-            pos: self.open_pos.pos,
-            entry_price: self.open_pos.entry_price,
-            position_size: remaining_size,
-            entry_time: self.open_pos.entry_time,
-            tp: Some(target.target_price),
-            sl: target.sl,
-            margin: self.open_pos.margin,
-            quantity: Some(remaining_size),
-            leverage: self.open_pos.leverage,
-            risk_pct: self.open_pos.risk_pct,
-            order_id: Some(exec_price.order_id),
-            position_id: self.open_pos.position_id.clone(),
-        };
-
-        warn!("NEW SL for LONG is: {:?}", target.sl);
+        warn!("NEW SL for LONG is: {:?}", self.open_pos.sl);
         self.store_position(self.pos, &self.open_pos.clone())
             .await?;
         Ok(())
@@ -701,7 +1560,7 @@ impl<'a> Bot<'a> {
         let dec_price = Helper::f64_to_decimal(price);
 
         if qty_to_close <= dec!(0.0000) {
-            let _: () = Self::close_short_position(self, dec_price).await?;
+            Self::close_short_position(self, dec_price).await?;
         }
 
         if self.partial_profit_target.is_empty() {
@@ -718,7 +1577,7 @@ impl<'a> Bot<'a> {
         if remaining_size <= dec!(0.0000) {
             self.open_pos.quantity = Some(remaining_size);
             self.open_pos.position_size = remaining_size;
-            let _: () = Self::close_short_position(self, dec_price).await?;
+            Self::close_short_position(self, dec_price).await?;
         }
 
         let roi = Helper::calc_roi(
@@ -747,16 +1606,36 @@ impl<'a> Bot<'a> {
             risk_pct: self.open_pos.risk_pct,
             order_id: self.open_pos.order_id.clone(),
             position_id: self.open_pos.position_id.clone(),
+            max_adverse_excursion: self.open_pos.max_adverse_excursion,
+            max_favorable_excursion: self.open_pos.max_favorable_excursion,
+            account: self.open_pos.account.clone(),
+            macro_reduced: self.open_pos.macro_reduced,
+            entry_funding_rate: self.open_pos.entry_funding_rate,
+            runner: self.open_pos.runner,
+            zone_id: self.open_pos.zone_id,
+            symbol: Some(self.keys.symbol.clone()),
         };
 
-        let (pnl_after_fees, exit_fee) = self
+        let (pnl_after_fees, costs) = self
             .fees
             .calc_pnl_for_exit(&modified_open_pos, dec_price)
             .await;
 
-        //Exchange call to take profit
-        //self.open_pos.tp = Some(dec_price);
-        let exec_price: PlaceOrderData = exchange.modify_market_order(&modified_open_pos).await?;
+        //Exchange call to take profit, moving the stop up behind the target
+        let closed_sl = self.open_pos.sl;
+        let buffer_pct = Helper::f64_to_decimal(self.config.partial_sl_buffer_pct);
+        let sl = self.open_pos.partial_stop(target.sl, dec_price, buffer_pct);
+        let exec_price: PlaceOrderData = self
+            .open_pos
+            .take_partial(
+                &modified_open_pos,
+                remaining_size,
+                target.target_price,
+                sl,
+                exchange,
+            )
+            .await?;
+        self.open_pos.symbol = Some(self.keys.symbol.clone());
         info!("exec_price: {exec_price:?}");
 
         let closed_pos = ClosedPosition {
@@ -769,38 +1648,130 @@ impl<'a> Bot<'a> {
             entry_time: self.open_pos.entry_time,
             pnl,
             quantity: Some(qty_to_close),
-            sl: self.open_pos.sl,
+            sl: closed_sl,
             roi: Some(roi),
             leverage: self.open_pos.leverage,
             margin: self.open_pos.margin,
             order_id: Some(exec_price.order_id),
             pnl_after_fees: Some(pnl_after_fees),
-            exit_fee: Some(exit_fee),
+            exit_fee: Some(costs.exit_fee),
+            entry_fee: Some(costs.entry_fee),
+            funding_fee: Some(costs.funding_fee),
+            max_adverse_excursion: self.open_pos.max_adverse_excursion,
+            max_favorable_excursion: self.open_pos.max_favorable_excursion,
+            zone_id: self.open_pos.zone_id,
+            symbol: Some(self.keys.symbol.clone()),
         };
-        let _ = Self::store_closed_position(&mut self.redis_conn, &closed_pos).await;
+        let _ = Self::store_closed_position(
+            &mut self.redis_conn,
+            &closed_pos,
+            self.config.serialization,
+            self.config.max_closed_history,
+        )
+        .await;
 
         //update the margin based on the pnl
         let _ = Self::prepare_current_margin(self, pnl_after_fees).await;
 
-        self.open_pos = OpenPosition {
+        self.store_position(self.pos, &self.open_pos.clone())
+            .await?;
+
+        warn!("NEW SL for SHORT is: {:?}", self.open_pos.sl);
+
+        Ok(())
+    }
+
+    /// De-risks ahead of a macro event: closes all but `target_exposure` of the
+    /// position with a reduce-only order and keeps the rest (and its ladder) open.
+    async fn reduce_exposure(
+        &mut self,
+        price: f64,
+        target_exposure: f64,
+        exchange: &dyn Exchange,
+    ) -> Result<()> {
+        let quantity = self.open_pos.quantity.unwrap_or(self.open_pos.position_size);
+        let qty_to_close = self
+            .open_pos
+            .reduction_for(target_exposure, self.size_precision());
+        let remaining_size = quantity - qty_to_close;
+
+        if qty_to_close <= dec!(0.0000) || remaining_size <= dec!(0.0000) {
+            info!(
+                "Nothing to reduce on {quantity} at {:.0}% exposure",
+                target_exposure * 100.0
+            );
+            self.open_pos.macro_reduced = true;
+            return self.store_position(self.pos, &self.open_pos.clone()).await;
+        }
+
+        let dec_price = Helper::f64_to_decimal(price);
+        let reduce_order = OpenPosition {
+            position_size: qty_to_close,
+            quantity: Some(qty_to_close),
+            ..self.open_pos.clone()
+        };
+
+        let (pnl_after_fees, costs) =
+            self.fees.calc_pnl_for_exit(&reduce_order, dec_price).await;
+        let exec_price: PlaceOrderData =
+            self.open_pos.close_partial(&reduce_order, exchange).await?;
+        info!("Reduced {:?} by {qty_to_close} at {exec_price:?}", self.pos);
+
+        let closed_pos = ClosedPosition {
             id: self.open_pos.id,
-            pos: self.open_pos.pos,
             entry_price: self.open_pos.entry_price,
-            position_size: remaining_size,
+            exit_price: dec_price,
+            exit_time: Utc::now(),
+            position: Some(self.pos),
+            side: Some(self.pos),
             entry_time: self.open_pos.entry_time,
-            tp: Some(target.target_price),
-            sl: target.sl,
-            margin: self.open_pos.margin,
-            quantity: Some(remaining_size),
+            pnl: Helper::compute_pnl(self.pos, self.open_pos.entry_price, qty_to_close, dec_price),
+            quantity: Some(qty_to_close),
+            sl: self.open_pos.sl,
+            roi: Some(Helper::calc_roi(
+                self.open_pos
+                    .margin
+                    .unwrap_or(Helper::f64_to_decimal(self.config.margin)),
+                self.open_pos.entry_price,
+                self.pos,
+                qty_to_close,
+                dec_price,
+            )),
             leverage: self.open_pos.leverage,
-            risk_pct: self.open_pos.risk_pct,
-            order_id: self.open_pos.order_id.clone(),
-            position_id: self.open_pos.position_id.clone(),
+            margin: self.open_pos.margin,
+            order_id: Some(exec_price.order_id),
+            pnl_after_fees: Some(pnl_after_fees),
+            exit_fee: Some(costs.exit_fee),
+            entry_fee: Some(costs.entry_fee),
+            funding_fee: Some(costs.funding_fee),
+            max_adverse_excursion: self.open_pos.max_adverse_excursion,
+            max_favorable_excursion: self.open_pos.max_favorable_excursion,
+            zone_id: self.open_pos.zone_id,
+            symbol: Some(self.keys.symbol.clone()),
         };
-        self.store_position(self.pos, &self.open_pos.clone())
-            .await?;
+        let _ = Self::store_closed_position(
+            &mut self.redis_conn,
+            &closed_pos,
+            self.config.serialization,
+            self.config.max_closed_history,
+        )
+        .await;
+        let _ = Self::prepare_current_margin(self, pnl_after_fees).await;
 
-        warn!("NEW SL for SHORT is: {:?}", target.sl);
+        self.open_pos.position_size = remaining_size;
+        self.open_pos.quantity = Some(remaining_size);
+        self.open_pos.macro_reduced = true;
+
+        // The ladder still sums to the old size; shrink it to what's left
+        if !self.partial_profit_target.is_empty() {
+            let size_precision = self.size_precision();
+            Helper::rescale_target_sizes(
+                &mut self.partial_profit_target,
+                remaining_size,
+                size_precision,
+            );
+        }
+        self.store_position(self.pos, &self.open_pos.clone()).await?;
 
         Ok(())
     }
@@ -816,11 +1787,11 @@ impl<'a> Bot<'a> {
 
         self.open_pos.tp = Some(dec_price);
 
-        let exec_price: PlaceOrderData = exchange.modify_market_order(&self.open_pos).await?;
+        let exec_price: PlaceOrderData = Self::place_close_order(exchange, &self.open_pos).await?;
 
         info!("Ranger Covered SHORT at {exec_price:?}");
 
-        let _: () = Self::close_short_position(self, dec_price).await?;
+        Self::close_short_position(self, dec_price).await?;
 
         self.pos = Position::Flat;
 
@@ -852,7 +1823,7 @@ impl<'a> Bot<'a> {
                 .unwrap_or(&Zone {
                     low: 0.00,
                     high: 0.00,
-                    side: zones::Side::Short,
+                    side: Side::Short,
                 });
 
             return the_zone.low - entry_price;
@@ -868,7 +1839,7 @@ impl<'a> Bot<'a> {
                 .collect();
 
             if valid_zones.is_empty() {
-                return self.config.ranger_price_difference;
+                return self.tunables.ranger_price_difference;
             }
 
             // Find the nearest zone by comparing distance to zone high
@@ -882,7 +1853,7 @@ impl<'a> Bot<'a> {
                 .unwrap_or(&Zone {
                     low: 0.00,
                     high: 0.00,
-                    side: zones::Side::Long,
+                    side: Side::Long,
                 });
 
             return entry_price - the_zone.high;
@@ -891,19 +1862,20 @@ impl<'a> Bot<'a> {
         0.00
     }
 
-    async fn store_partial_profit_targets(
+    /// Builds the ladder for an entry at `entry_price` opened with `leverage`, which must
+    /// be the position's own (zone overrides included) for the sizes to add up
+    async fn build_partial_profit_targets(
         &mut self,
         entry_price: f64,
         pos: Position,
+        leverage: f64,
     ) -> Result<()> {
-        self.zones = Bot::load_zones(&mut self.redis_conn)
-            .await
-            .unwrap_or(Zones::default());
+        self.zones = Bot::load_zones_or_default(&mut self.redis_conn, &self.keys).await?;
 
         let price_difference = Self::determine_profit_difference(self, entry_price, pos);
 
-        let profit_count = 4.00;
-        let mut ranger_price_difference = self.config.ranger_price_difference;
+        let profit_count = self.config.profit_target_count() as f64;
+        let mut ranger_price_difference = self.tunables.ranger_price_difference;
         if price_difference.is_finite() && price_difference != 0.00 {
             ranger_price_difference = price_difference.div(profit_count);
         }
@@ -911,24 +1883,47 @@ impl<'a> Bot<'a> {
         let current_margin = self.current_margin;
 
         let dec_entry_price = Decimal::from_f64(entry_price).unwrap();
-        let dec_leverage = Decimal::from_f64(self.config.leverage).unwrap();
+        let dec_leverage = Decimal::from_f64(leverage).unwrap_or(Decimal::ONE);
         let dec_ranger_price_difference = Decimal::from_f64(ranger_price_difference).unwrap();
 
-        let ppt = Helper::build_profit_targets(
+        let fee_breakeven = self.fees.breakeven_distance(dec_entry_price).await;
+        let min_net = Decimal::from_f64(self.config.min_net_tp1_distance).unwrap_or_default();
+        let min_tp1_distance = fee_breakeven + min_net;
+
+        let fractions: Vec<Decimal> = self
+            .config
+            .profit_target_fractions
+            .iter()
+            .map(|f| Helper::f64_to_decimal(*f))
+            .collect();
+
+        let mut ppt = Helper::build_profit_targets(
             dec_entry_price,
             current_margin,
             dec_leverage,
             dec_ranger_price_difference,
+            min_tp1_distance,
             pos,
+            &fractions,
+            self.size_precision(),
         );
+        let runner =
+            Helper::carve_runner(&mut ppt, self.config.runner_fraction, self.size_precision());
+        if runner > Decimal::ZERO {
+            info!("Keeping {runner} back from the final target as a runner");
+        }
+
+        self.partial_profit_target = ppt;
 
-        self.partial_profit_target = ppt.clone();
+        Ok(())
+    }
 
+    async fn store_partial_profit_targets(&mut self) -> Result<()> {
         let _: () = self
             .redis_conn
             .set(
-                TRADING_PARTIAL_PROFIT_TARGET,
-                serde_json::to_string(&ppt.clone()).unwrap(),
+                &self.keys.partial_targets,
+                serde_json::to_string(&self.partial_profit_target)?,
             )
             .await?;
 
@@ -950,20 +1945,14 @@ impl<'a> Bot<'a> {
 
         let dec_price = Decimal::from_f64(price).unwrap();
 
-        let idx_opt = self
-            .partial_profit_target
-            .iter()
-            .position(|t| dec_price >= t.target_price);
-
-        let idx = idx_opt.unwrap_or(usize::MAX);
-
-        if idx == usize::MAX {
-            return Ok(());
-        }
-
-        let target = self.partial_profit_target[idx].clone();
+        let batches = Helper::batch_crossed_targets(
+            &self.partial_profit_target,
+            dec_price,
+            Position::Long,
+            self.config.max_partials_per_cycle,
+        );
 
-        if target.target_price.is_zero() || target.target_price.is_sign_negative() {
+        if batches.is_empty() {
             return Ok(());
         }
 
@@ -971,9 +1960,27 @@ impl<'a> Bot<'a> {
             "LONG: Taking Partial Profits here.... {:?}, Take profit targets: {:?}",
             price, self.partial_profit_target
         );
-        let _: () = Self::take_partial_profit_on_long(self, price, target, exchange).await?;
 
-        self.partial_profit_target.remove(idx);
+        let mut covered: Vec<usize> = Vec::new();
+        for (target, indices) in batches {
+            if indices.len() > 1 {
+                info!("LONG: combining targets {indices:?} into one close of {}", target.size_btc);
+            }
+            let _: () = Self::take_partial_profit_on_long(self, price, target, exchange).await?;
+            covered.extend(indices);
+        }
+
+        // Remove back to front so earlier indices stay valid
+        covered.sort_unstable_by(|a, b| b.cmp(a));
+        for idx in covered {
+            self.partial_profit_target.remove(idx);
+        }
+        if self.config.renormalize_partial_fractions && !self.partial_profit_target.is_empty() {
+            Helper::renormalize_fractions(&mut self.partial_profit_target);
+        }
+        if self.partial_profit_target.is_empty() {
+            self.start_runner(dec_price);
+        }
 
         warn!(
             "self.partial_profit_target: {:?}",
@@ -983,7 +1990,7 @@ impl<'a> Bot<'a> {
         let _: () = self
             .redis_conn
             .set(
-                TRADING_PARTIAL_PROFIT_TARGET,
+                &self.keys.partial_targets,
                 serde_json::to_string(&self.partial_profit_target.clone()).unwrap(),
             )
             .await?;
@@ -1006,21 +2013,14 @@ impl<'a> Bot<'a> {
 
         let dec_price = Decimal::from_f64(price).unwrap();
 
-        let idx_opt = self
-            .partial_profit_target
-            .iter()
-            .position(|t| dec_price <= t.target_price);
-
-        let idx = idx_opt.unwrap_or(usize::MAX);
-
-        if idx == usize::MAX {
-            return Ok(());
-        }
-
-        let target = self.partial_profit_target[idx].clone();
-        info!("target: {target:?}");
+        let batches = Helper::batch_crossed_targets(
+            &self.partial_profit_target,
+            dec_price,
+            Position::Short,
+            self.config.max_partials_per_cycle,
+        );
 
-        if target.target_price.is_zero() || target.target_price.is_sign_negative() {
+        if batches.is_empty() {
             return Ok(());
         }
 
@@ -1028,9 +2028,28 @@ impl<'a> Bot<'a> {
             "SHORT: Taking Partial Profits here.... {:?}, Take profit targets: {:?}",
             price, self.partial_profit_target
         );
-        let _: () = Self::take_partial_profit_on_short(self, price, target, exchange).await?;
 
-        self.partial_profit_target.remove(idx);
+        let mut covered: Vec<usize> = Vec::new();
+        for (target, indices) in batches {
+            if indices.len() > 1 {
+                info!("SHORT: combining targets {indices:?} into one close of {}", target.size_btc);
+            }
+            let _: () = Self::take_partial_profit_on_short(self, price, target, exchange).await?;
+            covered.extend(indices);
+        }
+
+        // Remove back to front so earlier indices stay valid
+        covered.sort_unstable_by(|a, b| b.cmp(a));
+        for idx in covered {
+            self.partial_profit_target.remove(idx);
+        }
+        if self.config.renormalize_partial_fractions && !self.partial_profit_target.is_empty() {
+            Helper::renormalize_fractions(&mut self.partial_profit_target);
+        }
+        if self.partial_profit_target.is_empty() {
+            self.start_runner(dec_price);
+        }
+
         warn!(
             "self.partial_profit_target: {:?}",
             self.partial_profit_target
@@ -1039,7 +2058,7 @@ impl<'a> Bot<'a> {
         let _: () = self
             .redis_conn
             .set(
-                TRADING_PARTIAL_PROFIT_TARGET,
+                &self.keys.partial_targets,
                 serde_json::to_string(&self.partial_profit_target.clone()).unwrap(),
             )
             .await?;
@@ -1047,156 +2066,785 @@ impl<'a> Bot<'a> {
         Ok(())
     }
 
-    // pub async fn test(&mut self, exchange: &dyn Exchange) -> Result<()> {
-    //     self.open_pos = OpenPosition {
-    //         id: Uuid::nil(),
-    //         pos: Position::Long,
-    //         entry_price: Decimal::from_f64(65077.5).unwrap(),
-    //         entry_time: Utc::now(),
-    //         position_size: Decimal::from_f64(0.03024824431843623599262351).unwrap(),
-    //         tp: Some(Decimal::from_f64(65974.375).unwrap()),
-    //         sl: Some(Decimal::from_f64(64833.459375).unwrap()),
-    //         margin: Some(Decimal::from_f64(106.00).unwrap()),
-    //         quantity: Some(Decimal::from_f64(0.03024824431843623599262351).unwrap()),
-    //         risk_pct: Some(Decimal::from_f64(0.05).unwrap()),
-    //         leverage: Some(Decimal::from_f64(20.00).unwrap()),
-    //         order_id: Some("".to_string()),
-    //     };
-    //     let price = 65979.6;
-
-    //     self.partial_profit_target = vec![PartialProfitTarget {
-    //         target_price: Decimal::from_f64(65974.375).unwrap(),
-    //         size_btc: Decimal::from_f64(0.00657).unwrap(),
-    //         fraction: Decimal::from_f64(0.20).unwrap(),
-    //         sl: Some(Decimal::from_f64(65525.9375).unwrap()),
-    //     }];
-
-    //     self.evaluate_long_partial_profit(price, exchange).await?;
-    //     Ok(())
-    // }
+    /// Whatever is still open after the final target rides as a runner under the
+    /// trailing stop instead of closing.
+    fn start_runner(&mut self, price: Decimal) {
+        if self.config.runner_fraction <= 0.0
+            || self.pos == Position::Flat
+            || self.open_pos.quantity.unwrap_or_default() <= dec!(0.0000)
+        {
+            return;
+        }
+        self.open_pos.runner = true;
+        self.open_pos
+            .trail_runner(price, Helper::f64_to_decimal(self.config.runner_trail_pct));
+        info!(
+            "Runner of {:?} left open on {:?}, trailing stop at {:?}",
+            self.open_pos.quantity, self.pos, self.open_pos.sl
+        );
+    }
 
-    async fn run_cycle(&mut self, price: f64, exchange: &dyn Exchange) -> Result<()> {
-        let dec_price = Decimal::from_f64(price).unwrap();
-        if price == 1.11 {
-            warn!("Price failure! -> {price:?}");
+    /// Journals the entry about to be placed so a crash before `confirm_entry` can be
+    /// reconciled on restart
+    async fn journal_entry(&mut self) -> Result<()> {
+        if !self.config.journal_entries {
             return Ok(());
         }
+        EntryIntent::new(self.pos, &self.open_pos)
+            .store(&mut self.redis_conn, &self.keys.entry_intent)
+            .await
+    }
 
-        if !self.macro_guard.allow_entry(Utc::now()) {
-            warn!("Macro guard not allowing entry");
-            return Ok(());
+    /// Places an entry's market order; the exchange's rejection sentinel is an error,
+    /// so nothing gets booked for an order that doesn't exist
+    async fn place_entry_order(
+        exchange: &dyn Exchange,
+        open_pos: &OpenPosition,
+    ) -> Result<PlaceOrderData> {
+        let exec_price = exchange.place_market_order(open_pos).await?;
+        if exec_price.is_failed() {
+            return Err(anyhow!(
+                "Exchange rejected the {:?} entry {}",
+                open_pos.pos,
+                open_pos.id
+            ));
         }
+        Ok(exec_price)
+    }
 
-        if self.loss_count >= 2 {
-            warn!("Loss count reached 2, skipping cycle");
-            self.loss_count = Self::load_loss_count(&mut self.redis_conn).await?;
-            info!("loaded loss count: {}", self.loss_count);
-            return Ok(());
+    /// Sends a reduce-only close for `open_pos`; the exchange's rejection sentinel is
+    /// an error, so nothing gets booked for a position the exchange still holds
+    pub(crate) async fn place_close_order(
+        exchange: &dyn Exchange,
+        open_pos: &OpenPosition,
+    ) -> Result<PlaceOrderData> {
+        let exec_price = exchange.modify_market_order(open_pos).await?;
+        if exec_price.is_failed() {
+            return Err(anyhow!(
+                "Exchange rejected the {:?} close of {} {}",
+                open_pos.pos,
+                open_pos.position_size,
+                open_pos.id
+            ));
         }
+        Ok(exec_price)
+    }
 
-        //Load the zones, because it's usually updated, periodically.
-        self.zones = Bot::load_zones(&mut self.redis_conn)
-            .await
-            .unwrap_or(Zones::default());
+    /// Backs out of an entry whose order never filled: flat again, with its targets
+    /// and intent dropped so nothing of it is stored
+    async fn abandon_entry(&mut self) {
+        self.pos = Position::Flat;
+        self.open_pos = OpenPosition::default_open_position();
+        if let Err(e) = self.delete_partial_profit_target().await {
+            warn!("Failed to drop the abandoned entry's targets: {e}");
+        }
+        if let Err(e) = EntryIntent::clear(&mut self.redis_conn, &self.keys.entry_intent).await {
+            warn!("Failed to clear the abandoned entry's intent: {e}");
+        }
+    }
 
-        warn!("Ranger State = {:?}", self.pos);
+    /// Persists the filled entry, then drops its intent
+    async fn confirm_entry(&mut self) -> Result<()> {
+        if !self.config.journal_entries {
+            return Ok(());
+        }
+        let pos_snapshot = self.open_pos.clone();
+        self.store_position(self.pos, &pos_snapshot).await?;
+        EntryIntent::clear(&mut self.redis_conn, &self.keys.entry_intent).await
+    }
 
-        match self.pos {
-            Position::Flat => {
-                if let Some(zone) = self
-                    .zones
-                    .long_zones
-                    .iter()
-                    .find(|z| price != 1.11 && z.contains(price))
-                {
-                    let zone_id = ZoneId::from_zone(zone);
-                    info!("Zone ID: {zone_id:?}");
+    /// Persists a filled entry. With `atomic_entry` the position, open position and
+    /// targets are written in one transaction, otherwise one after another
+    async fn open_position(&mut self) -> Result<()> {
+        if self.config.atomic_entry {
+            return EntryRecord::new(self.pos, &self.open_pos, &self.partial_profit_target)?
+                .store(&mut self.redis_conn, &self.keys)
+                .await;
+        }
+        self.store_partial_profit_targets().await?;
+        self.confirm_entry().await
+    }
 
-                    let z_guard_trade_result = self.zone_guard.get_trade_result(zone_id).await;
-                    if z_guard_trade_result.disabled {
-                        warn!("Zone {zone:?} is not open for trading");
-                        return Ok(());
-                    }
+    /// Reconciles an entry interrupted between its order and `confirm_entry`
+    /// against what the exchange actually holds
+    pub async fn recover_entry(&mut self, exchange: &dyn Exchange) -> Result<()> {
+        let Some(intent) = EntryIntent::load(&mut self.redis_conn, &self.keys.entry_intent).await
+        else {
+            return Ok(());
+        };
 
-                    let gate = ConfluenceGate::read(&mut self.redis_conn).await;
-                    if !gate.permits_long() {
-                        return Ok(());
-                    }
-                    let size_mod = gate.size_modifier_long();
+        // The end-of-cycle store landed after all; only the clear was lost
+        if self.pos != Position::Flat {
+            return EntryIntent::clear(&mut self.redis_conn, &self.keys.entry_intent).await;
+        }
 
-                    info!("Ranger Entering LONG at {price:.2} in zone {zone:?}");
-                    let _: () = Self::delete_partial_profit_target(self).await?;
+        let has_position = exchange.has_open_position().await.unwrap_or_else(|e| {
+            warn!("Could not query the exchange position for recovery: {e}");
+            None
+        });
+
+        let (pos, id) = (intent.pos, intent.open_position.id);
+        match intent.reconcile(has_position) {
+            Recovery::Adopt(pos, open_pos) => {
+                warn!("Recovered interrupted {pos:?} entry {} from the exchange", open_pos.id);
+                self.pos = pos;
+                self.open_pos = *open_pos;
+
+                // The entry died before its ladder and exchange exits were set up
+                let entry_price = Helper::decimal_to_f64(self.open_pos.entry_price);
+                let leverage = self
+                    .open_pos
+                    .leverage
+                    .map_or(self.tunables.leverage, Helper::decimal_to_f64);
+                let built = self.build_partial_profit_targets(entry_price, pos, leverage).await;
+                if let Err(e) = built {
+                    warn!("Failed to rebuild the recovered entry's targets: {e}");
+                }
+                let (size, size_precision) = (self.open_pos.position_size, self.size_precision());
+                Helper::rescale_target_sizes(&mut self.partial_profit_target, size, size_precision);
+                let tp = self.exchange_take_profit();
+                if let Err(e) = self.open_pos.arm_exchange_exits(tp, exchange).await {
+                    warn!("Failed to re-arm the recovered entry's exchange TP/SL: {e}");
+                }
+                let pos_snapshot = self.open_pos.clone();
+                self.store_position(self.pos, &pos_snapshot).await?;
+                EntryIntent::clear(&mut self.redis_conn, &self.keys.entry_intent).await?;
+            }
+            Recovery::Discard => {
+                info!("Interrupted entry never filled, discarding its intent");
+                EntryIntent::clear(&mut self.redis_conn, &self.keys.entry_intent).await?;
+            }
+            Recovery::Unresolved => {
+                log::error!(
+                    "Interrupted {pos:?} entry {id} can't be checked against the exchange; verify it manually"
+                );
+            }
+        }
+        Ok(())
+    }
 
-                    self.pos = Position::Long;
+    /// Keeps one post-only limit entry resting in the nearest upcoming zone, moving it
+    /// when the zones are redrawn and taking the position over once it fills
+    async fn manage_resting_entry(&mut self, price: f64, exchange: &dyn Exchange) -> Result<()> {
+        let resting = RestingEntry::load(&mut self.redis_conn, &self.keys.resting_entry).await;
+
+        if let Some(resting) = &resting {
+            if let Some(mut qty) = resting.filled_quantity(exchange).await? {
+                if qty < resting.open_position.position_size {
+                    // Stop the rest filling behind our back, then take whatever filled by then
+                    resting.cancel(exchange).await?;
+                    qty = resting.filled_quantity(exchange).await?.unwrap_or(qty);
+                }
+                return self.adopt_resting_entry(resting, qty).await;
+            }
+        }
 
-                    let funding_rate = exchange.get_funding_rate().await.unwrap_or(0.0);
-                    let funding_multiplier = Helper::funding_multiplier(funding_rate, self.pos);
-                    info!(
-                        "Funding-aware sizing: rate={funding_rate:.6}, multiplier={funding_multiplier:.2}"
-                    );
+        let mut target = RestingEntry::upcoming_zone(&self.zones, price);
+        if let Some((_, zone)) = target {
+            if !self.zone_guard.can_trade(ZoneId::from_zone(&zone)).await {
+                warn!("Zone {zone:?} is not open for trading");
+                target = None;
+            }
+        }
+        let gate = ConfluenceGate::read(&mut self.redis_conn).await;
+        if let Some((side, _)) = target {
+            if gate.blocks_entry(side, price, self.config).is_some() {
+                target = None;
+            }
+        }
 
-                    let _: Result<()> =
-                        Self::store_partial_profit_targets(self, price, self.pos).await;
+        let Some((side, zone)) = target else {
+            self.withdraw_resting_entry(exchange).await;
+            return Ok(());
+        };
+        let limit_price = RestingEntry::limit_price(&zone, side, self.config.limit_entry_depth);
+        // Price is already through the level, so a post-only order there would be rejected
+        let crossed = match side {
+            Position::Short => limit_price <= price,
+            _ => limit_price >= price,
+        };
+        if crossed || resting.as_ref().is_some_and(|r| r.is_at(side, limit_price)) {
+            return Ok(());
+        }
+        if resting.is_some() {
+            // Withdrawing takes over anything that filled before the cancel landed
+            self.withdraw_resting_entry(exchange).await;
+            if self.pos != Position::Flat
+                || RestingEntry::load(&mut self.redis_conn, &self.keys.resting_entry)
+                    .await
+                    .is_some()
+            {
+                return Ok(());
+            }
+        }
 
-                    let combined_multiplier =
-                        funding_multiplier * Helper::f64_to_decimal(size_mod);
-                    self.open_pos = Self::prepare_open_position(
-                        self,
-                        self.pos,
-                        dec_price,
-                        Helper::f64_to_decimal(self.config.leverage),
-                        Helper::f64_to_decimal(self.config.ranger_risk_pct),
-                        combined_multiplier,
-                    )
-                    .await;
+        let zone_id = ZoneId::from_zone(&zone);
+        let (leverage, risk_pct) = ZoneOverride::resolve(
+            ZoneOverride::load(&mut self.redis_conn, zone_id).await,
+            self.tunables.leverage,
+            self.tunables.risk_pct,
+        );
+        let _: () = Self::delete_partial_profit_target(self).await?;
+        let _: Result<()> =
+            Self::build_partial_profit_targets(self, limit_price, side, leverage).await;
+
+        let funding_rate = exchange.get_funding_rate().await.unwrap_or(0.0);
+        let size_mod = match side {
+            Position::Short => gate.size_modifier_short(),
+            _ => gate.size_modifier_long(),
+        };
+        let combined_multiplier = Helper::funding_multiplier(funding_rate, side)
+            * Helper::f64_to_decimal(size_mod);
+        let mut open_position = Self::prepare_open_position(
+            self,
+            side,
+            Helper::f64_to_decimal(limit_price),
+            Helper::f64_to_decimal(leverage),
+            Helper::f64_to_decimal(risk_pct),
+            combined_multiplier,
+        )
+        .await;
+        open_position.entry_funding_rate = Some(Helper::f64_to_decimal(funding_rate));
+        open_position.zone_id = Some(zone_id);
+        // Stored once prepare_open_position has sized the ladder to the order
+        let _: () = self.store_partial_profit_targets().await?;
+
+        let placed =
+            match RestingEntry::replace(None, exchange, side, zone, limit_price, open_position)
+                .await
+            {
+                Ok(placed) => placed,
+                Err(e) => {
+                    self.record_missed_entry(price, target, MissReason::OrderFailed)
+                        .await;
+                    return Err(e);
+                }
+            };
+        info!("Resting {side:?} limit entry at {limit_price:.2} in zone {zone:?}");
+        placed
+            .store(&mut self.redis_conn, &self.keys.resting_entry)
+            .await
+    }
 
-                    if 2 + 2 == 5 {
-                        //We are not trading for now.
-                        info!("No trading, for the ranger for now");
-                        return Ok(());
-                    }
+    /// Takes a filled resting entry over as the position. `qty` is what actually filled,
+    /// so a partial fill shrinks the position, its margin and its targets down to it.
+    async fn adopt_resting_entry(&mut self, resting: &RestingEntry, qty: Decimal) -> Result<()> {
+        info!(
+            "Resting {:?} entry filled {qty} at {:.2}",
+            resting.side, resting.limit_price
+        );
+        self.pos = resting.side;
+        self.open_pos = resting.open_position.clone();
+        self.open_pos.order_id = Some(resting.order_id.clone());
+
+        let ordered = self.open_pos.position_size;
+        let partial = qty < ordered && !ordered.is_zero();
+        if partial {
+            self.open_pos.margin = self.open_pos.margin.map(|m| m * qty / ordered);
+            self.open_pos.position_size = qty;
+            self.open_pos.quantity = Some(qty);
+        }
 
-                    let exec_price: PlaceOrderData =
-                        exchange.place_market_order(&self.open_pos).await?;
-                    info!("Ranger Long executed at {exec_price:?}");
+        let mut throttle =
+            DailyEntryThrottle::load(&mut self.redis_conn, &self.keys.daily_entries).await;
+        throttle.record_entry(Utc::now());
+        if let Err(e) = throttle.store(&mut self.redis_conn, &self.keys.daily_entries).await {
+            warn!("Failed to store daily entry count: {e}");
+        }
+        // The targets were stored when the order was placed
+        if let Ok(targets) =
+            Self::load_partial_profit_target(&mut self.redis_conn, &self.keys.partial_targets).await
+        {
+            self.partial_profit_target = targets;
+        }
+        if partial {
+            let size_precision = self.size_precision();
+            Helper::rescale_target_sizes(&mut self.partial_profit_target, qty, size_precision);
+        }
+        self.open_position().await?;
+        RestingEntry::clear(&mut self.redis_conn, &self.keys.resting_entry).await
+    }
 
-                    if exec_price.client_oid == "Failed to place order" {
-                        warn!("Failed to place order");
-                        //return Ok(());
-                    }
+    /// Whether today's entries have reached `max_trades_per_day`. Reported once, when
+    /// the cap is first hit each day.
+    async fn entry_cap_reached(&mut self, throttle: &mut DailyEntryThrottle) -> bool {
+        let max_per_day = self.config.max_trades_per_day;
+        let now = Utc::now();
+        if throttle.allows_entry(now, max_per_day) {
+            return false;
+        }
+        if !throttle.announced {
+            warn!(
+                "Daily entry cap reached: {}/{max_per_day}, no new entries until UTC midnight",
+                throttle.count_for(now)
+            );
+            throttle.announced = true;
+            if let Err(e) = throttle
+                .store(&mut self.redis_conn, &self.keys.daily_entries)
+                .await
+            {
+                warn!("Failed to store daily entry count: {e}");
+            }
+        }
+        true
+    }
 
-                    if let Ok(Some(pos_id)) = exchange.get_position_id().await {
-                        self.open_pos.position_id = Some(pos_id.clone());
-                        let tp = self.open_pos.tp.map(Helper::decimal_to_f64);
-                        let sl = self.open_pos.sl.map(Helper::decimal_to_f64);
+    /// Whether today's realized losses have passed `max_daily_loss`. Reported once,
+    /// loudly, when the breaker first trips each day.
+    async fn daily_loss_tripped(&mut self) -> bool {
+        let max_daily_loss = Helper::f64_to_decimal(self.config.max_daily_loss);
+        let mut daily = DailyLoss::load(&mut self.redis_conn, TRADING_BOT_DAILY_PNL).await;
+        let now = Utc::now();
+        if !daily.is_tripped(now, max_daily_loss) {
+            return false;
+        }
+        if !daily.announced {
+            log::error!(
+                "DAILY LOSS LIMIT HIT: realized {} today against a limit of {max_daily_loss}, no new entries until UTC midnight",
+                daily.pnl_for(now)
+            );
+            if let Err(e) = daily.announce(&mut self.redis_conn, TRADING_BOT_DAILY_PNL).await {
+                warn!("Failed to store the daily loss state: {e}");
+            }
+        }
+        true
+    }
+
+    /// Cancels any resting limit entry; failures only warn and keep it for the next cycle
+    async fn withdraw_resting_entry(&mut self, exchange: &dyn Exchange) {
+        if !self.config.use_limit_entries {
+            return;
+        }
+        let Some(resting) =
+            RestingEntry::load(&mut self.redis_conn, &self.keys.resting_entry).await
+        else {
+            return;
+        };
+        if let Err(e) = resting.cancel(exchange).await {
+            warn!("Failed to cancel resting entry {}: {e}", resting.order_id);
+            return;
+        }
+        match resting.filled_quantity(exchange).await {
+            Ok(Some(qty)) => {
+                warn!(
+                    "Resting entry {} filled {qty} before it was cancelled, taking it over",
+                    resting.order_id
+                );
+                if let Err(e) = self.adopt_resting_entry(&resting, qty).await {
+                    warn!("Failed to take over the resting fill: {e}");
+                }
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!(
+                    "Could not confirm resting entry {} went unfilled, keeping it: {e}",
+                    resting.order_id
+                );
+                return;
+            }
+        }
+        info!(
+            "Withdrew resting {:?} entry at {:.2}",
+            resting.side, resting.limit_price
+        );
+        if let Err(e) = RestingEntry::clear(&mut self.redis_conn, &self.keys.resting_entry).await {
+            warn!("Failed to clear resting entry: {e}");
+        }
+    }
+
+    async fn record_price(&mut self, price: f64) -> Result<()> {
+        let entry = RecordedPrice {
+            timestamp: Utc::now(),
+            price,
+        };
+        let _: () = self
+            .redis_conn
+            .rpush(&self.keys.recorded_prices, serde_json::to_string(&entry)?)
+            .await?;
+        // Keep only the newest N entries
+        let _: () = self
+            .redis_conn
+            .ltrim(
+                &self.keys.recorded_prices,
+                -self.config.record_prices_max_len,
+                -1,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// One symbol's recorded series, oldest first, from its `SymbolKeys::recorded_prices`
+    #[cfg(test)]
+    pub async fn load_recorded_prices(
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> Result<Vec<RecordedPrice>> {
+        let raw: Vec<String> = conn.lrange(key, 0, -1).await?;
+        Ok(raw
+            .iter()
+            .filter_map(|r| serde_json::from_str::<RecordedPrice>(r).ok())
+            .collect())
+    }
+
+    /// Database `db` on `redis_url`'s server, emptied first, for a bot whose state must
+    /// stay clear of the live keys
+    #[cfg(test)]
+    async fn isolated_connection(
+        redis_url: &str,
+        db: i64,
+    ) -> Result<redis::aio::MultiplexedConnection> {
+        let mut info = redis::IntoConnectionInfo::into_connection_info(redis_url)?;
+        info.redis.db = db;
+        let mut conn = redis::Client::open(info)?
+            .get_multiplexed_async_connection()
+            .await?;
+        let _: () = redis::cmd("FLUSHDB").query_async(&mut conn).await?;
+        Ok(conn)
+    }
+
+    /// Feeds one symbol's recorded `prices` through a fresh bot, oldest first, the way
+    /// the live loop did. The bot keeps its state in database `db`, emptied and seeded
+    /// with `zones`, so nothing live is read or overwritten, and every price counts as
+    /// fresh. Its orders land on `exchange`; pass a `MockExchange`.
+    #[cfg(test)]
+    pub async fn replay(
+        config: &'a Config,
+        zones: &Zones,
+        prices: &[RecordedPrice],
+        exchange: &dyn Exchange,
+        db: i64,
+    ) -> Result<Bot<'a>> {
+        let mut conn = Self::isolated_connection(&config.redis_url, db).await?;
+        let keys = SymbolKeys::new(&config.symbol, true);
+        let _: () = conn.set(&keys.zones, serde_json::to_string(zones)?).await?;
+
+        let mut bot = Bot::new(conn, config, reqwest::Client::new()).await?;
+        info!("Replaying {} recorded prices", prices.len());
+        for p in prices {
+            bot.price_at = None;
+            // Bypass run_cycle so the replay itself isn't recorded again
+            bot.run_cycle_inner(p.price, exchange).await?;
+        }
+        Ok(bot)
+    }
+
+    /// PINGs redis ahead of a cycle. A dead connection is replaced, on a backoff, and
+    /// until then this returns false so the cycle doesn't trade on fallback defaults
+    async fn ensure_redis(&mut self) -> bool {
+        let now = Utc::now();
+        if !self.redis_health.ping_due(now) {
+            return true;
+        }
+        let err = match health::ping(&mut self.redis_conn).await {
+            Ok(()) => {
+                self.redis_health.observe_success(now);
+                return true;
+            }
+            Err(e) => e,
+        };
+        if !self.redis_health.should_retry(now) {
+            return false;
+        }
+
+        // Just dropped, or the backoff is up: try a fresh connection
+        warn!("{err}, reconnecting to redis");
+        let reconnected = match RedisClient::reconnect(&self.config.redis_url).await {
+            Ok(mut conn) => health::ping(&mut conn).await.map(|_| conn),
+            Err(e) => Err(e.into()),
+        };
+        match reconnected {
+            Ok(conn) => {
+                self.fees.redis_conn = conn.clone();
+                self.zone_guard.set_redis_conn(conn.clone());
+                self.redis_conn = conn;
+                self.redis_health.observe_success(now);
+                true
+            }
+            Err(e) => {
+                self.redis_health.observe_failure(&e, now);
+                false
+            }
+        }
+    }
+
+    async fn run_cycle(&mut self, price: f64, exchange: &dyn Exchange) -> Result<()> {
+        if !self.ensure_redis().await {
+            return Ok(());
+        }
+        self.tunables = match RuntimeOverride::try_load(&mut self.redis_conn).await {
+            Ok(runtime) => runtime.apply(Tunables::from_config(self.config)),
+            Err(e) => {
+                self.redis_health.suspect();
+                return Err(e);
+            }
+        };
+
+        if self.config.record_prices {
+            if let Err(e) = self.record_price(price).await {
+                log::error!("Failed to record price: {e}");
+            }
+        }
+        let result = self.run_cycle_inner(price, exchange).await;
+        if let Err(e) = BotStatus::record_cycle(&mut self.redis_conn, Utc::now()).await {
+            warn!("Failed to record cycle time: {e}");
+        }
+        // No subscribers is not an error
+        let _ = self.updates.send(BotUpdate::new(
+            &self.keys.symbol,
+            price,
+            self.pos,
+            &self.open_pos,
+            &self.partial_profit_target,
+        ));
+        result
+    }
+
+    async fn run_cycle_inner(&mut self, price: f64, exchange: &dyn Exchange) -> Result<()> {
+        let dec_price = Decimal::from_f64(price).unwrap();
+        if price == 1.11 {
+            warn!("Price failure! -> {price:?}");
+            return Ok(());
+        }
+
+        if self.price_is_stale(exchange).await? {
+            return Ok(());
+        }
+
+        if self.pos != Position::Flat {
+            match self.macro_guard.flatten_policy(Utc::now()) {
+                Some(FlattenPolicy::FullClose) => {
+                    warn!("Macro event ahead, flattening {:?} at {price:.2}", self.pos);
+                    match self.pos {
+                        Position::Long => self.take_profit_on_long(dec_price, exchange).await?,
+                        Position::Short => self.take_profit_on_short(price, exchange).await?,
+                        Position::Flat => {}
+                    }
+                    return Ok(());
+                }
+                Some(FlattenPolicy::Reduce { target_exposure }) => {
+                    if !self.open_pos.macro_reduced {
+                        warn!(
+                            "Macro event ahead, reducing {:?} to {:.0}% at {price:.2}",
+                            self.pos,
+                            target_exposure * 100.0
+                        );
+                        self.reduce_exposure(price, target_exposure, exchange)
+                            .await?;
+                    }
+                }
+                Some(FlattenPolicy::None) | None => {}
+            }
+        }
+
+        if self.pos != Position::Flat && self.config.ensemble_exit_threshold > 0 {
+            let gate = ConfluenceGate::read(&mut self.redis_conn).await;
+            let entry_price = Helper::decimal_to_f64(self.open_pos.entry_price);
+            if gate.ensemble_exit(
+                self.pos,
+                entry_price,
+                price,
+                self.config.ensemble_exit_threshold,
+            ) {
+                match self.pos {
+                    Position::Long => self.take_profit_on_long(dec_price, exchange).await?,
+                    Position::Short => self.take_profit_on_short(price, exchange).await?,
+                    Position::Flat => {}
+                }
+                return Ok(());
+            }
+        }
+
+        if self.loss_count >= 2 {
+            warn!("Loss count reached 2, skipping cycle");
+            self.loss_count =
+                Self::load_loss_count(&mut self.redis_conn, &self.keys.loss_count).await?;
+            info!("loaded loss count: {}", self.loss_count);
+            return Ok(());
+        }
+
+        //Load the zones, because it's usually updated, periodically.
+        self.zones = match Bot::load_zones_or_default(&mut self.redis_conn, &self.keys).await
+        {
+            Ok(zones) => zones,
+            Err(e) => {
+                // Never fall back to the hardcoded zones because redis hiccuped
+                self.redis_health.suspect();
+                return Err(e);
+            }
+        };
+
+        warn!("Ranger State = {:?}", self.pos);
+
+        match self.pos {
+            Position::Flat => {
+                let entry_close = self.load_entry_close().await;
+                let entry_ref = self.config.zone_entry_ref;
+                let candidate = self.entry_candidate(price, entry_close, entry_ref);
+                if candidate.is_none() {
+                    self.missed.end();
+                }
+
+                if BotStatus::is_paused(&mut self.redis_conn).await {
+                    info!("Bot paused: skipping entries");
+                    self.withdraw_resting_entry(exchange).await;
+                    return Ok(());
+                }
+
+                self.macro_guard
+                    .refresh_if_stale(
+                        &mut self.redis_conn,
+                        Utc::now(),
+                        chrono::Duration::seconds(self.config.macro_guard_refresh_secs),
+                    )
+                    .await;
+                if !self.macro_guard.allow_entry(Utc::now()) {
+                    warn!("Macro guard not allowing entry");
+                    self.withdraw_resting_entry(exchange).await;
+                    self.record_missed_entry(price, candidate, MissReason::MacroGuard)
+                        .await;
+                    return Ok(());
+                }
+
+                if !self.maintenance.allows_entry(Utc::now()) {
+                    warn!("MAINTENANCE PAUSE: skipping entries");
+                    self.withdraw_resting_entry(exchange).await;
+                    self.record_missed_entry(price, candidate, MissReason::Maintenance)
+                        .await;
+                    return Ok(());
+                }
+
+                let mut throttle =
+                    DailyEntryThrottle::load(&mut self.redis_conn, &self.keys.daily_entries).await;
+                if self.entry_cap_reached(&mut throttle).await {
+                    self.withdraw_resting_entry(exchange).await;
+                    self.record_missed_entry(price, candidate, MissReason::DailyCap)
+                        .await;
+                    return Ok(());
+                }
+
+                if self.daily_loss_tripped().await {
+                    self.withdraw_resting_entry(exchange).await;
+                    self.record_missed_entry(price, candidate, MissReason::DailyLoss)
+                        .await;
+                    return Ok(());
+                }
+
+                if self.config.use_limit_entries {
+                    self.missed.end();
+                    return self.manage_resting_entry(price, exchange).await;
+                }
+
+                if let Some((Position::Long, zone)) = candidate {
+                    let zone_id = ZoneId::from_zone(&zone);
+                    info!("Zone ID: {zone_id:?}");
+
+                    if !self.zone_guard.can_trade(zone_id).await {
+                        warn!("Zone {zone:?} is not open for trading");
+                        self.record_missed_entry(price, candidate, MissReason::ZoneDisabled)
+                            .await;
+                        return Ok(());
+                    }
+
+                    let gate = ConfluenceGate::read(&mut self.redis_conn).await;
+                    if let Some(reason) = gate.blocks_entry(Position::Long, price, self.config) {
+                        self.record_missed_entry(price, candidate, reason).await;
+                        return Ok(());
+                    }
+                    let size_mod = gate.size_modifier_long();
+
+                    self.missed.end();
+                    info!("Ranger Entering LONG at {price:.2} in zone {zone:?}");
+                    let _: () = Self::delete_partial_profit_target(self).await?;
+
+                    self.pos = Position::Long;
+
+                    let funding_rate = exchange.get_funding_rate().await.unwrap_or(0.0);
+                    let funding_multiplier = Helper::funding_multiplier(funding_rate, self.pos);
+                    info!(
+                        "Funding-aware sizing: rate={funding_rate:.6}, multiplier={funding_multiplier:.2}"
+                    );
+
+                    let (leverage, risk_pct) = ZoneOverride::resolve(
+                        ZoneOverride::load(&mut self.redis_conn, zone_id).await,
+                        self.tunables.leverage,
+                        self.tunables.risk_pct,
+                    );
+                    let _: Result<()> =
+                        Self::build_partial_profit_targets(self, price, self.pos, leverage).await;
+
+                    let combined_multiplier =
+                        funding_multiplier * Helper::f64_to_decimal(size_mod);
+                    self.open_pos = Self::prepare_open_position(
+                        self,
+                        self.pos,
+                        dec_price,
+                        Helper::f64_to_decimal(leverage),
+                        Helper::f64_to_decimal(risk_pct),
+                        combined_multiplier,
+                    )
+                    .await;
+                    self.open_pos.entry_funding_rate = Some(Helper::f64_to_decimal(funding_rate));
+                    self.open_pos.zone_id = Some(zone_id);
+
+                    if 2 + 2 == 5 {
+                        //We are not trading for now.
+                        info!("No trading, for the ranger for now");
+                        return Ok(());
+                    }
+
+                    self.journal_entry().await?;
+                    let exec_price: PlaceOrderData =
+                        match Self::place_entry_order(exchange, &self.open_pos).await {
+                            Ok(exec_price) => exec_price,
+                            Err(e) => {
+                                self.record_missed_entry(price, candidate, MissReason::OrderFailed)
+                                    .await;
+                                self.abandon_entry().await;
+                                return Err(e);
+                            }
+                        };
+                    self.maintenance.observe_success();
+
+                    throttle.record_entry(Utc::now());
+                    if let Err(e) = throttle
+                        .store(&mut self.redis_conn, &self.keys.daily_entries)
+                        .await
+                    {
+                        warn!("Failed to store daily entry count: {e}");
+                    }
+                    info!("Ranger Long executed at {exec_price:?}");
+
+                    if let Ok(Some(pos_id)) = exchange.get_position_id().await {
+                        self.open_pos.position_id = Some(pos_id.clone());
+                        let tp = self.exchange_take_profit();
+                        let sl = self.open_pos.sl.map(Helper::decimal_to_f64);
                         if let Err(e) = exchange.place_initial_tpsl(&pos_id, tp, sl).await {
                             warn!("Failed to place initial TPSL on long: {e}");
                         }
                     }
 
                     self.open_pos.order_id = Some(exec_price.order_id);
-                } else if let Some(zone) = self
-                    .zones
-                    .short_zones
-                    .iter()
-                    .find(|z| price != 1.11 && z.contains(price))
-                {
-                    let zone_id = ZoneId::from_zone(zone);
+                    self.open_position().await?;
+                } else if let Some((Position::Short, zone)) = candidate {
+                    let zone_id = ZoneId::from_zone(&zone);
                     info!("Zone ID: {zone_id:?}");
 
-                    let z_guard_trade_result = self.zone_guard.get_trade_result(zone_id).await;
-
-                    if z_guard_trade_result.disabled {
+                    if !self.zone_guard.can_trade(zone_id).await {
                         warn!("{zone:?} is not open for trading");
+                        self.record_missed_entry(price, candidate, MissReason::ZoneDisabled)
+                            .await;
                         return Ok(());
                     }
 
                     let gate = ConfluenceGate::read(&mut self.redis_conn).await;
-                    if !gate.permits_short() {
+                    if let Some(reason) = gate.blocks_entry(Position::Short, price, self.config) {
+                        self.record_missed_entry(price, candidate, reason).await;
                         return Ok(());
                     }
                     let size_mod = gate.size_modifier_short();
 
+                    self.missed.end();
                     info!("Ranger Entering SHORT at {price:.2} in zone {zone:?}");
                     let _: () = Self::delete_partial_profit_target(self).await?;
 
@@ -1208,8 +2856,13 @@ impl<'a> Bot<'a> {
                         "Funding-aware sizing: rate={funding_rate:.6}, multiplier={funding_multiplier:.2}"
                     );
 
+                    let (leverage, risk_pct) = ZoneOverride::resolve(
+                        ZoneOverride::load(&mut self.redis_conn, zone_id).await,
+                        self.tunables.leverage,
+                        self.tunables.risk_pct,
+                    );
                     let _: Result<()> =
-                        Self::store_partial_profit_targets(self, price, self.pos).await;
+                        Self::build_partial_profit_targets(self, price, self.pos, leverage).await;
 
                     let combined_multiplier =
                         funding_multiplier * Helper::f64_to_decimal(size_mod);
@@ -1217,11 +2870,13 @@ impl<'a> Bot<'a> {
                         self,
                         Position::Short,
                         dec_price,
-                        Helper::f64_to_decimal(self.config.leverage),
-                        Helper::f64_to_decimal(self.config.ranger_risk_pct),
+                        Helper::f64_to_decimal(leverage),
+                        Helper::f64_to_decimal(risk_pct),
                         combined_multiplier,
                     )
                     .await;
+                    self.open_pos.entry_funding_rate = Some(Helper::f64_to_decimal(funding_rate));
+                    self.open_pos.zone_id = Some(zone_id);
 
                     if 2 + 2 == 5 {
                         //We are not trading for now.
@@ -1229,18 +2884,31 @@ impl<'a> Bot<'a> {
                         return Ok(());
                     }
 
+                    self.journal_entry().await?;
                     let exec_price: PlaceOrderData =
-                        exchange.place_market_order(&self.open_pos).await?;
-                    info!("Ranger Short executed at {exec_price:?}");
-
-                    if exec_price.client_oid == "Failed to place order" {
-                        warn!("Failed to place order");
-                        //return Ok(());
+                        match Self::place_entry_order(exchange, &self.open_pos).await {
+                            Ok(exec_price) => exec_price,
+                            Err(e) => {
+                                self.record_missed_entry(price, candidate, MissReason::OrderFailed)
+                                    .await;
+                                self.abandon_entry().await;
+                                return Err(e);
+                            }
+                        };
+                    self.maintenance.observe_success();
+
+                    throttle.record_entry(Utc::now());
+                    if let Err(e) = throttle
+                        .store(&mut self.redis_conn, &self.keys.daily_entries)
+                        .await
+                    {
+                        warn!("Failed to store daily entry count: {e}");
                     }
+                    info!("Ranger Short executed at {exec_price:?}");
 
                     if let Ok(Some(pos_id)) = exchange.get_position_id().await {
                         self.open_pos.position_id = Some(pos_id.clone());
-                        let tp = self.open_pos.tp.map(Helper::decimal_to_f64);
+                        let tp = self.exchange_take_profit();
                         let sl = self.open_pos.sl.map(Helper::decimal_to_f64);
                         if let Err(e) = exchange.place_initial_tpsl(&pos_id, tp, sl).await {
                             warn!("Failed to place initial TPSL on short: {e}");
@@ -1248,6 +2916,7 @@ impl<'a> Bot<'a> {
                     }
 
                     self.open_pos.order_id = Some(exec_price.order_id);
+                    self.open_position().await?;
                 } else {
                     //Track for new zone targets
                     warn!("Price {price:.2} out of any Ranger zone -- staying flat");
@@ -1255,19 +2924,36 @@ impl<'a> Bot<'a> {
             }
 
             Position::Long => {
+                if self.open_pos.track_excursion(dec_price) {
+                    OpenPosition::store_open_position(
+                        self.redis_conn.clone(),
+                        &self.keys.active,
+                        &self.open_pos,
+                    )
+                    .await?;
+                }
+
+                self.trail_runner(dec_price, exchange).await?;
+                self.trail_stop(dec_price, exchange).await?;
+
                 //Trigger SL if it's met
-                let in_sl = Helper::stop_loss_price(
-                    self.open_pos.entry_price,
-                    Helper::f64_to_decimal(self.config.margin),
-                    Helper::f64_to_decimal(self.config.leverage),
-                    Helper::f64_to_decimal(self.config.risk_pct),
-                    Position::Long,
+                let in_sl = self
+                    .open_pos
+                    .fallback_stop_loss(self.current_margin, &self.tunables);
+                let ssl_hit = Helper::ssl_hit(
+                    self.stop_price.unwrap_or(dec_price),
+                    self.pos,
+                    self.open_pos.sl.unwrap_or(in_sl),
                 );
-                let ssl_hit =
-                    Helper::ssl_hit(dec_price, self.pos, self.open_pos.sl.unwrap_or(in_sl));
 
                 if ssl_hit {
-                    let _: () = Self::close_long_position(self, dec_price).await?;
+                    if self.open_pos.runner {
+                        // The runner has no target order behind it; close it ourselves
+                        if let Some(exec_price) = self.open_pos.close_runner(exchange).await? {
+                            self.open_pos.order_id = Some(exec_price.order_id);
+                        }
+                    }
+                    Self::close_long_position(self, dec_price).await?;
 
                     warn!(
                         "SL for Ranger Long Position entered at {:2}, with SL triggered at {:2}",
@@ -1294,19 +2980,36 @@ impl<'a> Bot<'a> {
             }
 
             Position::Short => {
+                if self.open_pos.track_excursion(dec_price) {
+                    OpenPosition::store_open_position(
+                        self.redis_conn.clone(),
+                        &self.keys.active,
+                        &self.open_pos,
+                    )
+                    .await?;
+                }
+
+                self.trail_runner(dec_price, exchange).await?;
+                self.trail_stop(dec_price, exchange).await?;
+
                 //Trigger SL if it's met
-                let in_sl = Helper::stop_loss_price(
-                    self.open_pos.entry_price,
-                    Helper::f64_to_decimal(self.config.margin),
-                    Helper::f64_to_decimal(self.config.leverage),
-                    Helper::f64_to_decimal(self.config.risk_pct),
-                    Position::Short,
+                let in_sl = self
+                    .open_pos
+                    .fallback_stop_loss(self.current_margin, &self.tunables);
+                let ssl_hit = Helper::ssl_hit(
+                    self.stop_price.unwrap_or(dec_price),
+                    self.pos,
+                    self.open_pos.sl.unwrap_or(in_sl),
                 );
-                let ssl_hit =
-                    Helper::ssl_hit(dec_price, self.pos, self.open_pos.sl.unwrap_or(in_sl));
 
                 if ssl_hit {
-                    let _: () = Self::close_short_position(self, dec_price).await?;
+                    if self.open_pos.runner {
+                        // The runner has no target order behind it; close it ourselves
+                        if let Some(exec_price) = self.open_pos.close_runner(exchange).await? {
+                            self.open_pos.order_id = Some(exec_price.order_id);
+                        }
+                    }
+                    Self::close_short_position(self, dec_price).await?;
 
                     warn!(
                         "SL for Ranger Short Position entered at {:2}, with SL triggered at {:2}",
@@ -1337,7 +3040,20 @@ impl<'a> Bot<'a> {
         Ok(())
     }
 
-    pub async fn start_live_trading(&mut self, exchange: &dyn Exchange) -> Result<()> {
+    /// Trades every configured symbol off one ticker stream, each tick running the
+    /// cycle for the symbol it quotes on that symbol's exchange client
+    pub async fn start_live_trading(
+        &mut self,
+        exchanges: &BTreeMap<String, Arc<dyn Exchange>>,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> Result<()> {
+        let symbols: Vec<String> = exchanges.keys().cloned().collect();
+        for symbol in &symbols {
+            if self.switch_symbol(symbol) {
+                self.recover_entry(exchanges[symbol].as_ref()).await?;
+            }
+        }
+
         let mut backoff_secs = 1;
         let max_backoff = 64;
 
@@ -1345,7 +3061,7 @@ impl<'a> Bot<'a> {
             info!("Connecting to Ranger live trading via WebSocket...");
 
             let ticker_stream_result =
-                BitgetWsClient::subscribe_tickers("USDT-FUTURES", "BTCUSDT").await;
+                BitgetWsClient::subscribe_tickers("USDT-FUTURES", &symbols).await;
 
             match ticker_stream_result {
                 std::result::Result::Ok(mut ticker_stream) => {
@@ -1354,18 +3070,68 @@ impl<'a> Bot<'a> {
 
                     let mut graph = Graph::new();
                     let mut last_midnight_check = Utc::now();
+                    let mut watchdog = self.feed_watchdog();
 
-                    while let Some(msg) = ticker_stream.next().await {
+                    while let Some(wake) = next_or_shutdown(
+                        &mut ticker_stream,
+                        &mut shutdown,
+                        &mut watchdog,
+                        &mut self.commands,
+                    )
+                    .await
+                    {
+                        let msg = match wake {
+                            Wake::Tick(msg) => msg,
+                            Wake::Watchdog => {
+                                self.check_price_feeds(exchanges).await;
+                                continue;
+                            }
+                            Wake::Command(command) => {
+                                // The API only acts on the primary symbol
+                                let primary = &self.config.symbol;
+                                if let Some(exchange) = exchanges.get(primary) {
+                                    if self.switch_symbol(primary) {
+                                        self.handle_command(command, exchange.as_ref()).await;
+                                    }
+                                }
+                                continue;
+                            }
+                        };
                         match msg {
                             std::result::Result::Ok(ticker) => {
-                                let price: f64 = ticker.last_pr.parse().unwrap_or(0.0);
+                                let Some(exchange) = exchanges.get(&ticker.inst_id) else {
+                                    continue;
+                                };
+                                if !self.switch_symbol(&ticker.inst_id) {
+                                    continue;
+                                }
+                                self.price_at = Some(
+                                    ticker
+                                        .ts
+                                        .parse::<i64>()
+                                        .ok()
+                                        .and_then(DateTime::from_timestamp_millis)
+                                        .unwrap_or_else(Utc::now),
+                                );
+                                let prices = ticker.prices();
+                                let price = prices.price;
+                                let trigger = prices.trigger_price(self.config.stop_trigger_price);
+                                self.stop_price =
+                                    (trigger > 0.0).then(|| Helper::f64_to_decimal(trigger));
 
                                 if price > 0.0 {
-                                    info!("Ticker Price = {price:.2}");
-
-                                    // Run Main Ranger Strategy
-                                    if let Err(e) = self.run_cycle(price, exchange).await {
-                                        log::error!("Error during trading cycle: {e}");
+                                    info!("{} Ticker Price = {price:.2}", ticker.inst_id);
+
+                                    // Run Main Ranger Strategy, unless Bitget is still down
+                                    // for maintenance: no calls until the pause runs out
+                                    let paused = self.maintenance.pause_remaining(Utc::now());
+                                    if paused.is_none() {
+                                        let cycle = self.run_cycle(price, exchange.as_ref()).await;
+                                        if let Err(e) = cycle {
+                                            if !self.maintenance.observe_error(&e, Utc::now()) {
+                                                log::error!("Error during trading cycle: {e}");
+                                            }
+                                        }
                                     }
                                 }
 
@@ -1391,6 +3157,9 @@ impl<'a> Bot<'a> {
                             }
                         }
                     }
+                    if *shutdown.borrow() {
+                        return Ok(());
+                    }
                     warn!("WebSocket stream closed. Attempting to reconnect...");
                 }
                 std::result::Result::Err(e) => {
@@ -1400,12 +3169,117 @@ impl<'a> Bot<'a> {
                 }
             }
 
-            tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(backoff_secs)) => {}
+                _ = shutdown.changed() => return Ok(()),
+            }
             backoff_secs = std::cmp::min(backoff_secs * 2, max_backoff);
         }
     }
 
-    pub async fn start_live_trading_bitunix(&mut self, exchange: &dyn Exchange) -> Result<()> {
+    /// Runs once the trading loop has stopped: flattens every open position when
+    /// `flatten_on_exit` is set, then persists each symbol's final state
+    pub async fn shutdown(&mut self, exchanges: &BTreeMap<String, Arc<dyn Exchange>>) {
+        for (symbol, exchange) in exchanges {
+            if !self.switch_symbol(symbol) {
+                continue;
+            }
+            if let Err(e) = self.shutdown_symbol(exchange.as_ref()).await {
+                log::error!("Failed to shut down {symbol} cleanly: {e}");
+            }
+        }
+    }
+
+    async fn shutdown_symbol(&mut self, exchange: &dyn Exchange) -> Result<()> {
+        if self.config.flatten_on_exit && self.pos != Position::Flat {
+            self.flatten_at_market(exchange, "Shutting down").await?;
+        } else if self.pos != Position::Flat {
+            warn!(
+                "Shutting down with {:?} {} open; only its exchange stop protects it",
+                self.pos, self.keys.symbol
+            );
+        }
+        let pos_snapshot = self.open_pos.clone();
+        self.store_position(self.pos, &pos_snapshot).await
+    }
+
+    /// Closes the active symbol's position at market, booked at the price the exchange
+    /// quotes now rather than the last tick, which may be the one that froze
+    async fn flatten_at_market(&mut self, exchange: &dyn Exchange, reason: &str) -> Result<()> {
+        let price = exchange.get_current_price().await?;
+        warn!(
+            "{reason}: flattening {:?} {} at {price:.2}",
+            self.pos, self.keys.symbol
+        );
+        match self.pos {
+            Position::Long => {
+                self.take_profit_on_long(Helper::f64_to_decimal(price), exchange)
+                    .await?
+            }
+            Position::Short => self.take_profit_on_short(price, exchange).await?,
+            Position::Flat => {}
+        }
+        Ok(())
+    }
+
+    /// Whether the active symbol's last price is too old to act on, flattening the
+    /// open position when `flatten_on_stale_price` is set
+    async fn price_is_stale(&mut self, exchange: &dyn Exchange) -> Result<bool> {
+        let Some(price_at) = self.price_at else {
+            return Ok(false);
+        };
+        if !self.staleness.is_stale(price_at, Utc::now()) {
+            return Ok(false);
+        }
+        if self.config.flatten_on_stale_price && self.pos != Position::Flat {
+            self.flatten_at_market(exchange, "Stale price feed").await?;
+            let pos_snapshot = self.open_pos.clone();
+            self.store_position(self.pos, &pos_snapshot).await?;
+        }
+        Ok(true)
+    }
+
+    /// Runs between ticks, so a feed that stops sending anything still trips the
+    /// staleness guard for every symbol it quotes
+    async fn check_price_feeds(&mut self, exchanges: &BTreeMap<String, Arc<dyn Exchange>>) {
+        for (symbol, exchange) in exchanges {
+            if !self.switch_symbol(symbol) {
+                continue;
+            }
+            if let Err(e) = self.price_is_stale(exchange.as_ref()).await {
+                log::error!("Failed to flatten {symbol} on a stale price: {e}");
+            }
+        }
+    }
+
+    /// How often the live loop checks for a frozen feed when no tick arrives; `None`
+    /// when the staleness guard is off
+    fn feed_watchdog(&self) -> Option<tokio::time::Interval> {
+        let secs = u64::try_from(self.config.max_price_staleness_secs).ok()?;
+        if secs == 0 {
+            return None;
+        }
+        let period = Duration::from_secs((secs / 2).max(1));
+        Some(tokio::time::interval_at(
+            tokio::time::Instant::now() + period,
+            period,
+        ))
+    }
+
+    /// Bitunix trades the primary symbol only
+    pub async fn start_live_trading_bitunix(
+        &mut self,
+        exchange: &dyn Exchange,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> Result<()> {
+        if !self.symbols.is_empty() {
+            warn!(
+                "Bitunix trades {} only; ignoring the other configured symbols",
+                self.config.symbol
+            );
+        }
+        self.recover_entry(exchange).await?;
+
         let mut backoff_secs = 1;
         let max_backoff = 64;
 
@@ -1422,17 +3296,44 @@ impl<'a> Bot<'a> {
 
                     let mut graph = Graph::new();
                     let mut last_midnight_check = Utc::now();
+                    let mut watchdog = self.feed_watchdog();
 
-                    while let Some(msg) = ticker_stream.next().await {
+                    while let Some(wake) = next_or_shutdown(
+                        &mut ticker_stream,
+                        &mut shutdown,
+                        &mut watchdog,
+                        &mut self.commands,
+                    )
+                    .await
+                    {
+                        let msg = match wake {
+                            Wake::Tick(msg) => msg,
+                            Wake::Watchdog => {
+                                if let Err(e) = self.price_is_stale(exchange).await {
+                                    log::error!("Failed to flatten on a stale price: {e}");
+                                }
+                                continue;
+                            }
+                            Wake::Command(command) => {
+                                self.handle_command(command, exchange).await;
+                                continue;
+                            }
+                        };
                         match msg {
                             std::result::Result::Ok(ticker) => {
                                 let price: f64 = ticker.la.parse().unwrap_or(0.0);
+                                self.price_at = Some(
+                                    DateTime::from_timestamp_millis(ticker.ts as i64)
+                                        .unwrap_or_else(Utc::now),
+                                );
 
                                 if price > 0.0 {
                                     info!("Ticker Price = {price:.2}");
 
                                     if let Err(e) = self.run_cycle(price, exchange).await {
-                                        log::error!("Error during trading cycle: {e}");
+                                        if !self.maintenance.observe_error(&e, Utc::now()) {
+                                            log::error!("Error during trading cycle: {e}");
+                                        }
                                     }
                                 }
 
@@ -1457,6 +3358,9 @@ impl<'a> Bot<'a> {
                             }
                         }
                     }
+                    if *shutdown.borrow() {
+                        return Ok(());
+                    }
                     warn!("Bitunix WebSocket stream closed. Attempting to reconnect...");
                 }
                 std::result::Result::Err(e) => {
@@ -1466,8 +3370,652 @@ impl<'a> Bot<'a> {
                 }
             }
 
-            tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(backoff_secs)) => {}
+                _ = shutdown.changed() => return Ok(()),
+            }
             backoff_secs = std::cmp::min(backoff_secs * 2, max_backoff);
         }
     }
 }
+
+/// What woke the live loop
+#[derive(Debug)]
+enum Wake<T> {
+    Tick(T),
+    /// The feed watchdog fired: check no symbol's price has gone stale
+    Watchdog,
+    /// The API sent an operator command
+    Command(BotCommand),
+}
+
+/// The stream's next item, a watchdog firing or an operator command, or `None` once
+/// shutdown is signalled or the stream ends. A cycle in flight is never cut short;
+/// the loop only stops between ticks.
+async fn next_or_shutdown<S>(
+    stream: &mut S,
+    shutdown: &mut watch::Receiver<bool>,
+    watchdog: &mut Option<tokio::time::Interval>,
+    commands: &mut mpsc::Receiver<BotCommand>,
+) -> Option<Wake<S::Item>>
+where
+    S: futures_util::Stream + Unpin,
+{
+    if *shutdown.borrow() {
+        return None;
+    }
+    let fired = async {
+        match watchdog {
+            Some(watchdog) => {
+                watchdog.tick().await;
+            }
+            None => std::future::pending::<()>().await,
+        }
+    };
+    tokio::select! {
+        item = stream.next() => item.map(Wake::Tick),
+        _ = fired => Some(Wake::Watchdog),
+        Some(command) = commands.recv() => Some(Wake::Command(command)),
+        _ = shutdown.changed() => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::MockExchange;
+    use crate::helper::{TRADING_BOT_ACTIVE, TRADING_BOT_POSITION, TRADING_PARTIAL_PROFIT_TARGET};
+
+    #[tokio::test]
+    #[ignore = "needs a Redis server at REDIS_URL"]
+    async fn test_replayed_prices_reproduce_the_live_orders() {
+        let mut config = Config::for_tests();
+        config.record_prices = true;
+        let zones = Zones {
+            long_zones: vec![Zone {
+                low: 59_000.0,
+                high: 60_000.0,
+                side: Side::Long,
+            }],
+            short_zones: vec![Zone {
+                low: 64_000.0,
+                high: 65_000.0,
+                side: Side::Short,
+            }],
+        };
+        let series = [62_000.0, 59_500.0, 60_500.0, 62_500.0, 64_500.0, 63_000.0];
+
+        // the live run, in a database of its own, recording each price it trades on
+        let mut conn = Bot::isolated_connection(&config.redis_url, 13).await.unwrap();
+        let keys = SymbolKeys::new(&config.symbol, true);
+        let _: () = conn
+            .set(&keys.zones, serde_json::to_string(&zones).unwrap())
+            .await
+            .unwrap();
+        let live_exchange = MockExchange::new(60_000.0);
+        let mut live = Bot::new(conn.clone(), &config, reqwest::Client::new())
+            .await
+            .unwrap();
+        for price in series {
+            live.run_cycle(price, &live_exchange).await.unwrap();
+        }
+        let recorded = Bot::load_recorded_prices(&mut conn, &keys.recorded_prices)
+            .await
+            .unwrap();
+        assert_eq!(recorded.iter().map(|p| p.price).collect::<Vec<_>>(), series);
+
+        let replay_exchange = MockExchange::new(60_000.0);
+        let replayed = Bot::replay(&config, &zones, &recorded, &replay_exchange, 14)
+            .await
+            .unwrap();
+
+        let actions = |exchange: &MockExchange| {
+            exchange
+                .orders
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|o| (o.pos, o.position_size))
+                .collect::<Vec<_>>()
+        };
+        assert!(!actions(&live_exchange).is_empty());
+        assert_eq!(actions(&replay_exchange), actions(&live_exchange));
+        assert_eq!(replayed.pos, live.pos);
+    }
+
+    #[test]
+    fn test_position_round_trips_through_stored_form() {
+        for pos in [Position::Flat, Position::Long, Position::Short] {
+            let stored = pos.as_str();
+            assert_eq!(stored, serde_json::to_string(&pos).unwrap());
+            assert_eq!(Position::from_stored(&stored), pos);
+        }
+    }
+
+    #[test]
+    fn test_position_writes_keep_position_and_targets_together() {
+        let keys = SymbolKeys::new("BTCUSDT", true);
+        let open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(65000),
+            ..OpenPosition::default_open_position()
+        };
+        let targets = [PartialProfitTarget {
+            target_price: dec!(66000),
+            fraction: dec!(1),
+            sl: Some(dec!(65000)),
+            size_btc: dec!(0.01),
+        }];
+
+        let writes = Bot::position_writes(&keys, Position::Long, &open, &targets).unwrap();
+        assert_eq!(
+            writes.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            [
+                TRADING_BOT_POSITION,
+                TRADING_BOT_ACTIVE,
+                TRADING_PARTIAL_PROFIT_TARGET
+            ]
+        );
+        assert_eq!(writes[0].1, Some(Position::Long.as_str()));
+        assert_eq!(writes[1].1, Some(open.as_str()));
+        assert!(writes[2].1.is_some());
+
+        // once flat with nothing left, the stale ladder goes with it
+        let writes = Bot::position_writes(&keys, Position::Flat, &open, &[]).unwrap();
+        assert_eq!(writes[0].1, Some(Position::Flat.as_str()));
+        assert_eq!(writes[2].1, None);
+    }
+
+    #[test]
+    fn test_position_reads_legacy_bare_strings() {
+        assert_eq!(Position::from_stored("Long"), Position::Long);
+        assert_eq!(Position::from_stored("Short"), Position::Short);
+        assert_eq!(Position::from_stored("garbage"), Position::Flat);
+    }
+
+    #[test]
+    fn test_position_stored_form_matches_open_position_field() {
+        let open = OpenPosition {
+            pos: Position::Short,
+            ..OpenPosition::default_open_position()
+        };
+        let json: serde_json::Value = serde_json::from_str(&open.as_str()).unwrap();
+        assert_eq!(json["pos"].to_string(), Position::Short.as_str());
+    }
+
+    #[test]
+    fn test_excursions_track_worst_and_best_unrealized_pnl() {
+        let mut open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(100000),
+            position_size: dec!(0.01),
+            ..OpenPosition::default_open_position()
+        };
+
+        // dips 1500, rallies 2000, then fades – extremes must stick
+        for price in [dec!(99500), dec!(98500), dec!(101000), dec!(102000), dec!(100500)] {
+            open.track_excursion(price);
+        }
+        assert_eq!(open.max_adverse_excursion, Some(dec!(-15.00)));
+        assert_eq!(open.max_favorable_excursion, Some(dec!(20.00)));
+
+        // a price inside the range changes nothing
+        assert!(!open.track_excursion(dec!(100000)));
+    }
+
+    #[test]
+    fn test_short_excursions_are_mirrored() {
+        let mut open = OpenPosition {
+            pos: Position::Short,
+            entry_price: dec!(100000),
+            position_size: dec!(0.01),
+            ..OpenPosition::default_open_position()
+        };
+
+        open.track_excursion(dec!(101000));
+        open.track_excursion(dec!(99000));
+        assert_eq!(open.max_adverse_excursion, Some(dec!(-10.00)));
+        assert_eq!(open.max_favorable_excursion, Some(dec!(10.00)));
+    }
+
+    #[test]
+    fn test_reduce_keeps_target_exposure() {
+        let open = OpenPosition {
+            pos: Position::Long,
+            quantity: Some(dec!(0.0153)),
+            ..OpenPosition::default_open_position()
+        };
+
+        // keep 25% -> close 75%, rounded down to the size precision
+        assert_eq!(open.reduction_for(0.25, 5), dec!(0.01147));
+        assert_eq!(open.reduction_for(1.0, 5), Decimal::ZERO);
+        assert_eq!(open.reduction_for(0.0, 5), dec!(0.0153));
+        // a coarser symbol rounds down to its own step
+        assert_eq!(open.reduction_for(0.25, 2), dec!(0.01));
+        assert!(!open.macro_reduced);
+    }
+
+    #[test]
+    fn test_runner_outlives_the_final_target_and_exits_on_trailing_stop() {
+        // 1000 notional at 100k = 0.01 BTC, targets every 500
+        let mut ladder = Helper::build_profit_targets(
+            dec!(100000),
+            dec!(50),
+            dec!(20),
+            dec!(500),
+            Decimal::ZERO,
+            Position::Long,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
+        );
+        let runner = Helper::carve_runner(&mut ladder, 1.0, 5);
+
+        // the whole last tranche rides: only three targets left to close
+        assert_eq!(runner, dec!(0.002));
+        assert_eq!(ladder.len(), 3);
+        let closed: Decimal = ladder.iter().map(|t| t.size_btc).sum();
+        assert_eq!(dec!(0.01) - closed, runner);
+
+        let mut open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(100000),
+            quantity: Some(runner),
+            position_size: runner,
+            sl: ladder[2].sl,
+            runner: true,
+            ..OpenPosition::default_open_position()
+        };
+
+        // price runs through where TP4 (102000) used to be; the stop only ratchets up
+        for price in [dec!(101800), dec!(102000), dec!(102600)] {
+            open.trail_runner(price, dec!(0.5));
+            assert!(!Helper::ssl_hit(price, Position::Long, open.sl.unwrap()));
+        }
+        assert_eq!(open.sl, Some(dec!(102087.00)));
+        assert!(!open.trail_runner(dec!(102300), dec!(0.5)));
+
+        // the pullback is what closes it
+        assert!(Helper::ssl_hit(dec!(102050), Position::Long, open.sl.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_runner_stop_follows_on_the_exchange_and_closes_there() {
+        let exchange = MockExchange::new(102_050.0);
+        let mut open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(100000),
+            quantity: Some(dec!(0.002)),
+            position_size: dec!(0.002),
+            sl: Some(dec!(101000)),
+            runner: true,
+            ..OpenPosition::default_open_position()
+        };
+
+        // every ratchet is pushed; a pullback that doesn't tighten isn't
+        for price in [dec!(102000), dec!(101900), dec!(102600)] {
+            open.ratchet_runner(price, dec!(0.5), &exchange).await.unwrap();
+        }
+        assert_eq!(
+            *exchange.stop_updates.lock().unwrap(),
+            vec![101_490.0, 102_087.0]
+        );
+
+        // the stop is hit: the runner is closed with a reduce-only order of its size
+        assert!(Helper::ssl_hit(dec!(102050), Position::Long, open.sl.unwrap()));
+        let closed = open.close_runner(&exchange).await.unwrap().unwrap();
+        assert_eq!(closed.order_id, "mock-1");
+        let orders = exchange.orders.lock().unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].position_size, dec!(0.002));
+    }
+
+    #[tokio::test]
+    async fn test_runner_already_stopped_out_on_the_exchange_still_closes() {
+        // the exchange stop fired first, so the reduce-only close has nothing to reduce
+        let exchange = MockExchange {
+            reject_closes: true,
+            ..MockExchange::new(102_050.0)
+        };
+        let open = OpenPosition {
+            pos: Position::Long,
+            position_size: dec!(0.002),
+            runner: true,
+            ..OpenPosition::default_open_position()
+        };
+        assert!(open.close_runner(&exchange).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_partial_close_reports_the_close_order_and_makes_it_the_latest() {
+        for side in [Position::Long, Position::Short] {
+            let exchange = MockExchange::new(100_000.0);
+            let mut open = OpenPosition {
+                pos: side,
+                entry_price: dec!(100000),
+                position_size: dec!(0.01),
+                quantity: Some(dec!(0.01)),
+                order_id: Some("entry".to_string()),
+                ..OpenPosition::default_open_position()
+            };
+            let slice = OpenPosition {
+                position_size: dec!(0.004),
+                quantity: Some(dec!(0.004)),
+                ..open.clone()
+            };
+
+            let close = open.close_partial(&slice, &exchange).await.unwrap();
+
+            // the closed slice records the close order, and the position moves on to it
+            assert_eq!(close.order_id, "mock-1", "{side:?}");
+            assert_eq!(open.order_id.as_deref(), Some("mock-1"), "{side:?}");
+            let orders = exchange.orders.lock().unwrap();
+            assert_eq!((orders[0].pos, orders[0].position_size), (side, dec!(0.004)));
+        }
+
+        // a rejected close leaves the entry order in place
+        let exchange = MockExchange {
+            reject_closes: true,
+            ..MockExchange::new(100_000.0)
+        };
+        let mut open = OpenPosition {
+            pos: Position::Short,
+            order_id: Some("entry".to_string()),
+            ..OpenPosition::default_open_position()
+        };
+        let slice = open.clone();
+        assert!(open.close_partial(&slice, &exchange).await.is_err());
+        assert_eq!(open.order_id.as_deref(), Some("entry"));
+    }
+
+    #[tokio::test]
+    async fn test_recovered_entry_gets_its_exchange_stop_back() {
+        let exchange = MockExchange::new(100_000.0);
+        let mut open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(100000),
+            position_size: dec!(0.01),
+            sl: Some(dec!(99000)),
+            ..OpenPosition::default_open_position()
+        };
+
+        open.arm_exchange_exits(None, &exchange).await.unwrap();
+
+        assert_eq!(*exchange.stop_updates.lock().unwrap(), vec![99_000.0]);
+    }
+
+    #[tokio::test]
+    async fn test_live_loop_stops_between_ticks_on_shutdown() {
+        let (_commands, mut inbox) = command::channel();
+        let (tx, mut rx) = watch::channel(false);
+        let mut ticks = futures_util::stream::iter([1, 2]);
+        assert!(matches!(
+            next_or_shutdown(&mut ticks, &mut rx, &mut None, &mut inbox).await,
+            Some(Wake::Tick(1))
+        ));
+
+        tx.send(true).unwrap();
+        assert!(next_or_shutdown(&mut ticks, &mut rx, &mut None, &mut inbox)
+            .await
+            .is_none());
+
+        // a quiet stream doesn't hold the shutdown up
+        let (tx, mut rx) = watch::channel(false);
+        let mut quiet = futures_util::stream::pending::<i32>();
+        tokio::spawn(async move { tx.send(true) });
+        assert!(next_or_shutdown(&mut quiet, &mut rx, &mut None, &mut inbox)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_frozen_feed_still_wakes_the_loop_for_the_staleness_check() {
+        let (_commands, mut inbox) = command::channel();
+        let (_tx, mut rx) = watch::channel(false);
+        let mut frozen = futures_util::stream::pending::<i32>();
+        let mut watchdog = Some(tokio::time::interval(Duration::from_millis(10)));
+
+        // no tick ever arrives, yet the loop gets to check staleness, and keeps doing so
+        for _ in 0..2 {
+            assert!(matches!(
+                next_or_shutdown(&mut frozen, &mut rx, &mut watchdog, &mut inbox).await,
+                Some(Wake::Watchdog)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_operator_command_wakes_a_quiet_loop() {
+        let (commands, mut inbox) = command::channel();
+        let (_tx, mut rx) = watch::channel(false);
+        let mut quiet = futures_util::stream::pending::<i32>();
+
+        // the API's close reaches the loop that owns the position, not the store
+        let asked = tokio::spawn(async move {
+            command::ask(&commands, |reply| BotCommand::Close { reply }).await
+        });
+        let Some(Wake::Command(BotCommand::Close { reply })) =
+            next_or_shutdown(&mut quiet, &mut rx, &mut None, &mut inbox).await
+        else {
+            panic!("the close command didn't wake the loop");
+        };
+        reply.send(Ok(None)).unwrap();
+        assert!(matches!(asked.await.unwrap(), Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn test_capped_day_blocks_the_next_entry_but_not_the_close() {
+        let now = Utc::now();
+        let mut throttle = DailyEntryThrottle::new(now);
+        for _ in 0..2 {
+            assert!(throttle.allows_entry(now, 2));
+            throttle.record_entry(now);
+        }
+        // entry 3 of a 2-a-day cap is refused
+        assert!(!throttle.allows_entry(now, 2));
+
+        // the open position still closes, and a close doesn't count as an entry
+        let exchange = MockExchange::new(100_500.0);
+        let open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(100000),
+            position_size: dec!(0.01),
+            ..OpenPosition::default_open_position()
+        };
+        assert!(Bot::place_close_order(&exchange, &open).await.is_ok());
+        assert_eq!(exchange.orders.lock().unwrap().len(), 1);
+        assert_eq!(throttle.count_for(now), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_entry_order_books_nothing() {
+        let open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(65000),
+            position_size: dec!(0.01),
+            ..OpenPosition::default_open_position()
+        };
+
+        let exchange = MockExchange {
+            reject_orders: true,
+            ..MockExchange::new(65_000.0)
+        };
+        assert!(Bot::place_entry_order(&exchange, &open).await.is_err());
+        assert!(exchange.orders.lock().unwrap().is_empty());
+
+        let exchange = MockExchange::new(65_000.0);
+        let exec_price = Bot::place_entry_order(&exchange, &open).await.unwrap();
+        assert!(!exec_price.is_failed());
+        assert_eq!(exchange.orders.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_first_partial_moves_the_exchange_stop_to_breakeven() {
+        let exchange = MockExchange::new(100_200.0);
+        let ladder = Helper::build_profit_targets(
+            dec!(100000),
+            dec!(50),
+            dec!(20),
+            dec!(200),
+            Decimal::ZERO,
+            Position::Long,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
+        );
+        let size: Decimal = ladder.iter().map(|t| t.size_btc).sum();
+        let mut open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(100000),
+            position_size: size,
+            quantity: Some(size),
+            sl: Some(dec!(99000)),
+            ..OpenPosition::default_open_position()
+        };
+
+        // TP1 fills, through the same steps take_partial_profit_on_long takes
+        let (tp1, price) = (&ladder[0], dec!(100200));
+        let buffer_pct = Helper::f64_to_decimal(Config::for_tests().partial_sl_buffer_pct);
+        let sl = open.partial_stop(tp1.sl, price, buffer_pct);
+        let slice = OpenPosition {
+            position_size: tp1.size_btc,
+            quantity: Some(tp1.size_btc),
+            ..open.clone()
+        };
+        let remaining = size - tp1.size_btc;
+        open.take_partial(&slice, remaining, tp1.target_price, sl, &exchange)
+            .await
+            .unwrap();
+
+        assert_eq!(open.sl, Some(open.entry_price));
+        assert_eq!(open.position_size, remaining);
+        assert_eq!(*exchange.stop_updates.lock().unwrap(), vec![100_000.0]);
+        let closes: Vec<Decimal> =
+            exchange.orders.lock().unwrap().iter().map(|o| o.position_size).collect();
+        assert_eq!(closes, vec![tp1.size_btc]);
+
+        // TP2's stop is TP1, a further move; one that isn't tighter leaves it alone
+        let sl = open.partial_stop(Some(dec!(99500)), dec!(100400), buffer_pct);
+        let slice = OpenPosition {
+            position_size: ladder[1].size_btc,
+            ..open.clone()
+        };
+        let remaining = remaining - ladder[1].size_btc;
+        open.take_partial(&slice, remaining, ladder[1].target_price, sl, &exchange)
+            .await
+            .unwrap();
+        assert_eq!(open.sl, Some(open.entry_price));
+        assert_eq!(exchange.stop_updates.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_trailing_stop_activates_and_only_tightens() {
+        let mut open = OpenPosition {
+            pos: Position::Short,
+            entry_price: dec!(100000),
+            sl: Some(dec!(101000)),
+            ..OpenPosition::default_open_position()
+        };
+
+        // 1% activation: nothing until price is at or below 99000
+        assert!(!open.trail_stop(dec!(99500), dec!(1), dec!(250)));
+        assert_eq!(open.sl, Some(dec!(101000)));
+
+        assert!(open.trail_stop(dec!(99000), dec!(1), dec!(250)));
+        assert_eq!(open.sl, Some(dec!(99250.00)));
+
+        // a bounce never loosens it, a new low ratchets it down
+        assert!(!open.trail_stop(dec!(99200), dec!(1), dec!(250)));
+        assert_eq!(open.sl, Some(dec!(99250.00)));
+        assert!(open.trail_stop(dec!(98500), dec!(1), dec!(250)));
+        assert_eq!(open.sl, Some(dec!(98750.00)));
+
+        // a later partial's stop has to beat the trail to replace it
+        assert!(!open.is_tighter_stop(dec!(99000)));
+        assert!(open.is_tighter_stop(dec!(98600)));
+
+        // disabled when the activation is zero
+        assert!(!open.trail_stop(dec!(90000), Decimal::ZERO, dec!(250)));
+    }
+
+    #[test]
+    fn test_bot_and_guard_agree_on_zone_id() {
+        // the bot trades whatever the SMC tracker stored; the guard keys its stats on
+        // the zone it was handed, so a stored round trip must not change the id
+        let defaults = Zones::default();
+        let json = serde_json::to_string(&defaults).unwrap();
+        let stored: Zones = serde_json::from_str(&json).unwrap();
+
+        for (traded, guarded) in stored
+            .long_zones
+            .iter()
+            .chain(&stored.short_zones)
+            .zip(defaults.long_zones.iter().chain(&defaults.short_zones))
+        {
+            assert_eq!(ZoneId::from_zone(traded), zones::ZoneId::from_zone(guarded));
+        }
+
+        let long = Zone::below(60_000.0, 0.01);
+        let short = Zone { side: Side::Short, ..long };
+        assert_ne!(ZoneId::from_zone(&long), ZoneId::from_zone(&short));
+    }
+
+    #[test]
+    fn test_vol_sized_entry_rescales_the_ladder_to_its_quantity() {
+        let mut config = Config::for_tests();
+        config.use_vol_sizing = true;
+        config.vol_target_risk = 0.02;
+        let (entry, margin, leverage) = (dec!(100000), dec!(1000), dec!(10));
+        let mut targets = Helper::build_profit_targets(
+            entry,
+            margin,
+            leverage,
+            dec!(500),
+            Decimal::ZERO,
+            Position::Long,
+            &[dec!(0.5), dec!(0.3), dec!(0.2)],
+            5,
+        );
+        let full: Decimal = targets.iter().map(|t| t.size_btc).sum();
+
+        let qty =
+            Bot::size_entry(&config, &mut targets, entry, margin, leverage, Some(dec!(2000)), 5);
+
+        assert!(qty < full);
+        assert_eq!(targets.iter().map(|t| t.size_btc).sum::<Decimal>(), qty);
+        assert!(targets[0].size_btc > targets[2].size_btc);
+    }
+
+    #[test]
+    fn test_fallback_stop_uses_the_positions_own_sizing() {
+        let defaults = Tunables::from_config(&Config::for_tests());
+        let open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(100000),
+            margin: Some(dec!(100)),
+            leverage: Some(dec!(10)),
+            risk_pct: Some(dec!(0.02)),
+            ..OpenPosition::default_open_position()
+        };
+        assert_eq!(
+            open.fallback_stop_loss(dec!(1000), &defaults),
+            Helper::stop_loss_price(dec!(100000), dec!(100), dec!(10), dec!(0.02), Position::Long)
+        );
+
+        // an older record without its sizing falls back to the current tunables
+        let legacy = OpenPosition {
+            margin: None,
+            leverage: None,
+            risk_pct: None,
+            ..open
+        };
+        assert_eq!(
+            legacy.fallback_stop_loss(dec!(1000), &defaults),
+            Helper::stop_loss_price(
+                dec!(100000),
+                dec!(1000),
+                Helper::f64_to_decimal(defaults.leverage),
+                Helper::f64_to_decimal(defaults.risk_pct),
+                Position::Long,
+            )
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::{OpenPosition, Position, Zone, Zones};
+use crate::exchange::{Exchange, OrderFill};
+
+/// A post-only limit entry waiting inside the next zone. The bot stays `Flat`
+/// while it rests; only a confirmed fill turns it into a position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestingEntry {
+    pub order_id: String,
+    pub side: Position,
+    pub zone: Zone,
+    pub limit_price: f64,
+    pub open_position: OpenPosition,
+    pub placed_at: DateTime<Utc>,
+}
+
+impl RestingEntry {
+    /// Level `depth` (0 = near edge, 1 = far edge) of the way into `zone`
+    pub fn limit_price(zone: &Zone, side: Position, depth: f64) -> f64 {
+        let depth = depth.clamp(0.0, 1.0);
+        match side {
+            Position::Short => zone.low + depth * (zone.high - zone.low),
+            _ => zone.high - depth * (zone.high - zone.low),
+        }
+    }
+
+    /// The nearest long zone at or below `price`, or short zone at or above it
+    pub fn upcoming_zone(zones: &Zones, price: f64) -> Option<(Position, Zone)> {
+        let longs = zones
+            .long_zones
+            .iter()
+            .filter(|z| z.low <= price)
+            .map(|z| ((price - z.high).max(0.0), Position::Long, *z));
+        let shorts = zones
+            .short_zones
+            .iter()
+            .filter(|z| z.high >= price)
+            .map(|z| ((z.low - price).max(0.0), Position::Short, *z));
+        longs
+            .chain(shorts)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, side, zone)| (side, zone))
+    }
+
+    /// Whether this order already rests at the level the zones now call for
+    pub fn is_at(&self, side: Position, limit_price: f64) -> bool {
+        self.side == side && (self.limit_price - limit_price).abs() < 0.05
+    }
+
+    /// Cancels `stale` if there is one, then rests a new order at `limit_price`
+    pub async fn replace(
+        stale: Option<&Self>,
+        exchange: &dyn Exchange,
+        side: Position,
+        zone: Zone,
+        limit_price: f64,
+        open_position: OpenPosition,
+    ) -> anyhow::Result<Self> {
+        if let Some(stale) = stale {
+            stale.cancel(exchange).await?;
+        }
+        let placed = exchange
+            .place_limit_order(&open_position, limit_price)
+            .await?;
+        Ok(Self {
+            order_id: placed.order_id,
+            side,
+            zone,
+            limit_price,
+            open_position,
+            placed_at: Utc::now(),
+        })
+    }
+
+    /// The quantity filled so far, `None` while nothing has (or the exchange can't tell)
+    pub async fn filled_quantity(
+        &self,
+        exchange: &dyn Exchange,
+    ) -> anyhow::Result<Option<Decimal>> {
+        let fill = exchange
+            .order_filled(&self.open_position, &self.order_id)
+            .await?;
+        Ok(match fill {
+            Some(OrderFill::Filled) => Some(self.open_position.position_size),
+            Some(OrderFill::Partial(qty)) if qty > Decimal::ZERO => Some(qty),
+            _ => None,
+        })
+    }
+
+    pub async fn cancel(&self, exchange: &dyn Exchange) -> anyhow::Result<()> {
+        exchange
+            .cancel_order(&self.open_position, &self.order_id)
+            .await
+    }
+
+    /// `key` is the symbol's own, see `SymbolKeys`
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection, key: &str) -> Option<Self> {
+        let raw: Option<String> = conn.get(key).await.unwrap_or(None);
+        raw.and_then(|r| serde_json::from_str(&r).ok())
+    }
+
+    pub async fn store(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> anyhow::Result<()> {
+        let _: () = conn.set(key, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    pub async fn clear(
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> anyhow::Result<()> {
+        let _: () = conn.del(key).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::Side;
+    use crate::exchange::MockExchange;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_zone_update_replaces_the_stale_resting_order() {
+        let exchange = MockExchange::new(65_000.0);
+        let old_zones = Zones {
+            long_zones: vec![Zone {
+                low: 64_000.0,
+                high: 64_200.0,
+                side: Side::Long,
+            }],
+            short_zones: vec![],
+        };
+
+        let (side, zone) = RestingEntry::upcoming_zone(&old_zones, 65_000.0).unwrap();
+        let level = RestingEntry::limit_price(&zone, side, 0.5);
+        assert_eq!(level, 64_100.0);
+        let resting = RestingEntry::replace(
+            None,
+            &exchange,
+            side,
+            zone,
+            level,
+            OpenPosition::default_open_position(),
+        )
+        .await
+        .unwrap();
+
+        // the zone is redrawn higher; the order no longer sits where it should
+        let new_zones = Zones {
+            long_zones: vec![Zone {
+                low: 64_500.0,
+                high: 64_700.0,
+                side: Side::Long,
+            }],
+            short_zones: vec![],
+        };
+        let (side, zone) = RestingEntry::upcoming_zone(&new_zones, 65_000.0).unwrap();
+        let level = RestingEntry::limit_price(&zone, side, 0.5);
+        assert!(!resting.is_at(side, level));
+
+        let replaced = RestingEntry::replace(
+            Some(&resting),
+            &exchange,
+            side,
+            zone,
+            level,
+            OpenPosition::default_open_position(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(*exchange.cancelled.lock().unwrap(), vec![resting.order_id]);
+        let limit_orders = exchange.limit_orders.lock().unwrap();
+        assert_eq!(limit_orders.len(), 2);
+        assert_eq!(limit_orders[1].1, 64_600.0);
+        assert_eq!(replaced.limit_price, 64_600.0);
+        assert_ne!(replaced.order_id, "mock-limit-1");
+    }
+
+    #[tokio::test]
+    async fn test_partial_fill_reports_only_what_filled() {
+        let exchange = MockExchange::new(65_000.0);
+        let zone = Zone {
+            low: 64_000.0,
+            high: 64_200.0,
+            side: Side::Long,
+        };
+        let resting = RestingEntry::replace(
+            None,
+            &exchange,
+            Position::Long,
+            zone,
+            64_100.0,
+            OpenPosition {
+                position_size: dec!(0.01),
+                ..OpenPosition::default_open_position()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(resting.filled_quantity(&exchange).await.unwrap(), None);
+
+        // a redraw cancels it after part has filled; that part is still the bot's
+        *exchange.fill.lock().unwrap() = Some(OrderFill::Partial(dec!(0.004)));
+        assert_eq!(
+            resting.filled_quantity(&exchange).await.unwrap(),
+            Some(dec!(0.004))
+        );
+
+        *exchange.fill.lock().unwrap() = Some(OrderFill::Filled);
+        assert_eq!(
+            resting.filled_quantity(&exchange).await.unwrap(),
+            Some(dec!(0.01))
+        );
+    }
+}
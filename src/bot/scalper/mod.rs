@@ -1,387 +1,832 @@
-use anyhow::{Ok, Result};
-use chrono::Utc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use log::{info, warn};
 use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use tokio::sync::watch;
 use uuid::Uuid;
 
 use crate::{
-    bot::{Bot, ClosedPosition, OpenPosition, Position, Zones},
+    bot::id_gen::{self, IdGen},
+    bot::{allocator::CapitalAllocator, allocator::Strategy},
+    bot::{control::BotStatus, daily_loss::DailyLoss, zones::ZoneGuard},
+    bot::{Bot, ClosedPosition, OpenPosition, Position, Zone, ZoneId, Zones},
+    cache::codec,
+    calendar::MacroGuard,
     config::Config,
-    exchange::{Exchange, OrderSide},
+    exchange::bitget::accounts::AccountRouter,
+    exchange::bitget::fees::BitgetFuturesFees,
+    exchange::bitget::maintenance::MaintenanceGuard,
+    exchange::{Exchange, PRICE_UNAVAILABLE},
     helper::{
-        Helper, SCALPER_CLOSED_POSITIONS, TRADING_BOT_CLOSE_POSITIONS, TRADING_SCALPER_BOT_ACTIVE,
-        TRADIN_SCALPER_BOT_POSITION,
+        Helper, PartialProfitTarget, SCALPER_CLOSED_POSITIONS, SCALPER_ID_COUNTER,
+        TRADING_BOT_DAILY_PNL,
+        TRADING_BOT_ZONES, TRADING_SCALPER_BOT_ACTIVE, TRADING_SCALPER_BOT_POSITION,
+        TRADING_SCALPER_PARTIAL_PROFIT_TARGET,
     },
 };
 
-pub struct ScalperBot {
+/// Quick in-and-out trades off the ranger's zones: enter at market on a zone touch,
+/// take profit a fixed `SCALPER_TP_DISTANCE` away or stop out at the risk stop. Its
+/// position lives under its own keys so it never touches the ranger's, and it refuses
+/// to start unless `ACCOUNT_ROUTING=by_strategy` gives it an account of its own, so the
+/// exchange never nets the two.
+/// With `SCALPER_PARTIAL_TARGETS` it scales out over a ladder up to the take profit.
+/// Entries go through the ranger's gates (pause, daily loss, macro, maintenance and
+/// zone guard) and wait `SCALPER_REENTRY_COOLDOWN_SECS` after each exit.
+pub struct ScalperBot<'a> {
     pub scalp_open_pos: OpenPosition,
 
     pub scalp_pos: Position,
 
     pub zones: Zones,
 
+    pub partial_profit_target: Vec<PartialProfitTarget>,
+
     // a *mutable* reference to the redis connection
     redis_conn: redis::aio::MultiplexedConnection,
+
+    config: &'a Config,
+
+    fees: BitgetFuturesFees,
+
+    accounts: AccountRouter,
+
+    zone_guard: ZoneGuard,
+
+    macro_guard: MacroGuard,
+
+    maintenance: MaintenanceGuard,
+
+    id_gen: Box<dyn IdGen>,
+
+    /// When the last trade closed, for the re-entry cooldown
+    last_exit: Option<DateTime<Utc>>,
+
+    /// PnL banked by partial closes of the open position, for its zone result
+    realized_pnl: Decimal,
 }
 
-impl ScalperBot {
-    pub async fn new(mut conn: redis::aio::MultiplexedConnection) -> Result<Self> {
-        let zones: Zones = Bot::load_zones(&mut conn)
+impl<'a> ScalperBot<'a> {
+    pub async fn new(
+        mut conn: redis::aio::MultiplexedConnection,
+        config: &'a Config,
+        http: reqwest::Client,
+    ) -> Result<Self> {
+        let accounts = AccountRouter::new(config.accounts.clone(), config.account_routing);
+        if !accounts.has_own_account(Strategy::Scalper) {
+            return Err(anyhow!(
+                "The scalper needs its own account: set ACCOUNT_ROUTING=by_strategy and tag a BITGET_SUB_ACCOUNTS entry with \"strategy\": \"scalper\""
+            ));
+        }
+
+        // The hardcoded defaults are guesses; trade nothing until the ranger's load
+        let zones = Bot::load_zones(&mut conn, TRADING_BOT_ZONES)
             .await
-            .unwrap_or_else(|_| Zones::default());
+            .unwrap_or_else(|e| {
+                warn!("Scalper has no zones until they load: {e}");
+                Zones {
+                    long_zones: Vec::new(),
+                    short_zones: Vec::new(),
+                }
+            });
 
-        let open_pos = Self::load_scalper_open_position(&mut conn)
+        let scalp_pos = Bot::load_position(&mut conn, TRADING_SCALPER_BOT_POSITION)
             .await
-            .unwrap_or_else(|_| Self::default_scalper_open_position());
-        // warn!("open_pos -> {:?}", open_pos);
+            .unwrap_or(Position::Flat);
+        let scalp_open_pos =
+            OpenPosition::load_open_position(&mut conn, TRADING_SCALPER_BOT_ACTIVE)
+                .await
+                .unwrap_or_else(|_| OpenPosition::default_open_position());
+        let partial_profit_target =
+            Bot::load_partial_profit_target(&mut conn, TRADING_SCALPER_PARTIAL_PROFIT_TARGET)
+                .await
+                .unwrap_or_default();
+
+        // Shares the ranger's zone stats, so a zone either strategy lost in is closed to both
+        let mut zone_guard = ZoneGuard::new(1, conn.clone(), 60 * 60);
+        if let Err(e) = zone_guard.hydrate().await {
+            warn!("Scalper failed to load zone guard stats: {e}");
+        }
+        let macro_guard = MacroGuard::new(
+            &mut conn.clone(),
+            config.calendar_tz,
+            config.macro_countries.clone(),
+        )
+        .await?;
+        let maintenance = MaintenanceGuard::from_config(config);
+        let last_exit = Self::load_last_exit(&mut conn).await;
+        let id_counter: Option<u64> = conn.get(SCALPER_ID_COUNTER).await?;
 
         Ok(Self {
-            scalp_pos: open_pos.pos,
+            scalp_pos,
+            scalp_open_pos,
             zones,
+            partial_profit_target,
+            fees: BitgetFuturesFees::new(conn.clone(), http),
             redis_conn: conn,
-            scalp_open_pos: open_pos,
+            config,
+            accounts,
+            zone_guard,
+            macro_guard,
+            maintenance,
+            id_gen: Self::id_gen(config.id_seed, id_counter.unwrap_or(0)),
+            last_exit,
+            realized_pnl: Decimal::ZERO,
         })
     }
 
-    async fn load_scalper_open_position(
-        conn: &mut redis::aio::MultiplexedConnection,
-    ) -> Result<OpenPosition> {
-        let key = TRADING_SCALPER_BOT_ACTIVE;
-
-        let open_pos: String = conn.get(key).await?;
+    /// Exit time of the newest scalper close, so a restart keeps the cooldown
+    async fn load_last_exit(conn: &mut redis::aio::MultiplexedConnection) -> Option<DateTime<Utc>> {
+        let raw: Option<Vec<u8>> = conn.lindex(SCALPER_CLOSED_POSITIONS, 0).await.ok()?;
+        raw.and_then(|r| codec::decode::<ClosedPosition>(&r).ok())
+            .map(|closed| closed.exit_time)
+    }
 
-        Ok(serde_json::from_str(&open_pos)?)
+    /// Whether `cooldown_secs` have passed since the last exit
+    pub fn cooled_down(
+        last_exit: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        cooldown_secs: i64,
+    ) -> bool {
+        last_exit.is_none_or(|exit| now - exit >= chrono::Duration::seconds(cooldown_secs))
     }
 
-    fn default_scalper_open_position() -> OpenPosition {
-        OpenPosition {
-            id: Uuid::nil(),
-            pos: Position::Flat,
-            entry_price: 0.00,
-            entry_time: Utc::now(),
-            position_size: 0.015,
-            sl: Some(0.00),
-            margin: Some(50.00),
-            quantity: Some(0.015),
-            risk_pct: Some(0.05),
-            leverage: Some(35.00),
+    /// The ranger's entry gates, plus the scalper's re-entry cooldown
+    async fn entries_allowed(&mut self) -> bool {
+        let now = Utc::now();
+        if !Self::cooled_down(
+            self.last_exit,
+            now,
+            self.config.scalper_reentry_cooldown_secs,
+        ) {
+            return false;
+        }
+        if BotStatus::is_paused(&mut self.redis_conn).await {
+            info!("Bot paused: scalper skipping entries");
+            return false;
+        }
+        let max_daily_loss = Helper::f64_to_decimal(self.config.max_daily_loss);
+        if DailyLoss::load(&mut self.redis_conn, TRADING_BOT_DAILY_PNL)
+            .await
+            .is_tripped(now, max_daily_loss)
+        {
+            return false;
+        }
+        self.macro_guard
+            .refresh_if_stale(
+                &mut self.redis_conn,
+                now,
+                chrono::Duration::seconds(self.config.macro_guard_refresh_secs),
+            )
+            .await;
+        if !self.macro_guard.allow_entry(now) {
+            warn!("Macro guard not allowing scalper entry");
+            return false;
         }
+        if !self.maintenance.allows_entry(now) {
+            warn!("MAINTENANCE PAUSE: scalper skipping entries");
+            return false;
+        }
+        true
     }
 
-    async fn store_position(&mut self, pos: Position, open_pos: OpenPosition) -> Result<()> {
-        let _: () = self
-            .redis_conn
-            .set(TRADIN_SCALPER_BOT_POSITION, pos.as_str())
-            .await?;
-
-        let scalper_key = TRADING_SCALPER_BOT_ACTIVE;
-
-        let _: () = self.redis_conn.set(scalper_key, open_pos.as_str()).await?;
+    async fn store_position(&mut self) -> Result<()> {
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .set(TRADING_SCALPER_BOT_POSITION, self.scalp_pos.as_str())
+            .ignore()
+            .set(TRADING_SCALPER_BOT_ACTIVE, self.scalp_open_pos.as_str())
+            .ignore();
+        if self.partial_profit_target.is_empty() {
+            pipe.del(TRADING_SCALPER_PARTIAL_PROFIT_TARGET).ignore();
+        } else {
+            pipe.set(
+                TRADING_SCALPER_PARTIAL_PROFIT_TARGET,
+                serde_json::to_string(&self.partial_profit_target)?,
+            )
+            .ignore();
+        }
+        let _: () = pipe.query_async(&mut self.redis_conn).await?;
 
         Ok(())
     }
 
-    /// Store *one* closed position in the list named `"closed_positions" & "scalper_closed_positions"`.
+    /// Store *one* closed position in the shared `closed_positions` list, and in
+    /// `scalper_closed_positions` so the two strategies can be compared.
     pub async fn store_closed_position(
         conn: &mut redis::aio::MultiplexedConnection,
         pos: &ClosedPosition,
+        config: &Config,
     ) -> Result<()> {
         //use the same as the ranger and other bots
-        let key = TRADING_BOT_CLOSE_POSITIONS;
+        Bot::store_closed_position(
+            conn,
+            pos,
+            config.serialization,
+            config.max_closed_history,
+        )
+        .await?;
 
-        //Now this one is for the scalper_closed_positions so we can track the difference in performance
         let scalper_key = SCALPER_CLOSED_POSITIONS;
-        let json = serde_json::to_string(pos)?;
-
-        // LPUSH pushes to the **left** of the list – newest element first
-        let _: () = conn.lpush(key, json.clone()).await?;
-
-        // RPUSH pushes to the **right** of the list - oldest element first
-        let _: () = conn.lpush(scalper_key, json.clone()).await?;
-
-        // OPTIONAL: keep only the last N trades (e.g. 10 000)
-        // conn.ltrim(key, 0, 9999).await?;
+        let _: () = conn
+            .lpush(scalper_key, codec::encode(pos, config.serialization)?)
+            .await?;
 
-        //Delete the open_position
-        let _: usize = conn.del(TRADING_SCALPER_BOT_ACTIVE).await?;
+        // Keep only the newest `max_closed_history` trades (0 = unbounded)
+        if config.max_closed_history > 0 {
+            let _: () = conn
+                .ltrim(scalper_key, 0, config.max_closed_history as isize - 1)
+                .await?;
+        }
 
         Ok(())
     }
 
+    /// Position ids for the scalper. A seeded run flips the seed's bits, so the two
+    /// strategies never hand out the same sequence off one `ID_SEED`.
+    fn id_gen(seed: Option<u64>, counter: u64) -> Box<dyn IdGen> {
+        id_gen::from_seed(seed.map(|seed| !seed), counter)
+    }
+
+    /// A market entry `id` sized off `margin`, with the risk stop and a take profit
+    /// `tp_distance` away in the trade's favour
     pub fn prepare_open_position(
-        &mut self,
+        id: Uuid,
         pos: Position,
-        entry_price: f64,
-        position_size: f64,
-        margin: f64,
-        leverage: f64,
-        risk_pct: f64,
+        entry_price: Decimal,
+        margin: Decimal,
+        leverage: Decimal,
+        risk_pct: Decimal,
+        tp_distance: Decimal,
     ) -> OpenPosition {
         let sl = Helper::stop_loss_price(entry_price, margin, leverage, risk_pct, pos);
         let qty = Helper::contract_amount(entry_price, margin, leverage);
+        let tp = match pos {
+            Position::Short => entry_price - tp_distance,
+            _ => entry_price + tp_distance,
+        };
         OpenPosition {
-            id: Uuid::new_v4(),
-            pos: pos,
-            entry_price: entry_price,
-            position_size, //does the same thing as quantity :(
+            id,
+            pos,
+            entry_price,
+            position_size: qty,
             entry_time: Utc::now(),
+            tp: Some(tp),
             sl: Some(sl),
             margin: Some(margin),
             quantity: Some(qty),
             leverage: Some(leverage),
             risk_pct: Some(risk_pct),
+            ..OpenPosition::default_open_position()
         }
     }
 
-    async fn close_long_position(&mut self, price: f64, config: &mut Config) {
-        let roi = Helper::calc_roi(
-            Helper::f64_to_decimal(self.scalp_open_pos.margin.unwrap_or(config.margin)),
-            Helper::f64_to_decimal(self.scalp_open_pos.entry_price),
-            self.scalp_pos,
-            Helper::f64_to_decimal(self.scalp_open_pos.position_size),
-            Helper::f64_to_decimal(price),
+    /// The ladder for scaling out of `open_pos`: one target per fraction, evenly spaced
+    /// out to `tp_distance`, each pulling the stop up behind the one before it
+    pub fn build_profit_targets(
+        open_pos: &OpenPosition,
+        tp_distance: Decimal,
+        fractions: &[Decimal],
+        size_precision: u32,
+    ) -> Vec<PartialProfitTarget> {
+        if fractions.is_empty() {
+            return Vec::new();
+        }
+        let mut targets = Helper::build_profit_targets(
+            open_pos.entry_price,
+            open_pos.margin.unwrap_or_default(),
+            open_pos.leverage.unwrap_or(Decimal::ONE),
+            tp_distance / Decimal::from(fractions.len()),
+            Decimal::ZERO,
+            open_pos.pos,
+            fractions,
+            size_precision,
         );
-        let closed_pos = ClosedPosition {
-            id: self.scalp_open_pos.id,
-            entry_price: self.scalp_open_pos.entry_price,
-            exit_price: price,
-            exit_time: Utc::now(),
-            position: Some(Position::Long),
-            side: None,
-            entry_time: self.scalp_open_pos.entry_time,
-            pnl: Helper::compute_pnl(
-                self.scalp_pos,
-                self.scalp_open_pos.entry_price,
-                self.scalp_open_pos.position_size,
-                price,
-            ),
-            quantity: Some(self.scalp_open_pos.position_size),
-            sl: self.scalp_open_pos.sl,
-            roi: Some(roi),
-            leverage: self.scalp_open_pos.leverage,
-            margin: self.scalp_open_pos.margin,
-        };
-        let _ = Self::store_closed_position(&mut self.redis_conn, &closed_pos).await;
+        // the ladder must close exactly what was opened, and end on the take profit
+        Helper::rescale_target_sizes(&mut targets, open_pos.position_size, size_precision);
+        if let (Some(last), Some(tp)) = (targets.last_mut(), open_pos.tp) {
+            last.target_price = tp;
+        }
+        targets
     }
 
-    async fn close_short_position(&mut self, price: f64, config: &mut Config) {
-        let pnl = Helper::compute_pnl(
-            self.scalp_open_pos.pos,
-            self.scalp_open_pos.entry_price,
-            self.scalp_open_pos.position_size,
+    /// Whether `price` has reached the position's take profit or its stop
+    pub fn should_exit(open_pos: &OpenPosition, price: Decimal) -> bool {
+        let sl_hit = open_pos
+            .sl
+            .is_some_and(|sl| Helper::ssl_hit(price, open_pos.pos, sl));
+        let tp_hit = open_pos.tp.is_some_and(|tp| match open_pos.pos {
+            Position::Long => price >= tp,
+            Position::Short => price <= tp,
+            Position::Flat => false,
+        });
+        sl_hit || tp_hit
+    }
+
+    async fn enter(
+        &mut self,
+        side: Position,
+        zone: &Zone,
+        price: Decimal,
+        exchange: &dyn Exchange,
+    ) -> Result<()> {
+        info!("Scalper is Entering {side:?} at {price:.2}");
+
+        let margin = CapitalAllocator::from_config(self.config)
+            .load(
+                &mut self.redis_conn,
+                Strategy::Scalper,
+                Helper::f64_to_decimal(self.config.margin),
+            )
+            .await;
+        if margin <= Decimal::ZERO {
+            warn!("Scalper has no capital, set SCALPER_CAPITAL_WEIGHT to give it a share");
+            return Ok(());
+        }
+        let id =
+            id_gen::next_stored(self.id_gen.as_ref(), &mut self.redis_conn, SCALPER_ID_COUNTER)
+                .await;
+        let mut open_pos = Self::prepare_open_position(
+            id,
+            side,
             price,
+            margin,
+            Helper::f64_to_decimal(self.config.leverage),
+            Helper::f64_to_decimal(self.config.risk_pct),
+            Helper::f64_to_decimal(self.config.scalper_tp_distance),
         );
-        let roi = Helper::calc_roi(
-            Helper::f64_to_decimal(self.scalp_open_pos.margin.unwrap_or(config.margin)),
-            Helper::f64_to_decimal(self.scalp_open_pos.entry_price),
-            self.scalp_open_pos.pos,
-            Helper::f64_to_decimal(self.scalp_open_pos.position_size),
-            Helper::f64_to_decimal(price),
+        open_pos.account = Some(self.accounts.select(Strategy::Scalper).name.clone());
+        open_pos.zone_id = Some(ZoneId::from_zone(zone));
+        open_pos.symbol = Some(self.config.symbol.clone());
+
+        let exec_price = exchange.place_market_order(&open_pos).await?;
+        if exec_price.is_failed() {
+            return Err(anyhow!("Exchange rejected the scalper {side:?} entry"));
+        }
+        self.maintenance.observe_success();
+        info!("Scalper {side:?} executed at {exec_price:?}");
+        open_pos.order_id = Some(exec_price.order_id);
+
+        if self.config.scalper_partial_targets {
+            let fractions: Vec<Decimal> = self
+                .config
+                .profit_target_fractions
+                .iter()
+                .map(|f| Helper::f64_to_decimal(*f))
+                .collect();
+            self.partial_profit_target = Self::build_profit_targets(
+                &open_pos,
+                Helper::f64_to_decimal(self.config.scalper_tp_distance),
+                &fractions,
+                self.config.size_precision(&self.config.symbol),
+            );
+        }
+
+        self.scalp_pos = side;
+        self.scalp_open_pos = open_pos;
+        self.store_position().await
+    }
+
+    /// Closes `open_pos` (all or part of the position) at market, records it and books
+    /// its PnL onto the scalper's slice of the capital. Returns the PnL after fees.
+    async fn close_quantity(
+        &mut self,
+        open_pos: &OpenPosition,
+        price: Decimal,
+        exchange: &dyn Exchange,
+    ) -> Result<Decimal> {
+        let exec_price = Bot::place_close_order(exchange, open_pos).await?;
+        info!("Scalper closed {:?} at {exec_price:?}", open_pos.pos);
+
+        let captured = Helper::calc_price_difference(open_pos.entry_price, price, open_pos.pos);
+        info!(
+            "Scalper captured {captured:.2} of a {:?} move",
+            open_pos.pos
         );
+        let (pnl_after_fees, costs) = self.fees.calc_pnl_for_exit(open_pos, price).await;
+        let margin = open_pos
+            .margin
+            .unwrap_or(Helper::f64_to_decimal(self.config.margin));
         let closed_pos = ClosedPosition {
-            id: self.scalp_open_pos.id,
-            entry_price: self.scalp_open_pos.entry_price,
+            id: open_pos.id,
+            position: Some(open_pos.pos),
+            side: None,
+            entry_price: open_pos.entry_price,
+            entry_time: open_pos.entry_time,
             exit_price: price,
             exit_time: Utc::now(),
-            position: Some(Position::Short),
-            side: None,
-            entry_time: self.scalp_open_pos.entry_time,
-            pnl,
-            quantity: Some(self.scalp_open_pos.position_size),
-            sl: self.scalp_open_pos.sl,
-            roi: Some(roi),
-            leverage: self.scalp_open_pos.leverage,
-            margin: self.scalp_open_pos.margin,
+            pnl: Helper::compute_pnl(
+                open_pos.pos,
+                open_pos.entry_price,
+                open_pos.position_size,
+                price,
+            ),
+            quantity: Some(open_pos.position_size),
+            sl: open_pos.sl,
+            roi: Some(Helper::calc_roi(
+                margin,
+                open_pos.entry_price,
+                open_pos.pos,
+                open_pos.position_size,
+                price,
+            )),
+            leverage: open_pos.leverage,
+            margin: open_pos.margin,
+            order_id: Some(exec_price.order_id),
+            pnl_after_fees: Some(pnl_after_fees),
+            exit_fee: Some(costs.exit_fee),
+            entry_fee: Some(costs.entry_fee),
+            funding_fee: Some(costs.funding_fee),
+            max_adverse_excursion: open_pos.max_adverse_excursion,
+            max_favorable_excursion: open_pos.max_favorable_excursion,
+            zone_id: open_pos.zone_id,
+            symbol: open_pos.symbol.clone(),
         };
-        let _ = Self::store_closed_position(&mut self.redis_conn, &closed_pos).await;
-    }
+        if let Err(e) =
+            Self::store_closed_position(&mut self.redis_conn, &closed_pos, self.config).await
+        {
+            warn!("Failed to store scalper close: {e}");
+        }
 
-    pub async fn take_profit_on_long(
-        &mut self,
-        price: f64,
-        size: f64,
-        config: &mut Config,
-        exchange: &dyn Exchange,
-    ) -> Result<()> {
-        info!("Scalper Taking profit on LONG at {:.2}", price);
+        // Only the scalper's own PnL moves its slice of the capital
+        let allocator = CapitalAllocator::from_config(self.config);
+        let config_margin = Helper::f64_to_decimal(self.config.margin);
+        let capital = allocator
+            .load(&mut self.redis_conn, Strategy::Scalper, config_margin)
+            .await;
+        if let Err(e) = allocator
+            .store(
+                &mut self.redis_conn,
+                Strategy::Scalper,
+                capital + pnl_after_fees,
+                config_margin,
+            )
+            .await
+        {
+            warn!("Failed to update scalper capital: {e}");
+        }
 
-        let exec_price = exchange.place_market_order(OrderSide::Sell, size).await?;
+        Ok(pnl_after_fees)
+    }
 
-        info!("Scalper Closed LONG at {:.2}", exec_price);
+    async fn exit(&mut self, price: Decimal, exchange: &dyn Exchange) -> Result<()> {
+        info!("Scalper closing {:?} at {price:.2}", self.scalp_pos);
 
-        Self::close_long_position(self, price, config).await;
+        let open_pos = self.scalp_open_pos.clone();
+        let pnl = self.close_quantity(&open_pos, price, exchange).await?;
 
+        if let Some(zone_id) = open_pos.zone_id {
+            let total = Helper::decimal_to_f64(self.realized_pnl + pnl);
+            self.zone_guard.record_trade_result(zone_id, total).await;
+        }
+        self.realized_pnl = Decimal::ZERO;
+        self.last_exit = Some(Utc::now());
         self.scalp_pos = Position::Flat;
-
-        Ok(())
+        self.scalp_open_pos = OpenPosition::default_open_position();
+        self.partial_profit_target = Vec::new();
+        self.store_position().await
     }
 
-    pub async fn take_profit_on_short(
-        &mut self,
-        price: f64,
-        size: f64,
-        config: &mut Config,
-        exchange: &dyn Exchange,
-    ) -> Result<()> {
-        info!("Scalper Covering SHORT at {:.2}", price);
-
-        let exec_price = exchange.place_market_order(OrderSide::Buy, size).await?;
-
-        info!("Scalper Covered SHORT at {:.2}", exec_price);
+    /// Closes every target `price` has crossed as one order and pulls the stop up to
+    /// the furthest one's. Once the ladder is used up the rest is closed out.
+    async fn take_partial_profit(&mut self, price: Decimal, exchange: &dyn Exchange) -> Result<()> {
+        let Some((target, covered)) = Helper::batch_crossed_targets(
+            &self.partial_profit_target,
+            price,
+            self.scalp_open_pos.pos,
+            1,
+        )
+        .into_iter()
+        .next() else {
+            return Ok(());
+        };
 
-        Self::close_short_position(self, price, config).await;
+        let remaining = self.scalp_open_pos.position_size - target.size_btc;
+        if covered.len() == self.partial_profit_target.len() || remaining <= Decimal::ZERO {
+            return self.exit(price, exchange).await;
+        }
 
-        self.scalp_pos = Position::Flat;
+        info!("Scalper taking {target}");
+        let closing = OpenPosition {
+            position_size: target.size_btc,
+            quantity: Some(target.size_btc),
+            ..self.scalp_open_pos.clone()
+        };
+        let pnl = self.close_quantity(&closing, price, exchange).await?;
+        self.realized_pnl += pnl;
+
+        self.scalp_open_pos.position_size = remaining;
+        self.scalp_open_pos.quantity = Some(remaining);
+        if let Some(sl) = target
+            .sl
+            .filter(|sl| self.scalp_open_pos.is_tighter_stop(*sl))
+        {
+            self.scalp_open_pos.sl = Some(sl);
+            if let Err(e) = self.scalp_open_pos.update_exchange_stop(exchange).await {
+                warn!("Failed to move the scalper's exchange stop to {sl}: {e}");
+            }
+        }
+        self.partial_profit_target = self
+            .partial_profit_target
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !covered.contains(i))
+            .map(|(_, t)| t.clone())
+            .collect();
+
+        self.store_position().await
+    }
 
-        Ok(())
+    /// The zone `price` is in, long zones first
+    fn entry_candidate(&self, price: f64) -> Option<(Position, Zone)> {
+        let long = self.zones.long_zones.iter().find(|z| z.contains(price));
+        let short = self.zones.short_zones.iter().find(|z| z.contains(price));
+        long.map(|z| (Position::Long, *z))
+            .or_else(|| short.map(|z| (Position::Short, *z)))
     }
 
-    pub async fn run_scalper_bot(
-        &mut self,
-        price: f64,
-        exchange: &dyn Exchange,
-        config: &mut Config,
-    ) -> Result<()> {
-        warn!("Scalper State = {:?}", self.scalp_pos);
-        let default_size = Helper::contract_amount(price, config.margin, config.leverage);
+    pub async fn run_scalper_bot(&mut self, price: f64, exchange: &dyn Exchange) -> Result<()> {
+        if price == PRICE_UNAVAILABLE {
+            warn!("Scalper skipping cycle: exchange price unavailable");
+            return Ok(());
+        }
+        let dec_price = Helper::f64_to_decimal(price);
 
         match self.scalp_pos {
             Position::Flat => {
-                if self.zones.long_zones.iter().any(|z| z.contains(price)) {
-                    info!("Scalper is Entering LONG at {:.2}", price);
-
-                    let exec_price = exchange
-                        .place_market_order(OrderSide::Buy, default_size)
-                        .await?;
-                    info!("Scalper Long executed at {:.2}", exec_price);
-
-                    self.scalp_pos = Position::Long;
-
-                    self.scalp_open_pos = Self::prepare_open_position(
-                        self,
-                        self.scalp_pos,
-                        price,
-                        default_size,
-                        config.margin,
-                        config.leverage,
-                        config.risk_pct,
-                    );
-                    self.store_position(self.scalp_pos, self.scalp_open_pos)
-                        .await?;
-                } else if self.zones.short_zones.iter().any(|z| z.contains(price)) {
-                    info!("Scalper is Entering SHORT at {:.2}", price);
-
-                    let exec_price = exchange
-                        .place_market_order(OrderSide::Sell, default_size)
-                        .await?;
-
-                    info!("Scalper Short executed at {:.2}", exec_price);
-
-                    self.scalp_pos = Position::Short;
-
-                    self.scalp_open_pos = Self::prepare_open_position(
-                        self,
-                        Position::Short,
-                        price,
-                        default_size,
-                        config.margin,
-                        config.leverage,
-                        config.risk_pct,
-                    );
-                    self.store_position(self.scalp_pos, self.scalp_open_pos)
-                        .await?;
+                let Some((side, zone)) = self.entry_candidate(price) else {
+                    return Ok(());
+                };
+                if !self.entries_allowed().await {
+                    return Ok(());
                 }
+                if !self.zone_guard.can_trade(ZoneId::from_zone(&zone)).await {
+                    warn!("Zone {zone:?} is not open for scalping");
+                    return Ok(());
+                }
+                self.enter(side, &zone, dec_price, exchange).await?;
             }
-
-            Position::Long => {
-                //Trigger SL if it's met
-                let in_sl = Helper::stop_loss_price(
-                    self.scalp_open_pos.entry_price,
-                    config.margin,
-                    config.leverage,
-                    config.risk_pct,
-                    Position::Long,
-                );
-                let ssl_hit = Helper::ssl_hit(
-                    price,
-                    self.scalp_pos,
-                    self.scalp_open_pos.sl.unwrap_or(in_sl),
-                );
-
-                if ssl_hit {
-                    Self::close_long_position(self, price, config).await;
-
-                    warn!(
-                        "SL for Scalper Long Position entered at {:2}, with SL triggered at {:2}",
-                        self.scalp_open_pos.entry_price, price
-                    );
-
-                    self.scalp_pos = Position::Flat;
+            Position::Long | Position::Short => {
+                if self.scalp_open_pos.track_excursion(dec_price) {
+                    self.store_position().await?;
                 }
-
-                let config_diff = 500.00;
-                let min_config_diff = config_diff - 100.00;
-                let diff = Helper::calc_price_difference(
-                    self.scalp_open_pos.entry_price,
-                    price,
-                    Position::Long,
-                );
-                info!(
-                    "SCALPER diff >= config_diff {:2.2} >= {:2.2}",
-                    diff, config_diff
-                );
-
-                if diff >= config_diff || diff >= min_config_diff {
-                    //Take your profits and get out!
-                    Self::take_profit_on_long(self, price, default_size, config, exchange).await?;
+                if !self.partial_profit_target.is_empty() {
+                    self.take_partial_profit(dec_price, exchange).await?;
+                }
+                if self.scalp_pos != Position::Flat
+                    && Self::should_exit(&self.scalp_open_pos, dec_price)
+                {
+                    self.exit(dec_price, exchange).await?;
                 }
             }
+        }
+        Ok(())
+    }
 
-            Position::Short => {
-                //Trigger SL if it's met
-                let in_sl = Helper::stop_loss_price(
-                    self.scalp_open_pos.entry_price,
-                    config.margin,
-                    config.leverage,
-                    config.risk_pct,
-                    Position::Long,
-                );
-                let ssl_hit = Helper::ssl_hit(
-                    price,
-                    self.scalp_pos,
-                    self.scalp_open_pos.sl.unwrap_or(in_sl),
-                );
-
-                if ssl_hit {
-                    Self::close_short_position(self, price, config).await;
-
-                    warn!(
-                        "SL for Scalper Short Position entered at {:2}, with SL triggered at {:2}",
-                        self.scalp_open_pos.entry_price, price
-                    );
-
-                    self.scalp_pos = Position::Flat;
+    /// Polls the price every `SCALPER_POLL_SECS` and runs a cycle on it, reloading the
+    /// ranger's zones first so the scalper trades the same levels. Stops between polls
+    /// once `shutdown` is signalled, then shuts the scalper down.
+    pub async fn run(mut self, exchange: &dyn Exchange, mut shutdown: watch::Receiver<bool>) {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.config.scalper_poll_secs.max(1)));
+        // a maintenance wait shouldn't be followed by a burst of catch-up polls
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            if !wait_or_shutdown(interval.tick(), &mut shutdown).await {
+                break;
+            }
+            // Bitget is down for maintenance: no polling until the pause runs out
+            if let Some(wait) = self.maintenance.pause_remaining(Utc::now()) {
+                if !wait_or_shutdown(tokio::time::sleep(wait), &mut shutdown).await {
+                    break;
                 }
+            }
 
-                //Operation scalp, if set
-                let config_diff = 500.00;
-                let min_config_diff = config_diff - 100.00;
-                let diff = Helper::calc_price_difference(
-                    self.scalp_open_pos.entry_price,
-                    price,
-                    Position::Short,
-                );
-                info!(
-                    "SCALPER diff >= config_diff {:2.2} >= {:2.2}",
-                    diff, config_diff
-                );
-                if diff >= config_diff || diff >= min_config_diff {
-                    //Take your profits and get out!
-                    Self::take_profit_on_short(self, price, default_size, config, exchange).await?;
+            if let Ok(zones) = Bot::load_zones(&mut self.redis_conn, TRADING_BOT_ZONES).await {
+                self.zones = zones;
+            }
+            let price = match exchange.get_current_price().await {
+                Ok(price) => price,
+                Err(e) => {
+                    if !self.maintenance.observe_error(&e, Utc::now()) {
+                        warn!("Scalper price fetch failed: {e}");
+                    }
+                    continue;
+                }
+            };
+            if let Err(e) = self.run_scalper_bot(price, exchange).await {
+                if !self.maintenance.observe_error(&e, Utc::now()) {
+                    warn!("Scalper cycle failed: {e}");
                 }
             }
         }
-        Ok(())
+        if let Err(e) = self.shutdown(exchange).await {
+            log::error!("Failed to shut the scalper down cleanly: {e}");
+        }
+    }
+
+    /// Runs once the polling loop has stopped: flattens the open scalp when
+    /// `flatten_on_exit` is set, as the ranger does with its own position
+    async fn shutdown(&mut self, exchange: &dyn Exchange) -> Result<()> {
+        if self.scalp_pos == Position::Flat {
+            return Ok(());
+        }
+        if !self.config.flatten_on_exit {
+            warn!(
+                "Scalper shutting down with {:?} open; only its exchange stop protects it",
+                self.scalp_pos
+            );
+            return Ok(());
+        }
+        let price = Helper::f64_to_decimal(exchange.get_current_price().await?);
+        warn!("Shutting down: flattening the scalper's {:?} at {price:.2}", self.scalp_pos);
+        self.exit(price, exchange).await
+    }
+}
+
+/// Awaits `wait` unless `shutdown` is signalled first (or already was); false means stop
+async fn wait_or_shutdown(
+    wait: impl std::future::Future,
+    shutdown: &mut watch::Receiver<bool>,
+) -> bool {
+    if *shutdown.borrow() {
+        return false;
+    }
+    tokio::select! {
+        _ = wait => true,
+        // a dropped sender counts as a shutdown too
+        _ = shutdown.changed() => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::MockExchange;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_rejected_close_is_an_error_not_a_close() {
+        let long = ScalperBot::prepare_open_position(
+            Uuid::nil(),
+            Position::Long,
+            dec!(65000),
+            dec!(100),
+            dec!(20),
+            dec!(0.05),
+            dec!(400),
+        );
+
+        // the exchange turns the close down: nothing may be booked as closed
+        let exchange = MockExchange {
+            reject_closes: true,
+            ..MockExchange::new(65_400.0)
+        };
+        assert!(Bot::place_close_order(&exchange, &long).await.is_err());
+        assert!(exchange.orders.lock().unwrap().is_empty());
+
+        let exchange = MockExchange::new(65_400.0);
+        let exec_price = Bot::place_close_order(&exchange, &long).await.unwrap();
+        assert!(!exec_price.is_failed());
+        assert_eq!(exchange.orders.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_entries_go_out_under_each_strategys_generated_id() {
+        let ranger_ids = id_gen::from_seed(Some(7), 0);
+        let scalper_ids = ScalperBot::id_gen(Some(7), 0);
+        let exchange = MockExchange::new(65_000.0);
+
+        let ranger = OpenPosition {
+            id: ranger_ids.next_id(),
+            ..OpenPosition::default_open_position()
+        };
+        Bot::place_entry_order(&exchange, &ranger).await.unwrap();
+        let scalp = ScalperBot::prepare_open_position(
+            scalper_ids.next_id(),
+            Position::Long,
+            dec!(65000),
+            dec!(100),
+            dec!(20),
+            dec!(0.05),
+            dec!(400),
+        );
+        exchange.place_market_order(&scalp).await.unwrap();
+
+        let placed: Vec<String> = exchange
+            .orders
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|o| o.id.to_string())
+            .collect();
+        assert_eq!(
+            placed,
+            [
+                "00000000-0000-0007-0000-000000000001",
+                "ffffffff-ffff-fff8-0000-000000000001",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scalp_exits_at_its_target_or_its_stop() {
+        let long = ScalperBot::prepare_open_position(
+            Uuid::nil(),
+            Position::Long,
+            dec!(65000),
+            dec!(100),
+            dec!(20),
+            dec!(0.05),
+            dec!(400),
+        );
+        assert_eq!(long.tp, Some(dec!(65400)));
+        assert!(long.sl.unwrap() < dec!(65000));
+        assert!(!ScalperBot::should_exit(&long, dec!(65200)));
+        assert!(ScalperBot::should_exit(&long, dec!(65400)));
+        assert!(ScalperBot::should_exit(&long, long.sl.unwrap()));
+
+        let short = ScalperBot::prepare_open_position(
+            Uuid::nil(),
+            Position::Short,
+            dec!(65000),
+            dec!(100),
+            dec!(20),
+            dec!(0.05),
+            dec!(400),
+        );
+        assert_eq!(short.tp, Some(dec!(64600)));
+        assert!(short.sl.unwrap() > dec!(65000));
+        assert!(!ScalperBot::should_exit(&short, dec!(64800)));
+        assert!(ScalperBot::should_exit(&short, dec!(64600)));
+        assert!(ScalperBot::should_exit(&short, short.sl.unwrap()));
+    }
+
+    #[test]
+    fn test_scalp_ladder_scales_out_to_its_target() {
+        let long = ScalperBot::prepare_open_position(
+            Uuid::nil(),
+            Position::Long,
+            dec!(65000),
+            dec!(100),
+            dec!(20),
+            dec!(0.05),
+            dec!(400),
+        );
+        let targets = ScalperBot::build_profit_targets(
+            &long,
+            dec!(400),
+            &[dec!(0.25), dec!(0.25), dec!(0.5)],
+            5,
+        );
+
+        let prices: Vec<Decimal> = targets.iter().map(|t| t.target_price).collect();
+        assert_eq!(prices.last().copied(), long.tp);
+        assert!(prices.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            targets.iter().map(|t| t.size_btc).sum::<Decimal>(),
+            long.position_size
+        );
+        // first target puts the stop at breakeven, the next behind the first target
+        assert_eq!(targets[0].sl, Some(dec!(65000)));
+        assert_eq!(targets[1].sl, Some(prices[0]));
+        assert!(ScalperBot::build_profit_targets(&long, dec!(400), &[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_no_reentry_until_the_cooldown_passes() {
+        let exit = Utc::now();
+        assert!(ScalperBot::cooled_down(None, exit, 300));
+        assert!(!ScalperBot::cooled_down(Some(exit), exit, 300));
+        assert!(!ScalperBot::cooled_down(
+            Some(exit),
+            exit + chrono::Duration::seconds(299),
+            300
+        ));
+        assert!(ScalperBot::cooled_down(
+            Some(exit),
+            exit + chrono::Duration::seconds(300),
+            300
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_polling_stops_once_shutdown_is_signalled() {
+        let (tx, mut rx) = watch::channel(false);
+        assert!(wait_or_shutdown(std::future::ready(()), &mut rx).await);
+
+        // a long wait, e.g. a maintenance pause, doesn't hold the shutdown up
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let _ = tx.send(true);
+        });
+        let pause = tokio::time::sleep(Duration::from_secs(3600));
+        assert!(!wait_or_shutdown(pause, &mut rx).await);
+
+        // and nothing polls again after it
+        assert!(!wait_or_shutdown(std::future::ready(()), &mut rx).await);
     }
 }
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info};
+
+/// Refuses to act on a price older than `max_age`, so a feed that stops updating
+/// can't open or close positions against a frozen price. Alerts once per stale
+/// episode rather than on every tick.
+#[derive(Debug, Clone)]
+pub struct StalenessGuard {
+    max_age: Option<Duration>,
+    alerted: bool,
+}
+
+impl StalenessGuard {
+    /// `max_secs <= 0` disables the guard
+    pub fn new(max_secs: i64) -> Self {
+        Self {
+            max_age: (max_secs > 0).then(|| Duration::seconds(max_secs)),
+            alerted: false,
+        }
+    }
+
+    pub fn is_stale(&mut self, price_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let Some(max_age) = self.max_age else {
+            return false;
+        };
+
+        let age = now - price_at;
+        if age <= max_age {
+            if self.alerted {
+                info!("Price feed recovered, last update {}s old", age.num_seconds());
+            }
+            self.alerted = false;
+            return false;
+        }
+
+        if !self.alerted {
+            error!(
+                "CRITICAL: price is {}s old (max {}s), not acting on it until the feed recovers",
+                age.num_seconds(),
+                max_age.num_seconds()
+            );
+        }
+        self.alerted = true;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_stale_price_blocks_and_alerts_until_fresh() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 2, 14, 0, 0).unwrap();
+        let mut guard = StalenessGuard::new(30);
+
+        assert!(!guard.is_stale(now - Duration::seconds(5), now));
+        assert!(!guard.alerted);
+
+        // feed froze 2 minutes ago: entries are blocked and the alert fires
+        assert!(guard.is_stale(now - Duration::seconds(120), now));
+        assert!(guard.alerted);
+
+        assert!(!guard.is_stale(now, now));
+        assert!(!guard.alerted);
+    }
+
+    #[test]
+    fn test_zero_disables_the_guard() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 2, 14, 0, 0).unwrap();
+        let mut guard = StalenessGuard::new(0);
+        assert!(!guard.is_stale(now - Duration::hours(1), now));
+    }
+}
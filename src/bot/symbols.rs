@@ -0,0 +1,185 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::bot::{Bot, OpenPosition, Position, Zones};
+use crate::config::Config;
+use crate::exchange::bitget::maintenance::MaintenanceGuard;
+use crate::helper::{
+    PartialProfitTarget, TRADING_BOT_ACTIVE, TRADING_BOT_DAILY_ENTRIES, TRADING_BOT_ENTRY_INTENT,
+    TRADING_BOT_LOSS_COUNT, TRADING_BOT_POSITION, TRADING_BOT_RECORDED_PRICES,
+    TRADING_BOT_RESTING_ENTRY, TRADING_BOT_ZONES, TRADING_PARTIAL_PROFIT_TARGET,
+};
+
+use super::allocator::Strategy;
+use super::missed::MissEpisode;
+use super::staleness::StalenessGuard;
+
+/// Redis keys one symbol's ranger state lives under. The primary symbol (`SYMBOL`)
+/// keeps the bare keys, so a single-symbol deployment reads what it always has;
+/// every other symbol gets them suffixed, e.g. `trading_bot:zones:ETHUSDT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolKeys {
+    pub symbol: String,
+    pub primary: bool,
+    pub zones: String,
+    pub position: String,
+    pub active: String,
+    pub partial_targets: String,
+    pub capital: String,
+    pub entry_intent: String,
+    pub resting_entry: String,
+    pub loss_count: String,
+    pub daily_entries: String,
+    pub recorded_prices: String,
+}
+
+impl SymbolKeys {
+    pub fn new(symbol: &str, primary: bool) -> Self {
+        let key = |base: &str| {
+            if primary {
+                base.to_string()
+            } else {
+                format!("{base}:{symbol}")
+            }
+        };
+        Self {
+            symbol: symbol.to_string(),
+            primary,
+            zones: key(TRADING_BOT_ZONES),
+            position: key(TRADING_BOT_POSITION),
+            active: key(TRADING_BOT_ACTIVE),
+            partial_targets: key(TRADING_PARTIAL_PROFIT_TARGET),
+            capital: key(&Strategy::Ranger.capital_key()),
+            entry_intent: key(TRADING_BOT_ENTRY_INTENT),
+            resting_entry: key(TRADING_BOT_RESTING_ENTRY),
+            loss_count: key(TRADING_BOT_LOSS_COUNT),
+            daily_entries: key(TRADING_BOT_DAILY_ENTRIES),
+            recorded_prices: key(TRADING_BOT_RECORDED_PRICES),
+        }
+    }
+
+    /// Zones to trade until the SMC tracker stores this symbol's own. The hardcoded
+    /// defaults are BTC prices, so every other symbol waits with none.
+    pub fn fallback_zones(&self) -> Zones {
+        if self.primary {
+            Zones::default()
+        } else {
+            Zones {
+                long_zones: Vec::new(),
+                short_zones: Vec::new(),
+            }
+        }
+    }
+
+    /// Keys for every configured symbol, the primary first
+    pub fn for_config(config: &Config) -> Vec<Self> {
+        config
+            .symbols
+            .iter()
+            .map(|s| Self::new(s, *s == config.symbol))
+            .collect()
+    }
+}
+
+/// One symbol's ranger state. The bot trades whichever symbol is active out of its
+/// own fields and parks the rest as these until their next tick.
+#[derive(Debug, Clone)]
+pub struct SymbolState {
+    pub keys: SymbolKeys,
+    pub pos: Position,
+    pub open_pos: OpenPosition,
+    pub zones: Zones,
+    pub partial_profit_target: Vec<PartialProfitTarget>,
+    pub current_margin: Decimal,
+    pub loss_count: usize,
+    /// This symbol's own feed and exchange health: one symbol's frozen ticker or
+    /// maintenance window doesn't stop the others
+    pub price_at: Option<DateTime<Utc>>,
+    pub staleness: StalenessGuard,
+    pub maintenance: MaintenanceGuard,
+    pub missed: MissEpisode,
+}
+
+impl SymbolState {
+    pub async fn load(
+        conn: &mut redis::aio::MultiplexedConnection,
+        keys: SymbolKeys,
+        config: &Config,
+    ) -> Self {
+        let pos = Bot::load_position(conn, &keys.position)
+            .await
+            .unwrap_or(Position::Flat);
+        let zones = Bot::load_zones(conn, &keys.zones)
+            .await
+            .unwrap_or_else(|_| keys.fallback_zones());
+        let open_pos = OpenPosition::load_open_position(conn, &keys.active)
+            .await
+            .unwrap_or_else(|_| OpenPosition::default_open_position());
+        let current_margin = Bot::load_current_margin(conn, config, &keys).await;
+        let partial_profit_target = Bot::load_partial_profit_target(conn, &keys.partial_targets)
+            .await
+            .unwrap_or_default();
+        let loss_count = Bot::load_loss_count(conn, &keys.loss_count)
+            .await
+            .unwrap_or(0);
+
+        Self {
+            keys,
+            pos,
+            open_pos,
+            zones,
+            partial_profit_target,
+            current_margin,
+            loss_count,
+            price_at: None,
+            staleness: StalenessGuard::new(config.max_price_staleness_secs),
+            maintenance: MaintenanceGuard::from_config(config),
+            missed: MissEpisode::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_secondary_symbols_get_their_own_keys() {
+        let btc = SymbolKeys::new("BTCUSDT", true);
+        assert_eq!(btc.zones, TRADING_BOT_ZONES);
+        assert_eq!(btc.position, TRADING_BOT_POSITION);
+        assert_eq!(btc.capital, Strategy::Ranger.capital_key());
+
+        let eth = SymbolKeys::new("ETHUSDT", false);
+        assert_eq!(eth.zones, "trading_bot:zones:ETHUSDT");
+        assert_eq!(eth.position, "trading_bot:position:ETHUSDT");
+        assert_eq!(eth.capital, "trading_capital:ranger:ETHUSDT");
+        assert_ne!(eth.active, btc.active);
+        assert_ne!(eth.partial_targets, btc.partial_targets);
+        assert_ne!(eth.entry_intent, btc.entry_intent);
+        assert_ne!(eth.resting_entry, btc.resting_entry);
+        assert_eq!(eth.loss_count, "trading_bot:loss_count:ETHUSDT");
+        assert_ne!(eth.daily_entries, btc.daily_entries);
+        assert_eq!(eth.recorded_prices, "trading_bot:recorded_prices:ETHUSDT");
+    }
+
+    #[test]
+    fn test_single_symbol_config_keeps_the_bare_keys() {
+        let config = Config::for_tests();
+        let keys = SymbolKeys::for_config(&config);
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].primary);
+        assert_eq!(keys[0].symbol, config.symbol);
+        assert_eq!(keys[0].zones, TRADING_BOT_ZONES);
+    }
+
+    #[test]
+    fn test_secondary_symbols_never_trade_the_btc_default_zones() {
+        let btc = SymbolKeys::new("BTCUSDT", true).fallback_zones();
+        assert!(!btc.long_zones.is_empty());
+
+        let eth = SymbolKeys::new("ETHUSDT", false).fallback_zones();
+        assert!(eth.long_zones.is_empty());
+        assert!(eth.short_zones.is_empty());
+    }
+}
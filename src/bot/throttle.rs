@@ -0,0 +1,119 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+/// Global cap on entries per UTC day, independent of zones. Only entries are
+/// counted; managing or closing an open position is never throttled.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DailyEntryThrottle {
+    pub day: NaiveDate,
+    pub count: u32,
+    /// Set once the cap has been logged, so it's reported once per day
+    #[serde(default)]
+    pub announced: bool,
+}
+
+impl DailyEntryThrottle {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            day: now.date_naive(),
+            count: 0,
+            announced: false,
+        }
+    }
+
+    /// Entries taken so far today; a stale day counts as zero (UTC midnight reset)
+    pub fn count_for(&self, now: DateTime<Utc>) -> u32 {
+        if self.day == now.date_naive() {
+            self.count
+        } else {
+            0
+        }
+    }
+
+    /// `max_per_day == 0` disables the throttle
+    pub fn allows_entry(&self, now: DateTime<Utc>, max_per_day: u32) -> bool {
+        max_per_day == 0 || self.count_for(now) < max_per_day
+    }
+
+    pub fn record_entry(&mut self, now: DateTime<Utc>) {
+        if self.day != now.date_naive() {
+            *self = Self::new(now);
+        }
+        self.count += 1;
+    }
+
+    /// Today's count under `key`, one per symbol (`SymbolKeys::daily_entries`)
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection, key: &str) -> Self {
+        let raw: Option<String> = conn.get(key).await.unwrap_or(None);
+        raw.and_then(|r| serde_json::from_str(&r).ok())
+            .unwrap_or_else(|| Self::new(Utc::now()))
+    }
+
+    pub async fn store(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        key: &str,
+    ) -> anyhow::Result<()> {
+        let _: () = conn.set(key, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_entry_past_daily_cap_is_blocked() {
+        let morning = Utc.with_ymd_and_hms(2025, 3, 10, 8, 0, 0).unwrap();
+        let mut throttle = DailyEntryThrottle::new(morning);
+
+        for _ in 0..3 {
+            assert!(throttle.allows_entry(morning, 3));
+            throttle.record_entry(morning);
+        }
+
+        // 4th entry of the day is refused
+        assert!(!throttle.allows_entry(morning, 3));
+    }
+
+    #[test]
+    fn test_cap_is_announced_again_the_next_day() {
+        let late = Utc.with_ymd_and_hms(2025, 3, 10, 23, 59, 0).unwrap();
+        let next_day = Utc.with_ymd_and_hms(2025, 3, 11, 0, 1, 0).unwrap();
+        let mut throttle = DailyEntryThrottle::new(late);
+        throttle.record_entry(late);
+        throttle.announced = true;
+
+        throttle.record_entry(next_day);
+        assert_eq!(throttle.count_for(next_day), 1);
+        assert!(!throttle.announced);
+    }
+
+    #[test]
+    fn test_cap_resets_at_utc_midnight() {
+        let late = Utc.with_ymd_and_hms(2025, 3, 10, 23, 59, 0).unwrap();
+        let next_day = Utc.with_ymd_and_hms(2025, 3, 11, 0, 1, 0).unwrap();
+        let mut throttle = DailyEntryThrottle::new(late);
+        throttle.record_entry(late);
+        throttle.record_entry(late);
+
+        assert!(!throttle.allows_entry(late, 2));
+        assert!(throttle.allows_entry(next_day, 2));
+
+        throttle.record_entry(next_day);
+        assert_eq!(throttle.count_for(next_day), 1);
+    }
+
+    #[test]
+    fn test_zero_cap_disables_throttle() {
+        let now = Utc::now();
+        let mut throttle = DailyEntryThrottle::new(now);
+        for _ in 0..50 {
+            throttle.record_entry(now);
+        }
+        assert!(throttle.allows_entry(now, 0));
+    }
+}
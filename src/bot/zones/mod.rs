@@ -1,10 +1,13 @@
-use log::info;
+use log::{info, warn};
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use std::hash::Hasher;
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::{collections::HashMap, hash::Hash};
 
+use crate::config::ZoneEntryRef;
+use crate::helper::{TRADING_BOT_ZONE_OVERRIDES, TRADING_BOT_ZONE_STATS};
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Side {
     Long,
@@ -19,12 +22,51 @@ pub struct Zone {
 }
 
 impl Zone {
+    /// Zone between two bounds given in either order, so a swapped pair can't leave a
+    /// zone that `contains` nothing
+    pub fn new(low: f64, high: f64, side: Side) -> Self {
+        Self {
+            low: low.min(high),
+            high: low.max(high),
+            side,
+        }
+    }
+
+    /// Long zone hanging `width_pct` below a single support price
+    pub fn below(price: f64, width_pct: f64) -> Self {
+        Self {
+            low: price - (price * width_pct),
+            high: price,
+            side: Side::Long,
+        }
+    }
+
+    /// Short zone sitting `width_pct` above a single resistance price
+    pub fn above(price: f64, width_pct: f64) -> Self {
+        Self {
+            low: price,
+            high: price + (price * width_pct),
+            side: Side::Short,
+        }
+    }
+
     /// Returns true if price lies in the zone
     #[inline]
     pub fn contains(&self, price: f64) -> bool {
         price >= self.low && price <= self.high
     }
 
+    /// Whether the ranger may enter this zone at `price`. Under `CandleClose` the
+    /// latest candle must also have closed inside, so a wick alone isn't enough.
+    pub fn admits_entry(&self, price: f64, last_close: Option<f64>, reference: ZoneEntryRef) -> bool {
+        match reference {
+            ZoneEntryRef::Touch => self.contains(price),
+            ZoneEntryRef::CandleClose => {
+                self.contains(price) && last_close.is_some_and(|close| self.contains(close))
+            }
+        }
+    }
+
     #[inline]
     pub fn midpoint(&self) -> f64 {
         (self.low + self.high) / 2.0
@@ -39,10 +81,64 @@ impl Zone {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredZones")]
 pub struct Zones {
     pub long_zones: Vec<Zone>,
     pub short_zones: Vec<Zone>,
 }
+
+/// Zones as stored in redis. Zones written before `Zone` carried a `side` have none;
+/// it's taken from the list they sit in.
+#[derive(Deserialize)]
+struct StoredZones {
+    long_zones: Vec<StoredZone>,
+    short_zones: Vec<StoredZone>,
+}
+
+#[derive(Deserialize)]
+struct StoredZone {
+    low: f64,
+    high: f64,
+    #[serde(default)]
+    side: Option<Side>,
+}
+
+impl From<StoredZones> for Zones {
+    fn from(stored: StoredZones) -> Self {
+        let with_side = |zones: Vec<StoredZone>, side: Side| {
+            zones
+                .into_iter()
+                .map(|z| Zone {
+                    low: z.low,
+                    high: z.high,
+                    side: z.side.unwrap_or(side),
+                })
+                .collect()
+        };
+        Self {
+            long_zones: with_side(stored.long_zones, Side::Long),
+            short_zones: with_side(stored.short_zones, Side::Short),
+        }
+    }
+}
+
+impl Zones {
+    /// Rejects zones whose bounds are inverted or not numbers
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for zone in self.long_zones.iter().chain(&self.short_zones) {
+            if !(zone.low.is_finite() && zone.high.is_finite()) || zone.low > zone.high {
+                anyhow::bail!(
+                    "{:?} zone {}..{} must have low <= high",
+                    zone.side,
+                    zone.low,
+                    zone.high
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 /**
  * For Zones, add a 1000 difference between a long and short zone.
  */
@@ -50,163 +146,39 @@ impl Default for Zones {
     fn default() -> Self {
         Self {
             long_zones: vec![
-                Zone {
-                    low: 74_306.80,
-                    high: 74_394.80,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 79_981.80,
-                    high: 80_102.80,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 83_991.80,
-                    high: 84_092.80,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 86_401.80,
-                    high: 86_602.80,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 109_018.9,
-                    high: 109_122.8,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 113_293.9,
-                    high: 113_393.8,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 114_548.9,
-                    high: 114_677.8,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 116_344.4,
-                    high: 116_464.4,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 121_100.0,
-                    high: 121_350.0,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 122_400.0,
-                    high: 122_350.0,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 123_100.0,
-                    high: 123_150.0,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 124_600.0,
-                    high: 124_650.0,
-                    side: Side::Long,
-                },
-                Zone {
-                    low: 124_199.0,
-                    high: 125_220.0,
-                    side: Side::Long,
-                },
+                Zone::new(74_306.80, 74_394.80, Side::Long),
+                Zone::new(79_981.80, 80_102.80, Side::Long),
+                Zone::new(83_991.80, 84_092.80, Side::Long),
+                Zone::new(86_401.80, 86_602.80, Side::Long),
+                Zone::new(109_018.9, 109_122.8, Side::Long),
+                Zone::new(113_293.9, 113_393.8, Side::Long),
+                Zone::new(114_548.9, 114_677.8, Side::Long),
+                Zone::new(116_344.4, 116_464.4, Side::Long),
+                Zone::new(121_100.0, 121_350.0, Side::Long),
+                Zone::new(122_350.0, 122_400.0, Side::Long),
+                Zone::new(123_100.0, 123_150.0, Side::Long),
+                Zone::new(124_600.0, 124_650.0, Side::Long),
+                Zone::new(124_199.0, 125_220.0, Side::Long),
             ],
             short_zones: vec![
-                Zone {
-                    low: 125_797.0,
-                    high: 125_897.0,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 125_097.0,
-                    high: 125_197.0,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 124_500.0,
-                    high: 124_540.0,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 123_990.0,
-                    high: 124_032.0,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 122_900.0,
-                    high: 123_000.0,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 120_931.4,
-                    high: 120_170.1,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 119_409.0,
-                    high: 119_479.7,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 117_514.0,
-                    high: 118_008.3,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 115_585.0,
-                    high: 116_085.2,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 114_316.0,
-                    high: 114_486.2,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 112_990.0,
-                    high: 113_100.0,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 108_511.0,
-                    high: 108_646.0,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 104_511.00,
-                    high: 104_596.30,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 98_030.10,
-                    high: 98_079.60,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 93_930.10,
-                    high: 94_079.60,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 92_630.10,
-                    high: 92_679.60,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 89_906.80,
-                    high: 90_008.60,
-                    side: Side::Short,
-                },
-                Zone {
-                    low: 73_906.80,
-                    high: 73_979.60,
-                    side: Side::Short,
-                },
+                Zone::new(125_797.0, 125_897.0, Side::Short),
+                Zone::new(125_097.0, 125_197.0, Side::Short),
+                Zone::new(124_500.0, 124_540.0, Side::Short),
+                Zone::new(123_990.0, 124_032.0, Side::Short),
+                Zone::new(122_900.0, 123_000.0, Side::Short),
+                Zone::new(120_170.1, 120_931.4, Side::Short),
+                Zone::new(119_409.0, 119_479.7, Side::Short),
+                Zone::new(117_514.0, 118_008.3, Side::Short),
+                Zone::new(115_585.0, 116_085.2, Side::Short),
+                Zone::new(114_316.0, 114_486.2, Side::Short),
+                Zone::new(112_990.0, 113_100.0, Side::Short),
+                Zone::new(108_511.0, 108_646.0, Side::Short),
+                Zone::new(104_511.00, 104_596.30, Side::Short),
+                Zone::new(98_030.10, 98_079.60, Side::Short),
+                Zone::new(93_930.10, 94_079.60, Side::Short),
+                Zone::new(92_630.10, 92_679.60, Side::Short),
+                Zone::new(89_906.80, 90_008.60, Side::Short),
+                Zone::new(73_906.80, 73_979.60, Side::Short),
             ],
         }
     }
@@ -235,17 +207,114 @@ impl ZoneId {
     }
 }
 
+/* =======================
+   Zone Overrides
+======================= */
+
+/// Sizing for entries in one zone, replacing the config defaults where set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ZoneOverride {
+    pub leverage: Option<f64>,
+    pub risk_pct: Option<f64>,
+}
+
+impl ZoneOverride {
+    /// `(leverage, risk_pct)` for an entry, each falling back to its default
+    pub fn resolve(zone_override: Option<Self>, leverage: f64, risk_pct: f64) -> (f64, f64) {
+        let zone_override = zone_override.unwrap_or_default();
+        (
+            zone_override.leverage.unwrap_or(leverage),
+            zone_override.risk_pct.unwrap_or(risk_pct),
+        )
+    }
+
+    pub async fn load(
+        conn: &mut redis::aio::MultiplexedConnection,
+        zone_id: ZoneId,
+    ) -> Option<Self> {
+        let raw: Option<String> = conn
+            .hget(TRADING_BOT_ZONE_OVERRIDES, zone_id.0)
+            .await
+            .unwrap_or(None);
+        raw.and_then(|r| serde_json::from_str(&r).ok())
+    }
+
+    pub async fn load_all(
+        conn: &mut redis::aio::MultiplexedConnection,
+    ) -> anyhow::Result<HashMap<ZoneId, Self>> {
+        let raw: HashMap<u64, String> = conn.hgetall(TRADING_BOT_ZONE_OVERRIDES).await?;
+        Ok(raw
+            .into_iter()
+            .filter_map(|(id, r)| Some((ZoneId(id), serde_json::from_str(&r).ok()?)))
+            .collect())
+    }
+
+    /// Sets the override for `zone_id`; one with neither field set removes it
+    pub async fn store(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        zone_id: ZoneId,
+    ) -> anyhow::Result<()> {
+        if *self == Self::default() {
+            let _: () = conn.hdel(TRADING_BOT_ZONE_OVERRIDES, zone_id.0).await?;
+        } else {
+            let _: () = conn
+                .hset(
+                    TRADING_BOT_ZONE_OVERRIDES,
+                    zone_id.0,
+                    serde_json::to_string(self)?,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
 /* =======================
    Zone Guard
 ======================= */
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub struct ZoneStats {
     pub consecutive_losses: u8,
     pub disabled: bool,
     pub cooldown_until: Option<u64>, // unix timestamp
 }
 
+/// How long a zone's stats are kept when it isn't cooling down
+const ZONE_STATS_TTL_SECS: u64 = 60 * 60 * 6;
+
+impl ZoneStats {
+    /// Disabled zones reopen once their cooldown has run out
+    pub fn is_open(&self, now: u64) -> bool {
+        !self.disabled || self.cooldown_until.is_some_and(|until| now >= until)
+    }
+
+    /// Re-enables a disabled zone whose cooldown is over, clearing its losing streak.
+    /// Returns whether it did.
+    pub fn reopen_if_cooled(&mut self, now: u64) -> bool {
+        if self.disabled && self.cooldown_until.is_some_and(|until| now >= until) {
+            *self = Self::default();
+            return true;
+        }
+        false
+    }
+
+    /// Counts a closed trade; `max_losses` in a row disables the zone for `cooldown_secs`
+    pub fn record(&mut self, pnl: f64, now: u64, max_losses: u8, cooldown_secs: u64) {
+        self.reopen_if_cooled(now);
+        if pnl < 0.0 {
+            self.consecutive_losses = self.consecutive_losses.saturating_add(1);
+            if self.consecutive_losses >= max_losses {
+                self.disabled = true;
+                self.cooldown_until = Some(now + cooldown_secs);
+            }
+        } else {
+            self.consecutive_losses = 0;
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ZoneGuard {
     zones: HashMap<ZoneId, ZoneStats>,
@@ -268,6 +337,48 @@ impl ZoneGuard {
         }
     }
 
+    /// Swaps in a new connection after the old one dropped
+    pub fn set_redis_conn(&mut self, conn: redis::aio::MultiplexedConnection) {
+        self.redis_conn = conn;
+    }
+
+    fn stats_key(zone_id: ZoneId) -> String {
+        format!("{TRADING_BOT_ZONE_STATS}::{}", zone_id.0)
+    }
+
+    fn zone_id_from_key(key: &str) -> Option<ZoneId> {
+        key.strip_prefix(TRADING_BOT_ZONE_STATS)?
+            .strip_prefix("::")?
+            .parse()
+            .ok()
+            .map(ZoneId)
+    }
+
+    /// Reads every zone's stored stats into memory, so a restart starts from the
+    /// same disabled zones and cooldowns. Returns how many zones were loaded.
+    pub async fn hydrate(&mut self) -> anyhow::Result<usize> {
+        let pattern = format!("{TRADING_BOT_ZONE_STATS}::*");
+        let keys: Vec<String> = {
+            let mut iter = self.redis_conn.scan_match::<_, String>(pattern).await?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+
+        for key in keys {
+            let Some(zone_id) = Self::zone_id_from_key(&key) else {
+                continue;
+            };
+            let raw: Option<String> = self.redis_conn.get(&key).await?;
+            if let Some(stats) = raw.and_then(|r| serde_json::from_str(&r).ok()) {
+                self.zones.insert(zone_id, stats);
+            }
+        }
+        Ok(self.zones.len())
+    }
+
     fn now() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -275,59 +386,198 @@ impl ZoneGuard {
             .as_secs()
     }
 
-    #[allow(dead_code)]
-    pub fn can_trade(&self, zone_id: ZoneId) -> bool {
-        self.zones
-            .get(&zone_id)
-            .map(|z| !z.disabled)
-            .unwrap_or(true)
+    /// Whether entries may be taken in `zone_id`, off its stored stats
+    pub async fn can_trade(&mut self, zone_id: ZoneId) -> bool {
+        let now = Self::now();
+        let mut stats = self.get_trade_result(zone_id).await;
+        if stats.reopen_if_cooled(now) {
+            info!("Zone {zone_id:?} cooldown is over, trading it again");
+            self.store_stats(zone_id, stats, now).await;
+        }
+        stats.is_open(now)
     }
 
+    /// The zone's stored stats, kept in step with the in-memory copy. If redis can't
+    /// be read the in-memory copy is used, so a disabled zone stays disabled.
     pub async fn get_trade_result(&mut self, zone_id: ZoneId) -> ZoneStats {
-        let key: String = format!("zone_stats::{}", zone_id.0);
-        let stats: String = self.redis_conn.get(key).await.unwrap_or(String::from("{}"));
-        let stats: ZoneStats = serde_json::from_str(&stats).unwrap_or(ZoneStats {
-            consecutive_losses: 0,
-            disabled: false,
-            cooldown_until: None,
-        });
+        let raw: redis::RedisResult<Option<String>> =
+            self.redis_conn.get(Self::stats_key(zone_id)).await;
+        let stats = match raw {
+            Ok(raw) => raw
+                .and_then(|r| serde_json::from_str(&r).ok())
+                .unwrap_or_default(),
+            Err(_) => return self.zones.get(&zone_id).copied().unwrap_or_default(),
+        };
+        self.zones.insert(zone_id, stats);
 
         stats
     }
 
     pub async fn record_trade_result(&mut self, zone_id: ZoneId, pnl: f64) {
-        let stats = self.zones.entry(zone_id).or_default();
+        // Build on the stored stats so a restart doesn't forget the streak
+        let now = Self::now();
+        let mut stats = self.get_trade_result(zone_id).await;
+        stats.record(pnl, now, self.max_losses, self.cooldown_secs);
+        if stats.disabled {
+            info!("Zone {zone_id:?} disabled until {:?}", stats.cooldown_until);
+        }
+        self.store_stats(zone_id, stats, now).await;
+    }
 
-        if pnl < 0.0 {
-            stats.consecutive_losses += 1;
+    /// Writes `stats` to memory and redis, expiring with the cooldown if there is one
+    async fn store_stats(&mut self, zone_id: ZoneId, stats: ZoneStats, now: u64) {
+        self.zones.insert(zone_id, stats);
 
-            if stats.consecutive_losses >= self.max_losses {
-                stats.disabled = true;
-                info!(
-                    "Self::now() + self.cooldown_secs): {:?}",
-                    Self::now() + self.cooldown_secs
-                );
-                stats.cooldown_until = Some(Self::now() + self.cooldown_secs);
-            }
-        } else {
-            stats.consecutive_losses = 0;
-        }
         let zone_expiry = stats
             .cooldown_until
-            .map(|ts| ts.saturating_sub(Self::now()))
-            .unwrap_or(60 * 60 * 6)
-            .try_into()
-            .unwrap();
-        info!("Zone expiry: {zone_expiry}");
-
-        let _: () = self
-            .redis_conn
-            .set_ex(
-                format!("zone_stats::{}", zone_id.0),
-                serde_json::to_string(&stats).unwrap(),
-                zone_expiry,
-            )
-            .await
-            .unwrap();
+            .map(|ts| ts.saturating_sub(now))
+            .filter(|secs| *secs > 0)
+            .unwrap_or(ZONE_STATS_TTL_SECS);
+        let stored = match serde_json::to_string(&stats) {
+            Ok(json) => {
+                self.redis_conn
+                    .set_ex::<_, _, ()>(Self::stats_key(zone_id), json, zone_expiry as usize)
+                    .await
+            }
+            Err(e) => {
+                warn!("Failed to encode zone stats: {e}");
+                return;
+            }
+        };
+        if let Err(e) = stored {
+            warn!("Failed to store zone stats for {zone_id:?}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverted_zones_fail_validation() {
+        let zones = Zones {
+            long_zones: vec![Zone::below(60_000.0, 0.01)],
+            short_zones: vec![Zone {
+                low: 64_000.0,
+                high: 64_500.0,
+                side: Side::Short,
+            }],
+        };
+        assert!(zones.validate().is_ok());
+        assert!(Zones::default().validate().is_ok());
+
+        let swapped = Zone::new(122_400.0, 122_350.0, Side::Long);
+        assert_eq!((swapped.low, swapped.high), (122_350.0, 122_400.0));
+        assert!(swapped.contains(122_375.0));
+    }
+
+    #[test]
+    fn zones_stored_without_a_side_take_it_from_their_list() {
+        let legacy = r#"{"long_zones":[{"low":59400.0,"high":60000.0}],"short_zones":[{"low":64000.0,"high":64500.0}]}"#;
+        let zones: Zones = serde_json::from_str(legacy).unwrap();
+        assert!(matches!(zones.long_zones[0].side, Side::Long));
+        assert!(matches!(zones.short_zones[0].side, Side::Short));
+
+        // a stored side is kept as is
+        let current = Zones {
+            long_zones: vec![Zone::above(64_000.0, 0.01)],
+            short_zones: vec![],
+        };
+        let json = serde_json::to_string(&current).unwrap();
+        let zones: Zones = serde_json::from_str(&json).unwrap();
+        assert!(matches!(zones.long_zones[0].side, Side::Short));
+    }
+
+    #[test]
+    fn wick_into_zone_only_enters_on_touch() {
+        let zone = Zone::below(60_000.0, 0.01); // 59_400..=60_000
+
+        // tick wicks down into the zone but the candle closed back above it
+        assert!(zone.admits_entry(59_800.0, Some(60_250.0), ZoneEntryRef::Touch));
+        assert!(!zone.admits_entry(59_800.0, Some(60_250.0), ZoneEntryRef::CandleClose));
+        assert!(!zone.admits_entry(59_800.0, None, ZoneEntryRef::CandleClose));
+
+        // candle closed inside the zone
+        assert!(zone.admits_entry(59_800.0, Some(59_700.0), ZoneEntryRef::CandleClose));
+    }
+
+    #[test]
+    fn disabled_zone_reopens_after_its_cooldown() {
+        let open = ZoneStats::default();
+        assert!(open.is_open(1_000));
+
+        let cooling = ZoneStats {
+            consecutive_losses: 1,
+            disabled: true,
+            cooldown_until: Some(1_000),
+        };
+        assert!(!cooling.is_open(999));
+        assert!(cooling.is_open(1_000));
+
+        let no_expiry = ZoneStats {
+            cooldown_until: None,
+            ..cooling
+        };
+        assert!(!no_expiry.is_open(u64::MAX));
+    }
+
+    #[test]
+    fn zone_reenables_once_the_clock_passes_its_cooldown() {
+        let mut stats = ZoneStats::default();
+        stats.record(-10.0, 1_000, 2, 3_600);
+        assert!(stats.is_open(1_000));
+        stats.record(-10.0, 1_100, 2, 3_600);
+        assert!(!stats.is_open(1_100));
+        assert_eq!(stats.cooldown_until, Some(4_700));
+
+        assert!(!stats.reopen_if_cooled(4_699));
+        assert!(stats.disabled);
+
+        assert!(stats.reopen_if_cooled(4_700));
+        assert_eq!(stats, ZoneStats::default());
+        assert!(!stats.reopen_if_cooled(4_700));
+
+        // the next loss after reopening starts a new streak
+        stats.record(-10.0, 5_000, 2, 3_600);
+        assert_eq!(stats.consecutive_losses, 1);
+        assert!(!stats.disabled);
+    }
+
+    #[test]
+    fn zone_stats_keys_round_trip_to_their_zone() {
+        let zone_id = ZoneId::from_zone(&Zone::below(60_000.0, 0.01));
+        let key = ZoneGuard::stats_key(zone_id);
+        assert!(key.starts_with("zone_stats::"));
+        assert_eq!(ZoneGuard::zone_id_from_key(&key), Some(zone_id));
+        assert_eq!(ZoneGuard::zone_id_from_key("zone_stats::oops"), None);
+        assert_eq!(ZoneGuard::zone_id_from_key("trading_bot:zones"), None);
+    }
+
+    #[test]
+    fn zone_override_replaces_config_sizing_only_where_set() {
+        let config = crate::config::Config::for_tests();
+        let (leverage, risk_pct) = (config.leverage, config.ranger_risk_pct);
+        let high_conviction = ZoneOverride {
+            leverage: Some(35.0),
+            risk_pct: Some(0.1),
+        };
+        let leverage_only = ZoneOverride {
+            leverage: Some(10.0),
+            risk_pct: None,
+        };
+
+        assert_eq!(
+            ZoneOverride::resolve(Some(high_conviction), leverage, risk_pct),
+            (35.0, 0.1)
+        );
+        assert_eq!(
+            ZoneOverride::resolve(Some(leverage_only), leverage, risk_pct),
+            (10.0, risk_pct)
+        );
+        assert_eq!(
+            ZoneOverride::resolve(None, leverage, risk_pct),
+            (leverage, risk_pct)
+        );
     }
 }
@@ -0,0 +1,120 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Prefix marking a MessagePack value. `0xC1` is never emitted by MessagePack and can't
+/// start JSON (or valid UTF-8), so unprefixed values are always read as legacy JSON.
+const MSGPACK_MAGIC: [u8; 2] = [0xC1, 0x01]; // magic, format version
+
+/// How large Redis values (closed-position lists etc.) are written. Reads detect the
+/// format from the bytes, so switching modes never strands existing data.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Serialization {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl FromStr for Serialization {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Serialization::Json),
+            "msgpack" | "messagepack" => Ok(Serialization::MessagePack),
+            other => Err(anyhow!(
+                "Unknown serialization '{}': expected 'json' or 'msgpack'",
+                other
+            )),
+        }
+    }
+}
+
+pub fn encode<T: Serialize>(value: &T, format: Serialization) -> Result<Vec<u8>> {
+    match format {
+        Serialization::Json => Ok(serde_json::to_vec(value)?),
+        Serialization::MessagePack => {
+            let mut out = MSGPACK_MAGIC.to_vec();
+            // named fields so `#[serde(default)]` additions keep old records readable
+            out.extend(rmp_serde::to_vec_named(value)?);
+            Ok(out)
+        }
+    }
+}
+
+pub fn decode<T: DeserializeOwned>(raw: &[u8]) -> Result<T> {
+    match raw.strip_prefix(&MSGPACK_MAGIC[..1]) {
+        Some([version, body @ ..]) if *version == MSGPACK_MAGIC[1] => {
+            Ok(rmp_serde::from_slice(body)?)
+        }
+        Some(_) => Err(anyhow!("Unsupported MessagePack version prefix")),
+        None => Ok(serde_json::from_slice(raw)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::{ClosedPosition, Position};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn closed(n: u128) -> ClosedPosition {
+        ClosedPosition {
+            id: Uuid::from_u128(n),
+            pnl: dec!(12.34),
+            position: Some(Position::Long),
+            side: Some(Position::Long),
+            entry_price: dec!(100000.5),
+            entry_time: Utc.timestamp_millis_opt(1_700_000_000_123).unwrap(),
+            exit_price: dec!(101234.5),
+            exit_time: Utc.timestamp_millis_opt(1_700_003_600_456).unwrap(),
+            quantity: Some(dec!(0.01)),
+            sl: Some(dec!(99000)),
+            roi: Some(dec!(24.68)),
+            leverage: Some(dec!(20)),
+            margin: Some(dec!(50)),
+            order_id: Some("abc".to_string()),
+            pnl_after_fees: None,
+            exit_fee: Some(dec!(0.61)),
+            entry_fee: None,
+            funding_fee: None,
+            max_adverse_excursion: Some(dec!(-3.2)),
+            max_favorable_excursion: None,
+            zone_id: None,
+            symbol: None,
+        }
+    }
+
+    fn round_trip(format: Serialization) {
+        let list: Vec<ClosedPosition> = (1..=3).map(closed).collect();
+
+        let raw = encode(&list, format).unwrap();
+        let back: Vec<ClosedPosition> = decode(&raw).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&back).unwrap(),
+            serde_json::to_value(&list).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_closed_positions_round_trip_as_json() {
+        round_trip(Serialization::Json);
+    }
+
+    #[test]
+    fn test_closed_positions_round_trip_as_msgpack() {
+        round_trip(Serialization::MessagePack);
+    }
+
+    #[test]
+    fn test_legacy_json_still_decodes() {
+        let legacy = serde_json::to_string(&closed(7)).unwrap();
+        let back: ClosedPosition = decode(legacy.as_bytes()).unwrap();
+        assert_eq!(back.id, Uuid::from_u128(7));
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
+use redis::aio::MultiplexedConnection;
+
+/// How long a PING may take before redis counts as down
+pub const PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long a successful PING vouches for the connection, so a fast price feed
+/// doesn't add a round-trip to every tick
+pub const PING_INTERVAL_SECS: i64 = 5;
+
+/// Same backoff `RedisClient::connect` uses at startup
+const BASE_BACKOFF_SECS: i64 = 1;
+const MAX_BACKOFF_SECS: i64 = 32;
+
+/// PINGs redis, failing on an error or once `PING_TIMEOUT` passes
+pub async fn ping(conn: &mut MultiplexedConnection) -> anyhow::Result<()> {
+    let cmd = redis::cmd("PING");
+    let ping = cmd.query_async::<_, String>(conn);
+    match tokio::time::timeout(PING_TIMEOUT, ping).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(anyhow::anyhow!("Redis PING failed: {e}")),
+        Err(_) => Err(anyhow::anyhow!("Redis PING timed out")),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedisState {
+    Up,
+    /// Unreachable since `since`; the next reconnect is tried at `retry_at`
+    Down {
+        since: DateTime<Utc>,
+        retry_at: DateTime<Utc>,
+        backoff_secs: i64,
+    },
+}
+
+/// Tracks whether the bot's redis connection is usable, spacing reconnect attempts
+/// out with a doubling backoff while it isn't.
+#[derive(Debug, Clone)]
+pub struct RedisHealth {
+    pub state: RedisState,
+    /// When redis last answered a PING
+    pub last_ping: Option<DateTime<Utc>>,
+}
+
+impl Default for RedisHealth {
+    fn default() -> Self {
+        Self {
+            state: RedisState::Up,
+            last_ping: None,
+        }
+    }
+}
+
+impl RedisHealth {
+    /// Records a failed PING or reconnect; each repeat doubles the wait, capped
+    pub fn observe_failure(&mut self, err: &anyhow::Error, now: DateTime<Utc>) {
+        let (since, backoff_secs) = match self.state {
+            RedisState::Up => {
+                error!("REDIS DOWN: {err}, skipping cycles until it's back");
+                (now, BASE_BACKOFF_SECS)
+            }
+            RedisState::Down {
+                since,
+                backoff_secs,
+                ..
+            } => {
+                let backoff_secs = (backoff_secs * 2).min(MAX_BACKOFF_SECS);
+                warn!("Redis still unavailable: {err}, retrying in {backoff_secs}s");
+                (since, backoff_secs)
+            }
+        };
+        self.state = RedisState::Down {
+            since,
+            retry_at: now + Duration::seconds(backoff_secs),
+            backoff_secs,
+        };
+        self.last_ping = None;
+    }
+
+    pub fn observe_success(&mut self, now: DateTime<Utc>) {
+        if let RedisState::Down { since, .. } = self.state {
+            info!("Redis reconnected after {}s", (now - since).num_seconds());
+            self.state = RedisState::Up;
+        }
+        self.last_ping = Some(now);
+    }
+
+    /// A command failed between PINGs; check the connection again next cycle
+    pub fn suspect(&mut self) {
+        self.last_ping = None;
+    }
+
+    /// Whether to PING before this cycle: always while down, otherwise once the last
+    /// successful PING is `PING_INTERVAL_SECS` old
+    pub fn ping_due(&self, now: DateTime<Utc>) -> bool {
+        match (self.state, self.last_ping) {
+            (RedisState::Up, Some(at)) => now - at >= Duration::seconds(PING_INTERVAL_SECS),
+            _ => true,
+        }
+    }
+
+    /// Whether a reconnect is due; always true while up
+    pub fn should_retry(&self, now: DateTime<Utc>) -> bool {
+        match self.state {
+            RedisState::Up => true,
+            RedisState::Down { retry_at, .. } => now >= retry_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnects_back_off_until_redis_answers() {
+        let now = Utc::now();
+        let err = anyhow::anyhow!("connection refused");
+        let mut health = RedisHealth::default();
+        assert!(health.should_retry(now));
+
+        health.observe_failure(&err, now);
+        assert!(!health.should_retry(now));
+        assert!(health.should_retry(now + Duration::seconds(1)));
+
+        // repeat failures double the wait, up to the cap
+        for _ in 0..10 {
+            health.observe_failure(&err, now);
+        }
+        assert!(!health.should_retry(now + Duration::seconds(MAX_BACKOFF_SECS - 1)));
+        assert!(health.should_retry(now + Duration::seconds(MAX_BACKOFF_SECS)));
+        assert!(matches!(health.state, RedisState::Down { since, .. } if since == now));
+
+        health.observe_success(now);
+        assert_eq!(health.state, RedisState::Up);
+    }
+
+    #[test]
+    fn test_pings_are_spaced_out_while_redis_is_up() {
+        let now = Utc::now();
+        let mut health = RedisHealth::default();
+        assert!(health.ping_due(now));
+
+        health.observe_success(now);
+        assert!(!health.ping_due(now + Duration::seconds(PING_INTERVAL_SECS - 1)));
+        assert!(health.ping_due(now + Duration::seconds(PING_INTERVAL_SECS)));
+
+        // a failed load in between forces the next cycle to check
+        health.suspect();
+        assert!(health.ping_due(now));
+    }
+}
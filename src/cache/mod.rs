@@ -1,3 +1,6 @@
+pub mod codec;
+pub mod health;
+
 use redis::aio::MultiplexedConnection;
 use redis::{Client, RedisError};
 
@@ -37,6 +40,11 @@ impl RedisClient {
         }
     }
 
+    /// A single attempt at a fresh connection, for replacing one that has dropped
+    pub async fn reconnect(url: &str) -> Result<MultiplexedConnection, RedisError> {
+        Client::open(url)?.get_multiplexed_async_connection().await
+    }
+
     #[inline]
     pub fn get_multiplexed_connection(&self) -> MultiplexedConnection {
         self.conn.clone()
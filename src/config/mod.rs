@@ -1,11 +1,20 @@
 use anyhow::anyhow;
 use anyhow::Ok;
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::env;
 use std::str::FromStr;
 
+use chrono_tz::Tz;
 use serde::Deserialize;
 
+use crate::cache::codec::Serialization;
+use crate::exchange::bitget::accounts::{AccountCreds, AccountRouting};
+use crate::exchange::bitget::maintenance::{parse_maintenance_codes, DEFAULT_MAINTENANCE_CODES};
+use crate::helper::Helper;
+
+pub mod runtime;
+
 #[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ExchangeType {
@@ -28,16 +37,92 @@ impl FromStr for ExchangeType {
     }
 }
 
+/// Which price the bot's stop-loss check compares against; match it to the trigger
+/// type configured on the exchange so both agree on when a stop is hit.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StopTriggerPrice {
+    Mark,
+    Last,
+    Index,
+}
+
+impl FromStr for StopTriggerPrice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "mark" => Ok(StopTriggerPrice::Mark),
+            "last" => Ok(StopTriggerPrice::Last),
+            "index" => Ok(StopTriggerPrice::Index),
+            other => Err(anyhow!(
+                "Unknown stop trigger '{}': expected 'mark', 'last' or 'index'",
+                other
+            )),
+        }
+    }
+}
+
+/// Where the weekly Ichimoku loop gets its candles: the Kaggle 1-minute dump
+/// (deep history, slow) or Bitget's 1W candles (fast, shorter history).
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IchimokuSource {
+    Kaggle,
+    Bitget,
+}
+
+impl FromStr for IchimokuSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "kaggle" => Ok(IchimokuSource::Kaggle),
+            "bitget" => Ok(IchimokuSource::Bitget),
+            other => Err(anyhow!(
+                "Unknown ichimoku source '{}': expected 'kaggle' or 'bitget'",
+                other
+            )),
+        }
+    }
+}
+
+/// Which price must sit inside a zone before the ranger enters: any polled tick
+/// (`touch`) or the latest 5m candle close as well (`candle_close`), so a wick
+/// that pokes into a zone and reverses doesn't trigger an entry.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneEntryRef {
+    Touch,
+    CandleClose,
+}
+
+impl FromStr for ZoneEntryRef {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "touch" => Ok(ZoneEntryRef::Touch),
+            "candle_close" => Ok(ZoneEntryRef::CandleClose),
+            other => Err(anyhow!(
+                "Unknown zone entry reference '{}': expected 'touch' or 'candle_close'",
+                other
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
-    /// API key / secret pair for your broker
-    pub api_key: String,
-    pub api_secret: String,
-    pub passphrase: String,
-
     /// Trading symbol (e.g. BTCUSDT)
     pub symbol: String,
 
+    /// Every symbol the ranger trades, `symbol` first (SYMBOLS, comma-separated)
+    pub symbols: Vec<String>,
+
+    /// Order-size decimals per symbol (SYMBOL_SIZE_PRECISION, e.g. `ETHUSDT:2,SOLUSDT:1`)
+    pub size_precisions: BTreeMap<String, u32>,
+
     /// Polling interval in seconds
     #[allow(dead_code)]
     pub poll_interval_secs: u64,
@@ -61,9 +146,12 @@ pub struct Config {
     pub use_smc_indicator: bool,
     pub use_ichimoku_indicator: bool,
 
-    pub smc_zone_multiplier: f64,
+    /// Zone width as a fraction of the pivot price (0.0001–0.01)
+    pub smc_zone_width_pct: f64,
     pub smc_min_distance: f64,
     pub smc_loop_interval: u64,
+    /// Cap on zones kept per side once fresh zones are merged with stored ones
+    pub smc_max_zones: usize,
 
     /// Exchange selector
     pub exchange: ExchangeType,
@@ -73,6 +161,126 @@ pub struct Config {
     pub bitunix_api_secret: String,
     pub bitunix_maker_fee: f64,
     pub bitunix_taker_fee: f64,
+
+    /// Skip ranger entries into stretched RSI (longs above max, shorts below min)
+    pub use_rsi_filter: bool,
+    pub max_entry_rsi: f64,
+    pub min_entry_rsi: f64,
+    /// Net price distance TP1 must clear on top of round-trip fees
+    pub min_net_tp1_distance: f64,
+    /// Share of the position each partial-profit target closes, TP1 first; one target
+    /// per entry and summing to 1.0
+    pub profit_target_fractions: Vec<f64>,
+    /// Append every cycle's (timestamp, price) to a bounded Redis list per symbol, for replay
+    pub record_prices: bool,
+    pub record_prices_max_len: isize,
+    /// Close orders allowed per cycle when several targets are crossed at once
+    pub max_partials_per_cycle: usize,
+    /// Rescale the remaining partial-target fractions to sum to 1.0 after targets are removed
+    pub renormalize_partial_fractions: bool,
+    /// Entry pause after a Bitget maintenance error, doubled on repeats up to the max
+    pub maintenance_backoff_secs: i64,
+    pub maintenance_max_backoff_secs: i64,
+    /// Bitget response codes that mean the venue is down for maintenance
+    pub maintenance_codes: Vec<String>,
+    /// Relative share of TRADING_CAPITAL each strategy sizes off
+    pub ranger_capital_weight: f64,
+    pub scalper_capital_weight: f64,
+    pub capitulation_capital_weight: f64,
+    /// Run the scalper alongside the ranger, sizing off its `SCALPER_CAPITAL_WEIGHT` slice
+    pub enable_scalper: bool,
+    /// Price distance from entry at which the scalper takes profit
+    pub scalper_tp_distance: f64,
+    /// How often the scalper polls the price, in seconds
+    pub scalper_poll_secs: u64,
+    /// Scale the scalper out over `PROFIT_TARGET_FRACTIONS` up to its take profit,
+    /// instead of closing it all there (default)
+    pub scalper_partial_targets: bool,
+    /// Seconds the scalper waits after an exit before it enters again
+    pub scalper_reentry_cooldown_secs: i64,
+    /// Run the 5m momentum tracker off live Bitget candles
+    pub use_momentum_tracker: bool,
+    /// Cap on entries per UTC day across all zones (0 = unlimited)
+    pub max_trades_per_day: u32,
+    /// Only enter when zone, latest SMC event and momentum all agree
+    pub require_confluence: bool,
+    /// Place the initial stop `atr_stop_multiple` ATRs from entry instead of risk_pct
+    pub use_atr_stop: bool,
+    pub atr_stop_multiple: f64,
+    /// Size entries so one ATR of movement costs `vol_target_risk` of the margin,
+    /// never more than margin × leverage allows
+    pub use_vol_sizing: bool,
+    pub vol_target_risk: f64,
+    /// Deterministic position / client order ids from this seed (tests, replays)
+    pub id_seed: Option<u64>,
+    /// Skip entries against strongly opposed 5m momentum (no-op without a snapshot)
+    pub use_momentum_filter: bool,
+    pub stop_trigger_price: StopTriggerPrice,
+    /// Ichimoku data refresh period; weekly by default, shorten for testing
+    pub ichimoku_interval_secs: u64,
+    /// Encoding for large Redis values (`json` | `msgpack`); reads accept either
+    pub serialization: Serialization,
+    /// Longs only above the weekly Ichimoku cloud, shorts only below it
+    pub use_kumo_filter: bool,
+    /// Longs only off a bullish weekly Tenkan/Kijun bias, shorts only off a bearish one
+    pub use_weekly_bias_filter: bool,
+    /// Candle source for the weekly Ichimoku (`kaggle` | `bitget`)
+    pub ichimoku_source: IchimokuSource,
+    /// Zone entry reference (`touch` | `candle_close`); `candle_close` needs the momentum tracker
+    pub zone_entry_ref: ZoneEntryRef,
+    /// Remote economic calendar (same JSON shape as data/calendar_data.json); unset keeps the file
+    pub calendar_url: Option<String>,
+    pub calendar_refresh_secs: u64,
+    /// Timezone the calendar quotes times in (e.g. America/New_York); unset maps each event's zone
+    pub calendar_tz: Option<Tz>,
+    /// Journal each entry before its order so a crash mid-entry is reconciled on restart
+    pub journal_entries: bool,
+    /// How often the macro no-trade windows are rebuilt from the cached calendar
+    pub macro_guard_refresh_secs: i64,
+    /// Refuse to act on a price older than this (0 disables); optionally flatten when it trips
+    pub max_price_staleness_secs: i64,
+    pub flatten_on_stale_price: bool,
+    /// Bitget API credentials: main account (API_KEY / API_SECRET / ACCESS_PASSPHRASE)
+    /// first, then BITGET_SUB_ACCOUNTS; entries are routed across them
+    pub accounts: Vec<AccountCreds>,
+    pub account_routing: AccountRouting,
+    /// Calendar countries whose critical events pause entries (comma-separated MACRO_COUNTRIES)
+    pub macro_countries: Vec<String>,
+    /// Check the exchange accepts the API key / passphrase before trading starts
+    pub verify_credentials_on_boot: bool,
+    /// Opposing signals (momentum, SMC, Ichimoku cross) that close a winning position early; 0 disables
+    pub ensemble_exit_threshold: u8,
+    /// Log zone entries stopped by a guard or the exchange to `trading_bot:missed_entries`
+    pub record_missed_entries: bool,
+    /// Cap on stored missed entries (newest kept); 0 keeps everything
+    pub missed_entries_max: usize,
+    /// Cap on the closed-positions list (newest kept); 0 keeps everything
+    pub max_closed_history: usize,
+    /// Share of the final target left open as a runner (0–1); 0 closes fully at the last target
+    pub runner_fraction: f64,
+    /// Runner trailing-stop distance, in percent of price
+    pub runner_trail_pct: f64,
+    /// Enter on a post-only limit order resting inside the next zone instead of at market
+    pub use_limit_entries: bool,
+    /// How far into the zone the limit order rests (0 = near edge, 1 = far edge)
+    pub limit_entry_depth: f64,
+    /// Write each tracker's last success / last error to redis for `/api/trackers/status`
+    pub tracker_health: bool,
+    /// Write a filled entry's position, open position and targets in one redis transaction
+    pub atomic_entry: bool,
+    /// Keep a stop moved by a partial target at least this far behind price, in percent
+    /// of price (0 applies the target's stop as is)
+    pub partial_sl_buffer_pct: f64,
+    /// Bearer token the API requires on `/api/*`; unset leaves the API open but read-only
+    pub api_token: Option<String>,
+    /// Start trailing the stop once price is this far past entry, in percent (0 disables)
+    pub trail_activate_pct: f64,
+    /// How far behind price the trailing stop follows, in price units
+    pub trail_distance: f64,
+    /// Realized loss per UTC day, in USDT, after which no new entries are taken (0 = off)
+    pub max_daily_loss: f64,
+    /// Close any open position at market on SIGTERM / Ctrl-C before exiting
+    pub flatten_on_exit: bool,
 }
 
 #[allow(dead_code)]
@@ -80,7 +288,96 @@ fn default_interval() -> u64 {
     5
 }
 
+/// Order-size decimals for a symbol not listed in SYMBOL_SIZE_PRECISION (BTC's)
+const DEFAULT_SIZE_PRECISION: u32 = 5;
+
+/// SYMBOL_SIZE_PRECISION as `SYMBOL:decimals` pairs, comma-separated
+fn size_precisions(raw: &str) -> Result<BTreeMap<String, u32>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let invalid = || anyhow!("Invalid size precision '{pair}': expected SYMBOL:decimals");
+            let (symbol, decimals) = pair.split_once(':').ok_or_else(invalid)?;
+            let decimals = decimals.trim().parse::<u32>().map_err(|_| invalid())?;
+            Ok((symbol.trim().to_uppercase(), decimals))
+        })
+        .collect()
+}
+
+/// The ranger's original partial-profit split, `Helper::DEFAULT_TARGET_FRACTIONS`
+fn default_profit_target_fractions() -> Vec<f64> {
+    Helper::DEFAULT_TARGET_FRACTIONS
+        .iter()
+        .map(|f| Helper::decimal_to_f64(*f))
+        .collect()
+}
+
+/// PROFIT_TARGET_FRACTIONS as a comma-separated list, else PROFIT_TARGET_COUNT equal
+/// shares, else the default split. Whatever is chosen has to sum to 1.0.
+fn profit_target_fractions(raw: Option<String>, count: Option<usize>) -> Result<Vec<f64>> {
+    let fractions: Vec<f64> = match (raw, count) {
+        (Some(raw), _) => raw
+            .split(',')
+            .map(|f| {
+                f.trim()
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("Invalid profit target fraction '{}'", f.trim()))
+            })
+            .collect::<Result<_>>()?,
+        (None, Some(count)) if count > 0 => vec![1.0 / count as f64; count],
+        _ => default_profit_target_fractions(),
+    };
+
+    if fractions.is_empty() || fractions.iter().any(|f| !(f.is_finite() && *f > 0.0)) {
+        return Err(anyhow!(
+            "Profit target fractions must all be greater than 0: {fractions:?}"
+        ));
+    }
+    let sum: f64 = fractions.iter().sum();
+    if (sum - 1.0).abs() > 1e-6 {
+        return Err(anyhow!(
+            "Profit target fractions must sum to 1.0, got {sum}: {fractions:?}"
+        ));
+    }
+    Ok(fractions)
+}
+
 impl Config {
+    /// How many partial-profit targets an entry is split into
+    pub fn profit_target_count(&self) -> usize {
+        self.profit_target_fractions.len()
+    }
+
+    /// Decimals `symbol`'s order sizes are rounded to
+    pub fn size_precision(&self, symbol: &str) -> u32 {
+        self.size_precisions
+            .get(symbol)
+            .copied()
+            .unwrap_or(DEFAULT_SIZE_PRECISION)
+    }
+
+    /// `from_env` with placeholders for the required variables that aren't set,
+    /// so tests get the same defaults as a bare deployment.
+    #[cfg(test)]
+    pub fn for_tests() -> Config {
+        for (key, value) in [
+            ("API_KEY", "key"),
+            ("API_SECRET", "secret"),
+            ("ACCESS_PASSPHRASE", "pass"),
+            ("REDIS_URL", "redis://127.0.0.1/"),
+            ("USE_SMC_INDICATOR", "false"),
+            ("USE_ICHIMOKU_INDICATOR", "false"),
+            ("BITUNIX_API_KEY", "key"),
+            ("BITUNIX_API_SECRET", "secret"),
+        ] {
+            if env::var(key).is_err() {
+                env::set_var(key, value);
+            }
+        }
+        Config::from_env().unwrap()
+    }
+
     /// Load from environment variables (dotenv recommended)
     pub fn from_env() -> Result<Self> {
         dotenv::dotenv().ok();
@@ -93,6 +390,17 @@ impl Config {
 
         let symbol = env::var("SYMBOL").unwrap_or_else(|_| "BTCUSDT".into());
 
+        let mut symbols: Vec<String> = env::var("SYMBOLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty() && *s != symbol)
+            .collect();
+        symbols.insert(0, symbol.clone());
+
+        let size_precisions =
+            size_precisions(&env::var("SYMBOL_SIZE_PRECISION").unwrap_or_default())?;
+
         let poll_interval_secs: u64 = env::var("POLL_INTERVAL_SECS")
             .ok()
             .and_then(|v| v.parse::<u64>().ok())
@@ -149,11 +457,19 @@ impl Config {
             .parse::<bool>()
             .map_err(|_| anyhow!("USE_ICHIMOKU_INDICATOR must be 'true' or 'false'"))?;
 
-        let smc_zone_multiplier = env::var("SMC_ZONE_MULTIPLIER")
+        // SMC_ZONE_MULTIPLIER is the old name, still honoured
+        let smc_zone_width_pct = env::var("SMC_ZONE_WIDTH_PCT")
+            .or_else(|_| env::var("SMC_ZONE_MULTIPLIER"))
             .ok()
             .and_then(|v| v.parse::<f64>().ok())
             .unwrap_or(0.00075);
 
+        if !(0.0001..=0.01).contains(&smc_zone_width_pct) {
+            return Err(anyhow!(
+                "SMC_ZONE_WIDTH_PCT must be between 0.0001 and 0.01, got {smc_zone_width_pct}"
+            ));
+        }
+
         let smc_min_distance = env::var("SMC_MIN_DISTANCE")
             .ok()
             .and_then(|v| v.parse::<f64>().ok())
@@ -164,6 +480,11 @@ impl Config {
             .and_then(|v| v.parse::<u64>().ok())
             .unwrap_or(1800);
 
+        let smc_max_zones = env::var("SMC_MAX_ZONES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(12);
+
         let exchange = env::var("EXCHANGE")
             .unwrap_or_else(|_| "bitget".into())
             .parse::<ExchangeType>()
@@ -182,11 +503,338 @@ impl Config {
             .and_then(|v| v.parse::<f64>().ok())
             .unwrap_or(0.0005);
 
-        Ok(Config {
+        let use_rsi_filter = env::var("USE_RSI_FILTER")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let max_entry_rsi = env::var("MAX_ENTRY_RSI")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(70.0);
+
+        let min_entry_rsi = env::var("MIN_ENTRY_RSI")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(30.0);
+
+        let min_net_tp1_distance = env::var("MIN_NET_TP1_DISTANCE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(50.0);
+
+        let profit_target_fractions = profit_target_fractions(
+            env::var("PROFIT_TARGET_FRACTIONS").ok(),
+            env::var("PROFIT_TARGET_COUNT")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok()),
+        )?;
+
+        let record_prices = env::var("RECORD_PRICES")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let record_prices_max_len = env::var("RECORD_PRICES_MAX_LEN")
+            .ok()
+            .and_then(|v| v.parse::<isize>().ok())
+            .unwrap_or(100_000);
+
+        if record_prices_max_len <= 0 {
+            return Err(anyhow!(
+                "RECORD_PRICES_MAX_LEN must be greater than 0, got {record_prices_max_len}"
+            ));
+        }
+
+        let max_partials_per_cycle = env::var("MAX_PARTIALS_PER_CYCLE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(1);
+
+        let renormalize_partial_fractions = env::var("RENORMALIZE_PARTIAL_FRACTIONS")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let maintenance_backoff_secs = env::var("MAINTENANCE_BACKOFF_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(60);
+
+        let maintenance_max_backoff_secs = env::var("MAINTENANCE_MAX_BACKOFF_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(1800);
+
+        let maintenance_codes = parse_maintenance_codes(
+            &env::var("BITGET_MAINTENANCE_CODES")
+                .unwrap_or_else(|_| DEFAULT_MAINTENANCE_CODES.to_string()),
+        );
+
+        let ranger_capital_weight = env::var("RANGER_CAPITAL_WEIGHT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(1.0);
+
+        let scalper_capital_weight = env::var("SCALPER_CAPITAL_WEIGHT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        let capitulation_capital_weight = env::var("CAPITULATION_CAPITAL_WEIGHT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        let enable_scalper = env::var("ENABLE_SCALPER")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let scalper_tp_distance = env::var("SCALPER_TP_DISTANCE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(400.0);
+
+        let scalper_poll_secs = env::var("SCALPER_POLL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5);
+
+        let scalper_partial_targets = env::var("SCALPER_PARTIAL_TARGETS")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let scalper_reentry_cooldown_secs = env::var("SCALPER_REENTRY_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(300);
+
+        let use_momentum_tracker = env::var("USE_MOMENTUM_TRACKER")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let max_trades_per_day = env::var("MAX_TRADES_PER_DAY")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(0);
+
+        let require_confluence = env::var("REQUIRE_CONFLUENCE")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let use_atr_stop = env::var("USE_ATR_STOP")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let atr_stop_multiple = env::var("ATR_STOP_MULTIPLE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(1.5);
+
+        let use_vol_sizing = env::var("USE_VOL_SIZING")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let vol_target_risk = env::var("VOL_TARGET_RISK")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.02);
+
+        let id_seed = env::var("ID_SEED")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok());
+
+        let use_momentum_filter = env::var("USE_MOMENTUM_FILTER")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let stop_trigger_price = env::var("STOP_TRIGGER_PRICE")
+            .unwrap_or_else(|_| "last".into())
+            .parse::<StopTriggerPrice>()
+            .map_err(|e| anyhow!("Invalid STOP_TRIGGER_PRICE value: {}", e))?;
+
+        let ichimoku_interval_secs = env::var("ICHIMOKU_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(604800);
+
+        let serialization = env::var("SERIALIZATION")
+            .unwrap_or_else(|_| "json".into())
+            .parse::<Serialization>()
+            .map_err(|e| anyhow!("Invalid SERIALIZATION value: {}", e))?;
+
+        let use_kumo_filter = env::var("USE_KUMO_FILTER")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let use_weekly_bias_filter = env::var("USE_WEEKLY_BIAS_FILTER")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let ichimoku_source = env::var("ICHIMOKU_SOURCE")
+            .unwrap_or_else(|_| "kaggle".into())
+            .parse::<IchimokuSource>()
+            .map_err(|e| anyhow!("Invalid ICHIMOKU_SOURCE value: {}", e))?;
+
+        let zone_entry_ref = env::var("ZONE_ENTRY_REF")
+            .unwrap_or_else(|_| "touch".into())
+            .parse::<ZoneEntryRef>()
+            .map_err(|e| anyhow!("Invalid ZONE_ENTRY_REF value: {}", e))?;
+
+        let calendar_url = env::var("CALENDAR_URL").ok().filter(|v| !v.is_empty());
+
+        let calendar_refresh_secs = env::var("CALENDAR_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(86400);
+
+        let calendar_tz = env::var("CALENDAR_TZ")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse::<Tz>())
+            .transpose()
+            .map_err(|e| anyhow!("Invalid CALENDAR_TZ value: {}", e))?;
+
+        let journal_entries = env::var("JOURNAL_ENTRIES")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        let macro_guard_refresh_secs = env::var("MACRO_GUARD_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(3600);
+
+        let max_price_staleness_secs = env::var("MAX_PRICE_STALENESS_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(0);
+
+        let flatten_on_stale_price = env::var("FLATTEN_ON_STALE_PRICE")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let mut accounts = vec![AccountCreds {
+            name: "main".to_string(),
             api_key,
             api_secret,
             passphrase,
+            strategy: None,
+        }];
+        if let std::result::Result::Ok(raw) = env::var("BITGET_SUB_ACCOUNTS") {
+            let subs: Vec<AccountCreds> = serde_json::from_str(&raw)
+                .map_err(|e| anyhow!("Invalid BITGET_SUB_ACCOUNTS value: {}", e))?;
+            accounts.extend(subs);
+        }
+
+        let account_routing = env::var("ACCOUNT_ROUTING")
+            .unwrap_or_else(|_| "round_robin".into())
+            .parse::<AccountRouting>()
+            .map_err(|e| anyhow!("Invalid ACCOUNT_ROUTING value: {}", e))?;
+
+        let macro_countries: Vec<String> = env::var("MACRO_COUNTRIES")
+            .unwrap_or_else(|_| "united states".into())
+            .split(',')
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+            .collect();
+
+        let verify_credentials_on_boot = env::var("VERIFY_CREDENTIALS_ON_BOOT")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        let ensemble_exit_threshold = env::var("ENSEMBLE_EXIT_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<u8>().ok())
+            .unwrap_or(0);
+
+        let record_missed_entries = env::var("RECORD_MISSED_ENTRIES")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        let missed_entries_max = env::var("MISSED_ENTRIES_MAX")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(1000);
+
+        let max_closed_history = env::var("MAX_CLOSED_HISTORY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(10_000);
+
+        let runner_fraction = env::var("RUNNER_FRACTION")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        let runner_trail_pct = env::var("RUNNER_TRAIL_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.5);
+
+        let use_limit_entries = env::var("USE_LIMIT_ENTRIES")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let limit_entry_depth = env::var("LIMIT_ENTRY_DEPTH")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.5);
+
+        let tracker_health = env::var("TRACKER_HEALTH")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        let atomic_entry = env::var("ATOMIC_ENTRY")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        let partial_sl_buffer_pct = env::var("PARTIAL_SL_BUFFER_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.1);
+
+        let api_token = env::var("API_TOKEN").ok().filter(|v| !v.is_empty());
+
+        let trail_activate_pct = env::var("TRAIL_ACTIVATE_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        let trail_distance = env::var("TRAIL_DISTANCE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(250.0);
+
+        let max_daily_loss = env::var("MAX_DAILY_LOSS")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        let flatten_on_exit = env::var("FLATTEN_ON_EXIT")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        Ok(Config {
             symbol,
+            symbols,
+            size_precisions,
             poll_interval_secs,
             redis_url,
             margin,
@@ -200,14 +848,117 @@ impl Config {
             smc_candle_count,
             use_smc_indicator,
             use_ichimoku_indicator,
-            smc_zone_multiplier,
+            smc_zone_width_pct,
             smc_min_distance,
             smc_loop_interval,
+            smc_max_zones,
             exchange,
             bitunix_api_key,
             bitunix_api_secret,
             bitunix_maker_fee,
             bitunix_taker_fee,
+            use_rsi_filter,
+            max_entry_rsi,
+            min_entry_rsi,
+            min_net_tp1_distance,
+            profit_target_fractions,
+            record_prices,
+            record_prices_max_len,
+            max_partials_per_cycle,
+            renormalize_partial_fractions,
+            maintenance_backoff_secs,
+            maintenance_max_backoff_secs,
+            maintenance_codes,
+            ranger_capital_weight,
+            scalper_capital_weight,
+            capitulation_capital_weight,
+            enable_scalper,
+            scalper_tp_distance,
+            scalper_poll_secs,
+            scalper_partial_targets,
+            scalper_reentry_cooldown_secs,
+            use_momentum_tracker,
+            max_trades_per_day,
+            require_confluence,
+            use_atr_stop,
+            atr_stop_multiple,
+            use_vol_sizing,
+            vol_target_risk,
+            id_seed,
+            use_momentum_filter,
+            stop_trigger_price,
+            ichimoku_interval_secs,
+            serialization,
+            use_kumo_filter,
+            use_weekly_bias_filter,
+            ichimoku_source,
+            zone_entry_ref,
+            calendar_url,
+            calendar_refresh_secs,
+            calendar_tz,
+            journal_entries,
+            macro_guard_refresh_secs,
+            max_price_staleness_secs,
+            flatten_on_stale_price,
+            accounts,
+            account_routing,
+            macro_countries,
+            verify_credentials_on_boot,
+            ensemble_exit_threshold,
+            record_missed_entries,
+            missed_entries_max,
+            max_closed_history,
+            runner_fraction,
+            runner_trail_pct,
+            use_limit_entries,
+            limit_entry_depth,
+            tracker_health,
+            atomic_entry,
+            partial_sl_buffer_pct,
+            api_token,
+            trail_activate_pct,
+            trail_distance,
+            max_daily_loss,
+            flatten_on_exit,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profit_target_fractions_must_sum_to_one() {
+        assert_eq!(
+            profit_target_fractions(None, None).unwrap(),
+            vec![0.20, 0.30, 0.30, 0.20]
+        );
+        assert_eq!(
+            profit_target_fractions(Some("0.6, 0.4".into()), None).unwrap(),
+            vec![0.6, 0.4]
+        );
+        assert_eq!(profit_target_fractions(None, Some(6)).unwrap().len(), 6);
+
+        assert!(profit_target_fractions(Some("0.5,0.25".into()), None).is_err());
+        assert!(profit_target_fractions(Some("1.2,-0.2".into()), None).is_err());
+        assert!(profit_target_fractions(Some("half,half".into()), None).is_err());
+    }
+
+    #[test]
+    fn test_size_precision_per_symbol() {
+        let precisions = size_precisions("ethusdt:2, SOLUSDT:1").unwrap();
+        assert_eq!(precisions.get("ETHUSDT"), Some(&2));
+        assert_eq!(precisions.get("SOLUSDT"), Some(&1));
+        assert!(size_precisions("").unwrap().is_empty());
+        assert!(size_precisions("ETHUSDT").is_err());
+        assert!(size_precisions("ETHUSDT:two").is_err());
+
+        let config = Config {
+            size_precisions: precisions,
+            ..Config::for_tests()
+        };
+        assert_eq!(config.size_precision("ETHUSDT"), 2);
+        assert_eq!(config.size_precision("BTCUSDT"), DEFAULT_SIZE_PRECISION);
+    }
+}
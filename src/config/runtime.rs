@@ -0,0 +1,143 @@
+use anyhow::{bail, Result};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::helper::TRADING_BOT_CONFIG_OVERRIDE;
+
+/// Highest leverage Bitget allows on BTCUSDT perpetuals
+pub const MAX_LEVERAGE: f64 = 125.0;
+
+/// The sizing tunables the bot reads every cycle. Margin isn't one: it compounds in
+/// redis from `MARGIN`, so an override of it would never reach the sizing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Tunables {
+    pub leverage: f64,
+    pub risk_pct: f64,
+    pub ranger_price_difference: f64,
+}
+
+impl Tunables {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            leverage: config.leverage,
+            risk_pct: config.ranger_risk_pct,
+            ranger_price_difference: config.ranger_price_difference,
+        }
+    }
+}
+
+/// Tunables changed through `PUT /api/config`, so leverage can be retuned without a
+/// restart. Unset fields keep the env value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeOverride {
+    #[serde(default)]
+    pub leverage: Option<f64>,
+    #[serde(default)]
+    pub risk_pct: Option<f64>,
+    #[serde(default)]
+    pub ranger_price_difference: Option<f64>,
+}
+
+impl RuntimeOverride {
+    pub fn apply(&self, base: Tunables) -> Tunables {
+        Tunables {
+            leverage: self.leverage.unwrap_or(base.leverage),
+            risk_pct: self.risk_pct.unwrap_or(base.risk_pct),
+            ranger_price_difference: self
+                .ranger_price_difference
+                .unwrap_or(base.ranger_price_difference),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .leverage
+            .is_some_and(|l| !(1.0..=MAX_LEVERAGE).contains(&l))
+        {
+            bail!("Leverage must be between 1 and {MAX_LEVERAGE}");
+        }
+        if self.risk_pct.is_some_and(|r| !(r > 0.0 && r <= 1.0)) {
+            bail!("Risk must be a fraction between 0 and 1");
+        }
+        if self
+            .ranger_price_difference
+            .is_some_and(|d| !(d.is_finite() && d > 0.0))
+        {
+            bail!("Ranger price difference must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// A missing or unreadable override counts as none
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection) -> Self {
+        Self::try_load(conn).await.unwrap_or_default()
+    }
+
+    /// Like `load`, but a redis failure is an error rather than no override
+    pub async fn try_load(conn: &mut redis::aio::MultiplexedConnection) -> Result<Self> {
+        let raw: Option<String> = conn.get(TRADING_BOT_CONFIG_OVERRIDE).await?;
+        Ok(raw
+            .and_then(|r| serde_json::from_str(&r).ok())
+            .unwrap_or_default())
+    }
+
+    /// Replaces the stored override; one with nothing set removes it
+    pub async fn store(&self, conn: &mut redis::aio::MultiplexedConnection) -> Result<()> {
+        if *self == Self::default() {
+            let _: () = conn.del(TRADING_BOT_CONFIG_OVERRIDE).await?;
+        } else {
+            let _: () = conn
+                .set(TRADING_BOT_CONFIG_OVERRIDE, serde_json::to_string(self)?)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_replaces_env_tunables_only_where_set() {
+        let base = Tunables::from_config(&Config::for_tests());
+        let retuned = RuntimeOverride {
+            leverage: Some(10.0),
+            ..RuntimeOverride::default()
+        };
+
+        let tunables = retuned.apply(base);
+        assert_eq!(tunables.leverage, 10.0);
+        assert_eq!(tunables.ranger_price_difference, base.ranger_price_difference);
+        assert_eq!(tunables.risk_pct, base.risk_pct);
+        assert_eq!(RuntimeOverride::default().apply(base), base);
+    }
+
+    #[test]
+    fn test_out_of_range_overrides_are_rejected() {
+        let valid = RuntimeOverride {
+            leverage: Some(20.0),
+            risk_pct: Some(0.05),
+            ranger_price_difference: Some(400.0),
+        };
+        assert!(valid.validate().is_ok());
+
+        for invalid in [
+            RuntimeOverride {
+                leverage: Some(200.0),
+                ..valid
+            },
+            RuntimeOverride {
+                risk_pct: Some(1.5),
+                ..valid
+            },
+            RuntimeOverride {
+                ranger_price_difference: Some(-1.0),
+                ..valid
+            },
+        ] {
+            assert!(invalid.validate().is_err());
+        }
+    }
+}
@@ -1,10 +1,34 @@
 use base64::{Engine as _, engine::general_purpose};
 use hmac::{Hmac, Mac};
 //use log::info;
+use reqwest::header::HeaderMap;
+use serde_json::Value;
 use sha2::Sha256;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Auth headers of signed Bitget / Bitunix requests (header names are lowercase)
+const SENSITIVE_HEADERS: [&str; 5] = [
+    "access-key",
+    "access-sign",
+    "access-passphrase",
+    "api-key",
+    "sign",
+];
+
+/// JSON fields stripped from logged request/response bodies
+const SENSITIVE_FIELDS: [&str; 9] = [
+    "apiKey",
+    "api_key",
+    "passphrase",
+    "secret",
+    "sign",
+    "signature",
+    "userId",
+    "uid",
+    "accountId",
+];
+
 //bitget uses Hmac with base64 encoding
 pub fn bitget_sign(
     secret: &str,
@@ -35,6 +59,60 @@ pub fn bitget_sign(
     general_purpose::STANDARD.encode(mac.finalize().into_bytes())
 }
 
+/// Keeps only the last 4 chars, enough to tell keys apart in logs
+pub fn mask(secret: &str) -> String {
+    let len = secret.chars().count();
+    if len <= 8 {
+        return "****".to_string();
+    }
+    let tail: String = secret.chars().skip(len - 4).collect();
+    format!("****{tail}")
+}
+
+/// Loggable form of request headers with keys, signatures and passphrases masked
+pub fn redact_headers(headers: &HeaderMap) -> String {
+    let fields: Vec<String> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.to_str().unwrap_or("<binary>");
+            if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                format!("{name}: {}", mask(value))
+            } else {
+                format!("{name}: {value}")
+            }
+        })
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// Loggable form of a JSON body with sensitive fields replaced at any depth.
+/// Anything that isn't JSON is reduced to its length.
+pub fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            strip_sensitive(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes, not JSON>", body.len()),
+    }
+}
+
+fn strip_sensitive(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if SENSITIVE_FIELDS.iter().any(|f| f.eq_ignore_ascii_case(key)) {
+                    *field = Value::String("[redacted]".to_string());
+                } else {
+                    strip_sensitive(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_sensitive),
+        _ => {}
+    }
+}
+
 // pub fn binance_sign() {
 //     //todo
 //     info!("binance not available yet!");
@@ -44,3 +122,46 @@ pub fn bitget_sign(
 //     //todo
 //     info!("hyperliquid not available yet!");
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_headers_hide_key_and_signature() {
+        let api_key = "bg_4f0c2a9e71d3b85566aa";
+        let sign = bitget_sign(
+            "secret",
+            "1700000000000",
+            "POST",
+            "/api/v2/mix/order/place-order",
+            None,
+            Some("{}"),
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("ACCESS-KEY", api_key.parse().unwrap());
+        headers.insert("ACCESS-SIGN", sign.parse().unwrap());
+        headers.insert("ACCESS-PASSPHRASE", "hunter2hunter2".parse().unwrap());
+        headers.insert("ACCESS-TIMESTAMP", "1700000000000".parse().unwrap());
+
+        let logged = redact_headers(&headers);
+        assert!(!logged.contains(api_key));
+        assert!(!logged.contains(&sign));
+        assert!(!logged.contains("hunter2hunter2"));
+        assert!(logged.contains("access-key: ****66aa"));
+        assert!(logged.contains("access-timestamp: 1700000000000"));
+    }
+
+    #[test]
+    fn test_redact_body_strips_nested_fields() {
+        let body =
+            r#"{"code":"00000","data":{"orderId":"1","userId":"8812","list":[{"apiKey":"k"}]}}"#;
+        let logged = redact_body(body);
+
+        assert!(!logged.contains("8812"));
+        assert!(!logged.contains(r#""k""#));
+        assert!(logged.contains(r#""orderId":"1""#));
+        assert_eq!(redact_body("<html>"), "<6 bytes, not JSON>");
+    }
+}
@@ -0,0 +1,308 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+
+use super::{maintenance, ApiResponse};
+use crate::bot::allocator::Strategy;
+use crate::encryption;
+
+/// Bitget codes for a rejected key, passphrase or signature
+const AUTH_FAILURE_CODES: [&str; 5] = ["40006", "40009", "40012", "40014", "40037"];
+
+/// Raised when Bitget rejects an account's key or passphrase, instead of the
+/// generic API error every order would otherwise fail with.
+#[derive(Debug, Clone)]
+pub struct InvalidCredentials {
+    pub account: String,
+    pub code: String,
+    pub msg: String,
+}
+
+impl fmt::Display for InvalidCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid credentials/passphrase for Bitget account '{}' ({}): {}",
+            self.account, self.code, self.msg
+        )
+    }
+}
+
+impl std::error::Error for InvalidCredentials {}
+
+/// API credentials for one Bitget (sub-)account. The main account comes from
+/// API_KEY / API_SECRET / ACCESS_PASSPHRASE, extra ones from BITGET_SUB_ACCOUNTS.
+#[derive(Clone, Deserialize)]
+pub struct AccountCreds {
+    pub name: String,
+    pub api_key: String,
+    pub api_secret: String,
+    pub passphrase: String,
+    /// Strategy this account trades under `AccountRouting::ByStrategy`
+    #[serde(default)]
+    pub strategy: Option<String>,
+}
+
+impl AccountCreds {
+    /// `ACCESS-SIGN` for a request made with this account
+    pub fn sign(
+        &self,
+        timestamp: &str,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        body: Option<&str>,
+    ) -> String {
+        encryption::bitget_sign(&self.api_secret, timestamp, method, path, query, body)
+    }
+
+    /// Auth headers for a signed request; log them via `encryption::redact_headers`
+    pub fn auth_headers(&self, timestamp: &str, sign: &str) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert("ACCESS-KEY", self.api_key.parse()?);
+        headers.insert("ACCESS-SIGN", sign.parse()?);
+        headers.insert("ACCESS-TIMESTAMP", timestamp.parse()?);
+        headers.insert("ACCESS-PASSPHRASE", self.passphrase.parse()?);
+        headers.insert("Content-Type", "application/json".parse()?);
+        Ok(headers)
+    }
+
+    /// One authenticated GET (futures account info) to prove the key, secret and
+    /// passphrase are accepted before the first trade depends on them.
+    pub async fn verify(&self, client: &reqwest::Client) -> Result<()> {
+        let path = "/api/v2/mix/account/accounts";
+        let query = "productType=USDT-FUTURES";
+        let timestamp = Utc::now().timestamp_millis().to_string();
+        let sign = self.sign(&timestamp, "GET", path, Some(query), None);
+
+        let text = client
+            .get(format!("https://api.bitget.com{path}?{query}"))
+            .headers(self.auth_headers(&timestamp, &sign)?)
+            .send()
+            .await?
+            .text()
+            .await?;
+        self.check_auth_response(&text)
+    }
+
+    fn check_auth_response(&self, text: &str) -> Result<()> {
+        let response: ApiResponse<serde_json::Value> = serde_json::from_str(text).map_err(|e| {
+            anyhow!(
+                "Failed to parse Bitget account response: {e}, response: {}",
+                encryption::redact_body(text)
+            )
+        })?;
+        if response.code == "00000" {
+            return Ok(());
+        }
+        if AUTH_FAILURE_CODES.contains(&response.code.as_str()) {
+            return Err(anyhow::Error::new(InvalidCredentials {
+                account: self.name.clone(),
+                code: response.code,
+                msg: response.msg,
+            }));
+        }
+        Err(maintenance::api_error(&response.code, &response.msg))
+    }
+}
+
+// Hand-written so credentials never reach a log through `{:?}` on Config or the router
+impl std::fmt::Debug for AccountCreds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountCreds")
+            .field("name", &self.name)
+            .field("api_key", &encryption::mask(&self.api_key))
+            .field("api_secret", &"****")
+            .field("passphrase", &"****")
+            .field("strategy", &self.strategy)
+            .finish()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountRouting {
+    RoundRobin,
+    ByStrategy,
+}
+
+impl FromStr for AccountRouting {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "round_robin" => Ok(AccountRouting::RoundRobin),
+            "strategy" | "by_strategy" => Ok(AccountRouting::ByStrategy),
+            other => Err(anyhow!(
+                "Unknown account routing '{}': expected 'round_robin' or 'strategy'",
+                other
+            )),
+        }
+    }
+}
+
+/// Picks the account a new entry is placed on. Exits always go back to the
+/// account recorded on the position, see [`find_account`].
+#[derive(Debug)]
+pub struct AccountRouter {
+    accounts: Vec<AccountCreds>,
+    routing: AccountRouting,
+    next: AtomicUsize,
+}
+
+impl AccountRouter {
+    /// `accounts` must not be empty; the first one is the fallback
+    pub fn new(accounts: Vec<AccountCreds>, routing: AccountRouting) -> Self {
+        Self {
+            accounts,
+            routing,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn select(&self, strategy: Strategy) -> &AccountCreds {
+        match self.routing {
+            AccountRouting::RoundRobin => {
+                let i = self.next.fetch_add(1, Ordering::Relaxed);
+                &self.accounts[i % self.accounts.len()]
+            }
+            AccountRouting::ByStrategy => self
+                .accounts
+                .iter()
+                .find(|a| a.strategy.as_deref() == Some(strategy.as_str()))
+                .unwrap_or(&self.accounts[0]),
+        }
+    }
+
+    /// Whether `strategy` trades an account no other strategy is routed to. Only
+    /// `ByStrategy` routing with an account tagged for it can promise that.
+    pub fn has_own_account(&self, strategy: Strategy) -> bool {
+        if self.routing != AccountRouting::ByStrategy {
+            return false;
+        }
+        let own = &self.select(strategy).name;
+        Strategy::ALL
+            .iter()
+            .filter(|other| **other != strategy)
+            .all(|other| self.select(*other).name != *own)
+    }
+}
+
+/// The account called `name`; positions from before routing existed carry no
+/// name and fall back to the first (main) account.
+pub fn find_account<'a>(accounts: &'a [AccountCreds], name: Option<&str>) -> &'a AccountCreds {
+    name.and_then(|n| accounts.iter().find(|a| a.name == n))
+        .unwrap_or(&accounts[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(name: &str, secret: &str, strategy: Option<&str>) -> AccountCreds {
+        AccountCreds {
+            name: name.to_string(),
+            api_key: format!("{name}-key"),
+            api_secret: secret.to_string(),
+            passphrase: format!("{name}-pass"),
+            strategy: strategy.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_each_account_signs_with_its_own_secret() {
+        let router = AccountRouter::new(
+            vec![account("main", "secret-a", None), account("sub1", "secret-b", None)],
+            AccountRouting::RoundRobin,
+        );
+        let (ts, path, body) = ("1700000000000", "/api/v2/mix/order/place-order", "{}");
+
+        let first = router.select(Strategy::Ranger);
+        let second = router.select(Strategy::Ranger);
+        assert_eq!((first.name.as_str(), second.name.as_str()), ("main", "sub1"));
+
+        assert_eq!(
+            first.sign(ts, "POST", path, None, Some(body)),
+            encryption::bitget_sign("secret-a", ts, "POST", path, None, Some(body))
+        );
+        assert_eq!(
+            second.sign(ts, "POST", path, None, Some(body)),
+            encryption::bitget_sign("secret-b", ts, "POST", path, None, Some(body))
+        );
+        assert_ne!(
+            first.sign(ts, "POST", path, None, Some(body)),
+            second.sign(ts, "POST", path, None, Some(body))
+        );
+
+        // wraps back around
+        assert_eq!(router.select(Strategy::Ranger).name, "main");
+    }
+
+    #[test]
+    fn test_strategy_routing_and_name_lookup() {
+        let accounts = vec![account("main", "a", None), account("scalp", "b", Some("scalper"))];
+        let router = AccountRouter::new(accounts.clone(), AccountRouting::ByStrategy);
+
+        assert_eq!(router.select(Strategy::Scalper).name, "scalp");
+        assert_eq!(router.select(Strategy::Ranger).name, "main");
+
+        assert_eq!(find_account(&accounts, Some("scalp")).api_secret, "b");
+        assert_eq!(find_account(&accounts, None).name, "main");
+        assert_eq!(find_account(&accounts, Some("gone")).name, "main");
+    }
+
+    #[test]
+    fn test_scalper_needs_an_account_of_its_own() {
+        let tagged = vec![
+            account("main", "a", None),
+            account("scalp", "b", Some("scalper")),
+        ];
+        let router = AccountRouter::new(tagged.clone(), AccountRouting::ByStrategy);
+        assert!(router.has_own_account(Strategy::Scalper));
+        // the ranger falls back to the main account, which others may share
+        assert!(!router.has_own_account(Strategy::Ranger));
+
+        let round_robin = AccountRouter::new(tagged, AccountRouting::RoundRobin);
+        assert!(!round_robin.has_own_account(Strategy::Scalper));
+
+        let untagged = AccountRouter::new(
+            vec![account("main", "a", None), account("sub1", "b", None)],
+            AccountRouting::ByStrategy,
+        );
+        assert!(!untagged.has_own_account(Strategy::Scalper));
+
+        // the scalper's account is also the fallback every untagged strategy lands on
+        let fallback = AccountRouter::new(
+            vec![
+                account("scalp", "b", Some("scalper")),
+                account("sub1", "a", None),
+            ],
+            AccountRouting::ByStrategy,
+        );
+        assert!(!fallback.has_own_account(Strategy::Scalper));
+    }
+
+    #[test]
+    fn test_rejected_passphrase_is_a_credentials_error() {
+        let main = account("main", "secret-a", None);
+        let rejected = r#"{"code":"40012","msg":"apikey/password is incorrect","requestTime":1700000000000,"data":null}"#;
+
+        let err = main.check_auth_response(rejected).unwrap_err();
+        let creds = err.downcast_ref::<InvalidCredentials>().expect("credentials error");
+        assert_eq!(creds.account, "main");
+        assert!(err.to_string().contains("Invalid credentials/passphrase"));
+
+        // other failures keep their own error
+        let maintenance = r#"{"code":"40015","msg":"system maintenance","requestTime":1700000000000,"data":null}"#;
+        let err = main.check_auth_response(maintenance).unwrap_err();
+        assert!(err.downcast_ref::<InvalidCredentials>().is_none());
+
+        let ok = r#"{"code":"00000","msg":"success","requestTime":1700000000000,"data":[]}"#;
+        assert!(main.check_auth_response(ok).is_ok());
+    }
+}
@@ -1,11 +1,12 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
 use redis::AsyncCommands;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::bot::{OpenPosition, Position};
-use crate::exchange::bitget::{deserialize_string_to_f64, ApiResponse};
-use crate::helper::Helper;
+use crate::exchange::bitget::{deserialize_string_to_f64, maintenance, ApiResponse};
+use crate::helper::{Helper, BITGET_VIP_FEE_RATES};
 
 #[derive(Debug, Clone, Copy)]
 pub enum ExecutionType {
@@ -20,6 +21,22 @@ pub enum ExecutionType {
 //     StopLoss,
 // }
 
+/// Costs charged against one closed slice of a position
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExitFees {
+    /// Taker fee on the entry notional of the closed quantity
+    pub entry_fee: Decimal,
+    pub exit_fee: Decimal,
+    /// Funding paid (positive) or received (negative) while the slice was open
+    pub funding_fee: Decimal,
+}
+
+impl ExitFees {
+    pub fn total(&self) -> Decimal {
+        self.entry_fee + self.exit_fee + self.funding_fee
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BitgetFuturesFees {
     #[allow(dead_code)]
@@ -29,6 +46,8 @@ pub struct BitgetFuturesFees {
     #[allow(dead_code)]
     pub funding_rate: f64,
     pub redis_conn: redis::aio::MultiplexedConnection,
+    /// Shared process-wide client, so a fee lookup doesn't build its own pool
+    pub client: reqwest::Client,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,22 +65,28 @@ pub struct VipFeeRate {
 }
 
 impl BitgetFuturesFees {
-    pub fn new(conn: redis::aio::MultiplexedConnection) -> Self {
+    pub fn new(conn: redis::aio::MultiplexedConnection, client: reqwest::Client) -> Self {
         Self {
             maker_fee: 0.0,
             taker_fee: 0.0,
             funding_rate: 0.0,
             redis_conn: conn,
+            client,
         }
     }
 
     #[allow(dead_code)]
-    pub fn from_vip_data(conn: redis::aio::MultiplexedConnection, vip_data: &VipFeeRate) -> Self {
+    pub fn from_vip_data(
+        conn: redis::aio::MultiplexedConnection,
+        client: reqwest::Client,
+        vip_data: &VipFeeRate,
+    ) -> Self {
         Self {
             maker_fee: vip_data.maker_fee_rate,
             taker_fee: vip_data.taker_fee_rate,
             funding_rate: 0.0,
             redis_conn: conn,
+            client,
         }
     }
 
@@ -70,7 +95,7 @@ impl BitgetFuturesFees {
         rates
             .iter()
             .find(|r| r.level == level)
-            .map(|r| Self::from_vip_data(self.redis_conn, r))
+            .map(|r| Self::from_vip_data(self.redis_conn, self.client, r))
     }
 
     pub async fn fee_on_notional(
@@ -104,6 +129,14 @@ impl BitgetFuturesFees {
         notional * Decimal::from_f64(rate).unwrap()
     }
 
+    /// Price distance (per unit) needed to cover taker fees on both entry and exit
+    pub async fn breakeven_distance(&self, entry_price: Decimal) -> Decimal {
+        let one_side = self
+            .fee_on_notional(entry_price, Decimal::ONE, ExecutionType::Taker)
+            .await;
+        one_side * Decimal::TWO
+    }
+
     pub async fn pnl_for_exit(
         side: Position,
         entry_price: Decimal,
@@ -131,11 +164,20 @@ impl BitgetFuturesFees {
         margin - entry_fee
     }
 
+    /// Net PnL of closing `open_position` (the quantity being closed) at
+    /// `current_price`, after entry + exit taker fees and funding.
     pub async fn calc_pnl_for_exit(
         &self,
         open_position: &OpenPosition,
         current_price: Decimal,
-    ) -> (Decimal, Decimal) {
+    ) -> (Decimal, ExitFees) {
+        let entry_fee = self
+            .fee_on_notional(
+                open_position.entry_price,
+                open_position.position_size,
+                ExecutionType::Taker,
+            )
+            .await;
         let exit_fee = self
             .fee_on_notional(
                 current_price,
@@ -143,6 +185,13 @@ impl BitgetFuturesFees {
                 ExecutionType::Taker,
             )
             .await;
+        let funding_fee = Self::funding_cost(
+            open_position.pos,
+            open_position.entry_price * open_position.position_size,
+            open_position.entry_funding_rate.unwrap_or_default(),
+            open_position.entry_time,
+            Utc::now(),
+        );
         let pnl = Self::pnl_for_exit(
             open_position.pos,
             open_position.entry_price,
@@ -150,11 +199,45 @@ impl BitgetFuturesFees {
             open_position.position_size,
         )
         .await;
-        (pnl - exit_fee, exit_fee)
+
+        let fees = ExitFees {
+            entry_fee,
+            exit_fee,
+            funding_fee,
+        };
+        (pnl - fees.total(), fees)
     }
 
+    /// Funding settlements (every 8h from 00:00 UTC) that fall inside (entry, exit]
+    pub fn funding_intervals(entry: DateTime<Utc>, exit: DateTime<Utc>) -> i64 {
+        let period = Duration::hours(8);
+        let (Ok(first), Ok(last)) = (entry.duration_trunc(period), exit.duration_trunc(period))
+        else {
+            return 0;
+        };
+        ((last - first).num_hours() / 8).max(0)
+    }
+
+    /// Funding over the hold at `rate` per settlement; longs pay a positive rate,
+    /// shorts receive it.
+    pub fn funding_cost(
+        side: Position,
+        notional: Decimal,
+        rate: Decimal,
+        entry: DateTime<Utc>,
+        exit: DateTime<Utc>,
+    ) -> Decimal {
+        let paid = notional * rate * Decimal::from(Self::funding_intervals(entry, exit));
+        match side {
+            Position::Long => paid,
+            Position::Short => -paid,
+            Position::Flat => Decimal::ZERO,
+        }
+    }
+
+    /// VIP fee table, served from redis for 24h so fee maths doesn't refetch it
     pub async fn get_vip_fee_rates(&self) -> Result<Vec<VipFeeRate>, anyhow::Error> {
-        let key = "bitget::vip_fee_rates";
+        let key = BITGET_VIP_FEE_RATES;
         let mut conn = self.redis_conn.clone();
 
         // Try to get from Redis
@@ -167,13 +250,13 @@ impl BitgetFuturesFees {
 
         let url = "https://api.bitget.com/api/v2/mix/market/vip-fee-rate";
 
-        let response = reqwest::get(url).await?;
+        let response = self.client.get(url).send().await?;
 
         let text = response.text().await?;
         let api_response: ApiResponse<Vec<VipFeeRate>> = serde_json::from_str(&text)?;
 
         if api_response.code != "00000" {
-            return Err(anyhow::anyhow!("Bitget API error: {}", api_response.msg));
+            return Err(maintenance::api_error(&api_response.code, &api_response.msg));
         }
 
         let rates = api_response.data.ok_or_else(|| {
@@ -188,3 +271,47 @@ impl BitgetFuturesFees {
         Ok(rates)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_funding_counts_settlements_crossed() {
+        let at = |h, m| Utc.with_ymd_and_hms(2025, 3, 10, h, m, 0).unwrap();
+
+        assert_eq!(BitgetFuturesFees::funding_intervals(at(1, 0), at(7, 59)), 0);
+        assert_eq!(BitgetFuturesFees::funding_intervals(at(7, 59), at(8, 0)), 1);
+        assert_eq!(BitgetFuturesFees::funding_intervals(at(1, 0), at(17, 0)), 2);
+
+        // 0.01% on 1000 notional over two settlements
+        let long = BitgetFuturesFees::funding_cost(
+            Position::Long,
+            dec!(1000),
+            dec!(0.0001),
+            at(1, 0),
+            at(17, 0),
+        );
+        assert_eq!(long, dec!(0.2));
+        let short = BitgetFuturesFees::funding_cost(
+            Position::Short,
+            dec!(1000),
+            dec!(0.0001),
+            at(1, 0),
+            at(17, 0),
+        );
+        assert_eq!(short, dec!(-0.2));
+    }
+
+    #[test]
+    fn test_exit_fees_total() {
+        let fees = ExitFees {
+            entry_fee: dec!(0.6),
+            exit_fee: dec!(0.62),
+            funding_fee: dec!(-0.1),
+        };
+        assert_eq!(fees.total(), dec!(1.12));
+    }
+}
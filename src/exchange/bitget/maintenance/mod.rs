@@ -0,0 +1,206 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use std::fmt;
+
+use crate::config::Config;
+
+/// Bitget codes returned while the venue is down for maintenance.
+/// Override with `BITGET_MAINTENANCE_CODES` (comma separated).
+pub const DEFAULT_MAINTENANCE_CODES: &str = "40015,40725";
+
+/// A non-ok Bitget response. Keeps the code so a `MaintenanceGuard` can tell a
+/// maintenance answer from any other failure.
+#[derive(Debug, Clone)]
+pub struct BitgetApiError {
+    pub code: String,
+    pub msg: String,
+}
+
+impl fmt::Display for BitgetApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bitget API error ({}): {}", self.code, self.msg)
+    }
+}
+
+impl std::error::Error for BitgetApiError {}
+
+pub fn parse_maintenance_codes(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+pub fn is_maintenance_code(code: &str, codes: &[String]) -> bool {
+    codes.iter().any(|c| c == code)
+}
+
+/// The error for a non-ok Bitget response
+pub fn api_error(code: &str, msg: &str) -> anyhow::Error {
+    anyhow::Error::new(BitgetApiError {
+        code: code.to_string(),
+        msg: msg.to_string(),
+    })
+}
+
+/// Whether `err` is Bitget answering with one of the maintenance `codes`
+pub fn is_maintenance(err: &anyhow::Error, codes: &[String]) -> bool {
+    err.downcast_ref::<BitgetApiError>()
+        .is_some_and(|e| is_maintenance_code(&e.code, codes))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaintenanceState {
+    Normal,
+    /// No new entries until `until`; open positions are left alone.
+    Paused { until: DateTime<Utc>, backoff_secs: i64 },
+}
+
+/// Holds the bot in a clean pause while Bitget is under maintenance. Each repeated
+/// maintenance error doubles the pause, capped at `max_backoff_secs`.
+#[derive(Debug, Clone)]
+pub struct MaintenanceGuard {
+    pub state: MaintenanceState,
+    base_backoff_secs: i64,
+    max_backoff_secs: i64,
+    codes: Vec<String>,
+}
+
+impl MaintenanceGuard {
+    pub fn new(base_backoff_secs: i64, max_backoff_secs: i64, codes: Vec<String>) -> Self {
+        Self {
+            state: MaintenanceState::Normal,
+            base_backoff_secs,
+            max_backoff_secs,
+            codes,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.maintenance_backoff_secs,
+            config.maintenance_max_backoff_secs,
+            config.maintenance_codes.clone(),
+        )
+    }
+
+    /// Inspect an error from any exchange call; returns true if it was a maintenance error.
+    pub fn observe_error(&mut self, err: &anyhow::Error, now: DateTime<Utc>) -> bool {
+        if !is_maintenance(err, &self.codes) {
+            return false;
+        }
+
+        let backoff_secs = match self.state {
+            MaintenanceState::Normal => self.base_backoff_secs,
+            MaintenanceState::Paused { backoff_secs, .. } => {
+                (backoff_secs * 2).min(self.max_backoff_secs)
+            }
+        };
+        self.state = MaintenanceState::Paused {
+            until: now + Duration::seconds(backoff_secs),
+            backoff_secs,
+        };
+        warn!("MAINTENANCE PAUSE: {err}, holding positions, no entries for {backoff_secs}s");
+        true
+    }
+
+    /// Call after a successful exchange round-trip to clear the pause.
+    pub fn observe_success(&mut self) {
+        if self.state != MaintenanceState::Normal {
+            info!("MAINTENANCE PAUSE lifted, Bitget API responding again");
+            self.state = MaintenanceState::Normal;
+        }
+    }
+
+    pub fn allows_entry(&self, now: DateTime<Utc>) -> bool {
+        match self.state {
+            MaintenanceState::Normal => true,
+            MaintenanceState::Paused { until, .. } => now >= until,
+        }
+    }
+
+    /// Time left on the current pause. Loops sleep it out instead of polling an API
+    /// that is still down; the first call after it decides whether the pause doubles.
+    pub fn pause_remaining(&self, now: DateTime<Utc>) -> Option<std::time::Duration> {
+        match self.state {
+            MaintenanceState::Paused { until, .. } if until > now => (until - now).to_std().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maintenance_err() -> anyhow::Error {
+        api_error("40725", "system maintenance")
+    }
+
+    fn guard() -> MaintenanceGuard {
+        MaintenanceGuard::new(60, 600, parse_maintenance_codes(DEFAULT_MAINTENANCE_CODES))
+    }
+
+    #[test]
+    fn test_maintenance_code_maps_to_maintenance_error() {
+        let codes = parse_maintenance_codes("40015, 40725");
+        assert!(is_maintenance_code("40725", &codes));
+        assert!(!is_maintenance_code("40034", &codes));
+
+        assert!(is_maintenance(&api_error("40725", "system maintenance"), &codes));
+        assert!(!is_maintenance(&api_error("40034", "param error"), &codes));
+        assert!(!is_maintenance(&anyhow::anyhow!("40725"), &codes));
+
+        // the configured codes decide, not the defaults
+        let codes = parse_maintenance_codes("50001");
+        assert!(!is_maintenance(&maintenance_err(), &codes));
+        assert!(is_maintenance(&api_error("50001", "upgrading"), &codes));
+    }
+
+    #[test]
+    fn test_maintenance_error_pauses_entries() {
+        let now = Utc::now();
+        let mut guard = guard();
+        assert!(guard.allows_entry(now));
+
+        assert!(guard.observe_error(&maintenance_err(), now));
+        assert!(!guard.allows_entry(now + Duration::seconds(30)));
+        assert!(guard.allows_entry(now + Duration::seconds(61)));
+
+        // Repeat failure doubles the pause
+        guard.observe_error(&maintenance_err(), now);
+        assert!(!guard.allows_entry(now + Duration::seconds(100)));
+
+        guard.observe_success();
+        assert!(guard.allows_entry(now));
+    }
+
+    #[test]
+    fn test_polling_waits_out_the_pause() {
+        let now = Utc::now();
+        let mut guard = guard();
+        assert_eq!(guard.pause_remaining(now), None);
+
+        guard.observe_error(&maintenance_err(), now);
+        assert_eq!(
+            guard.pause_remaining(now + Duration::seconds(20)),
+            Some(std::time::Duration::from_secs(40))
+        );
+        assert_eq!(guard.pause_remaining(now + Duration::seconds(60)), None);
+
+        // still down after the wait: the next one is twice as long
+        guard.observe_error(&maintenance_err(), now + Duration::seconds(60));
+        assert_eq!(
+            guard.pause_remaining(now + Duration::seconds(60)),
+            Some(std::time::Duration::from_secs(120))
+        );
+    }
+
+    #[test]
+    fn test_other_errors_do_not_pause() {
+        let now = Utc::now();
+        let mut guard = guard();
+        assert!(!guard.observe_error(&anyhow::anyhow!("timeout"), now));
+        assert!(guard.allows_entry(now));
+    }
+}
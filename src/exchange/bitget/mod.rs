@@ -2,7 +2,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Utc;
 use futures_util::{SinkExt, StreamExt};
-use log::{error, info};
+use log::{debug, error, info};
 use reqwest::Client;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
@@ -12,12 +12,16 @@ use uuid::Uuid;
 
 use crate::{
     bot::{OpenPosition, Position},
-    config::Config,
+    config::{Config, StopTriggerPrice},
     encryption,
     helper::Helper,
 };
 
+pub mod accounts;
 pub mod fees;
+pub mod maintenance;
+
+use accounts::AccountCreds;
 
 //For binance: https://api.binance.com/api/v3/klines?symbol=BTCUSDT&interval=5m&limit=100
 //FOR BITGET, USE: https://api.bitget.com/api/v2/public/time to get the Bitget Server time
@@ -57,6 +61,22 @@ pub struct PlaceOrderData {
     pub order_id: String,
 }
 
+impl PlaceOrderData {
+    /// What the exchanges return when they reject an order, entry or close
+    pub const FAILED: &'static str = "Failed to place order";
+
+    pub fn failed() -> Self {
+        Self {
+            client_oid: Self::FAILED.to_string(),
+            order_id: Self::FAILED.to_string(),
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.client_oid == Self::FAILED
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FundingRateData {
     pub symbol: String,
@@ -66,6 +86,22 @@ pub struct FundingRateData {
     pub funding_time: String,
 }
 
+/// One held side from `/api/v2/mix/position/single-position`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PositionDetail {
+    pub symbol: String,
+    #[serde(rename = "holdSide")]
+    pub hold_side: String,
+    /// Size held, in the base coin
+    pub total: String,
+}
+
+impl PositionDetail {
+    pub fn is_open(&self) -> bool {
+        self.total.parse::<f64>().is_ok_and(|total| total > 0.0)
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrderDetail {
@@ -127,27 +163,37 @@ pub struct OrderDetail {
     pub u_time: String,
 }
 
-// Custom deserializers for string-to-number conversion
+// Custom deserializers for string-to-number conversion. Bitget sends numbers as
+// strings; our own cached copies (serialized back out) hold plain numbers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber<T> {
+    Str(String),
+    Num(T),
+}
+
 pub(crate) fn deserialize_string_to_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    s.parse::<i64>().map_err(serde::de::Error::custom)
+    match StringOrNumber::<i64>::deserialize(deserializer)? {
+        StringOrNumber::Str(s) => s.parse::<i64>().map_err(serde::de::Error::custom),
+        StringOrNumber::Num(n) => Ok(n),
+    }
 }
 
 pub(crate) fn deserialize_string_to_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    s.parse::<f64>().map_err(serde::de::Error::custom)
+    match StringOrNumber::<f64>::deserialize(deserializer)? {
+        StringOrNumber::Str(s) => s.parse::<f64>().map_err(serde::de::Error::custom),
+        StringOrNumber::Num(n) => Ok(n),
+    }
 }
 
 #[async_trait]
 pub trait CandleData: Send + Sync {
-    fn new() -> Self;
-
     /// Return the latest candles
     async fn get_bitget_candles(&self, interval: String, limit: String) -> Result<Vec<Candle>>;
 
@@ -157,12 +203,42 @@ pub trait CandleData: Send + Sync {
 
 //#[async_trait]
 pub trait FuturesCall {
-    fn new() -> Self;
-
     /// Return the latest candles
-    async fn new_futures_call(&self, open_position: &OpenPosition) -> Result<PlaceOrderData>;
-
-    async fn modify_futures_order(&self, open_position: &OpenPosition) -> Result<PlaceOrderData>;
+    async fn new_futures_call(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+    ) -> Result<PlaceOrderData>;
+
+    async fn modify_futures_order(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+    ) -> Result<PlaceOrderData>;
+
+    /// Post-only limit entry resting at `limit_price`
+    async fn new_limit_order(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+        limit_price: f64,
+    ) -> Result<PlaceOrderData>;
+
+    async fn cancel_order(&self, account: &AccountCreds, order_id: &str) -> Result<()>;
+
+    /// Bitget order detail; `state` is `live`, `partially_filled`, `filled` or `canceled`
+    async fn order_detail(&self, account: &AccountCreds, order_id: &str) -> Result<OrderDetail>;
+
+    /// Replaces the position-level stop loss on the open position
+    async fn set_position_stop_loss(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+        sl_price: f64,
+    ) -> Result<()>;
+
+    /// The account's positions on the symbol, one per held side
+    async fn single_position(&self, account: &AccountCreds) -> Result<Vec<PositionDetail>>;
 }
 
 /// Fetches OHLCV candles from the Bitget public futures endpoint using a
@@ -182,11 +258,7 @@ pub async fn fetch_bitget_candles(
         anyhow::anyhow!("Failed to parse Bitget candles: {e}, response: {text}")
     })?;
     if response.code != "00000" {
-        return Err(anyhow::anyhow!(
-            "Bitget API error ({}): {}",
-            response.code,
-            response.msg
-        ));
+        return Err(maintenance::api_error(&response.code, &response.msg));
     }
     response
         .data
@@ -204,14 +276,6 @@ pub struct HttpCandleData {
 
 #[async_trait::async_trait]
 impl CandleData for HttpCandleData {
-    fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            symbol: String::from("BTCUSDT"),
-            config: Config::from_env().unwrap(),
-        }
-    }
-
     async fn get_bitget_candles(&self, interval: String, limit: String) -> Result<Vec<Candle>> {
         fetch_bitget_candles(&self.client, &self.symbol, &interval, &limit).await
     }
@@ -227,7 +291,7 @@ impl CandleData for HttpCandleData {
         let api_response: ApiResponse<Vec<FundingRateData>> = serde_json::from_str(&text)?;
 
         if api_response.code != "00000" {
-            return Err(anyhow::anyhow!("Bitget API error: {}", api_response.msg));
+            return Err(maintenance::api_error(&api_response.code, &api_response.msg));
         }
 
         Ok(api_response.data.ok_or_else(|| {
@@ -236,21 +300,76 @@ impl CandleData for HttpCandleData {
     }
 }
 
-//#[async_trait::async_trait]
-impl FuturesCall for HttpCandleData {
-    fn new() -> Self {
+impl HttpCandleData {
+    pub fn new(symbol: impl Into<String>, config: Config) -> Self {
         Self {
             client: reqwest::Client::new(),
-            symbol: String::new(),
-            config: Config::from_env().unwrap(),
+            symbol: symbol.into(),
+            config,
         }
     }
 
-    async fn modify_futures_order(&self, open_position: &OpenPosition) -> Result<PlaceOrderData> {
-        let api_key = &self.config.api_key;
-        let secret = &self.config.api_secret;
-        let passphrase = &self.config.passphrase;
+    pub fn accounts(&self) -> &[AccountCreds] {
+        &self.config.accounts
+    }
+
+    /// Credentials for the account `open_position` was opened on
+    pub fn account_for(&self, open_position: &OpenPosition) -> &AccountCreds {
+        accounts::find_account(&self.config.accounts, open_position.account.as_deref())
+    }
+
+    /// One signed request against the private API; a non-`00000` code is an error.
+    async fn signed_request<T: serde::de::DeserializeOwned>(
+        &self,
+        account: &AccountCreds,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        body: Option<String>,
+    ) -> Result<Option<T>> {
+        let timestamp = Utc::now().timestamp_millis().to_string();
+        let sign = account.sign(&timestamp, method, path, query, body.as_deref());
+        let headers = account.auth_headers(&timestamp, &sign)?;
+        debug!(
+            "{method} {path} headers: {} body: {}",
+            encryption::redact_headers(&headers),
+            encryption::redact_body(body.as_deref().unwrap_or(""))
+        );
 
+        let url = match query {
+            Some(query) => format!("https://api.bitget.com{path}?{query}"),
+            None => format!("https://api.bitget.com{path}"),
+        };
+        let request = match method {
+            "GET" => self.client.get(url),
+            _ => self.client.post(url).body(body.unwrap_or_default()),
+        };
+        let response_txt = request.headers(headers).send().await?.text().await?;
+        debug!("response_txt: {}", encryption::redact_body(&response_txt));
+
+        let response_json: ApiResponse<T> = serde_json::from_str(&response_txt).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to parse Bitget {path} response: {e}, response text: {}",
+                encryption::redact_body(&response_txt)
+            )
+        })?;
+        if response_json.code != "00000" {
+            return Err(maintenance::api_error(
+                &response_json.code,
+                &response_json.msg,
+            ));
+        }
+        Ok(response_json.data)
+    }
+}
+
+//#[async_trait::async_trait]
+impl FuturesCall for HttpCandleData {
+    async fn modify_futures_order(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+    ) -> Result<PlaceOrderData> {
         let base_url = "https://api.bitget.com";
         let path = "/api/v2/mix/order/place-order";
         let method = "POST";
@@ -272,7 +391,7 @@ impl FuturesCall for HttpCandleData {
         }
 
         let body_json = json!({
-            "symbol": "BTCUSDT",
+            "symbol": self.symbol,
             "side": side,
             "orderType": "market",
             "size": size,
@@ -288,37 +407,41 @@ impl FuturesCall for HttpCandleData {
 
         let timestamp = Utc::now().timestamp_millis().to_string();
 
-        let sign = encryption::bitget_sign(secret, &timestamp, method, path, None, Some(&body));
+        let sign = account.sign(&timestamp, method, path, None, Some(&body));
+
+        let headers = account.auth_headers(&timestamp, &sign)?;
+        debug!(
+            "{method} {path} headers: {} body: {}",
+            encryption::redact_headers(&headers),
+            encryption::redact_body(&body)
+        );
 
         let client = Client::new();
         let response = client
             .post(format!("{base_url}{path}"))
-            .header("ACCESS-KEY", api_key)
-            .header("ACCESS-SIGN", sign)
-            .header("ACCESS-TIMESTAMP", &timestamp)
-            .header("ACCESS-PASSPHRASE", passphrase)
-            .header("Content-Type", "application/json")
+            .headers(headers)
             .body(body)
             .send()
             .await?;
         let response_txt = response.text().await?;
-        info!("response_txt: {response_txt:?}");
+        debug!("response_txt: {}", encryption::redact_body(&response_txt));
 
         let response: ApiResponse<PlaceOrderData> =
             serde_json::from_str(&response_txt).map_err(|e| {
                 anyhow::anyhow!(
                     "Failed to parse Bitget place-order response: {}, response text: {}",
                     e,
-                    response_txt
+                    encryption::redact_body(&response_txt)
                 )
             })?;
         info!("response::modify_futures_order -> {response:?}");
 
         if response.code != "00000" {
-            return Ok(PlaceOrderData {
-                client_oid: String::from("Failed to modify order"),
-                order_id: String::from("Failed to modify order"),
-            });
+            let err = maintenance::api_error(&response.code, &response.msg);
+            if maintenance::is_maintenance(&err, &self.config.maintenance_codes) {
+                return Err(err);
+            }
+            return Ok(PlaceOrderData::failed());
         }
 
         let order_data = response.data.ok_or_else(|| {
@@ -328,11 +451,11 @@ impl FuturesCall for HttpCandleData {
         Ok(order_data)
     }
 
-    async fn new_futures_call(&self, open_position: &OpenPosition) -> Result<PlaceOrderData> {
-        let api_key = &self.config.api_key;
-        let secret = &self.config.api_secret;
-        let passphrase = &self.config.passphrase;
-
+    async fn new_futures_call(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+    ) -> Result<PlaceOrderData> {
         let base_url = "https://api.bitget.com";
         let path = "/api/v2/mix/order/place-order";
         let method = "POST";
@@ -358,7 +481,7 @@ impl FuturesCall for HttpCandleData {
         }
 
         let body_json = json!({
-            "symbol": "BTCUSDT",
+            "symbol": self.symbol,
             "side": side,
             "orderType": "market",
             "size": size,
@@ -377,36 +500,40 @@ impl FuturesCall for HttpCandleData {
 
         let timestamp = Utc::now().timestamp_millis().to_string();
 
-        let sign = encryption::bitget_sign(secret, &timestamp, method, path, None, Some(&body));
+        let sign = account.sign(&timestamp, method, path, None, Some(&body));
+
+        let headers = account.auth_headers(&timestamp, &sign)?;
+        debug!(
+            "{method} {path} headers: {} body: {}",
+            encryption::redact_headers(&headers),
+            encryption::redact_body(&body)
+        );
 
         let client = Client::new();
         let response = client
             .post(format!("{base_url}{path}"))
-            .header("ACCESS-KEY", api_key)
-            .header("ACCESS-SIGN", sign)
-            .header("ACCESS-TIMESTAMP", &timestamp)
-            .header("ACCESS-PASSPHRASE", passphrase)
-            .header("Content-Type", "application/json")
+            .headers(headers)
             .body(body)
             .send()
             .await?;
         let response_txt = response.text().await?;
-        info!("response_txt: {response_txt:?}");
+        debug!("response_txt: {}", encryption::redact_body(&response_txt));
 
         let response_json: ApiResponse<PlaceOrderData> = serde_json::from_str(&response_txt)
             .map_err(|e| {
                 anyhow::anyhow!(
                     "Failed to parse Bitget new-order response: {}, response text: {}",
                     e,
-                    response_txt
+                    encryption::redact_body(&response_txt)
                 )
             })?;
 
         if response_json.code != "00000" {
-            return Ok(PlaceOrderData {
-                client_oid: String::from("Failed to place order"),
-                order_id: String::from("Failed to place order"),
-            });
+            let err = maintenance::api_error(&response_json.code, &response_json.msg);
+            if maintenance::is_maintenance(&err, &self.config.maintenance_codes) {
+                return Err(err);
+            }
+            return Ok(PlaceOrderData::failed());
         }
 
         let order = response_json.data.ok_or_else(|| {
@@ -415,6 +542,132 @@ impl FuturesCall for HttpCandleData {
 
         Ok(order)
     }
+
+    async fn new_limit_order(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+        limit_price: f64,
+    ) -> Result<PlaceOrderData> {
+        let f64_sl = Helper::decimal_to_f64(open_position.sl.unwrap_or(dec!(0.00)));
+
+        let side = match open_position.pos {
+            Position::Short => "sell",
+            _ => "buy",
+        };
+
+        let body = json!({
+            "symbol": self.symbol,
+            "side": side,
+            "orderType": "limit",
+            "size": open_position.position_size.to_string(),
+            "price": Helper::truncate_to_1_dp(limit_price).to_string(),
+            "marginMode": "isolated",
+            "productType": "USDT-FUTURES",
+            "marginCoin": "USDT",
+            "force": "post_only",
+            "clientOid": open_position.id.to_string(),
+            "presetStopLossPrice": Helper::truncate_to_1_dp(f64_sl)
+        })
+        .to_string();
+
+        self.signed_request(
+            account,
+            "POST",
+            "/api/v2/mix/order/place-order",
+            None,
+            Some(body),
+        )
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!("Bitget returned ok code but null data in limit-order response")
+        })
+    }
+
+    async fn cancel_order(&self, account: &AccountCreds, order_id: &str) -> Result<()> {
+        let body = json!({
+            "symbol": self.symbol,
+            "productType": "USDT-FUTURES",
+            "marginCoin": "USDT",
+            "orderId": order_id,
+        })
+        .to_string();
+
+        let _: Option<serde_json::Value> = self
+            .signed_request(
+                account,
+                "POST",
+                "/api/v2/mix/order/cancel-order",
+                None,
+                Some(body),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn order_detail(&self, account: &AccountCreds, order_id: &str) -> Result<OrderDetail> {
+        self.signed_request(
+            account,
+            "GET",
+            "/api/v2/mix/order/detail",
+            Some(&format!(
+                "symbol={}&productType=USDT-FUTURES&orderId={order_id}",
+                self.symbol
+            )),
+            None,
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Bitget returned no detail for order {order_id}"))
+    }
+
+    async fn set_position_stop_loss(
+        &self,
+        account: &AccountCreds,
+        open_position: &OpenPosition,
+        sl_price: f64,
+    ) -> Result<()> {
+        let hold_side = match open_position.pos {
+            Position::Short => "short",
+            _ => "long",
+        };
+
+        let body = json!({
+            "symbol": self.symbol,
+            "productType": "USDT-FUTURES",
+            "marginCoin": "USDT",
+            "holdSide": hold_side,
+            "stopLossTriggerPrice": Helper::truncate_to_1_dp(sl_price).to_string(),
+            "stopLossTriggerType": "fill_price",
+        })
+        .to_string();
+
+        let _: Option<serde_json::Value> = self
+            .signed_request(
+                account,
+                "POST",
+                "/api/v2/mix/order/place-pos-tpsl",
+                None,
+                Some(body),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn single_position(&self, account: &AccountCreds) -> Result<Vec<PositionDetail>> {
+        Ok(self
+            .signed_request(
+                account,
+                "GET",
+                "/api/v2/mix/position/single-position",
+                Some(&format!(
+                    "symbol={}&productType=USDT-FUTURES&marginCoin=USDT",
+                    self.symbol
+                )),
+                None,
+            )
+            .await?
+            .unwrap_or_default())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -446,6 +699,17 @@ pub struct Prices {
     pub mark_price: f64,
 }
 
+impl Prices {
+    /// The price a stop should be judged against for the given trigger basis
+    pub fn trigger_price(&self, basis: StopTriggerPrice) -> f64 {
+        match basis {
+            StopTriggerPrice::Mark => self.mark_price,
+            StopTriggerPrice::Last => self.price,
+            StopTriggerPrice::Index => self.index_price,
+        }
+    }
+}
+
 pub fn parse_price_response(json: &str) -> Result<Vec<Prices>> {
     let response: PriceResponse = serde_json::from_str::<PriceResponse>(json)?;
 
@@ -501,6 +765,28 @@ pub struct WsTickerData {
     pub symbol_type: String,
     pub symbol: String,
     pub ts: String,
+    #[serde(default)]
+    pub mark_price: Option<String>,
+    #[serde(default)]
+    pub index_price: Option<String>,
+}
+
+impl WsTickerData {
+    /// Last / mark / index off one ticker push; mark and index fall back to last
+    /// when the push doesn't carry them.
+    pub fn prices(&self) -> Prices {
+        let last: f64 = self.last_pr.parse().unwrap_or(0.0);
+        let or_last = |v: &Option<String>| {
+            v.as_deref()
+                .and_then(|p| p.parse::<f64>().ok())
+                .unwrap_or(last)
+        };
+        Prices {
+            price: last,
+            index_price: or_last(&self.index_price),
+            mark_price: or_last(&self.mark_price),
+        }
+    }
 }
 
 // WebSocket Candlesticks Channel Types
@@ -569,9 +855,10 @@ pub fn parse_timeframe_to_channel(timeframe: &str) -> Result<String> {
 pub struct BitgetWsClient;
 
 impl BitgetWsClient {
+    /// One connection subscribed to the ticker of every symbol in `inst_ids`
     pub async fn subscribe_tickers(
         inst_type: &str,
-        inst_id: &str,
+        inst_ids: &[String],
     ) -> Result<impl futures_util::Stream<Item = Result<WsTickerData>>, Box<dyn std::error::Error>>
     {
         let url = "wss://ws.bitget.com/v2/ws/public";
@@ -580,13 +867,19 @@ impl BitgetWsClient {
         let (ws_stream, _) = connect_async(url).await?;
         let (mut write, mut read) = ws_stream.split();
 
+        let args: Vec<_> = inst_ids
+            .iter()
+            .map(|inst_id| {
+                json!({
+                    "instType": inst_type,
+                    "channel": "ticker",
+                    "instId": inst_id
+                })
+            })
+            .collect();
         let subscribe_msg = json!({
             "op": "subscribe",
-            "args": [{
-                "instType": inst_type,
-                "channel": "ticker",
-                "instId": inst_id
-            }]
+            "args": args
         });
 
         write
@@ -635,7 +928,6 @@ impl BitgetWsClient {
         std::result::Result::Ok(Box::pin(stream))
     }
 
-    #[allow(dead_code)]
     pub async fn subscribe_candlesticks(
         inst_type: &str,
         inst_id: &str,
@@ -711,6 +1003,46 @@ impl BitgetWsClient {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_http_candle_data_carries_configured_symbol() {
+        let client = HttpCandleData::new("ETHUSDT", Config::for_tests());
+        assert_eq!(client.symbol, "ETHUSDT");
+    }
+
+    #[test]
+    fn test_stop_trigger_basis_decides_the_hit() {
+        // long stop at 99_000: mark has pierced it, last hasn't
+        let prices = Prices {
+            price: 99_050.0,
+            index_price: 99_020.0,
+            mark_price: 98_980.0,
+        };
+        let sl = dec!(99000);
+        let hit = |basis| {
+            Helper::ssl_hit(
+                Helper::f64_to_decimal(prices.trigger_price(basis)),
+                Position::Long,
+                sl,
+            )
+        };
+
+        assert!(hit(StopTriggerPrice::Mark));
+        assert!(!hit(StopTriggerPrice::Last));
+        assert!(!hit(StopTriggerPrice::Index));
+    }
+
+    #[test]
+    fn test_position_detail_is_open_only_while_size_is_held() {
+        let json = r#"[
+            {"symbol": "BTCUSDT", "holdSide": "long", "total": "0.002", "marginCoin": "USDT"},
+            {"symbol": "BTCUSDT", "holdSide": "short", "total": "0", "marginCoin": "USDT"}
+        ]"#;
+
+        let positions: Vec<PositionDetail> = serde_json::from_str(json).unwrap();
+        assert!(positions[0].is_open());
+        assert!(!positions[1].is_open());
+    }
+
     #[test]
     fn test_parse_multiple_prices() {
         let json = r#"{
@@ -2,13 +2,14 @@
 #![allow(clippy::uninlined_format_args)]
 use anyhow::Result;
 use chrono::Utc;
-use log::info;
+use log::debug;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::bot::{OpenPosition, Position};
 use crate::config::Config;
+use crate::encryption;
 use crate::exchange::bitget::PlaceOrderData;
 use crate::helper::Helper;
 
@@ -226,9 +227,17 @@ impl BitunixHttpClient {
             .text()
             .await?;
 
-        info!("bitunix get_pending_positions: {resp}");
+        debug!(
+            "bitunix get_pending_positions: {}",
+            encryption::redact_body(&resp)
+        );
         let parsed: BitunixApiResponse<Vec<PendingPosition>> = serde_json::from_str(&resp)
-            .map_err(|e| anyhow::anyhow!("parse positions: {e}, body: {resp}"))?;
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "parse positions: {e}, body: {}",
+                    encryption::redact_body(&resp)
+                )
+            })?;
 
         if parsed.code != 0 {
             return Ok(None);
@@ -277,16 +286,21 @@ impl BitunixHttpClient {
             .text()
             .await?;
 
-        info!("bitunix place_order response: {resp}");
+        debug!(
+            "bitunix place_order response: {}",
+            encryption::redact_body(&resp)
+        );
 
-        let parsed: BitunixApiResponse<PlaceOrderResponse> = serde_json::from_str(&resp)
-            .map_err(|e| anyhow::anyhow!("parse place_order: {e}, body: {resp}"))?;
+        let parsed: BitunixApiResponse<PlaceOrderResponse> =
+            serde_json::from_str(&resp).map_err(|e| {
+                anyhow::anyhow!(
+                    "parse place_order: {e}, body: {}",
+                    encryption::redact_body(&resp)
+                )
+            })?;
 
         if parsed.code != 0 {
-            return Ok(PlaceOrderData {
-                client_oid: "Failed to place order".into(),
-                order_id: "Failed to place order".into(),
-            });
+            return Ok(PlaceOrderData::failed());
         }
 
         let data = parsed
@@ -318,7 +332,10 @@ impl BitunixHttpClient {
             .text()
             .await?;
 
-        info!("bitunix flash_close response: {resp}");
+        debug!(
+            "bitunix flash_close response: {}",
+            encryption::redact_body(&resp)
+        );
 
         Ok(PlaceOrderData {
             client_oid: position_id.to_string(),
@@ -361,16 +378,21 @@ impl BitunixHttpClient {
             .text()
             .await?;
 
-        info!("bitunix close_partial response: {resp}");
+        debug!(
+            "bitunix close_partial response: {}",
+            encryption::redact_body(&resp)
+        );
 
-        let parsed: BitunixApiResponse<PlaceOrderResponse> = serde_json::from_str(&resp)
-            .map_err(|e| anyhow::anyhow!("parse close_partial: {e}, body: {resp}"))?;
+        let parsed: BitunixApiResponse<PlaceOrderResponse> =
+            serde_json::from_str(&resp).map_err(|e| {
+                anyhow::anyhow!(
+                    "parse close_partial: {e}, body: {}",
+                    encryption::redact_body(&resp)
+                )
+            })?;
 
         if parsed.code != 0 {
-            return Ok(PlaceOrderData {
-                client_oid: "Failed to close partial".into(),
-                order_id: "Failed to close partial".into(),
-            });
+            return Ok(PlaceOrderData::failed());
         }
 
         let data = parsed
@@ -462,10 +484,15 @@ impl BitunixHttpClient {
             .text()
             .await?;
 
-        info!("bitunix tpsl {path} response: {resp}");
+        debug!(
+            "bitunix tpsl {path} response: {}",
+            encryption::redact_body(&resp)
+        );
 
-        let parsed: BitunixApiResponse<TpslOrderResponse> = serde_json::from_str(&resp)
-            .map_err(|e| anyhow::anyhow!("parse tpsl: {e}, body: {resp}"))?;
+        let parsed: BitunixApiResponse<TpslOrderResponse> =
+            serde_json::from_str(&resp).map_err(|e| {
+                anyhow::anyhow!("parse tpsl: {e}, body: {}", encryption::redact_body(&resp))
+            })?;
 
         if parsed.code != 0 {
             return Err(anyhow::anyhow!("Bitunix TPSL error: {}", parsed.msg));
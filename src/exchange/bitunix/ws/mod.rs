@@ -51,6 +51,9 @@ pub struct WsTickerData {
     pub bv: String,
     /// Best ask volume
     pub av: String,
+    /// When the exchange published the update (epoch ms), from the enclosing message
+    #[serde(default)]
+    pub ts: u64,
 }
 
 // ─── Kline ───────────────────────────────────────────────────────────────────
@@ -157,8 +160,9 @@ impl BitunixWsClient {
 
                         match serde_json::from_str::<WsTickerResponse>(&text) {
                             std::result::Result::Ok(resp) => {
-                                for ticker in resp.data {
+                                for mut ticker in resp.data {
                                     if ticker.s == symbol_owned {
+                                        ticker.ts = resp.ts;
                                         yield ticker;
                                     }
                                 }
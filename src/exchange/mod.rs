@@ -2,6 +2,7 @@ use anyhow::Ok;
 use anyhow::Result;
 use async_trait::async_trait;
 use log::info;
+use rust_decimal::Decimal;
 
 use crate::bot::OpenPosition;
 use crate::exchange::bitget::fees::VipFeeRate;
@@ -16,6 +17,19 @@ use crate::exchange::bitunix::fees::BitunixFuturesFees;
 pub mod bitget;
 pub mod bitunix;
 
+/// What `get_current_price` returns when Bitget's ticker can't be parsed. Never a
+/// real price; anything that acts on a price must check for it.
+pub const PRICE_UNAVAILABLE: f64 = 1.11;
+
+/// How much of a resting limit order has filled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderFill {
+    Unfilled,
+    /// Some of it filled, possibly before it was cancelled; carries the filled quantity
+    Partial(Decimal),
+    Filled,
+}
+
 #[async_trait]
 pub trait Exchange: Send + Sync {
     /// Return the latest spot price for the configured symbol.
@@ -44,6 +58,49 @@ pub trait Exchange: Send + Sync {
         Ok(None)
     }
 
+    /// Whether the exchange currently holds an open position for the symbol, used to
+    /// reconcile an interrupted entry on restart. Default: `None` (can't tell).
+    async fn has_open_position(&self) -> Result<Option<bool>> {
+        Ok(None)
+    }
+
+    /// Make one authenticated call so bad keys / passphrases fail at boot rather
+    /// than on the first order. Default: no-op.
+    async fn verify_credentials(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Rest a post-only limit entry at `limit_price`. Default: unsupported.
+    async fn place_limit_order(
+        &self,
+        _open_position: &OpenPosition,
+        _limit_price: f64,
+    ) -> Result<PlaceOrderData> {
+        Err(anyhow::anyhow!(
+            "limit entries are not supported on this exchange"
+        ))
+    }
+
+    /// Cancel a resting order. Default: no-op.
+    async fn cancel_order(&self, _open_position: &OpenPosition, _order_id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// How much of a resting order has filled. Default: `None` (can't tell).
+    async fn order_filled(
+        &self,
+        _open_position: &OpenPosition,
+        _order_id: &str,
+    ) -> Result<Option<OrderFill>> {
+        Ok(None)
+    }
+
+    /// Move the exchange-side stop on the open position so it still holds if the bot
+    /// goes away. Default: no-op; the bot watches its stop either way.
+    async fn update_stop_loss(&self, _open_position: &OpenPosition, _sl_price: f64) -> Result<()> {
+        Ok(())
+    }
+
     /// Register the initial TP/SL order on a newly opened position.
     /// Only meaningful for Bitunix (Bitget embeds TPSL in the order itself).
     /// Default: no-op.
@@ -63,6 +120,7 @@ pub trait Exchange: Send + Sync {
 pub struct HttpExchange {
     pub client: reqwest::Client,
     pub(crate) symbol: String,
+    pub bitget: HttpCandleData,
     #[allow(dead_code)]
     pub redis_conn: redis::aio::MultiplexedConnection,
 }
@@ -116,8 +174,11 @@ impl Exchange for HttpExchange {
         &self,
         open_position: &OpenPosition,
     ) -> Result<PlaceOrderData, anyhow::Error> {
-        let new_bitget_futures = <HttpCandleData as bitget::FuturesCall>::new();
-        let execute_call = new_bitget_futures.new_futures_call(open_position).await?;
+        let account = self.bitget.account_for(open_position);
+        let execute_call = self
+            .bitget
+            .new_futures_call(account, open_position)
+            .await?;
         Ok(execute_call)
     }
 
@@ -130,16 +191,17 @@ impl Exchange for HttpExchange {
             "Mock market {:?} for {:.6} {} at {price:.2}",
             open_position.pos, open_position.entry_price, self.symbol
         );
-        let new_bitget_futures = <HttpCandleData as bitget::FuturesCall>::new();
-        let execute_call = new_bitget_futures
-            .modify_futures_order(open_position)
+        let account = self.bitget.account_for(open_position);
+        let execute_call = self
+            .bitget
+            .modify_futures_order(account, open_position)
             .await?;
         Ok(execute_call)
     }
 
     async fn get_funding_rate(&self) -> Result<f64, anyhow::Error> {
-        let bitget_data = <HttpCandleData as bitget::CandleData>::new();
-        let funding_rates = bitget_data
+        let funding_rates = self
+            .bitget
             .get_history_funding_rate("1".to_string())
             .await?;
         if let Some(first) = funding_rates.first() {
@@ -148,9 +210,74 @@ impl Exchange for HttpExchange {
         Ok(0.0)
     }
 
+    async fn place_limit_order(
+        &self,
+        open_position: &OpenPosition,
+        limit_price: f64,
+    ) -> Result<PlaceOrderData, anyhow::Error> {
+        let account = self.bitget.account_for(open_position);
+        self.bitget
+            .new_limit_order(account, open_position, limit_price)
+            .await
+    }
+
+    async fn cancel_order(&self, open_position: &OpenPosition, order_id: &str) -> Result<()> {
+        let account = self.bitget.account_for(open_position);
+        self.bitget.cancel_order(account, order_id).await
+    }
+
+    async fn order_filled(
+        &self,
+        open_position: &OpenPosition,
+        order_id: &str,
+    ) -> Result<Option<OrderFill>> {
+        let account = self.bitget.account_for(open_position);
+        let detail = self.bitget.order_detail(account, order_id).await?;
+        if detail.state == "filled" {
+            return Ok(Some(OrderFill::Filled));
+        }
+        // `partially_filled`, or `canceled` after part of it filled
+        let filled: Decimal = detail.base_volume.parse().map_err(|e| {
+            anyhow::anyhow!(
+                "Bad filled volume {:?} on order {order_id}: {e}",
+                detail.base_volume
+            )
+        })?;
+        if filled > Decimal::ZERO {
+            return Ok(Some(OrderFill::Partial(filled)));
+        }
+        Ok(Some(OrderFill::Unfilled))
+    }
+
+    async fn update_stop_loss(&self, open_position: &OpenPosition, sl_price: f64) -> Result<()> {
+        let account = self.bitget.account_for(open_position);
+        self.bitget
+            .set_position_stop_loss(account, open_position, sl_price)
+            .await
+    }
+
+    /// Whether any configured account holds the symbol
+    async fn has_open_position(&self) -> Result<Option<bool>> {
+        for account in self.bitget.accounts() {
+            let positions = self.bitget.single_position(account).await?;
+            if positions.iter().any(|p| p.is_open()) {
+                return Ok(Some(true));
+            }
+        }
+        Ok(Some(false))
+    }
+
+    async fn verify_credentials(&self) -> Result<()> {
+        for account in self.bitget.accounts() {
+            account.verify(&self.client).await?;
+            info!("Bitget credentials accepted for account '{}'", account.name);
+        }
+        Ok(())
+    }
+
     async fn get_fee_rates(&self) -> Result<VipFeeRate, anyhow::Error> {
         let conn = self.redis_conn.clone();
-        let fees = bitget::fees::BitgetFuturesFees::new(conn);
+        let fees = bitget::fees::BitgetFuturesFees::new(conn, self.client.clone());
         let bitget_data = fees.get_vip_fee_rates().await?;
         Ok(bitget_data.first().unwrap().clone())
     }
@@ -208,6 +335,24 @@ impl Exchange for BitunixExchange {
         self.client.get_pending_position_id().await
     }
 
+    async fn has_open_position(&self) -> Result<Option<bool>> {
+        Ok(Some(self.get_position_id().await?.is_some()))
+    }
+
+    /// Moves the position's stop with a TP/SL modify; the take-profit is left as placed
+    async fn update_stop_loss(&self, open_position: &OpenPosition, sl_price: f64) -> Result<()> {
+        let position_id = match &open_position.position_id {
+            Some(position_id) => position_id.clone(),
+            None => self.get_position_id().await?.ok_or_else(|| {
+                anyhow::anyhow!("No open Bitunix position to move the stop on")
+            })?,
+        };
+        self.client
+            .modify_position_tpsl(&position_id, None, Some(sl_price))
+            .await
+            .map(|_| ())
+    }
+
     async fn place_initial_tpsl(
         &self,
         position_id: &str,
@@ -220,3 +365,149 @@ impl Exchange for BitunixExchange {
             .map(|_| ())
     }
 }
+
+/// In-memory `Exchange` for tests: fixed price, no network, and every order is
+/// recorded instead of sent.
+#[cfg(test)]
+pub struct MockExchange {
+    pub price: f64,
+    pub orders: std::sync::Mutex<Vec<OpenPosition>>,
+    pub limit_orders: std::sync::Mutex<Vec<(OpenPosition, f64)>>,
+    pub cancelled: std::sync::Mutex<Vec<String>>,
+    pub stop_updates: std::sync::Mutex<Vec<f64>>,
+    /// What `order_filled` reports for every resting order
+    pub fill: std::sync::Mutex<Option<OrderFill>>,
+    /// Answer new market orders with the rejection sentinel instead of filling them
+    pub reject_orders: bool,
+    /// Answer closes with the rejection sentinel instead of filling them
+    pub reject_closes: bool,
+}
+
+#[cfg(test)]
+impl MockExchange {
+    pub fn new(price: f64) -> Self {
+        Self {
+            price,
+            orders: std::sync::Mutex::new(Vec::new()),
+            limit_orders: std::sync::Mutex::new(Vec::new()),
+            cancelled: std::sync::Mutex::new(Vec::new()),
+            stop_updates: std::sync::Mutex::new(Vec::new()),
+            fill: std::sync::Mutex::new(None),
+            reject_orders: false,
+            reject_closes: false,
+        }
+    }
+
+    fn record(&self, open_position: &OpenPosition) -> PlaceOrderData {
+        let mut orders = self.orders.lock().unwrap();
+        orders.push(open_position.clone());
+        PlaceOrderData {
+            client_oid: open_position.id.to_string(),
+            order_id: format!("mock-{}", orders.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Exchange for MockExchange {
+    async fn get_bitget_price(&self) -> Result<f64> {
+        Ok(self.price)
+    }
+
+    async fn get_current_price(&self) -> Result<f64> {
+        Ok(self.price)
+    }
+
+    async fn place_market_order(&self, open_position: &OpenPosition) -> Result<PlaceOrderData> {
+        if self.reject_orders {
+            return Ok(PlaceOrderData::failed());
+        }
+        Ok(self.record(open_position))
+    }
+
+    async fn modify_market_order(&self, open_position: &OpenPosition) -> Result<PlaceOrderData> {
+        if self.reject_closes {
+            return Ok(PlaceOrderData::failed());
+        }
+        Ok(self.record(open_position))
+    }
+
+    async fn get_funding_rate(&self) -> Result<f64> {
+        Ok(0.0)
+    }
+
+    async fn place_limit_order(
+        &self,
+        open_position: &OpenPosition,
+        limit_price: f64,
+    ) -> Result<PlaceOrderData> {
+        let mut limit_orders = self.limit_orders.lock().unwrap();
+        limit_orders.push((open_position.clone(), limit_price));
+        Ok(PlaceOrderData {
+            client_oid: open_position.id.to_string(),
+            order_id: format!("mock-limit-{}", limit_orders.len()),
+        })
+    }
+
+    async fn cancel_order(&self, _open_position: &OpenPosition, order_id: &str) -> Result<()> {
+        self.cancelled.lock().unwrap().push(order_id.to_string());
+        Ok(())
+    }
+
+    async fn order_filled(
+        &self,
+        _open_position: &OpenPosition,
+        _order_id: &str,
+    ) -> Result<Option<OrderFill>> {
+        Ok(*self.fill.lock().unwrap())
+    }
+
+    async fn has_open_position(&self) -> Result<Option<bool>> {
+        Ok(Some(!self.orders.lock().unwrap().is_empty()))
+    }
+
+    async fn update_stop_loss(&self, _open_position: &OpenPosition, sl_price: f64) -> Result<()> {
+        self.stop_updates.lock().unwrap().push(sl_price);
+        Ok(())
+    }
+
+    async fn get_fee_rates(&self) -> Result<VipFeeRate> {
+        Ok(VipFeeRate {
+            level: "mock".to_string(),
+            deal_amount: "0".to_string(),
+            asset_amount: "0".to_string(),
+            taker_fee_rate: 0.0006,
+            maker_fee_rate: 0.0002,
+            btc_withdraw_amount: "0".to_string(),
+            usdt_withdraw_amount: "0".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::Position;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_mock_exchange_records_orders_without_network() {
+        let exchange = MockExchange::new(65_000.0);
+        let open = OpenPosition {
+            pos: Position::Long,
+            entry_price: dec!(65000),
+            position_size: dec!(0.01),
+            ..OpenPosition::default_open_position()
+        };
+
+        let placed = exchange.place_market_order(&open).await.unwrap();
+        assert_eq!(placed.order_id, "mock-1");
+        assert_eq!(placed.client_oid, open.id.to_string());
+        assert_eq!(exchange.get_current_price().await.unwrap(), 65_000.0);
+
+        let orders = exchange.orders.lock().unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].entry_price, dec!(65000));
+    }
+}
@@ -1,26 +1,119 @@
 use anyhow::anyhow;
 use anyhow::Result;
-use chrono::Datelike;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use redis::{aio::MultiplexedConnection, AsyncCommands};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::bot::ClosedPosition;
+use crate::cache::codec;
 use crate::bot::Position;
+use crate::bot::ZoneId;
 use crate::bot::{self};
 use crate::config::Config;
 use crate::helper::Helper;
 use crate::helper::TRADING_BOT_CLOSE_POSITIONS;
 use crate::helper::TRADING_CAPITAL;
 
+pub mod rollup;
+
 pub struct Graph {
     pub config: Config,
 }
 
+/// What one Sharpe observation is: a single closed trade, or a UTC day's trades summed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReturnBasis {
+    #[default]
+    Trade,
+    Daily,
+}
+
+/// Performance snapshot over the closed positions; money in USDT, rates in percent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PerformanceSummary {
+    pub total_trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub win_rate: f64,
+    /// Gross wins over gross losses; `None` until there is a loss
+    pub profit_factor: Option<f64>,
+    pub average_win: Decimal,
+    pub average_loss: Decimal,
+    pub largest_win: Decimal,
+    pub largest_loss: Decimal,
+    pub sharpe_ratio: Option<f64>,
+}
+
+/// One zone's record over the trades entered in it; PnL is net, in USDT
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ZonePerformance {
+    pub zone_id: ZoneId,
+    pub trades: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub total_pnl: Decimal,
+}
+
+/// Flat view of a `ClosedPosition` for spreadsheets
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    id: Uuid,
+    side: Option<Position>,
+    entry_price: Decimal,
+    entry_time: DateTime<Utc>,
+    exit_price: Decimal,
+    exit_time: DateTime<Utc>,
+    quantity: Option<Decimal>,
+    pnl: Decimal,
+    pnl_after_fees: Option<Decimal>,
+    roi: Option<Decimal>,
+    leverage: Option<Decimal>,
+    margin: Option<Decimal>,
+    sl: Option<Decimal>,
+    entry_fee: Option<Decimal>,
+    exit_fee: Option<Decimal>,
+    funding_fee: Option<Decimal>,
+    max_adverse_excursion: Option<Decimal>,
+    max_favorable_excursion: Option<Decimal>,
+    order_id: Option<&'a str>,
+    zone_id: Option<ZoneId>,
+    symbol: Option<&'a str>,
+}
+
+impl<'a> From<&'a bot::ClosedPosition> for CsvRow<'a> {
+    fn from(pos: &'a bot::ClosedPosition) -> Self {
+        Self {
+            id: pos.id,
+            side: pos.position.or(pos.side),
+            entry_price: pos.entry_price,
+            entry_time: pos.entry_time,
+            exit_price: pos.exit_price,
+            exit_time: pos.exit_time,
+            quantity: pos.quantity,
+            pnl: pos.pnl,
+            pnl_after_fees: pos.pnl_after_fees,
+            roi: pos.roi,
+            leverage: pos.leverage,
+            margin: pos.margin,
+            sl: pos.sl,
+            entry_fee: pos.entry_fee,
+            exit_fee: pos.exit_fee,
+            funding_fee: pos.funding_fee,
+            max_adverse_excursion: pos.max_adverse_excursion,
+            max_favorable_excursion: pos.max_favorable_excursion,
+            order_id: pos.order_id.as_deref(),
+            zone_id: pos.zone_id,
+            symbol: pos.symbol.as_deref(),
+        }
+    }
+}
+
 impl Graph {
     pub fn new() -> Self {
         let config = Config::from_env().expect("NO CONFIGURATION");
@@ -33,17 +126,7 @@ impl Graph {
         &mut self,
         positions: &[bot::ClosedPosition],
     ) -> BTreeMap<(i32, u32), f64> {
-        let grouped = Self::group_by_week(self, positions);
-        grouped
-            .into_iter()
-            .map(|(k, pcts)| {
-                let mut prod = 0.0; //1.0;
-                for &pct in &pcts {
-                    prod += pct; //1.0 + pct / 100.0;
-                }
-                (k, prod) //- 1.0 subtract the “starting capital”
-            })
-            .collect()
+        Self::cumulative(Self::group_by_week(self, positions))
     }
 
     /// Same idea, but by calendar month
@@ -51,7 +134,34 @@ impl Graph {
         &mut self,
         positions: &[bot::ClosedPosition],
     ) -> BTreeMap<(i32, u32), f64> {
-        let grouped = Self::group_by_month(self, positions);
+        Self::cumulative(Self::group_by_month(self, positions))
+    }
+
+    /// Weekly ROI after entry/exit fees and funding
+    pub fn cumulative_net_roi_weekly(
+        &mut self,
+        positions: &[bot::ClosedPosition],
+    ) -> BTreeMap<(i32, u32), f64> {
+        Self::cumulative(Self::group(
+            positions,
+            Helper::iso_week_key,
+            Self::trade_net_pnl_percent,
+        ))
+    }
+
+    /// Monthly ROI after entry/exit fees and funding
+    pub fn cumulative_net_roi_monthly(
+        &mut self,
+        positions: &[bot::ClosedPosition],
+    ) -> BTreeMap<(i32, u32), f64> {
+        Self::cumulative(Self::group(
+            positions,
+            Helper::month_key,
+            Self::trade_net_pnl_percent,
+        ))
+    }
+
+    fn cumulative(grouped: HashMap<(i32, u32), Vec<f64>>) -> BTreeMap<(i32, u32), f64> {
         grouped
             .into_iter()
             .map(|(k, pcts)| {
@@ -59,7 +169,7 @@ impl Graph {
                 for &pct in &pcts {
                     prod += pct; //1.0 + pct / 100.0;
                 }
-                (k, prod) //- 1.0
+                (k, prod) //- 1.0 subtract the “starting capital”
             })
             .collect()
     }
@@ -82,6 +192,12 @@ impl Graph {
             order_id: None,
             pnl_after_fees: None,
             exit_fee: None,
+            entry_fee: None,
+            funding_fee: None,
+            max_adverse_excursion: None,
+            max_favorable_excursion: None,
+            zone_id: None,
+            symbol: None,
         };
 
         closed.as_str()
@@ -92,65 +208,274 @@ impl Graph {
     ) -> Result<Vec<bot::ClosedPosition>> {
         let key = TRADING_BOT_CLOSE_POSITIONS; //SCALPER_CLOSED_POSITIONS TRADING_BOT_CLOSE_POSITIONS
 
-        let mut raw_jsons = [Self::load_default_closed_position()].to_vec();
+        let mut raw_values = [Self::load_default_closed_position().into_bytes()].to_vec();
 
         let exists: usize = conn.exists(key).await?;
 
         // `LRANGE 0 -1` returns the whole list (newest → oldest)
         if exists != 0 {
-            raw_jsons = conn.lrange(key, 0, -1).await?;
+            raw_values = conn.lrange(key, 0, -1).await?;
         }
 
-        // Deserialize each JSON string into a struct
-        raw_jsons
+        // Deserialize each entry (JSON or MessagePack) into a struct
+        raw_values
             .into_iter()
-            .map(|j| {
-                serde_json::from_str::<bot::ClosedPosition>(&j)
+            .map(|raw| {
+                codec::decode::<bot::ClosedPosition>(&raw)
                     .map_err(|e| anyhow!("Failed to parse: {}", e))
             })
             .collect()
     }
 
+    /// Per-trade PnL % used by every weekly/monthly view; `None` for placeholder
+    /// records without prices.
+    pub fn trade_pnl_percent(pos: &bot::ClosedPosition) -> Option<f64> {
+        if pos.entry_price == dec!(0.00) || pos.exit_price == dec!(0.00) {
+            return None;
+        }
+        Some(Helper::pnl_percent(
+            Helper::decimal_to_f64(pos.entry_price),
+            Helper::decimal_to_f64(pos.exit_price),
+            //pos.leverage.unwrap_or(self.config.leverage),
+            pos.position.unwrap_or(bot::Position::Flat),
+        ))
+    }
+
+    /// Net per-trade PnL %: the stored `pnl_after_fees` over the notional, or the
+    /// gross % less the recorded fees. Records with neither fall back to gross.
+    pub fn trade_net_pnl_percent(pos: &bot::ClosedPosition) -> Option<f64> {
+        let gross = Self::trade_pnl_percent(pos)?;
+        let notional = pos.entry_price * pos.quantity.unwrap_or(dec!(0.00));
+        if notional.is_zero() {
+            return Some(gross);
+        }
+
+        if let Some(net) = pos.pnl_after_fees {
+            return Some(Helper::decimal_to_f64(net / notional * dec!(100.0)));
+        }
+
+        match pos.exit_fee {
+            Some(exit_fee) => {
+                let fees = exit_fee
+                    + pos.entry_fee.unwrap_or(dec!(0.00))
+                    + pos.funding_fee.unwrap_or(dec!(0.00));
+                Some(gross - Helper::decimal_to_f64(fees / notional * dec!(100.0)))
+            }
+            None => Some(gross),
+        }
+    }
+
+    /// Largest peak-to-trough fall of the realised equity curve, as a percent of the
+    /// peak. The curve starts at `starting_capital` and follows [`Graph::equity_curve`],
+    /// so a loss straight after the first trade is measured against the account.
+    pub fn max_drawdown(positions: &[bot::ClosedPosition], starting_capital: f64) -> f64 {
+        let mut peak = starting_capital;
+        let mut max_dd = 0.0_f64;
+        for (_, equity) in Self::equity_curve(positions, starting_capital) {
+            if equity > peak {
+                peak = equity;
+            } else if peak > 0.0 {
+                max_dd = max_dd.max((peak - equity) / peak);
+            }
+        }
+        max_dd * 100.0
+    }
+
+    /// Account equity after each close: `starting_capital` plus the running net PnL,
+    /// in `exit_time` order. Placeholder records are skipped.
+    pub fn equity_curve(
+        positions: &[bot::ClosedPosition],
+        starting_capital: f64,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        let mut closed: Vec<&bot::ClosedPosition> = positions
+            .iter()
+            .filter(|p| Self::trade_pnl_percent(p).is_some())
+            .collect();
+        closed.sort_by_key(|p| p.exit_time);
+
+        let mut equity = Helper::f64_to_decimal(starting_capital);
+        closed
+            .into_iter()
+            .map(|pos| {
+                equity += pos.pnl_after_fees.unwrap_or(pos.pnl);
+                (pos.exit_time, Helper::decimal_to_f64(equity))
+            })
+            .collect()
+    }
+
+    /// Writes `positions` as CSV, one row per trade with a header row; placeholder
+    /// records are skipped.
+    pub fn write_csv<W: std::io::Write>(
+        positions: &[bot::ClosedPosition],
+        writer: W,
+    ) -> Result<()> {
+        let mut wtr = csv::Writer::from_writer(writer);
+        for pos in positions
+            .iter()
+            .filter(|p| Self::trade_pnl_percent(p).is_some())
+        {
+            wtr.serialize(CsvRow::from(pos))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Net PnL of every real trade; placeholders are skipped
+    fn trade_pnls(positions: &[bot::ClosedPosition]) -> Vec<Decimal> {
+        positions
+            .iter()
+            .filter(|p| Self::trade_pnl_percent(p).is_some())
+            .map(|p| p.pnl_after_fees.unwrap_or(p.pnl))
+            .collect()
+    }
+
+    /// Share of trades closed in profit, in percent
+    pub fn win_rate(positions: &[bot::ClosedPosition]) -> f64 {
+        let pnls = Self::trade_pnls(positions);
+        if pnls.is_empty() {
+            return 0.0;
+        }
+        let wins = pnls.iter().filter(|p| **p > Decimal::ZERO).count();
+        wins as f64 / pnls.len() as f64 * 100.0
+    }
+
+    /// Trade count, win rate and net PnL per entry zone, worst zone first. Trades
+    /// closed before zones were tagged, and placeholders, are left out.
+    pub fn performance_by_zone(positions: &[bot::ClosedPosition]) -> Vec<ZonePerformance> {
+        let mut by_zone: HashMap<ZoneId, Vec<Decimal>> = HashMap::new();
+        for pos in positions
+            .iter()
+            .filter(|p| Self::trade_pnl_percent(p).is_some())
+        {
+            if let Some(zone_id) = pos.zone_id {
+                by_zone
+                    .entry(zone_id)
+                    .or_default()
+                    .push(pos.pnl_after_fees.unwrap_or(pos.pnl));
+            }
+        }
+
+        let mut zones: Vec<ZonePerformance> = by_zone
+            .into_iter()
+            .map(|(zone_id, pnls)| {
+                let wins = pnls.iter().filter(|p| **p > Decimal::ZERO).count();
+                ZonePerformance {
+                    zone_id,
+                    trades: pnls.len(),
+                    wins,
+                    win_rate: wins as f64 / pnls.len() as f64 * 100.0,
+                    total_pnl: pnls.iter().sum(),
+                }
+            })
+            .collect();
+        zones.sort_by_key(|z| z.total_pnl);
+        zones
+    }
+
+    /// Gross profit over gross loss; `None` while there are no losing trades
+    pub fn profit_factor(positions: &[bot::ClosedPosition]) -> Option<f64> {
+        let pnls = Self::trade_pnls(positions);
+        let won: Decimal = pnls.iter().filter(|p| **p > Decimal::ZERO).sum();
+        let lost: Decimal = pnls.iter().filter(|p| **p < Decimal::ZERO).sum();
+        if lost.is_zero() {
+            return None;
+        }
+        Some(Helper::decimal_to_f64(won / lost.abs()))
+    }
+
+    /// Mean excess return over its sample standard deviation, not annualised. Returns
+    /// are net per-trade PnL % (summed per UTC day for `Daily`); `risk_free_rate` is
+    /// in percent per observation. `None` with fewer than two observations or no spread.
+    pub fn sharpe_ratio(
+        positions: &[bot::ClosedPosition],
+        risk_free_rate: f64,
+        basis: ReturnBasis,
+    ) -> Option<f64> {
+        let returns: Vec<f64> = match basis {
+            ReturnBasis::Trade => positions
+                .iter()
+                .filter_map(Self::trade_net_pnl_percent)
+                .collect(),
+            ReturnBasis::Daily => {
+                let mut days: BTreeMap<chrono::NaiveDate, f64> = BTreeMap::new();
+                for pos in positions {
+                    if let Some(pct) = Self::trade_net_pnl_percent(pos) {
+                        *days.entry(pos.exit_time.date_naive()).or_default() += pct;
+                    }
+                }
+                days.into_values().collect()
+            }
+        };
+        if returns.len() < 2 {
+            return None;
+        }
+
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let std_dev = variance.sqrt();
+        if std_dev == 0.0 || !std_dev.is_finite() {
+            return None;
+        }
+        Some((mean - risk_free_rate) / std_dev)
+    }
+
+    pub fn summary(
+        positions: &[bot::ClosedPosition],
+        risk_free_rate: f64,
+        basis: ReturnBasis,
+    ) -> PerformanceSummary {
+        let pnls = Self::trade_pnls(positions);
+        let wins: Vec<Decimal> = pnls.iter().copied().filter(|p| *p > Decimal::ZERO).collect();
+        let losses: Vec<Decimal> = pnls.iter().copied().filter(|p| *p < Decimal::ZERO).collect();
+        let average = |v: &[Decimal]| {
+            if v.is_empty() {
+                Decimal::ZERO
+            } else {
+                (v.iter().sum::<Decimal>() / Decimal::from(v.len())).round_dp(2)
+            }
+        };
+
+        PerformanceSummary {
+            total_trades: pnls.len(),
+            wins: wins.len(),
+            losses: losses.len(),
+            win_rate: Self::win_rate(positions),
+            profit_factor: Self::profit_factor(positions),
+            average_win: average(&wins),
+            average_loss: average(&losses),
+            largest_win: wins.iter().copied().max().unwrap_or_default(),
+            largest_loss: losses.iter().copied().min().unwrap_or_default(),
+            sharpe_ratio: Self::sharpe_ratio(positions, risk_free_rate, basis),
+        }
+    }
+
     /// Returns a map `[(year, week), Vec<pnl_percent>]`
     pub fn group_by_week(
         &mut self,
         positions: &[bot::ClosedPosition],
     ) -> HashMap<(i32, u32), Vec<f64>> {
-        let mut map: HashMap<(i32, u32), Vec<f64>> = HashMap::new();
-        for pos in positions {
-            let iso = pos.exit_time.iso_week(); // ISO‑8601 week (Mon–Sun)
-            let key = (iso.year(), iso.week());
-
-            if pos.entry_price != dec!(0.00) && pos.exit_price != dec!(0.00) {
-                let pnl_percent = Helper::pnl_percent(
-                    Helper::decimal_to_f64(pos.entry_price),
-                    Helper::decimal_to_f64(pos.exit_price),
-                    //pos.leverage.unwrap_or(self.config.leverage),
-                    pos.position.unwrap_or(bot::Position::Flat),
-                );
-                map.entry(key).or_default().push(pnl_percent);
-            }
-        }
-        map
+        // ISO‑8601 week (Mon–Sun), UTC
+        Self::group(positions, Helper::iso_week_key, Self::trade_pnl_percent)
     }
 
     /// Returns a map `[(year, month), Vec<pnl_percent>]`
     fn group_by_month(
         &mut self,
         positions: &[bot::ClosedPosition],
+    ) -> HashMap<(i32, u32), Vec<f64>> {
+        Self::group(positions, Helper::month_key, Self::trade_pnl_percent)
+    }
+
+    fn group(
+        positions: &[bot::ClosedPosition],
+        key: impl Fn(DateTime<Utc>) -> (i32, u32),
+        pnl_percent: impl Fn(&bot::ClosedPosition) -> Option<f64>,
     ) -> HashMap<(i32, u32), Vec<f64>> {
         let mut map: HashMap<(i32, u32), Vec<f64>> = HashMap::new();
         for pos in positions {
-            let key = (pos.exit_time.year(), pos.exit_time.month());
-
-            if pos.entry_price != dec!(0.00) && pos.exit_price != dec!(0.00) {
-                let pnl_percent = Helper::pnl_percent(
-                    Helper::decimal_to_f64(pos.entry_price),
-                    Helper::decimal_to_f64(pos.exit_price),
-                    //pos.leverage.unwrap_or(self.config.leverage),
-                    pos.position.unwrap_or(bot::Position::Flat),
-                );
-                map.entry(key).or_default().push(pnl_percent);
+            if let Some(pct) = pnl_percent(pos) {
+                map.entry(key(pos.exit_time)).or_default().push(pct);
             }
         }
         map
@@ -158,12 +483,35 @@ impl Graph {
 
     /// PnL and ROI relative to the margin you actually put up.
     fn pnl_and_roi(&mut self, pos: &bot::ClosedPosition) -> (Decimal, Decimal) {
-        let dec_config_margin = Helper::f64_to_decimal(self.config.margin);
-        let dec_config_leverage = Helper::f64_to_decimal(self.config.leverage);
+        Self::net_pnl_and_roi(
+            pos,
+            Helper::f64_to_decimal(self.config.margin),
+            Helper::f64_to_decimal(self.config.leverage),
+        )
+    }
+
+    /// Prefers the stored net PnL (after fees) so the report matches what hit the
+    /// account; legacy records without it fall back to a gross recomputation.
+    fn net_pnl_and_roi(
+        pos: &bot::ClosedPosition,
+        default_margin: Decimal,
+        default_leverage: Decimal,
+    ) -> (Decimal, Decimal) {
+        let margin = pos.margin.unwrap_or(default_margin);
+
+        if let Some(net) = pos.pnl_after_fees {
+            let roi = if margin.is_zero() {
+                dec!(0.00)
+            } else {
+                (net / margin) * dec!(100.0)
+            };
+            return (net, roi);
+        }
+
         let qty = Helper::contract_amount(
             pos.entry_price,
-            pos.margin.unwrap_or(dec_config_margin),
-            pos.leverage.unwrap_or(dec_config_leverage),
+            margin,
+            pos.leverage.unwrap_or(default_leverage),
         );
 
         let pnl = Helper::compute_pnl(
@@ -173,8 +521,6 @@ impl Graph {
             pos.exit_price,
         );
 
-        let margin = pos.margin.unwrap_or(dec_config_margin);
-
         let mut roi: Decimal = dec!(0.00); // fraction – multiply by 100 for percent
 
         //if pnl != 0.00 && margin != 0.00 {
@@ -256,18 +602,22 @@ impl Graph {
             Helper::decimal_to_f64(overall_roi) * 100.0
         );
 
-        println!("--- Cumulative ROI % per week ---");
+        println!("--- Cumulative ROI % per week (gross / net of fees) ---");
         //((y, w), pct)
+        let net_weekly = Self::cumulative_net_roi_weekly(self, &positions);
         for ((y, w), pct) in Self::cumulative_roi_weekly(self, &positions) {
-            println!("{y:04}-W{w:02}: {pct:.2} %");
+            let net = net_weekly.get(&(y, w)).copied().unwrap_or(pct);
+            println!("{y:04}-W{w:02}: {pct:.2} % / {net:.2} %");
         }
 
         // ------------------------------------------------------------------
         // 2. Cumulative ROI per month (as a percent)
         // ------------------------------------------------------------------
-        println!("\n--- Cumulative ROI % per month ---"); //((y, m), roi)
+        println!("\n--- Cumulative ROI % per month (gross / net of fees) ---"); //((y, m), roi)
+        let net_monthly = Self::cumulative_net_roi_monthly(self, &positions);
         for ((y, m), roi) in Self::cumulative_roi_monthly(self, &positions) {
-            println!("{y:04}-{m:02}: {roi:.2} %"); //* 100.0
+            let net = net_monthly.get(&(y, m)).copied().unwrap_or(roi);
+            println!("{y:04}-{m:02}: {roi:.2} % / {net:.2} %"); //* 100.0
         }
         println!("\n------------------------------------------------------------------------");
 
@@ -282,3 +632,210 @@ impl Graph {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::Zone;
+    use chrono::TimeZone;
+
+    fn closed_long(pnl_after_fees: Option<Decimal>) -> ClosedPosition {
+        ClosedPosition {
+            id: Uuid::nil(),
+            pnl: dec!(10.00),
+            position: Some(Position::Long),
+            side: Some(Position::Long),
+            entry_price: dec!(100000.00),
+            entry_time: Utc::now(),
+            exit_price: dec!(101000.00),
+            exit_time: Utc::now(),
+            quantity: Some(dec!(0.01)),
+            sl: None,
+            roi: None,
+            leverage: Some(dec!(20)),
+            margin: Some(dec!(50.00)),
+            order_id: None,
+            pnl_after_fees,
+            exit_fee: Some(dec!(0.61)),
+            entry_fee: None,
+            funding_fee: None,
+            max_adverse_excursion: None,
+            max_favorable_excursion: None,
+            zone_id: None,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn test_stored_net_pnl_is_reported() {
+        let pos = closed_long(Some(dec!(8.79)));
+        let (pnl, roi) = Graph::net_pnl_and_roi(&pos, dec!(100), dec!(20));
+
+        assert_eq!(pnl, dec!(8.79));
+        assert_eq!(roi, dec!(8.79) / dec!(50.00) * dec!(100.0));
+    }
+
+    #[test]
+    fn test_legacy_record_recomputes_gross_pnl() {
+        let pos = closed_long(None);
+        let (pnl, _) = Graph::net_pnl_and_roi(&pos, dec!(100), dec!(20));
+
+        // (101000 - 100000) * 0.01
+        assert_eq!(pnl, dec!(10.00));
+    }
+
+    #[test]
+    fn test_net_roi_subtracts_fees_and_falls_back_to_gross() {
+        // 1% gross move on 1000 notional
+        let gross = Graph::trade_pnl_percent(&closed_long(None)).unwrap();
+        assert!((gross - 1.0).abs() < 1e-9);
+
+        let stored = closed_long(Some(dec!(8.79)));
+        assert!((Graph::trade_net_pnl_percent(&stored).unwrap() - 0.879).abs() < 1e-9);
+
+        // no net stored: gross less the 0.61 exit fee
+        let fee_only = closed_long(None);
+        assert!((Graph::trade_net_pnl_percent(&fee_only).unwrap() - 0.939).abs() < 1e-9);
+
+        let mut legacy = closed_long(None);
+        legacy.exit_fee = None;
+        assert_eq!(Graph::trade_net_pnl_percent(&legacy), Some(gross));
+    }
+
+    #[test]
+    fn test_max_drawdown_from_equity_peak() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 3, d, 12, 0, 0).unwrap();
+        let trade = |d: u32, net: Decimal| {
+            let mut pos = closed_long(Some(net));
+            pos.exit_time = day(d);
+            pos
+        };
+        // equity 100 → 200 → 250 → 190 → 220: peak 250, trough 190
+        let positions = vec![
+            trade(3, dec!(-60)),
+            trade(1, dec!(100)),
+            trade(4, dec!(30)),
+            trade(2, dec!(50)),
+        ];
+        assert!((Graph::max_drawdown(&positions, 100.0) - 24.0).abs() < 1e-9);
+
+        // +10 then -20: the fall from 110 to 90 counts, not a fall from a zero peak
+        let early_loss = vec![trade(1, dec!(10)), trade(2, dec!(-20))];
+        assert!((Graph::max_drawdown(&early_loss, 100.0) - 20.0 / 110.0 * 100.0).abs() < 1e-9);
+        // a first trade that loses is already a drawdown from the starting capital
+        assert!((Graph::max_drawdown(&[trade(1, dec!(-25))], 100.0) - 25.0).abs() < 1e-9);
+
+        assert_eq!(Graph::max_drawdown(&[], 100.0), 0.0);
+        let placeholder: ClosedPosition =
+            codec::decode(Graph::load_default_closed_position().as_bytes()).unwrap();
+        assert_eq!(Graph::max_drawdown(&[placeholder], 100.0), 0.0);
+    }
+
+    #[test]
+    fn test_summary_win_rate_profit_factor_and_sharpe() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 3, d, 12, 0, 0).unwrap();
+        let trade = |d: u32, net: Decimal| {
+            let mut pos = closed_long(Some(net));
+            pos.exit_time = day(d);
+            pos
+        };
+        let positions = vec![
+            trade(1, dec!(10)),
+            trade(1, dec!(20)),
+            trade(2, dec!(-5)),
+            trade(3, dec!(-10)),
+        ];
+
+        let summary = Graph::summary(&positions, 0.0, ReturnBasis::Trade);
+        assert_eq!(summary.total_trades, 4);
+        assert_eq!((summary.wins, summary.losses), (2, 2));
+        assert_eq!(summary.win_rate, 50.0);
+        assert_eq!(summary.profit_factor, Some(2.0));
+        assert_eq!(summary.average_win, dec!(15.00));
+        assert_eq!(summary.average_loss, dec!(-7.50));
+        assert_eq!(summary.largest_win, dec!(20));
+        assert_eq!(summary.largest_loss, dec!(-10));
+
+        // per-trade returns on 1000 notional: 1, 2, -0.5, -1 %
+        let sharpe = summary.sharpe_ratio.unwrap();
+        let expected = 0.375 / (5.6875f64 / 3.0).sqrt();
+        assert!((sharpe - expected).abs() < 1e-9);
+
+        // per day: 3, -0.5, -1 %; a higher risk-free rate lowers it
+        let daily = Graph::sharpe_ratio(&positions, 0.0, ReturnBasis::Daily).unwrap();
+        assert!(daily > Graph::sharpe_ratio(&positions, 0.5, ReturnBasis::Daily).unwrap());
+
+        assert_eq!(Graph::win_rate(&[]), 0.0);
+        assert_eq!(Graph::profit_factor(&positions[..2]), None);
+        assert_eq!(
+            Graph::sharpe_ratio(&positions[..1], 0.0, ReturnBasis::Trade),
+            None
+        );
+    }
+
+    #[test]
+    fn test_equity_curve_accumulates_in_exit_order() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 3, d, 12, 0, 0).unwrap();
+        let trade = |d: u32, net: Decimal| {
+            let mut pos = closed_long(Some(net));
+            pos.exit_time = day(d);
+            pos
+        };
+        let placeholder: ClosedPosition =
+            codec::decode(Graph::load_default_closed_position().as_bytes()).unwrap();
+        let positions = vec![trade(2, dec!(-5.5)), placeholder, trade(1, dec!(10))];
+
+        let curve = Graph::equity_curve(&positions, 100.0);
+        assert_eq!(curve, vec![(day(1), 110.0), (day(2), 104.5)]);
+        assert!(Graph::equity_curve(&[], 100.0).is_empty());
+    }
+
+    #[test]
+    fn test_performance_by_zone_ranks_the_worst_zone_first() {
+        let chop = ZoneId::from_zone(&Zone::below(84_000.0, 0.001));
+        let good = ZoneId::from_zone(&Zone::below(80_000.0, 0.001));
+        let trade = |zone_id, net: Decimal| ClosedPosition {
+            zone_id: Some(zone_id),
+            ..closed_long(Some(net))
+        };
+        let untagged = closed_long(Some(dec!(100)));
+
+        let zones = Graph::performance_by_zone(&[
+            trade(good, dec!(12)),
+            trade(chop, dec!(-5)),
+            trade(chop, dec!(2)),
+            trade(chop, dec!(-4)),
+            untagged,
+        ]);
+
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].zone_id, chop);
+        assert_eq!((zones[0].trades, zones[0].wins), (3, 1));
+        assert_eq!(zones[0].total_pnl, dec!(-7));
+        assert!((zones[0].win_rate - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(zones[1].zone_id, good);
+        assert_eq!(zones[1].win_rate, 100.0);
+    }
+
+    #[test]
+    fn test_write_csv_has_a_header_and_one_row_per_trade() {
+        let mut pos = closed_long(Some(dec!(8.79)));
+        pos.exit_time = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        pos.symbol = Some("ETHUSDT".to_string());
+        let placeholder: ClosedPosition =
+            codec::decode(Graph::load_default_closed_position().as_bytes()).unwrap();
+
+        let mut out = Vec::new();
+        Graph::write_csv(&[pos, placeholder], &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("id,side,entry_price,entry_time,exit_price,exit_time,"));
+        assert!(lines[1].contains(",Long,100000.00,"));
+        assert!(lines[1].contains(",2024-03-01T12:00:00Z,"));
+        assert!(lines[1].contains(",8.79,"));
+        assert!(lines[0].ends_with(",zone_id,symbol"));
+        assert!(lines[1].ends_with(",ETHUSDT"));
+    }
+}
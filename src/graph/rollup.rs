@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use redis::{aio::MultiplexedConnection, AsyncCommands};
+use serde::{Deserialize, Serialize};
+
+use super::Graph;
+use crate::bot::ClosedPosition;
+use crate::helper::{Helper, TRADING_BOT_ROLLUP_MONTHLY, TRADING_BOT_ROLLUP_WEEKLY};
+
+/// One week's / month's aggregate: summed per-trade PnL % (what the analytics
+/// endpoints report) and how many trades went into it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RollupBucket {
+    pub roi_percent: f64,
+    /// Same sum net of fees; `None` on buckets stored before it was tracked.
+    #[serde(default)]
+    pub net_roi_percent: Option<f64>,
+    pub trades: u32,
+}
+
+impl RollupBucket {
+    fn add(&mut self, pnl_percent: f64, net_pnl_percent: f64) {
+        if self.trades == 0 {
+            self.net_roi_percent = Some(0.0);
+        }
+        self.roi_percent += pnl_percent;
+        if let Some(net) = self.net_roi_percent.as_mut() {
+            *net += net_pnl_percent;
+        }
+        self.trades += 1;
+    }
+
+    /// Stored before net ROI existed, so it can't be topped up incrementally.
+    pub fn is_legacy(&self) -> bool {
+        self.trades > 0 && self.net_roi_percent.is_none()
+    }
+}
+
+/// Precomputed weekly / monthly ROI, kept in two Redis hashes so the API never has to
+/// scan the whole closed-positions list. Updated on every close; `rebuild` recomputes
+/// from scratch when the incremental view needs correcting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rollups {
+    pub weekly: BTreeMap<(i32, u32), RollupBucket>,
+    pub monthly: BTreeMap<(i32, u32), RollupBucket>,
+}
+
+/// `RollupBucket::add` on the stored JSON, inside Redis so two closes landing at once
+/// can't both read the old bucket and drop one of the trades.
+/// KEYS[1] rollup hash, ARGV[1] field, ARGV[2] pnl %, ARGV[3] net pnl %
+const RECORD_SCRIPT: &str = r#"
+local raw = redis.call('HGET', KEYS[1], ARGV[1])
+local bucket = raw and cjson.decode(raw) or {}
+local trades = tonumber(bucket.trades) or 0
+if trades == 0 then bucket.net_roi_percent = 0 end
+bucket.roi_percent = (tonumber(bucket.roi_percent) or 0) + tonumber(ARGV[2])
+if type(bucket.net_roi_percent) == 'number' then
+    bucket.net_roi_percent = bucket.net_roi_percent + tonumber(ARGV[3])
+end
+bucket.trades = trades + 1
+redis.call('HSET', KEYS[1], ARGV[1], cjson.encode(bucket))
+"#;
+
+fn week_field((year, week): (i32, u32)) -> String {
+    format!("{year:04}-W{week:02}")
+}
+
+fn month_field((year, month): (i32, u32)) -> String {
+    format!("{year:04}-{month:02}")
+}
+
+fn parse_field(field: &str, sep: &str) -> Option<(i32, u32)> {
+    let (y, p) = field.split_once(sep)?;
+    Some((y.parse().ok()?, p.parse().ok()?))
+}
+
+impl Rollups {
+    /// Folds one closed trade in. Placeholder records without prices are skipped,
+    /// exactly as the full scan does.
+    pub fn apply(&mut self, pos: &ClosedPosition) {
+        let Some(pct) = Graph::trade_pnl_percent(pos) else {
+            return;
+        };
+        let net = Graph::trade_net_pnl_percent(pos).unwrap_or(pct);
+        self.weekly
+            .entry(Helper::iso_week_key(pos.exit_time))
+            .or_default()
+            .add(pct, net);
+        self.monthly
+            .entry(Helper::month_key(pos.exit_time))
+            .or_default()
+            .add(pct, net);
+    }
+
+    pub fn from_positions(positions: &[ClosedPosition]) -> Self {
+        let mut rollups = Self::default();
+        for pos in positions {
+            rollups.apply(pos);
+        }
+        rollups
+    }
+
+    /// Incremental update for a single close: touches only that trade's week and month.
+    pub async fn record(conn: &mut MultiplexedConnection, pos: &ClosedPosition) -> Result<()> {
+        let Some(pct) = Graph::trade_pnl_percent(pos) else {
+            return Ok(());
+        };
+        let net = Graph::trade_net_pnl_percent(pos).unwrap_or(pct);
+
+        let script = redis::Script::new(RECORD_SCRIPT);
+        for (key, field) in [
+            (TRADING_BOT_ROLLUP_WEEKLY, week_field(Helper::iso_week_key(pos.exit_time))),
+            (TRADING_BOT_ROLLUP_MONTHLY, month_field(Helper::month_key(pos.exit_time))),
+        ] {
+            let _: () = script
+                .key(key)
+                .arg(field)
+                .arg(pct)
+                .arg(net)
+                .invoke_async(conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// `self`, recomputed from a capped list whose oldest trade closed at `oldest`, with
+    /// `stored`'s buckets kept for that trade's week and month and everything before:
+    /// trades trimmed off the list still count there. Net ROI falls back to gross on
+    /// kept buckets that predate it, as it does for trades without fees.
+    fn keep_stored_through(mut self, stored: Self, oldest: DateTime<Utc>) -> Self {
+        let keep = |computed: &mut BTreeMap<(i32, u32), RollupBucket>,
+                    stored: BTreeMap<(i32, u32), RollupBucket>,
+                    last: (i32, u32)| {
+            for (key, mut bucket) in stored.into_iter().filter(|(key, _)| *key <= last) {
+                if bucket.is_legacy() {
+                    bucket.net_roi_percent = Some(bucket.roi_percent);
+                }
+                computed.insert(key, bucket);
+            }
+        };
+        keep(&mut self.weekly, stored.weekly, Helper::iso_week_key(oldest));
+        keep(&mut self.monthly, stored.monthly, Helper::month_key(oldest));
+        self
+    }
+
+    pub async fn load(conn: &mut MultiplexedConnection) -> Result<Self> {
+        let weekly: BTreeMap<String, String> = conn.hgetall(TRADING_BOT_ROLLUP_WEEKLY).await?;
+        let monthly: BTreeMap<String, String> = conn.hgetall(TRADING_BOT_ROLLUP_MONTHLY).await?;
+
+        let parse = |raw: BTreeMap<String, String>, sep: &str| {
+            raw.into_iter()
+                .filter_map(|(field, bucket)| {
+                    Some((parse_field(&field, sep)?, serde_json::from_str(&bucket).ok()?))
+                })
+                .collect()
+        };
+        Ok(Self {
+            weekly: parse(weekly, "-W"),
+            monthly: parse(monthly, "-"),
+        })
+    }
+
+    /// Nothing stored yet, or buckets written before net ROI was tracked.
+    pub fn needs_rebuild(&self) -> bool {
+        self.weekly.is_empty()
+            || self
+                .weekly
+                .values()
+                .chain(self.monthly.values())
+                .any(RollupBucket::is_legacy)
+    }
+
+    /// Recomputes both rollups from the closed-positions list and replaces what's stored.
+    /// Once the list has hit its `max_history` cap (0 = unbounded) the oldest trades are
+    /// gone from it, so the periods they closed in keep their stored buckets.
+    pub async fn rebuild(conn: &mut MultiplexedConnection, max_history: usize) -> Result<Self> {
+        let positions = Graph::load_all_closed_positions(conn).await?;
+        let mut rollups = Self::from_positions(&positions);
+        let oldest = positions
+            .iter()
+            .filter(|p| Graph::trade_pnl_percent(p).is_some())
+            .map(|p| p.exit_time)
+            .min();
+        if let Some(oldest) = oldest.filter(|_| max_history > 0 && positions.len() >= max_history)
+        {
+            rollups = rollups.keep_stored_through(Self::load(conn).await?, oldest);
+        }
+
+        let _: () = conn
+            .del(&[TRADING_BOT_ROLLUP_WEEKLY, TRADING_BOT_ROLLUP_MONTHLY])
+            .await?;
+        for (key, bucket) in &rollups.weekly {
+            let _: () = conn
+                .hset(TRADING_BOT_ROLLUP_WEEKLY, week_field(*key), serde_json::to_string(bucket)?)
+                .await?;
+        }
+        for (key, bucket) in &rollups.monthly {
+            let _: () = conn
+                .hset(TRADING_BOT_ROLLUP_MONTHLY, month_field(*key), serde_json::to_string(bucket)?)
+                .await?;
+        }
+        Ok(rollups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::Position;
+    use chrono::{DateTime, TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn trade(exit_time: DateTime<Utc>, entry: Decimal, exit: Decimal) -> ClosedPosition {
+        ClosedPosition {
+            id: Uuid::nil(),
+            pnl: dec!(0),
+            position: Some(Position::Long),
+            side: Some(Position::Long),
+            entry_price: entry,
+            entry_time: exit_time,
+            exit_price: exit,
+            exit_time,
+            quantity: Some(dec!(0.01)),
+            sl: None,
+            roi: None,
+            leverage: Some(dec!(20)),
+            margin: Some(dec!(50)),
+            order_id: None,
+            pnl_after_fees: None,
+            exit_fee: None,
+            entry_fee: None,
+            funding_fee: None,
+            max_adverse_excursion: None,
+            max_favorable_excursion: None,
+            zone_id: None,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn test_close_updates_its_week_incrementally_and_matches_recompute() {
+        let mut history = vec![
+            trade(Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap(), dec!(100), dec!(101)),
+            trade(Utc.with_ymd_and_hms(2024, 3, 12, 12, 0, 0).unwrap(), dec!(100), dec!(98)),
+        ];
+        let mut rollups = Rollups::from_positions(&history);
+        let week_10_before = rollups.weekly[&(2024, 10)];
+
+        // new close in week 11 (Monday 2024-03-11)
+        let close = trade(Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap(), dec!(100), dec!(103));
+        rollups.apply(&close);
+        history.push(close);
+
+        assert_eq!(rollups.weekly[&(2024, 10)], week_10_before);
+        assert_eq!(rollups.weekly[&(2024, 11)].trades, 2);
+        assert!((rollups.weekly[&(2024, 11)].roi_percent - 1.0).abs() < 1e-9);
+        assert_eq!(rollups.monthly[&(2024, 3)].trades, 3);
+        assert_eq!(rollups, Rollups::from_positions(&history));
+    }
+
+    #[test]
+    fn test_net_roi_is_tracked_and_legacy_buckets_need_a_rebuild() {
+        let at = Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap();
+        let mut close = trade(at, dec!(100), dec!(101));
+        close.exit_fee = Some(dec!(0.002));
+        let rollups = Rollups::from_positions(&[close]);
+        let week = rollups.weekly[&(2024, 10)];
+        assert!((week.roi_percent - 1.0).abs() < 1e-9);
+        // 0.002 on a 1.00 notional is 0.2%
+        assert!((week.net_roi_percent.unwrap() - 0.8).abs() < 1e-9);
+        assert!(!rollups.needs_rebuild());
+
+        let legacy: RollupBucket =
+            serde_json::from_str(r#"{"roi_percent":1.0,"trades":1}"#).unwrap();
+        assert!(legacy.is_legacy());
+        let mut stale = rollups.clone();
+        stale.monthly.insert((2024, 3), legacy);
+        assert!(stale.needs_rebuild());
+    }
+
+    #[test]
+    fn test_capped_rebuild_keeps_the_periods_trimmed_trades_closed_in() {
+        let at = |d| Utc.with_ymd_and_hms(2024, 3, d, 12, 0, 0).unwrap();
+        // weeks 9, 10 and 11; the list has been capped down to the last two trades
+        let history = vec![
+            trade(at(1), dec!(100), dec!(110)),
+            trade(at(5), dec!(100), dec!(104)),
+            trade(at(6), dec!(100), dec!(102)),
+            trade(at(12), dec!(100), dec!(98)),
+        ];
+        let stored = Rollups::from_positions(&history);
+        let retained = &history[2..];
+
+        let rebuilt = Rollups::from_positions(retained).keep_stored_through(stored.clone(), at(6));
+
+        assert_eq!(rebuilt, stored);
+        // recomputing the list alone would lose week 9 and half of week 10
+        let naive = Rollups::from_positions(retained);
+        assert!(!naive.weekly.contains_key(&(2024, 9)));
+        assert_eq!(naive.weekly[&(2024, 10)].trades, 1);
+    }
+
+    #[test]
+    fn test_kept_legacy_buckets_stop_asking_for_a_rebuild() {
+        let at = Utc.with_ymd_and_hms(2024, 3, 12, 12, 0, 0).unwrap();
+        let mut stored = Rollups::default();
+        stored.weekly.insert(
+            (2024, 9),
+            serde_json::from_str(r#"{"roi_percent":10.0,"trades":1}"#).unwrap(),
+        );
+
+        let rebuilt = Rollups::from_positions(&[trade(at, dec!(100), dec!(98))])
+            .keep_stored_through(stored, at);
+
+        assert_eq!(rebuilt.weekly[&(2024, 9)].net_roi_percent, Some(10.0));
+        assert!(!rebuilt.needs_rebuild());
+    }
+
+    #[test]
+    fn test_placeholder_records_are_skipped() {
+        let placeholder = trade(Utc::now(), dec!(0), dec!(0));
+        assert_eq!(Rollups::from_positions(&[placeholder]), Rollups::default());
+    }
+
+    #[test]
+    fn test_fields_round_trip() {
+        assert_eq!(parse_field(&week_field((2024, 3)), "-W"), Some((2024, 3)));
+        assert_eq!(parse_field(&month_field((2024, 12)), "-"), Some((2024, 12)));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::exchange::bitget::Candle;
 use crate::{bot::Position, config::Config};
 use anyhow::{anyhow, Result};
-use chrono::{Datelike, Duration as ChronoDuration, Local, TimeZone, Timelike, Utc};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, TimeZone, Timelike, Utc};
 use log::warn;
 use rust_decimal::prelude::{FromPrimitive as _, ToPrimitive};
 use rust_decimal::Decimal;
@@ -33,21 +33,46 @@ pub const TRADING_BOT_ZONES: &str = "trading_bot:zones";
 pub const TRADING_BOT_POSITION: &str = "trading_bot:position";
 pub const TRADING_BOT_ACTIVE: &str = "trading::active";
 pub const TRADING_BOT_CLOSE_POSITIONS: &str = "closed_positions";
+pub const TRADING_SCALPER_BOT_POSITION: &str = "trading_scalper:position";
+pub const TRADING_SCALPER_BOT_ACTIVE: &str = "trading_scalper::active";
+pub const SCALPER_CLOSED_POSITIONS: &str = "scalper_closed_positions";
+pub const TRADING_SCALPER_PARTIAL_PROFIT_TARGET: &str = "trading_scalper_partial_profit_target";
 pub const TRADING_CAPITAL: &str = "trading_capital";
 pub const TRADING_PARTIAL_PROFIT_TARGET: &str = "trading_partial_profit_target";
 pub const TRADING_BOT_LOSS_COUNT: &str = "trading_bot:loss_count";
+pub const TRADING_BOT_RECORDED_PRICES: &str = "trading_bot:recorded_prices";
+pub const TRADING_BOT_DAILY_ENTRIES: &str = "trading_bot:daily_entries";
+pub const TRADING_BOT_DAILY_PNL: &str = "trading_bot:daily_pnl";
+pub const TRADING_BOT_ENTRY_INTENT: &str = "trading_bot:entry_intent";
+pub const TRADING_BOT_ROLLUP_WEEKLY: &str = "trading_bot:rollup:weekly";
+pub const TRADING_BOT_ROLLUP_MONTHLY: &str = "trading_bot:rollup:monthly";
+pub const TRADING_BOT_MISSED_ENTRIES: &str = "trading_bot:missed_entries";
+pub const TRADING_BOT_RESTING_ENTRY: &str = "trading_bot:resting_entry";
+pub const TRADING_BOT_ID_COUNTER: &str = "trading_bot:id_counter";
+pub const SCALPER_ID_COUNTER: &str = "trading_scalper:id_counter";
+pub const TRADING_BOT_ZONE_OVERRIDES: &str = "trading_bot:zone_overrides";
+/// Prefix of the per-zone guard stats, `zone_stats::<zone id>`
+pub const TRADING_BOT_ZONE_STATS: &str = "zone_stats";
+pub const TRADING_BOT_PAUSED: &str = "trading_bot:paused";
+pub const TRADING_BOT_LAST_CYCLE: &str = "trading_bot:last_cycle";
+pub const TRADING_BOT_CONFIG_OVERRIDE: &str = "trading_bot:config_override";
+pub const BITGET_VIP_FEE_RATES: &str = "bitget::vip_fee_rates";
 
-// Legacy constants retained to avoid breaking unused imports in other modules (marked for future cleanup)
-#[allow(dead_code)]
 pub const TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL: &str =
     "trading_bot:smart_money_concepts_next_call";
+
+// Legacy constants retained to avoid breaking unused imports in other modules (marked for future cleanup)
 #[allow(dead_code)]
 pub const TRADING_BOT_RECOMMENDED_CALL: &str = "trading_bot:recommended_call";
 
 pub const TRADING_BOT_VRVP: &str = "trading_bot:vrvp";
 pub const TRADING_BOT_RSI_REGIME: &str = "trading_bot:rsi_regime";
 pub const TRADING_BOT_MACRO_TRACKER: &str = "trading_bot:macro_tracker";
+pub const TRADING_BOT_MACRO_WINDOWS: &str = "trading_bot:macro_windows";
 pub const TRADING_BOT_TREND_STATE: &str = "trading_bot:trend_state";
+pub const TRADING_BOT_SMC_SIGNAL: &str = "trading_bot:smc_signal";
+pub const TRADING_BOT_MOMENTUM: &str = "trading_bot:momentum";
+pub const TRADING_BOT_MOMENTUM_INDICATORS: &str = "trading_bot:momentum:indicators";
 
 pub const TRADING_BOT_RSI_SNAPSHOT_2W:  &str = "trading_bot:rsi_snapshot:2W";
 pub const TRADING_BOT_RSI_SNAPSHOT_3D:  &str = "trading_bot:rsi_snapshot:3D";
@@ -60,6 +85,8 @@ pub const WEEKLY_CANDLES: &str = "weekly_candles";
 pub const WEEKLY_ICHIMOKU: &str = "weekly_ichimoku";
 pub const LAST_25_WEEKLY_ICHIMOKU_SPANS: &str = "last_25_weekly_ichimoku_spans";
 pub const TRADING_BOT_ICHIMOKU_CROSS: &str = "trading_bot:ichimoku_cross";
+pub const TRADING_BOT_ICHIMOKU_SIGNALS: &str = "trading_bot:ichimoku_signals";
+pub const TRADING_BOT_ICHIMOKU_KUMO: &str = "trading_bot:ichimoku_kumo";
 pub const TRADING_BOT_GAUSSIAN_3D: &str = "trading_bot:gaussian_regime_3d";
 pub const TRADING_BOT_RSI_DIV_4H: &str = "trading_bot:rsi_div:4H";
 pub const TRADING_BOT_RSI_DIV_1D: &str = "trading_bot:rsi_div:1D";
@@ -137,6 +164,21 @@ impl Helper {
         dec!(0.00)
     }
 
+    /// How far price moved in the position's favour, per contract: `exit - entry` for
+    /// longs, `entry - exit` for shorts, and 0 when it moved against it or when flat
+    pub fn calc_price_difference(
+        entry_price: Decimal,
+        exit_price: Decimal,
+        pos: Position,
+    ) -> Decimal {
+        let diff = match pos {
+            Position::Long => exit_price - entry_price,
+            Position::Short => entry_price - exit_price,
+            Position::Flat => dec!(0.00),
+        };
+        diff.max(dec!(0.00))
+    }
+
     pub fn position_size(margin: Decimal, leverage: Decimal) -> Decimal {
         margin * leverage
     }
@@ -170,12 +212,55 @@ impl Helper {
         position_size / entry_price
     }
 
+    /// Quantity for which a one-ATR move costs `target_risk` of the margin, so size
+    /// shrinks as volatility rises. Capped at `contract_amount`; without a usable ATR
+    /// it is `contract_amount`.
+    pub fn volatility_adjusted_size(
+        price: Decimal,
+        margin: Decimal,
+        leverage: Decimal,
+        atr: Decimal,
+        target_risk: Decimal,
+    ) -> Decimal {
+        let full = Self::contract_amount(price, margin, leverage);
+        if atr <= Decimal::ZERO || target_risk <= Decimal::ZERO {
+            return full;
+        }
+
+        (margin * target_risk / atr).min(full)
+    }
+
     /// Returns **true** iff the supplied `DateTime<Utc>` is exactly midnight (00:00).
     pub fn is_midnight() -> bool {
         let now = Local::now();
         now.hour() == 00 && now.minute() == 0
     }
 
+    /// Drops sub-millisecond precision, matching what `ts_milliseconds` keeps on disk,
+    /// so a fresh `Utc::now()` and its stored round-trip always bucket the same way.
+    pub fn to_stored_precision(t: DateTime<Utc>) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(t.timestamp_millis())
+            .single()
+            .unwrap_or(t)
+    }
+
+    /// `(iso_year, iso_week)` of a trade timestamp, always in UTC.
+    ///
+    /// Weeks are half-open: `[Mon 00:00:00.000, next Mon 00:00:00.000)`. Sub-millisecond
+    /// precision is truncated first, so Sunday 23:59:59.999x stays in the old week and
+    /// only Monday 00:00:00.000 opens the new one. The year is the ISO year, so
+    /// 2024-12-30 is `(2025, 1)`.
+    pub fn iso_week_key(t: DateTime<Utc>) -> (i32, u32) {
+        let iso = Self::to_stored_precision(t).iso_week();
+        (iso.year(), iso.week())
+    }
+
+    /// `(year, month)` of a trade timestamp in UTC, same truncation as `iso_week_key`
+    pub fn month_key(t: DateTime<Utc>) -> (i32, u32) {
+        let t = Self::to_stored_precision(t);
+        (t.year(), t.month())
+    }
+
     /// Percentage PnL of a single trade
     pub fn pnl_percent(entry: f64, exit: f64, pos: Position) -> f64 {
         if !entry.is_finite() || !exit.is_finite() {
@@ -236,6 +321,22 @@ impl Helper {
         dec!(0.00)
     }
 
+    /// Stop placed `atr_multiple` ATRs away from entry instead of a fixed risk-percent
+    pub fn atr_stop_loss_price(
+        entry_price: Decimal,
+        atr: Decimal,
+        atr_multiple: Decimal,
+        pos: Position,
+    ) -> Decimal {
+        let delta_price = atr * atr_multiple;
+
+        match pos {
+            Position::Long => entry_price - delta_price,
+            Position::Short => entry_price + delta_price,
+            Position::Flat => dec!(0.00),
+        }
+    }
+
     //Function to trigger Stop Loss
     pub fn ssl_hit(current_price: Decimal, side: Position, sl: Decimal) -> bool {
         if side == Position::Long {
@@ -249,6 +350,23 @@ impl Helper {
         false
     }
 
+    /// Pulls a stop that `price` has already run through (or nearly) back to `buffer_pct`
+    /// percent of price behind it, so moving the stop can't close on the next tick
+    pub fn clamp_stop_loss(
+        sl: Decimal,
+        price: Decimal,
+        side: Position,
+        buffer_pct: Decimal,
+    ) -> Decimal {
+        let buffer = price * buffer_pct / dec!(100);
+
+        match side {
+            Position::Long if sl > price - buffer => price - buffer,
+            Position::Short if sl < price + buffer => price + buffer,
+            _ => sl,
+        }
+    }
+
     fn tp_prices(
         ranger_price_difference: Decimal,
         entry_price: Decimal,
@@ -286,21 +404,142 @@ impl Helper {
         val.to_f64().unwrap()
     }
 
+    /// Groups every target `price` has crossed into at most `max_orders` close orders.
+    /// The first `max_orders - 1` crossed targets go out on their own, the rest are
+    /// folded into one combined target (summed size/fraction, furthest price and SL).
+    /// Returns each order together with the indices of the targets it covers.
+    pub fn batch_crossed_targets(
+        targets: &[PartialProfitTarget],
+        price: Decimal,
+        pos: Position,
+        max_orders: usize,
+    ) -> Vec<(PartialProfitTarget, Vec<usize>)> {
+        let crossed: Vec<usize> = targets
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| match pos {
+                Position::Long => price >= t.target_price,
+                Position::Short => price <= t.target_price,
+                Position::Flat => false,
+            })
+            .filter(|(_, t)| !t.target_price.is_zero() && !t.target_price.is_sign_negative())
+            .map(|(i, _)| i)
+            .collect();
+
+        if crossed.is_empty() {
+            return Vec::new();
+        }
+
+        let singles = max_orders.max(1) - 1;
+        let mut batches: Vec<(PartialProfitTarget, Vec<usize>)> = crossed
+            .iter()
+            .take(singles)
+            .map(|&i| (targets[i].clone(), vec![i]))
+            .collect();
+
+        let rest: Vec<usize> = crossed.into_iter().skip(singles).collect();
+        if let Some(&last) = rest.last() {
+            let combined = PartialProfitTarget {
+                target_price: targets[last].target_price,
+                fraction: rest.iter().map(|&i| targets[i].fraction).sum(),
+                size_btc: rest.iter().map(|&i| targets[i].size_btc).sum(),
+                sl: targets[last].sl,
+            };
+            batches.push((combined, rest));
+        }
+
+        batches
+    }
+
+    /// Rescales the remaining targets' fractions to sum to 1.0 after some were removed,
+    /// keeping their relative split. The last target takes the rounding remainder.
+    pub fn renormalize_fractions(targets: &mut [PartialProfitTarget]) {
+        let total: Decimal = targets.iter().map(|t| t.fraction).sum();
+        if total.is_zero() || total == Decimal::ONE {
+            return;
+        }
+
+        let mut assigned = Decimal::ZERO;
+        let last = targets.len() - 1;
+        for (i, target) in targets.iter_mut().enumerate() {
+            target.fraction = if i == last {
+                Decimal::ONE - assigned
+            } else {
+                target.fraction / total
+            };
+            assigned += target.fraction;
+        }
+    }
+
+    /// Scales each target's `size_btc` so the ladder sums to `total`, keeping
+    /// their proportions; the last target takes the rounding remainder.
+    pub fn rescale_target_sizes(
+        targets: &mut [PartialProfitTarget],
+        total: Decimal,
+        size_precision: u32,
+    ) {
+        let current: Decimal = targets.iter().map(|t| t.size_btc).sum();
+        if targets.is_empty() || current.is_zero() {
+            return;
+        }
+
+        let mut assigned = Decimal::ZERO;
+        let last = targets.len() - 1;
+        for (i, target) in targets.iter_mut().enumerate() {
+            target.size_btc = if i == last {
+                total - assigned
+            } else {
+                (target.size_btc * total / current)
+                    .round_dp_with_strategy(size_precision, rust_decimal::RoundingStrategy::ToZero)
+            };
+            assigned += target.size_btc;
+        }
+    }
+
+    /// Holds back `runner_fraction` of the final target as a runner, dropping the
+    /// target if nothing is left for it to close. Returns the runner size.
+    pub fn carve_runner(
+        targets: &mut Vec<PartialProfitTarget>,
+        runner_fraction: f64,
+        size_precision: u32,
+    ) -> Decimal {
+        let Some(last) = targets.last_mut() else {
+            return Decimal::ZERO;
+        };
+        let fraction = Decimal::from_f64(runner_fraction.clamp(0.0, 1.0)).unwrap_or_default();
+        let runner = (last.size_btc * fraction)
+            .round_dp_with_strategy(size_precision, rust_decimal::RoundingStrategy::ToZero);
+
+        last.size_btc -= runner;
+        if last.size_btc <= Decimal::ZERO {
+            targets.pop();
+        }
+        runner
+    }
+
+    /// The partial-profit split the ranger used before it was configurable, and still
+    /// the default for PROFIT_TARGET_FRACTIONS
+    pub const DEFAULT_TARGET_FRACTIONS: [Decimal; 4] =
+        [dec!(0.20), dec!(0.30), dec!(0.30), dec!(0.20)];
+
+    /// One target per entry in `fractions`, each closing that share of the position,
+    /// sized to the symbol's `size_precision` decimals
+    #[allow(clippy::too_many_arguments)]
     pub fn build_profit_targets(
         entry_price: Decimal,
         margin: Decimal,
         leverage: Decimal,
         ranger_price_difference: Decimal,
+        min_tp1_distance: Decimal,
         pos: Position,
+        fractions: &[Decimal],
+        size_precision: u32,
     ) -> Vec<PartialProfitTarget> {
-        // BTC precision (e.g. 5 or 6)
-        let size_precision: u32 = 5;
-
-        let tp_counts: usize = 4;
-        let tp_prices: Vec<Decimal> =
-            Helper::tp_prices(ranger_price_difference, entry_price, tp_counts, pos);
+        // Never let TP1 sit inside the fee band, push the whole ladder out if needed
+        let step = ranger_price_difference.max(min_tp1_distance);
 
-        let fractions: &[Decimal] = &[dec!(0.20), dec!(0.30), dec!(0.30), dec!(0.20)];
+        let tp_counts: usize = fractions.len();
+        let tp_prices: Vec<Decimal> = Helper::tp_prices(step, entry_price, tp_counts, pos);
 
         // Total notional
         let notional = margin * leverage;
@@ -334,8 +573,7 @@ impl Helper {
                 None
             } else if i == 0 {
                 // After TP1 → SL moves to entry
-                let sl_one = (entry_price + tp_prices[i]) / dec!(2.0);
-                Some(sl_one)
+                Some(entry_price)
             } else {
                 // After TPn → SL moves to previous TP price
                 Some(tp_prices[i - 1])
@@ -506,6 +744,69 @@ mod tests {
     use super::*;
     use rust_decimal_macros::dec;
 
+    #[test]
+    fn test_calc_price_difference_is_the_favorable_move() {
+        assert_eq!(
+            Helper::calc_price_difference(dec!(65000), dec!(65400), Position::Long),
+            dec!(400)
+        );
+        assert_eq!(
+            Helper::calc_price_difference(dec!(65000), dec!(64600), Position::Long),
+            dec!(0)
+        );
+
+        assert_eq!(
+            Helper::calc_price_difference(dec!(65000), dec!(64600), Position::Short),
+            dec!(400)
+        );
+        assert_eq!(
+            Helper::calc_price_difference(dec!(65000), dec!(65400), Position::Short),
+            dec!(0)
+        );
+
+        assert_eq!(
+            Helper::calc_price_difference(dec!(65000), dec!(65400), Position::Flat),
+            dec!(0)
+        );
+    }
+
+    #[test]
+    fn test_iso_week_key_at_week_boundary() {
+        // 2024-03-10 is a Sunday (ISO week 10), 2024-03-11 the Monday of week 11
+        let sunday_last_ms = Utc.with_ymd_and_hms(2024, 3, 10, 23, 59, 59).unwrap()
+            + ChronoDuration::milliseconds(999);
+        let sunday_last_ns = sunday_last_ms + ChronoDuration::nanoseconds(999_999);
+        let monday_open = Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap();
+
+        assert_eq!(Helper::iso_week_key(sunday_last_ms), (2024, 10));
+        assert_eq!(Helper::iso_week_key(sunday_last_ns), (2024, 10));
+        assert_eq!(Helper::iso_week_key(monday_open), (2024, 11));
+    }
+
+    #[test]
+    fn test_iso_week_key_uses_iso_year() {
+        let monday = Utc.with_ymd_and_hms(2024, 12, 30, 0, 0, 0).unwrap();
+        assert_eq!(Helper::iso_week_key(monday), (2025, 1));
+        assert_eq!(Helper::month_key(monday), (2024, 12));
+    }
+
+    #[test]
+    fn test_instantly_hit_partial_stop_is_clamped() {
+        // price gapped down to 65_050 while the target trails the stop up to 65_100
+        let price = dec!(65050);
+        let sl = Helper::clamp_stop_loss(dec!(65100), price, Position::Long, dec!(0.1));
+        assert_eq!(sl, dec!(64984.95));
+        assert!(!Helper::ssl_hit(price, Position::Long, sl));
+
+        let sl = Helper::clamp_stop_loss(dec!(64900), price, Position::Short, dec!(0.1));
+        assert_eq!(sl, dec!(65115.05));
+        assert!(!Helper::ssl_hit(price, Position::Short, sl));
+
+        // a stop comfortably behind price is applied as is
+        let sl = Helper::clamp_stop_loss(dec!(64500), price, Position::Long, dec!(0.1));
+        assert_eq!(sl, dec!(64500));
+    }
+
     #[test]
     fn test_calc_roi_zero_margin() {
         let roi = Helper::calc_roi(
@@ -543,8 +844,175 @@ mod tests {
             dec!(100.0),
             dec!(20.0),
             dec!(1000.0),
+            dec!(0.00),
             Position::Long,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
         );
         assert!(targets.is_empty() || targets.iter().all(|t| t.size_btc.is_zero()));
     }
+
+    #[test]
+    fn test_build_profit_targets_pushes_tp1_past_fee_breakeven() {
+        // 0.06% taker both ways at 100k is ~120 of price movement
+        let fee_breakeven = dec!(100000.0) * dec!(0.0006) * dec!(2);
+        let min_tp1 = fee_breakeven + dec!(30.0);
+
+        let targets = Helper::build_profit_targets(
+            dec!(100000.0),
+            dec!(100.0),
+            dec!(20.0),
+            dec!(50.0), // too close, fees would eat TP1
+            min_tp1,
+            Position::Long,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
+        );
+
+        assert_eq!(targets[0].target_price, dec!(100150.0));
+        assert!(targets[0].target_price - dec!(100000.0) > fee_breakeven);
+        assert_eq!(targets[3].target_price, dec!(100600.0));
+
+        let shorts = Helper::build_profit_targets(
+            dec!(100000.0),
+            dec!(100.0),
+            dec!(20.0),
+            dec!(50.0),
+            min_tp1,
+            Position::Short,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
+        );
+        assert_eq!(shorts[0].target_price, dec!(99850.0));
+    }
+
+    #[test]
+    fn test_build_profit_targets_keeps_wide_step() {
+        let targets = Helper::build_profit_targets(
+            dec!(100000.0),
+            dec!(100.0),
+            dec!(20.0),
+            dec!(500.0),
+            dec!(150.0),
+            Position::Long,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
+        );
+        assert_eq!(targets[0].target_price, dec!(100500.0));
+    }
+
+    #[test]
+    fn test_batch_crossed_targets_combines_into_one_order() {
+        let targets = Helper::build_profit_targets(
+            dec!(100000.0),
+            dec!(100.0),
+            dec!(20.0),
+            dec!(200.0),
+            dec!(0.00),
+            Position::Long,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
+        );
+
+        // Price jumps past TP1..TP3 in a single tick
+        let batches = Helper::batch_crossed_targets(&targets, dec!(100650.0), Position::Long, 1);
+
+        assert_eq!(batches.len(), 1);
+        let (combined, covered) = &batches[0];
+        assert_eq!(covered, &vec![0, 1, 2]);
+        assert_eq!(
+            combined.size_btc,
+            targets[0].size_btc + targets[1].size_btc + targets[2].size_btc
+        );
+        assert_eq!(combined.fraction, dec!(0.80));
+        assert_eq!(combined.target_price, targets[2].target_price);
+        assert_eq!(combined.sl, targets[2].sl);
+    }
+
+    #[test]
+    fn test_batch_crossed_targets_respects_max_orders() {
+        let targets = Helper::build_profit_targets(
+            dec!(100000.0),
+            dec!(100.0),
+            dec!(20.0),
+            dec!(200.0),
+            dec!(0.00),
+            Position::Short,
+            &Helper::DEFAULT_TARGET_FRACTIONS,
+            5,
+        );
+
+        let batches = Helper::batch_crossed_targets(&targets, dec!(99100.0), Position::Short, 2);
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].1, vec![0]);
+        assert_eq!(batches[1].1, vec![1, 2, 3]);
+
+        let none = Helper::batch_crossed_targets(&targets, dec!(99900.0), Position::Short, 2);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_volatility_adjusted_size_shrinks_as_atr_grows() {
+        // 1000 margin at 10x on 100k: at most 0.1 BTC
+        let size = |atr| {
+            Helper::volatility_adjusted_size(dec!(100000), dec!(1000), dec!(10), atr, dec!(0.02))
+        };
+
+        // a 1-ATR move costs 2% of margin, 20 USDT
+        assert_eq!(size(dec!(400)), dec!(0.05));
+        assert_eq!(size(dec!(800)), dec!(0.025));
+        assert_eq!(size(dec!(800)) * dec!(800), dec!(20));
+
+        // calm markets are capped at the full margin x leverage size
+        assert_eq!(size(dec!(100)), dec!(0.1));
+        assert_eq!(size(Decimal::ZERO), dec!(0.1));
+    }
+
+    #[test]
+    fn test_atr_stop_loss_price() {
+        let long_sl =
+            Helper::atr_stop_loss_price(dec!(100000.0), dec!(400.0), dec!(1.5), Position::Long);
+        assert_eq!(long_sl, dec!(99400.0));
+
+        let short_sl =
+            Helper::atr_stop_loss_price(dec!(100000.0), dec!(400.0), dec!(1.5), Position::Short);
+        assert_eq!(short_sl, dec!(100600.0));
+    }
+
+    #[test]
+    fn test_renormalize_after_removing_the_half_target() {
+        let target = |fraction| PartialProfitTarget {
+            target_price: dec!(100000),
+            fraction,
+            sl: None,
+            size_btc: dec!(0.01),
+        };
+        let mut targets = vec![target(dec!(0.50)), target(dec!(0.25)), target(dec!(0.15)), target(dec!(0.10))];
+
+        targets.remove(0);
+        Helper::renormalize_fractions(&mut targets);
+
+        let fractions: Vec<Decimal> = targets.iter().map(|t| t.fraction).collect();
+        assert_eq!(fractions, vec![dec!(0.5), dec!(0.3), dec!(0.2)]);
+        assert_eq!(fractions.iter().copied().sum::<Decimal>(), Decimal::ONE);
+    }
+
+    #[test]
+    fn test_rescale_target_sizes_to_remaining_quantity() {
+        let target = |size_btc| PartialProfitTarget {
+            target_price: dec!(100000),
+            fraction: dec!(0.25),
+            sl: None,
+            size_btc,
+        };
+        let mut targets = vec![target(dec!(0.003)), target(dec!(0.0045)), target(dec!(0.0045)), target(dec!(0.003))];
+
+        Helper::rescale_target_sizes(&mut targets, dec!(0.00375), 5);
+
+        let sizes: Vec<Decimal> = targets.iter().map(|t| t.size_btc).collect();
+        assert_eq!(sizes, vec![dec!(0.00075), dec!(0.00112), dec!(0.00112), dec!(0.00076)]);
+        assert_eq!(sizes.iter().copied().sum::<Decimal>(), dec!(0.00375));
+    }
 }
+
@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::sync::Arc;
+use std::time::Duration;
 
 use reqwest::Client;
 
@@ -7,6 +9,7 @@ use log::info;
 
 use crate::cache::RedisClient;
 use crate::config::{Config, ExchangeType};
+use crate::exchange::bitget::HttpCandleData;
 use crate::exchange::HttpExchange;
 use crate::exchange::BitunixExchange;
 
@@ -47,20 +50,51 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // Single shared HTTP client — one connection pool for the entire process.
     let http = Arc::new(Client::new());
 
-    // 3️⃣ Create exchange instance based on EXCHANGE env var
-    let exchange: Arc<dyn crate::exchange::Exchange> = match cfg.exchange {
-        ExchangeType::Bitunix => Arc::new(BitunixExchange::new(&cfg)),
-        ExchangeType::Bitget => Arc::new(HttpExchange {
-            client: (*http).clone(),
-            symbol: cfg.symbol.clone(),
-            redis_conn: redis_conn.clone(),
-        }),
-    };
+    // 3️⃣ Create an exchange instance per symbol based on EXCHANGE env var
+    let exchanges: BTreeMap<String, Arc<dyn crate::exchange::Exchange>> = cfg
+        .symbols
+        .iter()
+        .map(|symbol| {
+            let symbol_cfg = Config {
+                symbol: symbol.clone(),
+                ..cfg.clone()
+            };
+            let exchange: Arc<dyn crate::exchange::Exchange> = match cfg.exchange {
+                ExchangeType::Bitunix => Arc::new(BitunixExchange::new(&symbol_cfg)),
+                ExchangeType::Bitget => Arc::new(HttpExchange {
+                    client: (*http).clone(),
+                    symbol: symbol.clone(),
+                    bitget: HttpCandleData::new(symbol.clone(), symbol_cfg),
+                    redis_conn: redis_conn.clone(),
+                }),
+            };
+            (symbol.clone(), exchange)
+        })
+        .collect();
+    // Background tasks, the API and the credential check use the primary symbol's
+    let exchange = Arc::clone(&exchanges[&cfg.symbol]);
+
+    // Fail fast on a wrong key / passphrase instead of on the first order
+    if cfg.verify_credentials_on_boot {
+        exchange.verify_credentials().await?;
+    }
 
     // 4️⃣ Bot state
-    let mut bot = bot::Bot::new(redis_conn.clone(), &cfg).await?;
-
-    let mut task_set = tasks::spawn_background_tasks(redis_conn.clone(), &cfg, Arc::clone(&http)).await;
+    let mut bot = bot::Bot::new(redis_conn.clone(), &cfg, (*http).clone()).await?;
+
+    // SIGTERM / Ctrl-C stops the loops between ticks, then the bot shuts down
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+    let mut task_set = tasks::spawn_background_tasks(
+        redis_conn.clone(),
+        &cfg,
+        Arc::clone(&http),
+        Arc::clone(&exchange),
+        bot.updates(),
+        bot.commands(),
+        shutdown_rx.clone(),
+    )
+    .await;
 
     // Supervisor: watches every background task for unexpected exits or panics.
     // Dropping the JoinSet would abort all tasks, so it must live here for the
@@ -76,15 +110,72 @@ async fn main() -> Result<(), Box<dyn Error>> {
         log::error!("[supervisor] All background tasks have stopped");
     });
 
+    let signal_tx = shutdown_tx.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("Shutdown signal received, stopping the bot loop...");
+        let _ = signal_tx.send(true);
+    });
+
     info!("Starting bot loop...");
 
     let bot_result = match cfg.exchange {
-        ExchangeType::Bitunix => bot.start_live_trading_bitunix(exchange.as_ref()).await,
-        ExchangeType::Bitget => bot.start_live_trading(exchange.as_ref()).await,
+        ExchangeType::Bitunix => {
+            bot.start_live_trading_bitunix(exchange.as_ref(), shutdown_rx)
+                .await
+        }
+        ExchangeType::Bitget => bot.start_live_trading(&exchanges, shutdown_rx).await,
     };
     if let Err(e) = bot_result {
         log::error!("Bot loop error: {e}");
     }
 
+    // Bounded, so a hung exchange or redis call can't keep the process alive
+    if tokio::time::timeout(Duration::from_secs(30), bot.shutdown(&exchanges))
+        .await
+        .is_err()
+    {
+        log::error!("Shutdown timed out; check the exchange for open positions");
+    }
+    // The scalper flattens on its own task; it drops its receiver once done
+    let _ = shutdown_tx.send(true);
+    if tokio::time::timeout(Duration::from_secs(30), shutdown_tx.closed())
+        .await
+        .is_err()
+    {
+        log::error!("Scalper shutdown timed out; check its account for open positions");
+    }
+    info!("Bot stopped");
+
     Ok(())
 }
+
+/// Resolves on Ctrl-C or, on unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            log::error!("Failed to listen for Ctrl-C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                log::error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
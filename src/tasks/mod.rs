@@ -1,16 +1,22 @@
 use std::sync::Arc;
 
 use log::info;
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio::task::JoinSet;
 
 use crate::api;
+use crate::bot::command::BotCommand;
+use crate::bot::feed::BotUpdate;
+use crate::config::runtime::Tunables;
 use crate::config::Config;
+use crate::exchange::Exchange;
 use crate::helper::{
     TRADING_BOT_RSI_SNAPSHOT_1D, TRADING_BOT_RSI_SNAPSHOT_1H,
     TRADING_BOT_RSI_SNAPSHOT_15M, TRADING_BOT_RSI_SNAPSHOT_3D,
     TRADING_BOT_RSI_SNAPSHOT_4H,
 };
 use crate::trackers;
+use crate::trackers::health::TrackerHealth;
 use crate::trackers::smart_money_concepts::Bar;
 
 /// Loads a seed candle file into a `Vec<Bar>` for use by RSI tracker loops.
@@ -43,10 +49,16 @@ fn load_seed_bars(path: &'static str) -> Vec<Bar> {
 ///
 /// The caller must drive the `JoinSet` — dropping it aborts every task inside.
 /// Pass it to a supervisor loop (see `main.rs`) so unexpected exits are logged.
+/// Tasks holding a position (the scalper) stop on `shutdown` and drop their receiver
+/// once they've wound down, which `main` waits for.
 pub async fn spawn_background_tasks(
     redis_conn: redis::aio::MultiplexedConnection,
     cfg: &Config,
     http: Arc<reqwest::Client>,
+    exchange: Arc<dyn Exchange>,
+    updates: broadcast::Sender<BotUpdate>,
+    commands: mpsc::Sender<BotCommand>,
+    shutdown: watch::Receiver<bool>,
 ) -> JoinSet<()> {
     let symbol: Arc<str> = Arc::from(cfg.symbol.as_str());
 
@@ -89,14 +101,25 @@ pub async fn spawn_background_tasks(
     }
 
     if cfg.use_ichimoku_indicator {
-        let conn = redis_conn.clone();
+        let (conn, h, sym) = (redis_conn.clone(), Arc::clone(&http), Arc::clone(&symbol));
+        let (source, interval_secs) = (cfg.ichimoku_source, cfg.ichimoku_interval_secs);
+        let health = TrackerHealth::new("ichimoku", cfg.tracker_health);
         task_set.spawn(async move {
-            if let Err(e) = trackers::ichimoku::ichimoku_loop(conn).await {
+            if let Err(e) = trackers::ichimoku::ichimoku_loop(conn, h, sym, source, interval_secs, health).await {
                 log::error!("Ichimoku tracker error: {e}");
             }
         });
     }
 
+    // Economic calendar — pull the remote feed into redis; refresh daily
+    if let Some(url) = cfg.calendar_url.clone() {
+        let (conn, h) = (redis_conn.clone(), Arc::clone(&http));
+        let (tz, interval_secs) = (cfg.calendar_tz, cfg.calendar_refresh_secs);
+        task_set.spawn(async move {
+            crate::calendar::calendar_loop(conn, h, url, tz, interval_secs).await;
+        });
+    }
+
     // 4H VRVP — 500 candles (~83 days of structure); 100 bins; refresh every 30 min
     let (conn, h, sym) = (redis_conn.clone(), Arc::clone(&http), Arc::clone(&symbol));
     task_set.spawn(async move {
@@ -224,8 +247,43 @@ pub async fn spawn_background_tasks(
         crate::regime::gaussian_3d_loop(conn, h, sym, s3d, 10800).await;
     });
 
+    // 5m momentum — RSI/MACD/volume off live candles, recomputed on every closed bar
+    // of the kline stream
+    if cfg.use_momentum_tracker {
+        let (conn, h, sym) = (redis_conn.clone(), Arc::clone(&http), Arc::clone(&symbol));
+        let health = TrackerHealth::new("momentum", cfg.tracker_health);
+        task_set.spawn(async move {
+            trackers::momentum::start_live_tracking(conn, h, sym, health).await;
+        });
+    }
+
+    // Scalper — its own polling loop and position keys, trading the ranger's zones
+    if cfg.enable_scalper {
+        let (conn, h, ex) = (redis_conn.clone(), Arc::clone(&http), Arc::clone(&exchange));
+        let scalper_config = cfg.clone();
+        task_set.spawn(async move {
+            match crate::bot::scalper::ScalperBot::new(conn, &scalper_config, (*h).clone()).await {
+                Ok(scalper) => scalper.run(ex.as_ref(), shutdown).await,
+                Err(e) => log::error!("Scalper failed to start: {e}"),
+            }
+        });
+    }
+
+    let margin = cfg.margin;
+    let tunables = Tunables::from_config(cfg);
+    let api_token = cfg.api_token.clone();
+    let max_closed_history = cfg.max_closed_history;
     task_set.spawn(async move {
-        let app = api::create_router(redis_conn);
+        let app = api::create_router(
+            redis_conn,
+            exchange,
+            margin,
+            tunables,
+            max_closed_history,
+            commands,
+            updates,
+            api_token,
+        );
         let listener = tokio::net::TcpListener::bind("0.0.0.0:4545")
             .await
             .expect("Failed to bind API server");
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use log::warn;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+/// Trackers that report their health, as surfaced by `/api/trackers/status`
+pub const TRACKERS: [&str; 3] = ["smc", "ichimoku", "momentum"];
+
+fn last_success_key(name: &str) -> String {
+    format!("tracker:{name}:last_success")
+}
+
+fn last_error_key(name: &str) -> String {
+    format!("tracker:{name}:last_error")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackerError {
+    pub at: DateTime<Utc>,
+    pub message: String,
+}
+
+/// When a tracker last ran cleanly and when (and why) it last failed. A
+/// `last_success` that stops moving means the tracker has stalled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackerStatus {
+    pub name: String,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<TrackerError>,
+}
+
+impl TrackerStatus {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            last_success: None,
+            last_error: None,
+        }
+    }
+
+    /// Folds one run's outcome in; an error leaves `last_success` where it was
+    pub fn observe(&mut self, outcome: &anyhow::Result<()>, now: DateTime<Utc>) {
+        match outcome {
+            Ok(()) => self.last_success = Some(now),
+            Err(e) => {
+                self.last_error = Some(TrackerError {
+                    at: now,
+                    message: e.to_string(),
+                })
+            }
+        }
+    }
+
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection, name: &str) -> Self {
+        let success: Option<String> = conn.get(last_success_key(name)).await.unwrap_or(None);
+        let error: Option<String> = conn.get(last_error_key(name)).await.unwrap_or(None);
+        Self {
+            name: name.to_string(),
+            last_success: success.and_then(|s| s.parse().ok()),
+            last_error: error.and_then(|e| serde_json::from_str(&e).ok()),
+        }
+    }
+
+    pub async fn store(&self, conn: &mut redis::aio::MultiplexedConnection) -> anyhow::Result<()> {
+        if let Some(at) = self.last_success {
+            let _: () = conn
+                .set(last_success_key(&self.name), at.to_rfc3339())
+                .await?;
+        }
+        if let Some(error) = &self.last_error {
+            let _: () = conn
+                .set(last_error_key(&self.name), serde_json::to_string(error)?)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Handle a tracker loop reports each run through; a no-op when disabled
+#[derive(Debug, Clone, Copy)]
+pub struct TrackerHealth {
+    name: &'static str,
+    enabled: bool,
+}
+
+impl TrackerHealth {
+    pub fn new(name: &'static str, enabled: bool) -> Self {
+        Self { name, enabled }
+    }
+
+    /// Records `outcome` against this tracker; failures only warn
+    pub async fn report(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+        outcome: &anyhow::Result<()>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let mut status = TrackerStatus::new(self.name);
+        status.observe(outcome, Utc::now());
+        if let Err(e) = status.store(conn).await {
+            warn!("Failed to store {} tracker health: {e}", self.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_observe_moves_last_success_only_on_a_clean_run() {
+        let earlier = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2024, 3, 1, 12, 15, 0).unwrap();
+        let mut status = TrackerStatus::new("smc");
+
+        status.observe(&Ok(()), earlier);
+        status.observe(&Err(anyhow::anyhow!("no 4H candles returned")), later);
+        assert_eq!(status.last_success, Some(earlier));
+        assert_eq!(status.last_error.as_ref().unwrap().at, later);
+
+        status.observe(&Ok(()), later);
+        assert_eq!(status.last_success, Some(later));
+        assert_eq!(status.last_error.unwrap().message, "no 4H candles returned");
+    }
+}
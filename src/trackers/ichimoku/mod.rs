@@ -9,17 +9,23 @@ use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::io;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::exchange::bitget::Candle;
+use crate::config::IchimokuSource;
+use crate::exchange::bitget::{fetch_bitget_candles, Candle};
 use crate::helper::Helper;
-use crate::helper::{LAST_25_WEEKLY_ICHIMOKU_SPANS, TRADING_BOT_ICHIMOKU_CROSS, WEEKLY_CANDLES, WEEKLY_ICHIMOKU};
+use crate::trackers::health::TrackerHealth;
+use crate::helper::{
+    LAST_25_WEEKLY_ICHIMOKU_SPANS, TRADING_BOT_ICHIMOKU_CROSS, TRADING_BOT_ICHIMOKU_KUMO,
+    TRADING_BOT_ICHIMOKU_SIGNALS, WEEKLY_CANDLES, WEEKLY_ICHIMOKU,
+};
 
-// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-// pub enum TenkanKijunCross {
-//     Bullish,
-//     Bearish,
-// }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TenkanKijunCross {
+    Bullish,
+    Bearish,
+}
 
 // pub enum CrossStrength {
 //     StrongBullish,
@@ -28,16 +34,32 @@ use crate::helper::{LAST_25_WEEKLY_ICHIMOKU_SPANS, TRADING_BOT_ICHIMOKU_CROSS, W
 //     WeakBearish,
 // }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum KumoCross {
-    #[allow(dead_code)]
     Bullish,
-    #[allow(dead_code)]
     Bearish,
 }
 
-#[derive(Debug, Serialize)]
+/// Latest weekly Tenkan/Kijun and Kumo crosses, stored for the ranger to read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IchimokuSignals {
+    pub tenkan_kijun: Option<TenkanKijunCross>,
+    pub kumo: Option<KumoCross>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IchimokuSignals {
+    /// Weekly bias: the last Tenkan/Kijun cross, or the last Kumo flip if Tenkan/Kijun
+    /// hasn't crossed inside the data yet.
+    pub fn bias(&self) -> Option<TenkanKijunCross> {
+        self.tenkan_kijun.or(self.kumo.map(|k| match k {
+            KumoCross::Bullish => TenkanKijunCross::Bullish,
+            KumoCross::Bearish => TenkanKijunCross::Bearish,
+        }))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Ichimoku {
     pub conversion_line: Vec<Option<f64>>, // Tenkan-sen
     pub base_line: Vec<Option<f64>>,       // Kijun-sen
@@ -46,48 +68,90 @@ pub struct Ichimoku {
     pub lagging_span: Vec<Option<f64>>,    // Chikou
 }
 
+/// Weekly bars fetched per refresh on the Bitget source — enough for the
+/// 52-period Senkou B plus its 26-bar displacement, with room to spare.
+const BITGET_WEEKLY_LIMIT: &str = "200";
+
 //Ichimoku is used for BTC on the weekly timeframe
 ///Download the one-minute BTCUSD from the dataset from : https://www.kaggle.com/api/v1/datasets/download/mczielinski/bitcoin-historical-data,
-/// resolve it into a weekly timeframe, and calculate the ichimoku
-pub async fn ichimoku_loop(redis_conn: MultiplexedConnection) -> Result<()> {
-    let loop_interval_seconds = 604800;
+/// resolve it into a weekly timeframe, and calculate the ichimoku.
+/// With `IchimokuSource::Bitget` the weekly candles come straight from Bitget instead.
+pub async fn ichimoku_loop(
+    redis_conn: MultiplexedConnection,
+    http: Arc<reqwest::Client>,
+    symbol: Arc<str>,
+    source: IchimokuSource,
+    loop_interval_seconds: u64,
+    health: TrackerHealth,
+) -> Result<()> {
 
     let mut interval = time::interval(Duration::from_secs(loop_interval_seconds));
 
-    let url = "https://www.kaggle.com/api/v1/datasets/download/mczielinski/bitcoin-historical-data";
-
     loop {
         interval.tick().await;
 
-        //let url = url.to_string();
-        let result = tokio::task::spawn_blocking(move || {
-            download_large_file(url, "data/btcusd_1-min_data.zip")
-        })
-        .await;
+        let weekly_candles = match source {
+            IchimokuSource::Kaggle => kaggle_weekly_candles().await,
+            IchimokuSource::Bitget => bitget_weekly_candles(&http, &symbol).await,
+        };
 
-        match result {
-            Ok(Err(e)) => {
-                eprintln!("CRITICAL ERROR in ichimoku_loop: {e:?}");
-                eprintln!("Retrying in {loop_interval_seconds} seconds...");
+        let outcome = match weekly_candles {
+            Ok(candles) => {
+                let processed = process_weekly_ichimoku(redis_conn.clone(), &candles).await;
+                if let Err(e) = &processed {
+                    eprintln!("Ichimoku processing error: {e:?}");
+                }
+                processed
             }
             Err(e) => {
-                eprintln!("Task Join Error: {e:?}");
+                eprintln!("CRITICAL ERROR in ichimoku_loop: {e:?}");
+                eprintln!("Retrying in {loop_interval_seconds} seconds...");
+                Err(e)
             }
-            _ => {}
-        }
+        };
+        health.report(&mut redis_conn.clone(), &outcome).await;
+    }
+}
 
-        let _extract_weekly = tokio::task::spawn_blocking(move || {
-            Helper::extract_into_weekly_candle(
-                "data/btcusd_1-min_data.csv",
-                "data/btcusd_weekly_data.csv",
-            )
-        })
-        .await;
+async fn kaggle_weekly_candles() -> Result<Vec<Candle>> {
+    let url = "https://www.kaggle.com/api/v1/datasets/download/mczielinski/bitcoin-historical-data";
 
-        let ichimoku_conn = redis_conn.clone();
-        let _process_weekly_ichimoku =
-            tokio::task::spawn(async move { process_weekly_ichimoku(ichimoku_conn).await }).await;
+    let result = tokio::task::spawn_blocking(move || {
+        download_large_file(url, "data/btcusd_1-min_data.zip")
+    })
+    .await;
+
+    match result {
+        Ok(Err(e)) => {
+            eprintln!("CRITICAL ERROR in ichimoku_loop: {e:?}");
+        }
+        Err(e) => {
+            eprintln!("Task Join Error: {e:?}");
+        }
+        _ => {}
     }
+
+    let _extract_weekly = tokio::task::spawn_blocking(move || {
+        Helper::extract_into_weekly_candle(
+            "data/btcusd_1-min_data.csv",
+            "data/btcusd_weekly_data.csv",
+        )
+    })
+    .await;
+
+    Helper::read_candles_from_csv("data/btcusd_weekly_data.csv")
+        .map_err(|e| anyhow::anyhow!("Failed to read weekly candles: {e}"))
+}
+
+async fn bitget_weekly_candles(http: &reqwest::Client, symbol: &str) -> Result<Vec<Candle>> {
+    let mut candles = fetch_bitget_candles(http, symbol, "1W", BITGET_WEEKLY_LIMIT).await?;
+    sort_chronologically(&mut candles);
+    Ok(candles)
+}
+
+/// `ichimoku_processor` walks bars oldest-first; don't rely on the API's ordering.
+fn sort_chronologically(candles: &mut [Candle]) {
+    candles.sort_by_key(|c| c.timestamp);
 }
 
 fn download_large_file(url: &str, path: &str) -> Result<()> {
@@ -153,74 +217,174 @@ fn ichimoku_processor(
     span_b_periods: usize,     // 52
     displacement: usize,       // 26
 ) -> Ichimoku {
+    let mut ichimoku = Ichimoku {
+        conversion_line: Vec::new(),
+        base_line: Vec::new(),
+        leading_span_a: Vec::new(),
+        leading_span_b: Vec::new(),
+        lagging_span: Vec::new(),
+    };
+
+    ichimoku_extend(
+        &mut ichimoku,
+        candles,
+        0,
+        conversion_periods,
+        base_periods,
+        span_b_periods,
+        displacement,
+    );
+
+    ichimoku
+}
+
+/// Brings an `Ichimoku` computed over `candles[..from]` up to date with all of
+/// `candles`, recomputing only bars `from..`. A bar's lines depend on nothing
+/// after it, so the result is identical to a full `ichimoku_processor` run.
+fn ichimoku_extend(
+    ichimoku: &mut Ichimoku,
+    candles: &[Candle],
+    from: usize,
+    conversion_periods: usize,
+    base_periods: usize,
+    span_b_periods: usize,
+    displacement: usize,
+) {
     let len = candles.len();
+    let from = from.min(len);
+
+    ichimoku.conversion_line.truncate(from);
+    ichimoku.base_line.truncate(from);
+    ichimoku.leading_span_a.truncate(from + displacement);
+    ichimoku.leading_span_b.truncate(from + displacement);
+    ichimoku.lagging_span.truncate(from);
+    // lagging entries fed by the bars being recomputed
+    for lag in ichimoku.lagging_span.iter_mut().skip(from.saturating_sub(displacement)) {
+        *lag = None;
+    }
 
-    let mut conversion = vec![None; len];
-    let mut base = vec![None; len];
-    let mut span_a = vec![None; len + displacement];
-    let mut span_b = vec![None; len + displacement];
-    let mut lagging = vec![None; len];
+    ichimoku.conversion_line.resize(len, None);
+    ichimoku.base_line.resize(len, None);
+    ichimoku.leading_span_a.resize(len + displacement, None);
+    ichimoku.leading_span_b.resize(len + displacement, None);
+    ichimoku.lagging_span.resize(len, None);
 
-    for i in 0..len {
-        conversion[i] = donchian_midpoint(candles, i, conversion_periods);
-        base[i] = donchian_midpoint(candles, i, base_periods);
+    for i in from..len {
+        let conversion = donchian_midpoint(candles, i, conversion_periods);
+        let base = donchian_midpoint(candles, i, base_periods);
+        ichimoku.conversion_line[i] = conversion;
+        ichimoku.base_line[i] = base;
 
         // Lagging span (close shifted backward)
         if i >= displacement {
-            lagging[i - displacement] = Some(candles[i].close);
+            ichimoku.lagging_span[i - displacement] = Some(candles[i].close);
         }
 
         // Leading spans (shifted forward)
-        if let (Some(conv), Some(base)) = (conversion[i], base[i]) {
-            span_a[i + displacement] = Some((conv + base) / 2.0);
+        if let (Some(conv), Some(base)) = (conversion, base) {
+            ichimoku.leading_span_a[i + displacement] = Some((conv + base) / 2.0);
         }
 
         if let Some(b) = donchian_midpoint(candles, i, span_b_periods) {
-            span_b[i + displacement] = Some(b);
+            ichimoku.leading_span_b[i + displacement] = Some(b);
         }
     }
+}
 
-    //let bounds = kumo_bounds(&span_a, &span_b);
-    //println!("bounds: {:?}", bounds);
+/// Number of leading bars `fresh` shares unchanged with `cached`. The still-forming
+/// weekly bar usually differs, so that's where the recompute starts.
+fn unchanged_prefix(cached: &[Candle], fresh: &[Candle]) -> usize {
+    cached
+        .iter()
+        .zip(fresh)
+        .take_while(|(old, new)| {
+            old.timestamp == new.timestamp
+                && old.high == new.high
+                && old.low == new.low
+                && old.close == new.close
+        })
+        .count()
+}
+
+/// Recomputes the weekly Ichimoku from the first bar that differs from the cached
+/// `WEEKLY_CANDLES`, falling back to a full pass when nothing usable is cached.
+async fn weekly_ichimoku(
+    redis_conn: &mut MultiplexedConnection,
+    weekly_candles: &[Candle],
+) -> Ichimoku {
+    let cached_candles: Option<String> = redis_conn.get(WEEKLY_CANDLES).await.ok().flatten();
+    let cached_ichimoku: Option<String> = redis_conn.get(WEEKLY_ICHIMOKU).await.ok().flatten();
+
+    let cached = cached_candles
+        .and_then(|c| serde_json::from_str::<Vec<Candle>>(&c).ok())
+        .zip(cached_ichimoku.and_then(|i| serde_json::from_str::<Ichimoku>(&i).ok()))
+        .filter(|(candles, ichimoku)| ichimoku.conversion_line.len() == candles.len());
+
+    match cached {
+        Some((cached_candles, mut ichimoku)) => {
+            let from = unchanged_prefix(&cached_candles, weekly_candles);
+            ichimoku_extend(&mut ichimoku, weekly_candles, from, 9, 26, 52, 26);
+            ichimoku
+        }
+        None => ichimoku_processor(weekly_candles, 9, 26, 52, 26),
+    }
+}
 
-    //println!("span_a: {:?}", span_a);
-    //println!("span_b: {:?}", span_b);
+fn tenkan_kijun_cross(
+    tenkan: &[Option<f64>],
+    kijun: &[Option<f64>],
+) -> Vec<Option<TenkanKijunCross>> {
+    let len = tenkan.len().min(kijun.len());
+    let mut signals = vec![None; len];
+
+    for i in 1..len {
+        let (t_prev, k_prev) = (tenkan[i - 1], kijun[i - 1]);
+        let (t_now, k_now) = (tenkan[i], kijun[i]);
+
+        if let (Some(tp), Some(kp), Some(tn), Some(kn)) = (t_prev, k_prev, t_now, k_now) {
+            // Bullish cross
+            if tp <= kp && tn > kn {
+                signals[i] = Some(TenkanKijunCross::Bullish);
+            }
 
-    Ichimoku {
-        conversion_line: conversion,
-        base_line: base,
-        leading_span_a: span_a,
-        leading_span_b: span_b,
-        lagging_span: lagging,
+            // Bearish cross
+            if tp >= kp && tn < kn {
+                signals[i] = Some(TenkanKijunCross::Bearish);
+            }
+        }
     }
+
+    signals
 }
 
-// fn tenkan_kijun_cross(
-//     tenkan: &[Option<f64>],
-//     kijun: &[Option<f64>],
-// ) -> Vec<Option<TenkanKijunCross>> {
-//     let len = tenkan.len().min(kijun.len());
-//     let mut signals = vec![None; len];
+pub fn kumo_cross(span_a: &[Option<f64>], span_b: &[Option<f64>]) -> Vec<Option<KumoCross>> {
+    let len = span_a.len().min(span_b.len());
+    let mut signals = vec![None; len];
 
-//     for i in 1..len {
-//         let (t_prev, k_prev) = (tenkan[i - 1], kijun[i - 1]);
-//         let (t_now, k_now) = (tenkan[i], kijun[i]);
+    for i in 1..len {
+        let (a_prev, b_prev) = (span_a[i - 1], span_b[i - 1]);
+        let (a_now, b_now) = (span_a[i], span_b[i]);
 
-//         if let (Some(tp), Some(kp), Some(tn), Some(kn)) = (t_prev, k_prev, t_now, k_now) {
-//             // Bullish cross
-//             if tp <= kp && tn > kn {
-//                 signals[i] = Some(TenkanKijunCross::Bullish);
-//             }
+        if let (Some(ap), Some(bp), Some(an), Some(bn)) = (a_prev, b_prev, a_now, b_now) {
+            // Bullish Kumo flip
+            if ap <= bp && an > bn {
+                signals[i] = Some(KumoCross::Bullish);
+            }
 
-//             // Bearish cross
-//             if tp >= kp && tn < kn {
-//                 signals[i] = Some(TenkanKijunCross::Bearish);
-//             }
-//         }
-//     }
+            // Bearish Kumo flip
+            if ap >= bp && an < bn {
+                signals[i] = Some(KumoCross::Bearish);
+            }
+        }
+    }
 
-//     signals
-// }
+    signals
+}
+
+/// Most recent cross in a per-bar signal series
+fn latest_cross<T: Copy>(signals: &[Option<T>]) -> Option<T> {
+    signals.iter().rev().find_map(|s| *s)
+}
 
 // fn kumo_bounds(
 //     span_a: &[Option<f64>],
@@ -257,6 +421,47 @@ fn get_last_25_spans(
     (span_a[start_a..].to_vec(), span_b[start_b..].to_vec())
 }
 
+/// Weekly cloud (Senkou A/B) at the latest closed week
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KumoSnapshot {
+    pub leading_span_a: f64,
+    pub leading_span_b: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Which side of the weekly cloud price is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bias {
+    Above,
+    Inside,
+    Below,
+}
+
+/// Where `price` sits relative to the weekly cloud; the cloud edges count as inside.
+pub fn ichimoku_bias(price: f64, kumo: &KumoSnapshot) -> Bias {
+    let top = kumo.leading_span_a.max(kumo.leading_span_b);
+    let bottom = kumo.leading_span_a.min(kumo.leading_span_b);
+
+    if price > top {
+        Bias::Above
+    } else if price < bottom {
+        Bias::Below
+    } else {
+        Bias::Inside
+    }
+}
+
+/// Cloud at the last candle: spans are projected forward, so index `i` of the span
+/// vectors is the cloud for candle `i`.
+fn current_kumo(ichimoku: &Ichimoku) -> Option<KumoSnapshot> {
+    let last = ichimoku.conversion_line.len().checked_sub(1)?;
+    Some(KumoSnapshot {
+        leading_span_a: (*ichimoku.leading_span_a.get(last)?)?,
+        leading_span_b: (*ichimoku.leading_span_b.get(last)?)?,
+        updated_at: Utc::now(),
+    })
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IchimokuCrossState {
     KijunAboveSpanB,
@@ -289,12 +494,14 @@ fn detect_kijun_spanb_state(ichimoku: &Ichimoku) -> Option<IchimokuCrossState> {
     None
 }
 
-async fn process_weekly_ichimoku(mut redis_conn: MultiplexedConnection) -> Result<()> {
-    let weekly_candles = Helper::read_candles_from_csv("data/btcusd_weekly_data.csv").unwrap();
-    let serde_weekly_candles = serde_json::to_string(&weekly_candles).unwrap();
-    let _: () = redis_conn.set(WEEKLY_CANDLES, serde_weekly_candles).await?;
+async fn process_weekly_ichimoku(
+    mut redis_conn: MultiplexedConnection,
+    weekly_candles: &[Candle],
+) -> Result<()> {
+    let weekly_ichimoku = weekly_ichimoku(&mut redis_conn, weekly_candles).await;
 
-    let weekly_ichimoku = ichimoku_processor(&weekly_candles, 9, 26, 52, 26);
+    let serde_weekly_candles = serde_json::to_string(weekly_candles).unwrap();
+    let _: () = redis_conn.set(WEEKLY_CANDLES, serde_weekly_candles).await?;
     let serde_weekly_ichimoku = serde_json::to_string(&weekly_ichimoku).unwrap();
     let _: () = redis_conn
         .set(WEEKLY_ICHIMOKU, serde_weekly_ichimoku)
@@ -314,6 +521,25 @@ async fn process_weekly_ichimoku(mut redis_conn: MultiplexedConnection) -> Resul
         .set(LAST_25_WEEKLY_ICHIMOKU_SPANS, serde_last_25_spans)
         .await?;
 
+    let signals = IchimokuSignals {
+        tenkan_kijun: latest_cross(&tenkan_kijun_cross(
+            &weekly_ichimoku.conversion_line,
+            &weekly_ichimoku.base_line,
+        )),
+        kumo: latest_cross(&kumo_cross(
+            &weekly_ichimoku.leading_span_a,
+            &weekly_ichimoku.leading_span_b,
+        )),
+        updated_at: Utc::now(),
+    };
+    let serialized = serde_json::to_string(&signals).unwrap();
+    let _: () = redis_conn.set(TRADING_BOT_ICHIMOKU_SIGNALS, serialized).await?;
+
+    if let Some(kumo) = current_kumo(&weekly_ichimoku) {
+        let serialized = serde_json::to_string(&kumo).unwrap();
+        let _: () = redis_conn.set(TRADING_BOT_ICHIMOKU_KUMO, serialized).await?;
+    }
+
     if let Some(state) = detect_kijun_spanb_state(&weekly_ichimoku) {
         let snapshot = IchimokuCrossSnapshot {
             state,
@@ -375,6 +601,109 @@ impl Default for IchimokuBaseline {
 mod tests {
     use super::*;
 
+    fn weekly_bars(n: usize) -> Vec<Candle> {
+        (0..n)
+            .map(|i| {
+                // quoted to 0.1 like Bitget, so the JSON cache round trip is exact
+                let mid =
+                    ((30_000.0 + (i as f64 * 0.7).sin() * 4_000.0 + i as f64 * 50.0) * 10.0)
+                        .round()
+                        / 10.0;
+                Candle {
+                    timestamp: 1_600_000_000_000 + i as i64 * 604_800_000,
+                    open: mid - 100.0,
+                    high: mid + 900.0,
+                    low: mid - 800.0,
+                    close: mid + 150.0,
+                    volume: 1_000.0,
+                    quote_volume: mid * 1_000.0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn incremental_update_matches_full_recompute() {
+        let fresh = weekly_bars(120);
+
+        // cache as of last run: 119 bars, the newest still forming
+        let mut cached = weekly_bars(119);
+        cached[118].high -= 400.0;
+        cached[118].close -= 250.0;
+        let cached_ichimoku = ichimoku_processor(&cached, 9, 26, 52, 26);
+
+        // survive the Redis round trip like the live path does
+        let cached: Vec<Candle> =
+            serde_json::from_str(&serde_json::to_string(&cached).unwrap()).unwrap();
+        let mut incremental: Ichimoku =
+            serde_json::from_str(&serde_json::to_string(&cached_ichimoku).unwrap()).unwrap();
+
+        let from = unchanged_prefix(&cached, &fresh);
+        assert_eq!(from, 118);
+
+        ichimoku_extend(&mut incremental, &fresh, from, 9, 26, 52, 26);
+        let full = ichimoku_processor(&fresh, 9, 26, 52, 26);
+
+        assert_eq!(incremental.leading_span_a, full.leading_span_a);
+        assert_eq!(incremental.leading_span_b, full.leading_span_b);
+        assert_eq!(incremental.conversion_line, full.conversion_line);
+        assert_eq!(incremental.base_line, full.base_line);
+        assert_eq!(incremental.lagging_span, full.lagging_span);
+    }
+
+    #[test]
+    fn bitget_weekly_candles_feed_the_processor_oldest_first() {
+        let raw = r#"[
+            ["1700438400000","37000","38000","36000","37500","100","3750000"],
+            ["1699833600000","36000","37200","35500","37000","120","4440000"]
+        ]"#;
+        let mut candles: Vec<Candle> = serde_json::from_str(raw).unwrap();
+        sort_chronologically(&mut candles);
+
+        assert_eq!(candles[0].timestamp, 1699833600000);
+        assert_eq!(candles[1].close, 37500.0);
+
+        let ichimoku = ichimoku_processor(&candles, 9, 26, 52, 26);
+        assert_eq!(ichimoku.conversion_line.len(), candles.len());
+    }
+
+    #[test]
+    fn price_is_classified_against_the_cloud() {
+        let kumo = KumoSnapshot {
+            leading_span_a: 60_000.0,
+            leading_span_b: 55_000.0,
+            updated_at: Utc::now(),
+        };
+        assert_eq!(ichimoku_bias(61_000.0, &kumo), Bias::Above);
+        assert_eq!(ichimoku_bias(57_500.0, &kumo), Bias::Inside);
+        assert_eq!(ichimoku_bias(55_000.0, &kumo), Bias::Inside);
+        assert_eq!(ichimoku_bias(54_000.0, &kumo), Bias::Below);
+    }
+
+    #[test]
+    fn latest_tenkan_kijun_cross_is_surfaced() {
+        let tenkan = [Some(10.0), Some(12.0), Some(10.5), Some(9.0), Some(9.5)];
+        let kijun = [Some(11.0), Some(11.0), Some(11.0), Some(10.0), Some(10.0)];
+
+        let crosses = tenkan_kijun_cross(&tenkan, &kijun);
+        assert_eq!(crosses[1], Some(TenkanKijunCross::Bullish));
+        assert_eq!(crosses[2], Some(TenkanKijunCross::Bearish));
+        assert_eq!(latest_cross(&crosses), Some(TenkanKijunCross::Bearish));
+    }
+
+    #[test]
+    fn bias_falls_back_to_kumo_flip() {
+        let span_a = [None, Some(10.0), Some(12.0)];
+        let span_b = [None, Some(11.0), Some(11.0)];
+        let signals = IchimokuSignals {
+            tenkan_kijun: None,
+            kumo: latest_cross(&kumo_cross(&span_a, &span_b)),
+            updated_at: Utc::now(),
+        };
+        assert_eq!(signals.kumo, Some(KumoCross::Bullish));
+        assert_eq!(signals.bias(), Some(TenkanKijunCross::Bullish));
+    }
+
     #[test]
     fn baseline_needs_26_bars() {
         let mut bl = IchimokuBaseline::new();
@@ -409,30 +738,6 @@ mod tests {
     }
 }
 
-// pub fn kumo_cross(span_a: &[Option<f64>], span_b: &[Option<f64>]) -> Vec<Option<KumoCross>> {
-//     let len = span_a.len().min(span_b.len());
-//     let mut signals = vec![None; len];
-
-//     for i in 1..len {
-//         let (a_prev, b_prev) = (span_a[i - 1], span_b[i - 1]);
-//         let (a_now, b_now) = (span_a[i], span_b[i]);
-
-//         if let (Some(ap), Some(bp), Some(an), Some(bn)) = (a_prev, b_prev, a_now, b_now) {
-//             // Bullish Kumo flip
-//             if ap <= bp && an > bn {
-//                 signals[i] = Some(KumoCross::Bullish);
-//             }
-
-//             // Bearish Kumo flip
-//             if ap >= bp && an < bn {
-//                 signals[i] = Some(KumoCross::Bearish);
-//             }
-//         }
-//     }
-
-//     signals
-// }
-
 // pub fn kumo_cross_from_bounds(
 //     upper: &[Option<f64>],
 //     lower: &[Option<f64>],
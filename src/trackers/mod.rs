@@ -1,8 +1,9 @@
 pub mod ema;
 pub mod gaussian;
+pub mod health;
 pub mod ichimoku;
 pub(crate) mod rsi_core;
-//pub mod momentum;
+pub mod momentum;
 pub mod rsi_divergence_indicator;
 pub mod rsi_regime_tracker;
 pub mod smart_money_concepts;
@@ -0,0 +1,939 @@
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use log::{error, info, warn};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use tokio::time;
+
+use crate::exchange::bitget::{fetch_bitget_candles, BitgetWsClient, Candle, WsCandleData};
+use crate::helper::{TRADING_BOT_MOMENTUM, TRADING_BOT_MOMENTUM_INDICATORS};
+use crate::trackers::health::TrackerHealth;
+
+/// Bar length of the live kline stream (5m)
+const LIVE_BAR_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MACDData {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MomentumSignal {
+    Bullish,
+    Bearish,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Divergence {
+    Bullish,
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MomentumIndicators {
+    pub rsi: f64,
+    pub macd: MACDData,
+    pub price_momentum: f64,
+    pub volume_ratio: f64,
+    pub overall_signal: MomentumSignal,
+    #[serde(default)]
+    pub divergence: Option<Divergence>,
+}
+
+/// Latest 5m momentum reading, written to Redis each tracker tick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MomentumSnapshot {
+    pub overall_signal: MomentumSignal,
+    pub strong_bullish: bool,
+    pub strong_bearish: bool,
+    /// 14-period ATR on 5m candles, used for ATR-multiple stops
+    #[serde(default)]
+    pub atr: Option<f64>,
+    /// Close of the latest 5m bar, for close-confirmed zone entries
+    #[serde(default)]
+    pub last_close: Option<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MomentumSnapshot {
+    pub fn from_indicators(
+        indicators: &MomentumIndicators,
+        atr: Option<f64>,
+        last_close: Option<f64>,
+    ) -> Self {
+        Self {
+            overall_signal: indicators.overall_signal,
+            strong_bullish: indicators.is_strong_bullish(),
+            strong_bearish: indicators.is_strong_bearish(),
+            atr,
+            last_close,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+pub struct BitcoinMomentumTracker {
+    price_history: VecDeque<f64>,
+    high_history: VecDeque<f64>,
+    low_history: VecDeque<f64>,
+    volume_history: VecDeque<f64>,
+    timestamps: VecDeque<u64>,
+    max_history: usize,
+}
+
+impl BitcoinMomentumTracker {
+    /// Creates a new momentum tracker with specified history limit
+    pub fn new(max_history: usize) -> Self {
+        Self {
+            price_history: VecDeque::with_capacity(max_history),
+            high_history: VecDeque::with_capacity(max_history),
+            low_history: VecDeque::with_capacity(max_history),
+            volume_history: VecDeque::with_capacity(max_history),
+            timestamps: VecDeque::with_capacity(max_history),
+            max_history,
+        }
+    }
+
+    /// Adds a close-only data point
+    #[cfg(test)]
+    pub fn add_data_point(&mut self, price: f64, volume: f64) {
+        self.add_ohlc_point(price, price, price, volume);
+    }
+
+    /// Adds a full candle so ATR can use the real high/low range
+    pub fn add_ohlc_point(&mut self, high: f64, low: f64, close: f64, volume: f64) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        self.price_history.push_back(close);
+        self.high_history.push_back(high);
+        self.low_history.push_back(low);
+        self.volume_history.push_back(volume);
+        self.timestamps.push_back(timestamp);
+
+        // Maintain max history limit
+        while self.price_history.len() > self.max_history {
+            self.price_history.pop_front();
+            self.high_history.pop_front();
+            self.low_history.pop_front();
+            self.volume_history.pop_front();
+            self.timestamps.pop_front();
+        }
+    }
+
+    /// Average True Range with Wilder smoothing, seeded by the mean of the first `period` TRs.
+    /// TR = max(high - low, |high - prev close|, |low - prev close|)
+    pub fn calculate_atr(&self, period: usize) -> Option<f64> {
+        if period == 0 || self.price_history.len() < period + 1 {
+            return None;
+        }
+
+        let true_ranges: Vec<f64> = (1..self.price_history.len())
+            .map(|i| {
+                let high = self.high_history[i];
+                let low = self.low_history[i];
+                let prev_close = self.price_history[i - 1];
+                (high - low)
+                    .max((high - prev_close).abs())
+                    .max((low - prev_close).abs())
+            })
+            .collect();
+
+        let mut atr = true_ranges[..period].iter().sum::<f64>() / period as f64;
+        for tr in &true_ranges[period..] {
+            atr = (atr * (period as f64 - 1.0) + tr) / period as f64;
+        }
+
+        Some(atr)
+    }
+
+    /// Calculates RSI (Relative Strength Index)
+    pub fn calculate_rsi(&self, period: usize) -> Option<f64> {
+        if self.price_history.len() < period + 1 {
+            return None;
+        }
+
+        let prices: Vec<f64> = self.price_history.iter().cloned().collect();
+        Some(Self::rsi_at(&prices, prices.len() - 1, period))
+    }
+
+    /// RSI over the `period` changes ending at `prices[end]`; needs `end >= period`
+    fn rsi_at(prices: &[f64], end: usize, period: usize) -> f64 {
+        let mut gains = 0.0;
+        let mut losses = 0.0;
+
+        for i in (end + 1 - period)..=end {
+            let change = prices[i] - prices[i - 1];
+            if change > 0.0 {
+                gains += change;
+            } else {
+                losses -= change;
+            }
+        }
+
+        let avg_gain = gains / period as f64;
+        let avg_loss = losses / period as f64;
+
+        if avg_loss == 0.0 {
+            return 100.0;
+        }
+
+        let rs = avg_gain / avg_loss;
+        100.0 - (100.0 / (1.0 + rs))
+    }
+
+    /// Indices in `lo..hi` that are strictly below (`lows`) or above every price two
+    /// bars either side. The last two bars can't be confirmed yet and are skipped.
+    fn pivots(prices: &[f64], lo: usize, hi: usize, lows: bool) -> Vec<usize> {
+        (lo.max(2)..hi.saturating_sub(2))
+            .filter(|&k| {
+                [k - 2, k - 1, k + 1, k + 2].iter().all(|&j| {
+                    if lows {
+                        prices[k] < prices[j]
+                    } else {
+                        prices[k] > prices[j]
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// RSI/price divergence over the last `window` bars: the two latest pivot lows
+    /// making a lower low while RSI makes a higher low is bullish; two pivot highs
+    /// making a higher high on a lower RSI is bearish. If both show, the more recent
+    /// pivot wins.
+    pub fn detect_divergence(&self, window: usize, rsi_period: usize) -> Option<Divergence> {
+        let prices: Vec<f64> = self.price_history.iter().cloned().collect();
+        let n = prices.len();
+        if n < window + rsi_period {
+            return None;
+        }
+        let lo = n - window;
+
+        let slopes_disagree = |pivots: &[usize], lows: bool| -> Option<usize> {
+            let [.., a, b] = *pivots else {
+                return None;
+            };
+            let price_slope = prices[b] - prices[a];
+            let rsi_slope =
+                Self::rsi_at(&prices, b, rsi_period) - Self::rsi_at(&prices, a, rsi_period);
+            let diverges = if lows {
+                price_slope < 0.0 && rsi_slope > 0.0
+            } else {
+                price_slope > 0.0 && rsi_slope < 0.0
+            };
+            diverges.then_some(b)
+        };
+
+        let bullish = slopes_disagree(&Self::pivots(&prices, lo, n, true), true);
+        let bearish = slopes_disagree(&Self::pivots(&prices, lo, n, false), false);
+
+        match (bullish, bearish) {
+            (Some(bull), Some(bear)) if bear > bull => Some(Divergence::Bearish),
+            (Some(_), _) => Some(Divergence::Bullish),
+            (None, Some(_)) => Some(Divergence::Bearish),
+            (None, None) => None,
+        }
+    }
+
+    /// Calculates Exponential Moving Average
+    pub fn calculate_ema(&self, prices: &[f64], period: usize) -> Option<f64> {
+        self.ema_series(prices, period).last().copied()
+    }
+
+    /// EMA series seeded with the SMA of the first `period` prices. Element `k` is the
+    /// EMA at `prices[k + period - 1]`; empty when there are fewer than `period` prices.
+    pub fn ema_series(&self, prices: &[f64], period: usize) -> Vec<f64> {
+        if period == 0 || prices.len() < period {
+            return Vec::new();
+        }
+
+        let multiplier = 2.0 / (period as f64 + 1.0);
+        let mut ema = prices[..period].iter().sum::<f64>() / period as f64;
+
+        let mut series = Vec::with_capacity(prices.len() - period + 1);
+        series.push(ema);
+
+        for &price in &prices[period..] {
+            ema = (price * multiplier) + (ema * (1.0 - multiplier));
+            series.push(ema);
+        }
+
+        series
+    }
+
+    /// Calculates MACD (Moving Average Convergence Divergence)
+    /// MACD = EMA12 - EMA26, signal = 9-period EMA of the MACD series.
+    pub fn calculate_macd(&self) -> Option<MACDData> {
+        // 26 bars for the slow EMA, then 9 MACD values for the signal line
+        if self.price_history.len() < 26 + 8 {
+            return None;
+        }
+
+        let prices: Vec<f64> = self.price_history.iter().cloned().collect();
+
+        let ema12 = self.ema_series(&prices, 12);
+        let ema26 = self.ema_series(&prices, 26);
+
+        // Line both series up on price index: ema12[i - 11], ema26[i - 25]
+        let macd_history: Vec<f64> = (25..prices.len())
+            .map(|i| ema12[i - 11] - ema26[i - 25])
+            .collect();
+
+        let macd = *macd_history.last()?;
+        let signal = self.calculate_ema(&macd_history, 9)?;
+        let histogram = macd - signal;
+
+        Some(MACDData {
+            macd,
+            signal,
+            histogram,
+        })
+    }
+
+    /// Calculates price momentum over specified period
+    pub fn calculate_price_momentum(&self, period: usize) -> Option<f64> {
+        if self.price_history.len() < period {
+            return None;
+        }
+
+        let current_price = *self.price_history.back()?;
+        let previous_price = self.price_history[self.price_history.len() - period];
+
+        Some(((current_price - previous_price) / previous_price) * 100.0)
+    }
+
+    /// Calculates volume ratio compared to average
+    pub fn calculate_volume_ratio(&self) -> Option<f64> {
+        if self.volume_history.is_empty() {
+            return None;
+        }
+
+        let current_volume = *self.volume_history.back()?;
+        let avg_volume: f64 =
+            self.volume_history.iter().sum::<f64>() / self.volume_history.len() as f64;
+
+        if avg_volume == 0.0 {
+            return Some(1.0);
+        }
+
+        Some(current_volume / avg_volume)
+    }
+
+    /// Determines momentum signal based on RSI
+    pub fn get_rsi_signal(&self, rsi: f64) -> MomentumSignal {
+        if rsi > 70.0 {
+            MomentumSignal::Bearish // Overbought
+        } else if rsi < 30.0 {
+            MomentumSignal::Bullish // Oversold
+        } else {
+            MomentumSignal::Neutral
+        }
+    }
+
+    /// Determines momentum signal based on MACD
+    pub fn get_macd_signal(&self, macd: &MACDData) -> MomentumSignal {
+        if macd.histogram > 0.0 {
+            MomentumSignal::Bullish
+        } else if macd.histogram < 0.0 {
+            MomentumSignal::Bearish
+        } else {
+            MomentumSignal::Neutral
+        }
+    }
+
+    /// Determines momentum signal based on price movement
+    pub fn get_price_momentum_signal(&self, momentum: f64) -> MomentumSignal {
+        if momentum > 1.0 {
+            MomentumSignal::Bullish
+        } else if momentum < -1.0 {
+            MomentumSignal::Bearish
+        } else {
+            MomentumSignal::Neutral
+        }
+    }
+
+    /// Calculates overall momentum signal by combining all indicators
+    pub fn get_overall_momentum_signal(&self, indicators: &MomentumIndicators) -> MomentumSignal {
+        let rsi_signal = self.get_rsi_signal(indicators.rsi);
+        let macd_signal = self.get_macd_signal(&indicators.macd);
+        let price_signal = self.get_price_momentum_signal(indicators.price_momentum);
+
+        // Count bullish and bearish signals
+        let mut bullish_count = 0;
+        let mut bearish_count = 0;
+
+        for signal in [&rsi_signal, &macd_signal, &price_signal] {
+            match signal {
+                MomentumSignal::Bullish => bullish_count += 1,
+                MomentumSignal::Bearish => bearish_count += 1,
+                MomentumSignal::Neutral => {}
+            }
+        }
+
+        if bullish_count > bearish_count {
+            MomentumSignal::Bullish
+        } else if bearish_count > bullish_count {
+            MomentumSignal::Bearish
+        } else {
+            MomentumSignal::Neutral
+        }
+    }
+
+    /// Calculates all momentum indicators
+    pub fn calculate_all_indicators(&self) -> Option<MomentumIndicators> {
+        let rsi = self.calculate_rsi(14).unwrap_or(50.0);
+        let macd = self.calculate_macd().unwrap_or(MACDData {
+            macd: 0.0,
+            signal: 0.0,
+            histogram: 0.0,
+        });
+        let price_momentum = self.calculate_price_momentum(5).unwrap_or(0.0);
+        let volume_ratio = self.calculate_volume_ratio().unwrap_or(1.0);
+
+        let indicators = MomentumIndicators {
+            rsi,
+            macd,
+            price_momentum,
+            volume_ratio,
+            overall_signal: MomentumSignal::Neutral, // Will be calculated next
+            divergence: self.detect_divergence(30, 14),
+        };
+
+        let overall_signal = self.get_overall_momentum_signal(&indicators);
+
+        Some(MomentumIndicators {
+            overall_signal,
+            ..indicators
+        })
+    }
+
+    /// Generates momentum alerts based on current indicators
+    pub fn generate_alerts(&self, indicators: &MomentumIndicators) -> Vec<String> {
+        let mut alerts = Vec::new();
+
+        // RSI alerts
+        if indicators.rsi > 75.0 {
+            alerts.push("⚠️ RSI severely overbought - potential reversal incoming".to_string());
+        } else if indicators.rsi < 25.0 {
+            alerts.push("🚀 RSI severely oversold - potential bounce opportunity".to_string());
+        }
+
+        // MACD alerts
+        if indicators.macd.histogram > 100.0 {
+            alerts.push("📈 Strong MACD bullish momentum detected".to_string());
+        } else if indicators.macd.histogram < -100.0 {
+            alerts.push("📉 Strong MACD bearish momentum detected".to_string());
+        }
+
+        // Price momentum alerts
+        if indicators.price_momentum > 2.0 {
+            alerts.push("🔥 Strong positive price momentum - trend acceleration".to_string());
+        } else if indicators.price_momentum < -2.0 {
+            alerts.push("❄️ Strong negative price momentum - trend deceleration".to_string());
+        }
+
+        // Volume alerts
+        if indicators.volume_ratio > 2.0 {
+            alerts.push("📊 Exceptional volume detected - significant market interest".to_string());
+        }
+
+        // Divergence alerts
+        match indicators.divergence {
+            Some(Divergence::Bullish) => {
+                alerts.push("🔀 Bullish RSI divergence - lower low on a higher RSI".to_string())
+            }
+            Some(Divergence::Bearish) => {
+                alerts.push("🔀 Bearish RSI divergence - higher high on a lower RSI".to_string())
+            }
+            None => {}
+        }
+
+        if alerts.is_empty() {
+            alerts.push("✅ No momentum alerts - markets stable".to_string());
+        }
+
+        alerts
+    }
+
+    /// Gets current price
+    pub fn get_current_price(&self) -> Option<f64> {
+        self.price_history.back().copied()
+    }
+}
+
+/// Feeds candles into the tracker oldest first, skipping any at or before `last_seen`.
+/// Returns the newest timestamp fed so the next tick only appends fresh candles.
+pub fn load_candles(
+    tracker: &mut BitcoinMomentumTracker,
+    mut candles: Vec<Candle>,
+    last_seen: Option<i64>,
+) -> Option<i64> {
+    candles.sort_by_key(|c| c.timestamp);
+
+    let mut newest = last_seen;
+    for candle in candles
+        .iter()
+        .filter(|c| last_seen.is_none_or(|seen| c.timestamp > seen))
+    {
+        tracker.add_ohlc_point(candle.high, candle.low, candle.close, candle.volume);
+        newest = Some(candle.timestamp);
+    }
+    newest
+}
+
+// Additional utility functions
+
+impl MomentumIndicators {
+    /// Formats the momentum indicators for display
+    pub fn format_report(&self) -> String {
+        format!(
+            "RSI: {:.1} | MACD: {:.4} | Momentum: {:.2}% | Volume: {:.2}x | Signal: {:?}",
+            self.rsi,
+            self.macd.histogram,
+            self.price_momentum,
+            self.volume_ratio,
+            self.overall_signal
+        )
+    }
+
+    /// Checks if momentum is strongly bullish
+    pub fn is_strong_bullish(&self) -> bool {
+        matches!(self.overall_signal, MomentumSignal::Bullish)
+            && self.price_momentum > 1.5
+            && self.volume_ratio > 1.2
+    }
+
+    /// Checks if momentum is strongly bearish
+    pub fn is_strong_bearish(&self) -> bool {
+        matches!(self.overall_signal, MomentumSignal::Bearish)
+            && self.price_momentum < -1.5
+            && self.volume_ratio > 1.2
+    }
+}
+
+/// One kline off the Bitget candle channel, numbers already parsed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiveBar {
+    pub timestamp: i64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl LiveBar {
+    pub fn from_ws(candle: &WsCandleData) -> Option<Self> {
+        Some(Self {
+            timestamp: candle.timestamp.parse().ok()?,
+            high: candle.high.parse().ok()?,
+            low: candle.low.parse().ok()?,
+            close: candle.close.parse().ok()?,
+            volume: candle.base_volume.parse().ok()?,
+        })
+    }
+}
+
+/// Bitget pushes the in-progress bar on every update; a bar only counts as closed once
+/// a push for a later bar arrives. Holds the latest update and hands it back then.
+#[derive(Debug, Default)]
+pub struct ClosedBarFilter {
+    pending: Option<LiveBar>,
+}
+
+impl ClosedBarFilter {
+    pub fn push(&mut self, bar: LiveBar) -> Option<LiveBar> {
+        match self.pending {
+            Some(prev) if bar.timestamp > prev.timestamp => {
+                self.pending = Some(bar);
+                Some(prev)
+            }
+            Some(prev) if bar.timestamp < prev.timestamp => None, // stale replay
+            _ => {
+                self.pending = Some(bar);
+                None
+            }
+        }
+    }
+}
+
+async fn store_json<T: Serialize>(
+    conn: &mut redis::aio::MultiplexedConnection,
+    key: &str,
+    value: &T,
+    ttl: usize,
+) {
+    let serialized = match serde_json::to_string(value) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Momentum: failed to serialize {key}: {e}");
+            return;
+        }
+    };
+    if let Err(e) = conn.set_ex::<_, _, ()>(key, serialized, ttl).await {
+        error!("Momentum: failed to write {key}: {e}");
+    }
+}
+
+/// Writes the bot-facing snapshot and the full indicators (read by the API)
+async fn store_reading(
+    conn: &mut redis::aio::MultiplexedConnection,
+    tracker: &BitcoinMomentumTracker,
+    indicators: &MomentumIndicators,
+    ttl: usize,
+) {
+    let snapshot = MomentumSnapshot::from_indicators(
+        indicators,
+        tracker.calculate_atr(14),
+        tracker.get_current_price(),
+    );
+    store_json(conn, TRADING_BOT_MOMENTUM, &snapshot, ttl).await;
+    store_json(conn, TRADING_BOT_MOMENTUM_INDICATORS, indicators, ttl).await;
+}
+
+/// Live 5m momentum off the Bitget kline stream. History is backfilled over REST on
+/// every (re)connect so a dropped socket never leaves a gap; each closed bar then
+/// recomputes the indicators and writes the snapshot.
+pub async fn start_live_tracking(
+    mut conn: redis::aio::MultiplexedConnection,
+    http: Arc<reqwest::Client>,
+    symbol: Arc<str>,
+    health: TrackerHealth,
+) {
+    let mut tracker = BitcoinMomentumTracker::new(288); // 24 hours of 5-min data
+    let mut last_seen: Option<i64> = None;
+    let mut backoff_secs = 1;
+    let max_backoff = 64;
+    let ttl = (LIVE_BAR_SECS * 2) as usize;
+
+    loop {
+        let limit = if last_seen.is_none() { "288" } else { "50" };
+        match fetch_bitget_candles(&http, &symbol, "5m", limit).await {
+            // the newest REST candle is still forming, the stream delivers it once closed
+            Ok(mut candles) => {
+                candles.sort_by_key(|c| c.timestamp);
+                candles.pop();
+                last_seen = load_candles(&mut tracker, candles, last_seen);
+            }
+            Err(e) => {
+                error!("Momentum: failed to backfill candles: {e}");
+                health.report(&mut conn, &Err(e)).await;
+            }
+        }
+
+        // Convert the connect error up front: the boxed error isn't Send, and the match
+        // scrutinee would otherwise live across every await in the stream loop
+        let subscribed = BitgetWsClient::subscribe_candlesticks("USDT-FUTURES", &symbol, "5m")
+            .await
+            .map_err(|e| anyhow::anyhow!("kline connect: {e}"));
+        match subscribed {
+            std::result::Result::Ok(mut stream) => {
+                info!("Momentum: connected to Bitget kline stream");
+                backoff_secs = 1;
+
+                let mut bars = ClosedBarFilter::default();
+
+                while let Some(msg) = stream.next().await {
+                    let candle = match msg {
+                        Ok(c) => c,
+                        Err(e) => {
+                            error!("Momentum: kline stream error: {e}");
+                            health
+                                .report(&mut conn, &Err(anyhow::anyhow!("kline stream: {e}")))
+                                .await;
+                            break;
+                        }
+                    };
+                    let Some(bar) = LiveBar::from_ws(&candle).and_then(|b| bars.push(b)) else {
+                        continue;
+                    };
+                    if last_seen.is_some_and(|seen| bar.timestamp <= seen) {
+                        continue;
+                    }
+
+                    tracker.add_ohlc_point(bar.high, bar.low, bar.close, bar.volume);
+                    last_seen = Some(bar.timestamp);
+
+                    if let Some(ind) = tracker.calculate_all_indicators() {
+                        info!("Momentum: {}", ind.format_report());
+                        for alert in tracker.generate_alerts(&ind) {
+                            info!("Momentum: {alert}");
+                        }
+                        store_reading(&mut conn, &tracker, &ind, ttl).await;
+                        health.report(&mut conn, &Ok(())).await;
+                    }
+                }
+                warn!("Momentum: kline stream closed, reconnecting...");
+            }
+            std::result::Result::Err(e) => {
+                error!("Momentum: failed to connect kline stream: {e}");
+                health.report(&mut conn, &Err(e)).await;
+            }
+        }
+
+        time::sleep(Duration::from_secs(backoff_secs)).await;
+        backoff_secs = (backoff_secs * 2).min(max_backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn live_bar(timestamp: i64, close: f64) -> LiveBar {
+        LiveBar {
+            timestamp,
+            high: close,
+            low: close,
+            close,
+            volume: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_closed_bar_filter_emits_bar_once_next_opens() {
+        let mut bars = ClosedBarFilter::default();
+
+        assert_eq!(bars.push(live_bar(0, 100.0)), None);
+        assert_eq!(bars.push(live_bar(0, 101.0)), None);
+        assert_eq!(bars.push(live_bar(300_000, 102.0)), Some(live_bar(0, 101.0)));
+        // a late update for the already-closed bar is ignored
+        assert_eq!(bars.push(live_bar(0, 99.0)), None);
+        assert_eq!(bars.push(live_bar(600_000, 103.0)), Some(live_bar(300_000, 102.0)));
+    }
+
+    #[test]
+    fn test_indicators_round_trip_for_api() {
+        let mut tracker = BitcoinMomentumTracker::new(100);
+        for i in 0..40 {
+            tracker.add_data_point(65000.0 + (i as f64 * 50.0), 25000000.0);
+        }
+        let ind = tracker.calculate_all_indicators().unwrap();
+
+        let json = serde_json::to_string(&ind).unwrap();
+        let back: MomentumIndicators = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(back.rsi, ind.rsi);
+        assert_eq!(back.macd.histogram, ind.macd.histogram);
+        assert_eq!(back.overall_signal, ind.overall_signal);
+    }
+
+    fn tracker_from(prices: &[f64]) -> BitcoinMomentumTracker {
+        let mut tracker = BitcoinMomentumTracker::new(200);
+        for &p in prices {
+            tracker.add_data_point(p, 1.0);
+        }
+        tracker
+    }
+
+    /// Steep drop to 90, bounce, then a slow grind to a marginally lower 89.5
+    fn bullish_divergence_series() -> Vec<f64> {
+        let mut prices = vec![100.0; 30];
+        prices.extend([
+            98.0, 96.0, 94.0, 92.0, 90.0, 93.0, 95.0, 96.0, 95.0, 94.0, 93.0, 92.0, 91.0, 90.5,
+            90.0, 89.5, 91.0, 92.0,
+        ]);
+        prices
+    }
+
+    #[test]
+    fn test_detects_bullish_divergence() {
+        let tracker = tracker_from(&bullish_divergence_series());
+        assert_eq!(tracker.detect_divergence(30, 14), Some(Divergence::Bullish));
+
+        let ind = tracker.calculate_all_indicators().unwrap();
+        assert_eq!(ind.divergence, Some(Divergence::Bullish));
+        assert!(tracker
+            .generate_alerts(&ind)
+            .iter()
+            .any(|a| a.contains("Bullish RSI divergence")));
+    }
+
+    #[test]
+    fn test_detects_bearish_divergence() {
+        let mirrored: Vec<f64> = bullish_divergence_series().iter().map(|p| 200.0 - p).collect();
+        let tracker = tracker_from(&mirrored);
+        assert_eq!(tracker.detect_divergence(30, 14), Some(Divergence::Bearish));
+    }
+
+    #[test]
+    fn test_no_divergence_without_enough_history_or_pivots() {
+        assert_eq!(tracker_from(&[100.0; 20]).detect_divergence(30, 14), None);
+
+        let choppy: Vec<f64> = (0..60).map(|i| 100.0 + (i % 3) as f64).collect();
+        assert_eq!(tracker_from(&choppy).detect_divergence(30, 14), None);
+    }
+
+    #[test]
+    fn test_tracker_creation() {
+        let tracker = BitcoinMomentumTracker::new(100);
+        assert_eq!(tracker.max_history, 100);
+        assert_eq!(tracker.price_history.len(), 0);
+    }
+
+    #[test]
+    fn test_add_data_point() {
+        let mut tracker = BitcoinMomentumTracker::new(10);
+        tracker.add_data_point(65000.0, 25000000.0);
+
+        assert_eq!(tracker.price_history.len(), 1);
+        assert_eq!(tracker.volume_history.len(), 1);
+        assert_eq!(*tracker.price_history.front().unwrap(), 65000.0);
+    }
+
+    #[test]
+    fn test_rsi_calculation() {
+        let mut tracker = BitcoinMomentumTracker::new(100);
+
+        // Add insufficient data
+        for i in 0..10 {
+            tracker.add_data_point(65000.0 + (i as f64 * 100.0), 25000000.0);
+        }
+        assert!(tracker.calculate_rsi(14).is_none());
+
+        // Add sufficient data
+        for i in 10..30 {
+            tracker.add_data_point(65000.0 + (i as f64 * 100.0), 25000000.0);
+        }
+        let rsi = tracker.calculate_rsi(14);
+        assert!(rsi.is_some());
+        assert!(rsi.unwrap() >= 0.0 && rsi.unwrap() <= 100.0);
+    }
+
+    #[test]
+    fn test_momentum_signals() {
+        let tracker = BitcoinMomentumTracker::new(100);
+
+        assert_eq!(tracker.get_rsi_signal(80.0), MomentumSignal::Bearish);
+        assert_eq!(tracker.get_rsi_signal(20.0), MomentumSignal::Bullish);
+        assert_eq!(tracker.get_rsi_signal(50.0), MomentumSignal::Neutral);
+    }
+
+    #[test]
+    fn test_price_momentum() {
+        let mut tracker = BitcoinMomentumTracker::new(100);
+
+        // Add data with clear upward momentum
+        for i in 0..10 {
+            tracker.add_data_point(65000.0 + (i as f64 * 500.0), 25000000.0);
+        }
+
+        let momentum = tracker.calculate_price_momentum(5);
+        assert!(momentum.is_some());
+        assert!(momentum.unwrap() > 0.0); // Should be positive momentum
+    }
+
+    #[test]
+    fn test_max_history_limit() {
+        let mut tracker = BitcoinMomentumTracker::new(5);
+
+        // Add more data points than limit
+        for i in 0..10 {
+            tracker.add_data_point(65000.0 + (i as f64), 25000000.0);
+        }
+
+        assert_eq!(tracker.price_history.len(), 5);
+        assert_eq!(*tracker.price_history.front().unwrap(), 65005.0); // Should keep last 5
+    }
+
+    fn candle(timestamp: i64, close: f64, volume: f64) -> Candle {
+        Candle {
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume,
+            quote_volume: close * volume,
+        }
+    }
+
+    #[test]
+    fn test_load_candles_feeds_chronologically_and_skips_seen() {
+        let mut tracker = BitcoinMomentumTracker::new(100);
+
+        // Bitget returns newest first
+        let candles = vec![candle(3, 103.0, 1.0), candle(1, 101.0, 1.0), candle(2, 102.0, 1.0)];
+        let last = load_candles(&mut tracker, candles, None);
+
+        assert_eq!(last, Some(3));
+        assert!(tracker.price_history.iter().eq(&[101.0, 102.0, 103.0]));
+
+        // Overlapping refresh only appends the new candle
+        let refresh = vec![candle(4, 104.0, 2.0), candle(3, 103.0, 1.0)];
+        let last = load_candles(&mut tracker, refresh, last);
+
+        assert_eq!(last, Some(4));
+        assert_eq!(tracker.price_history.len(), 4);
+        assert_eq!(tracker.get_current_price(), Some(104.0));
+    }
+
+    #[test]
+    fn test_macd_signal_is_ema_of_macd_series() {
+        let mut tracker = BitcoinMomentumTracker::new(100);
+
+        // 20 bars up, then 20 bars drifting down: MACD turns below its signal
+        for i in 0..20 {
+            tracker.add_data_point(100.0 + i as f64, 1.0);
+        }
+        for i in 20..40 {
+            tracker.add_data_point(120.0 - (i - 20) as f64 * 0.5, 1.0);
+        }
+
+        let macd = tracker.calculate_macd().unwrap();
+
+        assert!((macd.macd - 0.27967965902716685).abs() < 1e-9);
+        assert!((macd.signal - 1.5348598229915724).abs() < 1e-9);
+        assert!((macd.histogram - (-1.2551801639644056)).abs() < 1e-9);
+        assert_eq!(tracker.get_macd_signal(&macd), MomentumSignal::Bearish);
+    }
+
+    #[test]
+    fn test_macd_needs_signal_warmup() {
+        let mut tracker = BitcoinMomentumTracker::new(100);
+        for i in 0..30 {
+            tracker.add_data_point(100.0 + i as f64, 1.0);
+        }
+        assert!(tracker.calculate_macd().is_none());
+    }
+
+    #[test]
+    fn test_ema_seeds_with_sma() {
+        let tracker = BitcoinMomentumTracker::new(10);
+
+        // period 3 → k = 0.5, seed = SMA(2, 4, 6) = 4
+        // 8  → 8 * 0.5 + 4 * 0.5 = 6
+        // 12 → 12 * 0.5 + 6 * 0.5 = 9
+        let prices = [2.0, 4.0, 6.0, 8.0, 12.0];
+        assert_eq!(tracker.ema_series(&prices, 3), vec![4.0, 6.0, 9.0]);
+        assert_eq!(tracker.calculate_ema(&prices, 3), Some(9.0));
+
+        // Not enough prices for a full period
+        assert_eq!(tracker.calculate_ema(&prices[..2], 3), None);
+    }
+
+    #[test]
+    fn test_atr_uses_true_range_with_gaps() {
+        let mut tracker = BitcoinMomentumTracker::new(10);
+
+        // (high, low, close)
+        tracker.add_ohlc_point(10.0, 8.0, 9.0, 1.0);
+        tracker.add_ohlc_point(11.0, 9.0, 10.0, 1.0); // TR = max(2, 2, 0) = 2
+        tracker.add_ohlc_point(14.0, 12.0, 13.0, 1.0); // gap up: TR = max(2, 4, 2) = 4
+        tracker.add_ohlc_point(13.0, 12.0, 12.5, 1.0); // TR = max(1, 0, 1) = 1
+
+        // period 2: seed = (2 + 4) / 2 = 3, then (3 * 1 + 1) / 2 = 2
+        assert_eq!(tracker.calculate_atr(2), Some(2.0));
+        assert_eq!(tracker.calculate_atr(4), None);
+    }
+}
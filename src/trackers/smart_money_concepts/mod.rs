@@ -1,13 +1,17 @@
 use std::time::Duration;
 
-use log::info;
+use log::{info, warn};
 use redis::AsyncCommands;
-use tokio::time;
+use tokio::time::{self, MissedTickBehavior};
 
-use crate::bot::zones::{Side, Zone, Zones};
+use crate::bot::symbols::SymbolKeys;
+use crate::bot::{Zone, Zones};
 use crate::config::Config;
-use crate::exchange::bitget::{self, Candle, CandleData, HttpCandleData};
-use crate::helper::{TRADING_BOT_TREND_STATE, TRADING_BOT_ZONES};
+use crate::exchange::bitget::{Candle, CandleData, HttpCandleData};
+use crate::helper::{
+    TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL, TRADING_BOT_SMC_SIGNAL, TRADING_BOT_TREND_STATE,
+};
+use crate::trackers::health::TrackerHealth;
 use chrono::TimeZone;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -84,6 +88,12 @@ pub enum SMCEvent {
         time: DateTime<Utc>,
         index: usize,
     }, // Sweep high followed by bearish BOS (SHORT)
+    LiquidityPool {
+        price: f64,
+        kind: PivotKind,
+        time: DateTime<Utc>,
+        index: usize,
+    }, // equal highs / equal lows: resting liquidity price tends to sweep
 }
 
 // ---------------------------------------------------------------------------
@@ -115,6 +125,26 @@ pub struct TrendState {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Which side the most recent Strong Low / Strong High supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StrongKind {
+    /// Sweep low followed by bullish BOS — supports longs.
+    StrongLow,
+    /// Sweep high followed by bearish BOS — supports shorts.
+    StrongHigh,
+}
+
+/// Most recent Strong Low / Strong High from the last SMC tick, used by the
+/// optional `REQUIRE_CONFLUENCE` entry check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmcSignal {
+    pub kind: Option<StrongKind>,
+    pub price: Option<f64>,
+    pub time: Option<DateTime<Utc>>,
+    pub timeframe: String,
+    pub updated_at: DateTime<Utc>,
+}
+
 // ---------------------------------------------------------------------------
 // Internal engine state
 // ---------------------------------------------------------------------------
@@ -156,6 +186,13 @@ pub struct SmcEngine {
     /// Keep last known BOS levels (to avoid double emitting)
     last_bullish_bos_level: Option<f64>,
     last_bearish_bos_level: Option<f64>,
+    /// Relative distance (e.g. 0.001 = 0.1%) under which two pivots count as "equal"
+    equal_level_tolerance: f64,
+    /// Every confirmed pivot, kept to look for equal highs / equal lows
+    pivot_highs: Vec<Pivot>,
+    pivot_lows: Vec<Pivot>,
+    /// Equal highs / equal lows found so far, price is the mean of the cluster
+    liquidity_pools: Vec<Pivot>,
 }
 
 impl SmcEngine {
@@ -171,9 +208,58 @@ impl SmcEngine {
             pending_sweep_high: None,
             last_bullish_bos_level: None,
             last_bearish_bos_level: None,
+            equal_level_tolerance: 0.001,
+            pivot_highs: Vec::new(),
+            pivot_lows: Vec::new(),
+            liquidity_pools: Vec::new(),
         }
     }
 
+    /// Equal highs / equal lows detected so far, oldest first
+    pub fn liquidity_pools(&self) -> &[Pivot] {
+        &self.liquidity_pools
+    }
+
+    /// Checks `p` against earlier pivots of the same kind; a match within tolerance
+    /// records a liquidity pool at the mean price of the cluster.
+    fn detect_liquidity_pool(&mut self, p: &Pivot) -> Option<SMCEvent> {
+        let history = match p.kind {
+            PivotKind::High => &self.pivot_highs,
+            PivotKind::Low => &self.pivot_lows,
+        };
+
+        let cluster: Vec<f64> = history
+            .iter()
+            .filter(|prev| ((prev.price - p.price) / p.price).abs() <= self.equal_level_tolerance)
+            .map(|prev| prev.price)
+            .collect();
+
+        if cluster.is_empty() {
+            return None;
+        }
+
+        let price = (cluster.iter().sum::<f64>() + p.price) / (cluster.len() as f64 + 1.0);
+
+        // A third touch of the same level refreshes the pool instead of adding a new one
+        self.liquidity_pools.retain(|pool| {
+            pool.kind != p.kind
+                || ((pool.price - price) / price).abs() > self.equal_level_tolerance
+        });
+        self.liquidity_pools.push(Pivot {
+            kind: p.kind.clone(),
+            price,
+            time: p.time,
+            index: p.index,
+        });
+
+        Some(SMCEvent::LiquidityPool {
+            price,
+            kind: p.kind.clone(),
+            time: p.time,
+            index: p.index,
+        })
+    }
+
     /// Process a new bar (in chronological order). Returns events that occurred at this bar.
     ///
     /// Note: Because pivot detection needs `pivot_right` future bars, a pivot emitted for
@@ -267,6 +353,10 @@ impl SmcEngine {
                     });
                 }
             }
+            if let Some(ev) = self.detect_liquidity_pool(&p) {
+                events.push(ev);
+            }
+            self.pivot_lows.push(p.clone());
             self.last_pivot_low = Some(p);
         }
 
@@ -301,6 +391,10 @@ impl SmcEngine {
                     });
                 }
             }
+            if let Some(ev) = self.detect_liquidity_pool(&p) {
+                events.push(ev);
+            }
+            self.pivot_highs.push(p.clone());
             self.last_pivot_high = Some(p);
         }
 
@@ -374,11 +468,19 @@ impl SmcEngine {
 ///15m, 333
 /// 4H, 1000
 /// TODO, make configurable the time frame and the number of candles
-async fn return_data(timeframe: String, limit: String) -> Vec<Bar> {
-    let bitget_candles = <HttpCandleData as bitget::CandleData>::new();
+async fn return_data(
+    config: &Config,
+    symbol: &str,
+    timeframe: String,
+    limit: String,
+) -> Vec<Bar> {
+    let bitget_candles = HttpCandleData::new(symbol.to_string(), config.clone());
     let res: Result<Vec<Candle>, anyhow::Error> =
         bitget_candles.get_bitget_candles(timeframe, limit).await;
-    let candle_data = res.unwrap_or_else(|_| Vec::new());
+    let candle_data = res.unwrap_or_else(|e| {
+        warn!("SMC: failed to fetch {symbol} candles: {e}");
+        Vec::new()
+    });
     if candle_data.is_empty() {
         return Vec::new();
     }
@@ -400,13 +502,75 @@ async fn return_data(timeframe: String, limit: String) -> Vec<Bar> {
 //A customizable loop that will run at configured times
 // If we need 4H candle data, we can run the loop every 30minutes so we can be on-sync with the changes as the market can move fast
 //If we need 15m candle data, we can run the loop every 45 seconds so we can be on-sync with the changes as the market can move fast
+/// Owns the only SMC schedule. Late ticks are skipped rather than burst-fired, so runs
+/// are at least a period apart and never overlap; a tick that lands while another
+/// process's next-call is still pending is dropped.
 pub async fn smc_loop(mut conn: redis::aio::MultiplexedConnection, config: Config) {
-    let mut interval = time::interval(Duration::from_secs(config.smc_loop_interval));
+    let period = Duration::from_secs(config.smc_loop_interval);
+    let mut interval = time::interval(period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let health = TrackerHealth::new("smc", config.tracker_health);
 
     loop {
         interval.tick().await;
-        smc_main(&mut conn, &config).await;
+
+        match claim_next_call(&mut conn, period).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("SMC: next-call already pending, skipping");
+                continue;
+            }
+            Err(e) => warn!("SMC: could not check next-call flag, running anyway: {e}"),
+        }
+
+        // Every configured symbol trades its own zones; one failing keeps its
+        // last-good zones without holding the others back
+        let mut outcome = Ok(());
+        for keys in SymbolKeys::for_config(&config) {
+            if let Err(e) = smc_main(&mut conn, &config, &keys).await {
+                warn!("SMC: {} run failed: {e}", keys.symbol);
+                outcome = Err(e);
+            }
+        }
+        health.report(&mut conn, &outcome).await;
+
+        if let Err(e) = conn
+            .del::<_, ()>(TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL)
+            .await
+        {
+            warn!("SMC: failed to clear next-call flag: {e}");
+        }
+    }
+}
+
+/// Sets the shared next-call flag with a TTL of `delay` if nobody holds it, in a single
+/// `SET NX EX` so two processes can't both claim it; a run that crashes before clearing
+/// it self-clears. Returns false (no-op) while another run is pending.
+async fn claim_next_call<C: redis::aio::ConnectionLike + Send>(
+    conn: &mut C,
+    delay: Duration,
+) -> redis::RedisResult<bool> {
+    let secs = delay.as_secs().max(1);
+    let due = Utc::now() + chrono::Duration::seconds(secs as i64);
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL)
+        .arg(due.timestamp())
+        .arg("NX")
+        .arg("EX")
+        .arg(secs)
+        .query_async(conn)
+        .await?;
+    if claimed.is_some() {
+        return Ok(true);
+    }
+
+    // A legacy flag written without expiry would block every run; let it lapse
+    let ttl: i64 = conn.ttl(TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL).await?;
+    if ttl == -1 {
+        conn.expire::<_, ()>(TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL, secs as usize)
+            .await?;
     }
+    Ok(false)
 }
 
 fn remove_conflicting_zones(
@@ -464,9 +628,13 @@ fn filter_close_zones(mut zones: Vec<Zone>, min_distance: f64) -> Vec<Zone> {
     let mut filtered = vec![zones[0]];
 
     for zone in zones.into_iter().skip(1) {
-        let last_accepted = filtered.last().unwrap();
+        let last_accepted = filtered.last_mut().unwrap();
 
-        if !zone.overlaps_or_too_close(last_accepted, min_distance) {
+        if zone.low <= last_accepted.high && zone.high >= last_accepted.low {
+            // Genuine overlap: widen the accepted zone to cover both
+            last_accepted.low = last_accepted.low.min(zone.low);
+            last_accepted.high = last_accepted.high.max(zone.high);
+        } else if !zone.overlaps_or_too_close(last_accepted, min_distance) {
             filtered.push(zone);
         }
     }
@@ -474,24 +642,58 @@ fn filter_close_zones(mut zones: Vec<Zone>, min_distance: f64) -> Vec<Zone> {
     filtered
 }
 
-// Convert the candles to Bar, which are used to find the Strong Lows and Strong Highs, then convert the Bar to Zones needed for trading.
-///todo!: setup config for the pivot low and pivot high
-async fn smc_main(conn: &mut redis::aio::MultiplexedConnection, config: &Config) {
-    let mut eng = SmcEngine::new(3, 3);
-    let mut sample_bars = return_data(
-        config.smc_timeframe.clone(),
-        config.smc_candle_count.clone(),
-    )
-    .await;
+/// Merges freshly detected zones into the stored ones. Fresh zones win over any stored
+/// zone they overlap (or sit within `min_distance` of), and at most `cap` zones are kept,
+/// favouring fresh ones. Result is sorted by midpoint.
+fn merge_zones(existing: Vec<Zone>, fresh: Vec<Zone>, min_distance: f64, cap: usize) -> Vec<Zone> {
+    let mut merged: Vec<Zone> = fresh.clone();
+
+    for zone in existing {
+        if !merged
+            .iter()
+            .any(|z| z.overlaps_or_too_close(&zone, min_distance))
+        {
+            merged.push(zone);
+        }
+    }
+
+    merged.truncate(cap);
+    merged.sort_by(|a, b| a.midpoint().partial_cmp(&b.midpoint()).unwrap());
+    merged
+}
+
+async fn load_stored_zones<C: redis::aio::ConnectionLike + Send>(
+    conn: &mut C,
+    key: &str,
+) -> Option<Zones> {
+    let raw: Option<String> = conn.get(key).await.ok()?;
+    serde_json::from_str(&raw?).ok()
+}
 
-    sample_bars.sort_by_key(|s| s.time);
+/// What one SMC pass derives from a batch of bars, before anything is written
+struct SmcAnalysis {
+    trend_state: TrendState,
+    signal: SmcSignal,
+    long_zones: Vec<Zone>,
+    short_zones: Vec<Zone>,
+}
 
+/// Runs the engine over `bars`. `None` when there are no bars (e.g. a failed
+/// candle fetch), so the caller keeps the last-good trend, signal and zones.
+fn analyse_bars(mut bars: Vec<Bar>, config: &Config) -> Option<SmcAnalysis> {
+    if bars.is_empty() {
+        return None;
+    }
+    bars.sort_by_key(|s| s.time);
+
+    let mut eng = SmcEngine::new(3, 3);
     let mut sweep_lows: Vec<Zone> = Vec::new();
     let mut sweep_highs: Vec<Zone> = Vec::new();
     let mut last_bullish_bos: Option<(f64, DateTime<Utc>)> = None;
     let mut last_bearish_bos: Option<(f64, DateTime<Utc>)> = None;
+    let mut last_strong: Option<(StrongKind, f64, DateTime<Utc>)> = None;
 
-    for b in sample_bars {
+    for b in bars {
         let events = eng.process_bar(b);
         for ev in events {
             match ev {
@@ -503,27 +705,31 @@ async fn smc_main(conn: &mut redis::aio::MultiplexedConnection, config: &Config)
                     info!("SMC BearishBOS: level={level:.2} time={time} tf={}", config.smc_timeframe);
                     last_bearish_bos = Some((level, time));
                 }
-                SMCEvent::StrongLow { price, .. } => {
-                    let low_low = price - (price * config.smc_zone_multiplier);
-                    sweep_lows.push(Zone {
-                        low: low_low,
-                        high: price,
-                        side: Side::Long,
-                    });
+                SMCEvent::StrongLow { price, time, .. } => {
+                    sweep_lows.push(Zone::below(price, config.smc_zone_width_pct));
+                    last_strong = Some((StrongKind::StrongLow, price, time));
                 }
-                SMCEvent::StrongHigh { price, .. } => {
-                    let high_high = price + (price * config.smc_zone_multiplier);
-                    sweep_highs.push(Zone {
-                        low: price,
-                        high: high_high,
-                        side: Side::Short,
-                    });
+                SMCEvent::StrongHigh { price, time, .. } => {
+                    sweep_highs.push(Zone::above(price, config.smc_zone_width_pct));
+                    last_strong = Some((StrongKind::StrongHigh, price, time));
+                }
+                SMCEvent::LiquidityPool { price, kind, time, .. } => {
+                    info!("SMC LiquidityPool: {kind:?} price={price:.2} time={time}");
                 }
                 _ => {}
             }
         }
     }
 
+    // Equal highs / lows are resting liquidity price tends to sweep: trade the sweep
+    // just past each pool, like a strong pivot
+    for pool in eng.liquidity_pools() {
+        match pool.kind {
+            PivotKind::Low => sweep_lows.push(Zone::below(pool.price, config.smc_zone_width_pct)),
+            PivotKind::High => sweep_highs.push(Zone::above(pool.price, config.smc_zone_width_pct)),
+        }
+    }
+
     // Resolve trend direction from whichever BOS is most recent.
     let trend_state = match (last_bullish_bos, last_bearish_bos) {
         (Some((b_level, b_time)), Some((r_level, r_time))) => {
@@ -573,11 +779,13 @@ async fn smc_main(conn: &mut redis::aio::MultiplexedConnection, config: &Config)
         trend_state.direction, trend_state.last_bos_level, trend_state.timeframe
     );
 
-    let serialized_trend = serde_json::to_string(&trend_state).unwrap();
-    let _: () = conn
-        .set(TRADING_BOT_TREND_STATE, serialized_trend)
-        .await
-        .unwrap();
+    let smc_signal = SmcSignal {
+        kind: last_strong.map(|(k, _, _)| k),
+        price: last_strong.map(|(_, p, _)| p),
+        time: last_strong.map(|(_, _, t)| t),
+        timeframe: config.smc_timeframe.clone(),
+        updated_at: Utc::now(),
+    };
 
     let (filtered_highs, filtered_lows) =
         remove_conflicting_zones(sweep_highs, sweep_lows, config.smc_min_distance);
@@ -585,30 +793,255 @@ async fn smc_main(conn: &mut redis::aio::MultiplexedConnection, config: &Config)
     let long_zones = filter_close_zones(filtered_lows, config.smc_min_distance);
     let short_zones = filter_close_zones(filtered_highs, config.smc_min_distance);
 
-    if short_zones.is_empty() || long_zones.is_empty() {
-        info!("No zones found");
-        return;
-    }
+    Some(SmcAnalysis {
+        trend_state,
+        signal: smc_signal,
+        long_zones,
+        short_zones,
+    })
+}
+
+// Convert the candles to Bar, which are used to find the Strong Lows and Strong Highs, then convert the Bar to Zones needed for trading.
+/// Writes `keys.symbol`'s zones under its own key; the trend and signal are the
+/// primary symbol's only.
+///todo!: setup config for the pivot low and pivot high
+async fn smc_main(
+    conn: &mut redis::aio::MultiplexedConnection,
+    config: &Config,
+    keys: &SymbolKeys,
+) -> anyhow::Result<()> {
+    let sample_bars = return_data(
+        config,
+        &keys.symbol,
+        config.smc_timeframe.clone(),
+        config.smc_candle_count.clone(),
+    )
+    .await;
+    store_analysis(conn, config, keys, sample_bars).await
+}
 
-    let zones = Zones {
+/// Analyses `bars` and stores the trend, signal and merged zones they yield. With no
+/// bars (a failed fetch) nothing is written, so the last-good state stays in place.
+async fn store_analysis<C: redis::aio::ConnectionLike + Send>(
+    conn: &mut C,
+    config: &Config,
+    keys: &SymbolKeys,
+    bars: Vec<Bar>,
+) -> anyhow::Result<()> {
+    let Some(SmcAnalysis {
+        trend_state,
+        signal: smc_signal,
         long_zones,
         short_zones,
+    }) = analyse_bars(bars, config)
+    else {
+        return Err(anyhow::anyhow!(
+            "no {} {} candles returned, keeping the last-good trend and zones",
+            keys.symbol,
+            config.smc_timeframe
+        ));
     };
 
-    info!("zones.long_zones: {:?}", zones.long_zones);
-    info!("zones.short_zones: {:?}", zones.short_zones);
+    if keys.primary {
+        let serialized_trend = serde_json::to_string(&trend_state)?;
+        let _: () = conn.set(TRADING_BOT_TREND_STATE, serialized_trend).await?;
+
+        let serialized_signal = serde_json::to_string(&smc_signal)?;
+        let _: () = conn.set(TRADING_BOT_SMC_SIGNAL, serialized_signal).await?;
+    }
+
+    if short_zones.is_empty() || long_zones.is_empty() {
+        info!("No {} zones found", keys.symbol);
+        return Ok(());
+    }
+
+    let zones = match load_stored_zones(conn, &keys.zones).await {
+        Some(stored) => Zones {
+            long_zones: merge_zones(
+                stored.long_zones,
+                long_zones,
+                config.smc_min_distance,
+                config.smc_max_zones,
+            ),
+            short_zones: merge_zones(
+                stored.short_zones,
+                short_zones,
+                config.smc_min_distance,
+                config.smc_max_zones,
+            ),
+        },
+        None => Zones {
+            long_zones,
+            short_zones,
+        },
+    };
+
+    info!("{} zones.long_zones: {:?}", keys.symbol, zones.long_zones);
+    info!("{} zones.short_zones: {:?}", keys.symbol, zones.short_zones);
 
     // Save the zones to redis
-    let serialized_zones = serde_json::to_string(&zones).unwrap();
-    let _: () = conn.set(TRADING_BOT_ZONES, serialized_zones).await.unwrap();
+    let serialized_zones = serde_json::to_string(&zones)?;
+    let _: () = conn.set(&keys.zones, serialized_zones).await?;
+    Ok(())
 }
 
 // -------------------------- Example usage --------------------------
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bot::Side;
     use chrono::Duration;
 
+    #[tokio::test]
+    async fn test_empty_candles_keep_last_good_state() {
+        let config = Config::for_tests();
+        let keys = SymbolKeys::new(&config.symbol, true);
+        let stored = serde_json::to_string(&Zones::default()).unwrap();
+        let mut conn = FakeRedis::default();
+        conn.values.insert(keys.zones.clone(), stored.clone());
+
+        // a failed fetch yields no bars: the run fails and writes nothing
+        assert!(store_analysis(&mut conn, &config, &keys, Vec::new()).await.is_err());
+        assert_eq!(conn.values[&keys.zones], stored);
+        assert!(!conn.values.contains_key(TRADING_BOT_TREND_STATE));
+    }
+
+    #[test]
+    fn test_equal_lows_become_a_long_zone_below_the_pool() {
+        let config = Config::for_tests();
+        let start = Utc::now();
+        // pivot lows at 90.0 and 90.05 are equal lows, pooled at 90.025
+        let prices = [
+            100.0, 99.0, 98.0, 90.0, 98.0, 99.0, 100.0, 105.0, 100.0, 99.0, 98.0, 90.05, 98.0,
+            99.0, 100.0,
+        ];
+        let bars = prices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| make_bar(start + Duration::seconds(60 * i as i64), *p, *p, *p, *p))
+            .collect();
+
+        let analysis = analyse_bars(bars, &config).unwrap();
+
+        let pool = Zone::below(90.025, config.smc_zone_width_pct);
+        assert!(
+            analysis
+                .long_zones
+                .iter()
+                .any(|z| z.low <= pool.low && z.high >= pool.high),
+            "no long zone covers the pool: {:?}",
+            analysis.long_zones
+        );
+    }
+
+    /// Just enough of redis for the next-call flag and stored zones: each key's value
+    /// and TTL, `-1` for no expiry
+    #[derive(Default)]
+    struct FakeRedis {
+        values: std::collections::HashMap<String, String>,
+        ttls: std::collections::HashMap<String, i64>,
+    }
+
+    impl redis::aio::ConnectionLike for FakeRedis {
+        fn req_packed_command<'a>(
+            &'a mut self,
+            cmd: &'a redis::Cmd,
+        ) -> redis::RedisFuture<'a, redis::Value> {
+            let args: Vec<String> = cmd
+                .args_iter()
+                .filter_map(|a| match a {
+                    redis::Arg::Simple(a) => Some(String::from_utf8_lossy(a).into_owned()),
+                    redis::Arg::Cursor => None,
+                })
+                .collect();
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let reply = match args.as_slice() {
+                ["SET", key, value, opts @ ..] => {
+                    if opts.contains(&"NX") && self.ttls.contains_key(*key) {
+                        redis::Value::Nil
+                    } else {
+                        let ttl = opts
+                            .iter()
+                            .position(|o| *o == "EX")
+                            .map_or(-1, |i| opts[i + 1].parse().unwrap());
+                        self.values.insert(key.to_string(), value.to_string());
+                        self.ttls.insert(key.to_string(), ttl);
+                        redis::Value::Okay
+                    }
+                }
+                ["GET", key] => match self.values.get(*key) {
+                    Some(value) => redis::Value::Data(value.clone().into_bytes()),
+                    None => redis::Value::Nil,
+                },
+                ["TTL", key] => redis::Value::Int(self.ttls.get(*key).copied().unwrap_or(-2)),
+                ["EXPIRE", key, secs] => match self.ttls.get_mut(*key) {
+                    Some(ttl) => {
+                        *ttl = secs.parse().unwrap();
+                        redis::Value::Int(1)
+                    }
+                    None => redis::Value::Int(0),
+                },
+                ["DEL", key] => {
+                    self.values.remove(*key);
+                    redis::Value::Int(self.ttls.remove(*key).is_some() as i64)
+                }
+                other => panic!("unexpected command {other:?}"),
+            };
+            Box::pin(async move { Ok(reply) })
+        }
+
+        fn req_packed_commands<'a>(
+            &'a mut self,
+            _: &'a redis::Pipeline,
+            _: usize,
+            _: usize,
+        ) -> redis::RedisFuture<'a, Vec<redis::Value>> {
+            unimplemented!("the next-call flag never pipelines")
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_sets_the_next_call_with_an_expiry() {
+        let mut conn = FakeRedis::default();
+        let period = std::time::Duration::from_secs(1800);
+
+        assert!(claim_next_call(&mut conn, period).await.unwrap());
+        assert_eq!(conn.ttls[TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL], 1800);
+    }
+
+    #[tokio::test]
+    async fn test_second_claim_during_a_pending_run_is_a_noop() {
+        let mut conn = FakeRedis::default();
+        let period = std::time::Duration::from_secs(1800);
+
+        // another process's tick lands while the first run holds the flag
+        assert!(claim_next_call(&mut conn, period).await.unwrap());
+        assert!(!claim_next_call(&mut conn, period).await.unwrap());
+
+        // the run clears it on the way out, freeing the next tick
+        let _: () = conn
+            .del(TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL)
+            .await
+            .unwrap();
+        assert!(claim_next_call(&mut conn, period).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_claim_gives_a_legacy_next_call_an_expiry() {
+        // written by an older build without a TTL: skip this tick, but let it lapse
+        let mut conn = FakeRedis::default();
+        conn.ttls
+            .insert(TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL.to_string(), -1);
+        let period = std::time::Duration::from_secs(1800);
+
+        assert!(!claim_next_call(&mut conn, period).await.unwrap());
+        assert_eq!(conn.ttls[TRADING_BOT_SMART_MONEY_CONCEPTS_NEXT_CALL], 1800);
+    }
+
     fn make_bar(t: DateTime<Utc>, o: f64, h: f64, l: f64, c: f64) -> Bar {
         Bar {
             time: t,
@@ -705,4 +1138,96 @@ mod tests {
             "expected StrongHigh in events, got {emitted:?}"
         );
     }
+
+    #[test]
+    fn test_equal_highs_emit_liquidity_pool() {
+        let mut eng = SmcEngine::new(2, 2);
+        let start = Utc::now();
+
+        // Two pivot highs at 110.0 and 110.05 (within 0.1%) → equal highs
+        let prices = [
+            100.0, 101.0, 110.0, 101.0, 100.0, 95.0, 100.0, 101.0, 110.05, 101.0, 100.0,
+        ];
+
+        let mut pools = Vec::new();
+        for (i, p) in prices.iter().enumerate() {
+            let bar = make_bar(start + Duration::seconds(60 * i as i64), *p, *p, *p, *p);
+            for ev in eng.process_bar(bar) {
+                if let SMCEvent::LiquidityPool { price, kind, .. } = ev {
+                    pools.push((price, kind));
+                }
+            }
+        }
+
+        assert_eq!(pools.len(), 1, "expected one pool, got {pools:?}");
+        assert_eq!(pools[0].1, PivotKind::High);
+        assert!((pools[0].0 - 110.025).abs() < 1e-9);
+        assert_eq!(eng.liquidity_pools().len(), 1);
+    }
+
+    #[test]
+    fn test_distinct_highs_do_not_form_pool() {
+        let mut eng = SmcEngine::new(2, 2);
+        let start = Utc::now();
+
+        let prices = [
+            100.0, 101.0, 110.0, 101.0, 100.0, 95.0, 100.0, 101.0, 120.0, 101.0, 100.0,
+        ];
+
+        for (i, p) in prices.iter().enumerate() {
+            let bar = make_bar(start + Duration::seconds(60 * i as i64), *p, *p, *p, *p);
+            eng.process_bar(bar);
+        }
+
+        assert!(eng.liquidity_pools().is_empty());
+    }
+
+    fn long_zone(low: f64, high: f64) -> Zone {
+        Zone {
+            low,
+            high,
+            side: Side::Long,
+        }
+    }
+
+    #[test]
+    fn test_merge_zones_replaces_overlapping_and_keeps_distinct() {
+        let existing = vec![long_zone(90_000.0, 90_100.0), long_zone(95_000.0, 95_100.0)];
+        let fresh = vec![long_zone(90_050.0, 90_150.0), long_zone(100_000.0, 100_100.0)];
+
+        let merged = merge_zones(existing, fresh, 1500.0, 10);
+
+        let lows: Vec<f64> = merged.iter().map(|z| z.low).collect();
+        assert_eq!(lows, vec![90_050.0, 95_000.0, 100_000.0]);
+    }
+
+    #[test]
+    fn test_merge_zones_caps_length_favouring_fresh() {
+        let existing: Vec<Zone> = (0..10)
+            .map(|i| long_zone(50_000.0 + i as f64 * 2_000.0, 50_100.0 + i as f64 * 2_000.0))
+            .collect();
+        let fresh = vec![long_zone(100_000.0, 100_100.0)];
+
+        let merged = merge_zones(existing, fresh, 1500.0, 4);
+
+        assert_eq!(merged.len(), 4);
+        assert!(merged.iter().any(|z| z.low == 100_000.0));
+    }
+
+    #[test]
+    fn test_filter_close_zones_merges_overlaps_and_drops_near_duplicates() {
+        let zones = vec![
+            long_zone(90_000.0, 90_100.0),
+            long_zone(90_050.0, 90_180.0), // overlaps → merged
+            long_zone(90_900.0, 91_000.0), // within 1500 of the merged zone → dropped
+            long_zone(95_000.0, 95_100.0),
+        ];
+
+        let filtered = filter_close_zones(zones, 1500.0);
+
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].low, 90_000.0);
+        assert_eq!(filtered[0].high, 90_180.0);
+        assert_eq!(filtered[1].low, 95_000.0);
+    }
 }